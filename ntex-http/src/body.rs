@@ -6,6 +6,8 @@ use std::{
 use futures_core::Stream;
 use ntex_bytes::{Bytes, BytesMut};
 
+use crate::HeaderMap;
+
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
 /// Body size hint
 pub enum BodySize {
@@ -30,6 +32,15 @@ pub trait MessageBody: 'static {
         &mut self,
         cx: &mut Context<'_>,
     ) -> Poll<Option<Result<Bytes, Box<dyn Error>>>>;
+
+    /// Attempt to pull out trailing headers of the body.
+    ///
+    /// Called once after `poll_next_chunk()` returned `None`. Trailers are
+    /// sent only with chunked transfer encoding for http/1.1 and with
+    /// trailing headers frame for http/2.
+    fn poll_trailers(&mut self, _: &mut Context<'_>) -> Poll<Option<HeaderMap>> {
+        Poll::Ready(None)
+    }
 }
 
 impl MessageBody for () {
@@ -60,6 +71,11 @@ impl<T: MessageBody> MessageBody for Box<T> {
     ) -> Poll<Option<Result<Bytes, Box<dyn Error>>>> {
         self.as_mut().poll_next_chunk(cx)
     }
+
+    #[inline]
+    fn poll_trailers(&mut self, cx: &mut Context<'_>) -> Poll<Option<HeaderMap>> {
+        self.as_mut().poll_trailers(cx)
+    }
 }
 
 #[derive(Debug)]
@@ -134,6 +150,14 @@ impl<B: MessageBody> MessageBody for ResponseBody<B> {
             ResponseBody::Other(ref mut body) => body.poll_next_chunk(cx),
         }
     }
+
+    #[inline]
+    fn poll_trailers(&mut self, cx: &mut Context<'_>) -> Poll<Option<HeaderMap>> {
+        match self {
+            ResponseBody::Body(ref mut body) => body.poll_trailers(cx),
+            ResponseBody::Other(ref mut body) => body.poll_trailers(cx),
+        }
+    }
 }
 
 impl<B: MessageBody + Unpin> Stream for ResponseBody<B> {
@@ -200,6 +224,13 @@ impl MessageBody for Body {
             Body::Message(ref mut body) => body.poll_next_chunk(cx),
         }
     }
+
+    fn poll_trailers(&mut self, cx: &mut Context<'_>) -> Poll<Option<HeaderMap>> {
+        match self {
+            Body::Message(ref mut body) => body.poll_trailers(cx),
+            _ => Poll::Ready(None),
+        }
+    }
 }
 
 impl PartialEq for Body {
@@ -556,6 +587,65 @@ where
     }
 }
 
+/// Message body with trailing headers.
+///
+/// Body is always sent as a stream, for http/1.1 chunked transfer
+/// encoding is used.
+pub struct WithTrailers<B> {
+    body: B,
+    trailers: Option<HeaderMap>,
+}
+
+impl<B: MessageBody> WithTrailers<B> {
+    /// Create body that sends `trailers` after `body` is complete
+    ///
+    /// Fields that are not allowed in trailer section, like `Content-Length`,
+    /// `Transfer-Encoding` or `Host`, are dropped by http dispatchers.
+    pub fn new(body: B, trailers: HeaderMap) -> Self {
+        WithTrailers {
+            body,
+            trailers: Some(trailers),
+        }
+    }
+
+    /// Get mutable reference to trailing headers
+    pub fn trailers_mut(&mut self) -> Option<&mut HeaderMap> {
+        self.trailers.as_mut()
+    }
+}
+
+impl<B> fmt::Debug for WithTrailers<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WithTrailers")
+            .field("body", &std::any::type_name::<B>())
+            .field("trailers", &self.trailers)
+            .finish()
+    }
+}
+
+impl<B: MessageBody> MessageBody for WithTrailers<B> {
+    fn size(&self) -> BodySize {
+        BodySize::Stream
+    }
+
+    fn poll_next_chunk(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Box<dyn Error>>>> {
+        self.body.poll_next_chunk(cx)
+    }
+
+    fn poll_trailers(&mut self, _: &mut Context<'_>) -> Poll<Option<HeaderMap>> {
+        Poll::Ready(self.trailers.take())
+    }
+}
+
+impl<B: MessageBody> From<WithTrailers<B>> for Body {
+    fn from(b: WithTrailers<B>) -> Body {
+        Body::from_message(b)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::{future::poll_fn, io};
@@ -782,4 +872,31 @@ mod tests {
             Some(Bytes::from("2")),
         );
     }
+
+    #[ntex::test]
+    async fn test_trailers() {
+        let mut hdrs = HeaderMap::new();
+        hdrs.insert(
+            crate::HeaderName::from_static("grpc-status"),
+            crate::HeaderValue::from_static("0"),
+        );
+
+        let mut body = Body::from(WithTrailers::new("test", hdrs));
+        assert_eq!(body.size(), BodySize::Stream);
+        assert_eq!(
+            poll_fn(|cx| body.poll_next_chunk(cx)).await.unwrap().ok(),
+            Some(Bytes::from("test"))
+        );
+        assert!(poll_fn(|cx| body.poll_next_chunk(cx)).await.is_none());
+        let trailers = poll_fn(|cx| body.poll_trailers(cx)).await.unwrap();
+        assert_eq!(trailers.get("grpc-status").unwrap(), "0");
+        assert!(poll_fn(|cx| body.poll_trailers(cx)).await.is_none());
+
+        let mut body = Body::from("test");
+        assert!(poll_fn(|cx| body.poll_trailers(cx)).await.is_none());
+        let mut body = ResponseBody::new(WithTrailers::new((), HeaderMap::new()));
+        assert!(poll_fn(|cx| body.poll_trailers(cx)).await.is_some());
+        assert!(format!("{:?}", WithTrailers::new((), HeaderMap::new()))
+            .contains("WithTrailers"));
+    }
 }
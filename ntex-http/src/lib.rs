@@ -5,6 +5,7 @@ pub mod body;
 pub mod error;
 mod map;
 mod serde;
+mod typed;
 mod value;
 
 pub use self::error::Error;
@@ -35,6 +36,11 @@ pub mod header {
 
     #[doc(hidden)]
     pub use crate::map::{AsName, Either, GetAll, Iter, Value};
+    pub use crate::typed::{
+        Accept, Authorization, CacheControl, CacheDirective, ContentType, ETag, EntityTag,
+        Forwarded, ForwardedElement, IfMatch, IfNoneMatch, QualityItem, Range, RangeSpec,
+        TypedHeader, TypedHeaderError,
+    };
     pub use crate::value::{HeaderValue, InvalidHeaderValue, ToStrError};
 
     pub use http::header::{HeaderName, InvalidHeaderName};
@@ -1,5 +1,7 @@
 use std::collections::{self, hash_map, hash_map::Entry, VecDeque};
 
+use crate::typed::{TypedHeader, TypedHeaderError};
+use crate::value::InvalidHeaderValue;
 use crate::{HeaderName, HeaderValue};
 
 type HashMap<K, V> = collections::HashMap<K, V, fxhash::FxBuildHasher>;
@@ -341,6 +343,20 @@ impl HeaderMap {
             }
         }
     }
+
+    /// Decode typed header from all values of the header.
+    pub fn typed_get<H: TypedHeader>(&self) -> Result<H, TypedHeaderError> {
+        H::decode(self.get_all(H::name()))
+    }
+
+    /// Inserts typed header into the map, previous values are removed.
+    pub fn typed_insert<H: TypedHeader>(
+        &mut self,
+        header: H,
+    ) -> Result<(), InvalidHeaderValue> {
+        self.insert(H::name(), header.encode()?);
+        Ok(())
+    }
 }
 
 #[doc(hidden)]
@@ -0,0 +1,1134 @@
+//! Typed headers
+use std::{borrow::Cow, error, fmt, str::FromStr};
+
+use base64::{engine::general_purpose::STANDARD as base64, Engine};
+use http::header::{self, HeaderName};
+
+use crate::value::{HeaderValue, InvalidHeaderValue};
+
+/// Header that could be decoded from and encoded to header values.
+pub trait TypedHeader: Sized {
+    /// Name of the header
+    fn name() -> HeaderName;
+
+    /// Decode header from all values of the header
+    fn decode<'a, I>(values: I) -> Result<Self, TypedHeaderError>
+    where
+        I: Iterator<Item = &'a HeaderValue>;
+
+    /// Encode header to a header value
+    fn encode(&self) -> Result<HeaderValue, InvalidHeaderValue>;
+}
+
+/// An error that can occur during typed header decoding
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TypedHeaderError {
+    /// Header is not present
+    Missing(HeaderName),
+    /// Header value is malformed
+    Invalid(HeaderName),
+}
+
+impl fmt::Display for TypedHeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypedHeaderError::Missing(name) => write!(f, "Header {} is missing", name),
+            TypedHeaderError::Invalid(name) => write!(f, "Header {} is malformed", name),
+        }
+    }
+}
+
+impl error::Error for TypedHeaderError {}
+
+fn invalid<H: TypedHeader>() -> TypedHeaderError {
+    TypedHeaderError::Invalid(H::name())
+}
+
+/// Decode first value of single value header
+fn single<'a, H, I>(mut values: I) -> Result<&'a str, TypedHeaderError>
+where
+    H: TypedHeader,
+    I: Iterator<Item = &'a HeaderValue>,
+{
+    values
+        .next()
+        .ok_or_else(|| TypedHeaderError::Missing(H::name()))?
+        .to_str()
+        .map(str::trim)
+        .map_err(|_| invalid::<H>())
+}
+
+/// Decode elements of comma separated list header, from all values
+fn list<'a, H, I>(values: I) -> Result<Vec<&'a str>, TypedHeaderError>
+where
+    H: TypedHeader,
+    I: Iterator<Item = &'a HeaderValue>,
+{
+    let mut found = false;
+    let mut items = Vec::new();
+    for value in values {
+        found = true;
+        items.extend(split(value.to_str().map_err(|_| invalid::<H>())?, b','));
+    }
+    if found {
+        Ok(items)
+    } else {
+        Err(TypedHeaderError::Missing(H::name()))
+    }
+}
+
+/// Split string by separator, separators in quoted strings are ignored
+fn split(s: &str, sep: u8) -> impl Iterator<Item = &str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    let mut escaped = false;
+
+    for (idx, b) in s.bytes().enumerate() {
+        if escaped {
+            escaped = false;
+        } else if b == b'\\' && quoted {
+            escaped = true;
+        } else if b == b'"' {
+            quoted = !quoted;
+        } else if b == sep && !quoted {
+            parts.push(&s[start..idx]);
+            start = idx + 1;
+        }
+    }
+    parts.push(&s[start..]);
+    parts.into_iter().map(str::trim).filter(|s| !s.is_empty())
+}
+
+fn is_token(s: &str) -> bool {
+    !s.is_empty()
+        && s.bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
+/// Token or quoted string value
+fn unquote(s: &str) -> Option<Cow<'_, str>> {
+    if let Some(s) = s.strip_prefix('"') {
+        let s = s.strip_suffix('"')?;
+        if s.contains('\\') {
+            let mut value = String::with_capacity(s.len());
+            let mut chars = s.chars();
+            while let Some(ch) = chars.next() {
+                if ch == '\\' {
+                    value.push(chars.next()?);
+                } else {
+                    value.push(ch);
+                }
+            }
+            Some(Cow::Owned(value))
+        } else {
+            Some(Cow::Borrowed(s))
+        }
+    } else if is_token(s) {
+        Some(Cow::Borrowed(s))
+    } else {
+        None
+    }
+}
+
+/// Quote value if it is not a token
+fn quote(s: &str) -> Cow<'_, str> {
+    if is_token(s) {
+        Cow::Borrowed(s)
+    } else {
+        Cow::Owned(format!(
+            "\"{}\"",
+            s.replace('\\', "\\\\").replace('"', "\\\"")
+        ))
+    }
+}
+
+/// Split `key=value` parameter
+fn param(s: &str) -> Option<(&str, Option<Cow<'_, str>>)> {
+    match s.split_once('=') {
+        Some((key, value)) => Some((key.trim(), Some(unquote(value.trim())?))),
+        None => Some((s, None)),
+    }
+}
+
+fn encode_str(s: String) -> Result<HeaderValue, InvalidHeaderValue> {
+    HeaderValue::from_str(&s)
+}
+
+/// Item with quality value
+///
+/// Quality is represented in thousandths, `1000` is `q=1`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QualityItem<T> {
+    /// Item
+    pub item: T,
+    /// Quality value, from `0` to `1000`
+    pub quality: u16,
+}
+
+impl<T> QualityItem<T> {
+    /// Create item with maximum quality
+    pub fn max(item: T) -> Self {
+        QualityItem {
+            item,
+            quality: 1000,
+        }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for QualityItem<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.quality >= 1000 {
+            write!(f, "{}", self.item)
+        } else {
+            let q = format!("{}.{:03}", self.quality / 1000, self.quality % 1000);
+            write!(
+                f,
+                "{};q={}",
+                self.item,
+                q.trim_end_matches('0').trim_end_matches('.')
+            )
+        }
+    }
+}
+
+/// Parse quality value, RFC 9110 section 12.4.2
+fn parse_quality(s: &str) -> Option<u16> {
+    let (int, frac) = s.split_once('.').unwrap_or((s, ""));
+    if frac.len() > 3 || !frac.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let int = match int {
+        "0" => 0,
+        "1" => 1000,
+        _ => return None,
+    };
+    let frac = frac
+        .bytes()
+        .chain(std::iter::repeat(b'0'))
+        .take(3)
+        .fold(0, |acc, b| acc * 10 + u16::from(b - b'0'));
+
+    let quality = int + frac;
+    if quality > 1000 {
+        None
+    } else {
+        Some(quality)
+    }
+}
+
+/// `Accept` header, RFC 9110 section 12.5.1
+///
+/// Media ranges are stored without parameters, in lower case.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Accept(pub Vec<QualityItem<String>>);
+
+impl Accept {
+    /// Quality of the media type
+    ///
+    /// Most specific matching media range is used, `0` is returned
+    /// if media type is not acceptable.
+    pub fn quality(&self, media_type: &str) -> u16 {
+        let media_type = media_type.split(';').next().unwrap_or("").trim();
+        let (ty, subty) = media_type.split_once('/').unwrap_or((media_type, ""));
+
+        let mut result = (0, 0);
+        for item in &self.0 {
+            let (ity, isubty) = item
+                .item
+                .split_once('/')
+                .unwrap_or((item.item.as_str(), ""));
+            let specificity = if ity == "*" {
+                1
+            } else if !ity.eq_ignore_ascii_case(ty) {
+                continue;
+            } else if isubty == "*" {
+                2
+            } else if isubty.eq_ignore_ascii_case(subty) {
+                3
+            } else {
+                continue;
+            };
+            if specificity > result.0 {
+                result = (specificity, item.quality);
+            }
+        }
+        result.1
+    }
+
+    /// Iterate over media ranges ordered by quality
+    pub fn ranges(&self) -> impl Iterator<Item = &QualityItem<String>> {
+        let mut items: Vec<_> = self.0.iter().collect();
+        items.sort_by(|a, b| b.quality.cmp(&a.quality));
+        items.into_iter()
+    }
+}
+
+impl TypedHeader for Accept {
+    fn name() -> HeaderName {
+        header::ACCEPT
+    }
+
+    fn decode<'a, I>(values: I) -> Result<Self, TypedHeaderError>
+    where
+        I: Iterator<Item = &'a HeaderValue>,
+    {
+        let mut items = Vec::new();
+        for item in list::<Self, _>(values)? {
+            let mut parts = split(item, b';');
+            let range = parts.next().unwrap_or("");
+            if !range
+                .split_once('/')
+                .is_some_and(|(t, s)| is_token(t) && is_token(s))
+            {
+                return Err(invalid::<Self>());
+            }
+
+            let mut quality = 1000;
+            for part in parts {
+                if let Some((key, value)) = part.split_once('=') {
+                    if key.trim().eq_ignore_ascii_case("q") {
+                        quality =
+                            parse_quality(value.trim()).ok_or_else(invalid::<Self>)?;
+                        break;
+                    }
+                }
+            }
+            items.push(QualityItem {
+                quality,
+                item: range.to_ascii_lowercase(),
+            });
+        }
+        Ok(Accept(items))
+    }
+
+    fn encode(&self) -> Result<HeaderValue, InvalidHeaderValue> {
+        let items: Vec<_> = self.0.iter().map(|item| item.to_string()).collect();
+        encode_str(items.join(", "))
+    }
+}
+
+/// `Authorization` header, RFC 9110 section 11.6.2
+#[derive(Clone, PartialEq, Eq)]
+pub enum Authorization {
+    /// Basic authentication scheme, RFC 7617
+    Basic {
+        /// User id
+        username: String,
+        /// Password
+        password: String,
+    },
+    /// Bearer token, RFC 6750
+    Bearer(String),
+    /// Other authentication scheme
+    Other {
+        /// Authentication scheme
+        scheme: String,
+        /// Credentials
+        credentials: String,
+    },
+}
+
+impl Authorization {
+    /// Create basic credentials
+    pub fn basic(username: &str, password: &str) -> Self {
+        Authorization::Basic {
+            username: username.to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    /// Create bearer credentials
+    pub fn bearer(token: &str) -> Self {
+        Authorization::Bearer(token.to_string())
+    }
+}
+
+impl fmt::Debug for Authorization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Authorization::Basic { username, .. } => f
+                .debug_struct("Basic")
+                .field("username", username)
+                .finish_non_exhaustive(),
+            Authorization::Bearer(_) => f.debug_tuple("Bearer").finish_non_exhaustive(),
+            Authorization::Other { scheme, .. } => f
+                .debug_struct("Other")
+                .field("scheme", scheme)
+                .finish_non_exhaustive(),
+        }
+    }
+}
+
+impl TypedHeader for Authorization {
+    fn name() -> HeaderName {
+        header::AUTHORIZATION
+    }
+
+    fn decode<'a, I>(values: I) -> Result<Self, TypedHeaderError>
+    where
+        I: Iterator<Item = &'a HeaderValue>,
+    {
+        let value = single::<Self, _>(values)?;
+        let (scheme, credentials) = value.split_once(' ').ok_or_else(invalid::<Self>)?;
+        let credentials = credentials.trim();
+        if !is_token(scheme) || credentials.is_empty() {
+            return Err(invalid::<Self>());
+        }
+
+        if scheme.eq_ignore_ascii_case("basic") {
+            let decoded = base64
+                .decode(credentials)
+                .ok()
+                .and_then(|data| String::from_utf8(data).ok())
+                .ok_or_else(invalid::<Self>)?;
+            let (username, password) =
+                decoded.split_once(':').ok_or_else(invalid::<Self>)?;
+            Ok(Authorization::basic(username, password))
+        } else if scheme.eq_ignore_ascii_case("bearer") {
+            Ok(Authorization::Bearer(credentials.to_string()))
+        } else {
+            Ok(Authorization::Other {
+                scheme: scheme.to_string(),
+                credentials: credentials.to_string(),
+            })
+        }
+    }
+
+    fn encode(&self) -> Result<HeaderValue, InvalidHeaderValue> {
+        let mut value = match self {
+            Authorization::Basic { username, password } => encode_str(format!(
+                "Basic {}",
+                base64.encode(format!("{}:{}", username, password))
+            )),
+            Authorization::Bearer(token) => encode_str(format!("Bearer {}", token)),
+            Authorization::Other {
+                scheme,
+                credentials,
+            } => encode_str(format!("{} {}", scheme, credentials)),
+        }?;
+        value.set_sensitive(true);
+        Ok(value)
+    }
+}
+
+/// `Cache-Control` directive, RFC 9111 section 5.2
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CacheDirective {
+    /// `no-cache`
+    NoCache,
+    /// `no-store`
+    NoStore,
+    /// `no-transform`
+    NoTransform,
+    /// `only-if-cached`
+    OnlyIfCached,
+    /// `must-revalidate`
+    MustRevalidate,
+    /// `proxy-revalidate`
+    ProxyRevalidate,
+    /// `public`
+    Public,
+    /// `private`
+    Private,
+    /// `immutable`, RFC 8246
+    Immutable,
+    /// `max-age=seconds`
+    MaxAge(u32),
+    /// `max-stale[=seconds]`
+    MaxStale(Option<u32>),
+    /// `min-fresh=seconds`
+    MinFresh(u32),
+    /// `s-maxage=seconds`
+    SMaxAge(u32),
+    /// Extension directive
+    Extension(String, Option<String>),
+}
+
+impl fmt::Display for CacheDirective {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheDirective::NoCache => f.write_str("no-cache"),
+            CacheDirective::NoStore => f.write_str("no-store"),
+            CacheDirective::NoTransform => f.write_str("no-transform"),
+            CacheDirective::OnlyIfCached => f.write_str("only-if-cached"),
+            CacheDirective::MustRevalidate => f.write_str("must-revalidate"),
+            CacheDirective::ProxyRevalidate => f.write_str("proxy-revalidate"),
+            CacheDirective::Public => f.write_str("public"),
+            CacheDirective::Private => f.write_str("private"),
+            CacheDirective::Immutable => f.write_str("immutable"),
+            CacheDirective::MaxAge(secs) => write!(f, "max-age={}", secs),
+            CacheDirective::MaxStale(None) => f.write_str("max-stale"),
+            CacheDirective::MaxStale(Some(secs)) => write!(f, "max-stale={}", secs),
+            CacheDirective::MinFresh(secs) => write!(f, "min-fresh={}", secs),
+            CacheDirective::SMaxAge(secs) => write!(f, "s-maxage={}", secs),
+            CacheDirective::Extension(name, None) => f.write_str(name),
+            CacheDirective::Extension(name, Some(value)) => {
+                write!(f, "{}={}", name, quote(value))
+            }
+        }
+    }
+}
+
+/// `Cache-Control` header, RFC 9111 section 5.2
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CacheControl(pub Vec<CacheDirective>);
+
+impl CacheControl {
+    /// Check if directive is present
+    pub fn contains(&self, directive: &CacheDirective) -> bool {
+        self.0.contains(directive)
+    }
+
+    /// Value of `max-age` directive
+    pub fn max_age(&self) -> Option<u32> {
+        self.0.iter().find_map(|d| match d {
+            CacheDirective::MaxAge(secs) => Some(*secs),
+            _ => None,
+        })
+    }
+}
+
+impl TypedHeader for CacheControl {
+    fn name() -> HeaderName {
+        header::CACHE_CONTROL
+    }
+
+    fn decode<'a, I>(values: I) -> Result<Self, TypedHeaderError>
+    where
+        I: Iterator<Item = &'a HeaderValue>,
+    {
+        let mut directives = Vec::new();
+        for item in list::<Self, _>(values)? {
+            let (name, value) = param(item).ok_or_else(invalid::<Self>)?;
+            if !is_token(name) {
+                return Err(invalid::<Self>());
+            }
+            let name = name.to_ascii_lowercase();
+            let secs = || {
+                value
+                    .as_ref()
+                    .and_then(|v| v.parse::<u32>().ok())
+                    .ok_or_else(invalid::<Self>)
+            };
+
+            directives.push(match name.as_str() {
+                "no-cache" => CacheDirective::NoCache,
+                "no-store" => CacheDirective::NoStore,
+                "no-transform" => CacheDirective::NoTransform,
+                "only-if-cached" => CacheDirective::OnlyIfCached,
+                "must-revalidate" => CacheDirective::MustRevalidate,
+                "proxy-revalidate" => CacheDirective::ProxyRevalidate,
+                "public" => CacheDirective::Public,
+                "private" => CacheDirective::Private,
+                "immutable" => CacheDirective::Immutable,
+                "max-age" => CacheDirective::MaxAge(secs()?),
+                "max-stale" if value.is_none() => CacheDirective::MaxStale(None),
+                "max-stale" => CacheDirective::MaxStale(Some(secs()?)),
+                "min-fresh" => CacheDirective::MinFresh(secs()?),
+                "s-maxage" => CacheDirective::SMaxAge(secs()?),
+                _ => CacheDirective::Extension(name, value.map(|v| v.into_owned())),
+            });
+        }
+        Ok(CacheControl(directives))
+    }
+
+    fn encode(&self) -> Result<HeaderValue, InvalidHeaderValue> {
+        let items: Vec<_> = self.0.iter().map(|d| d.to_string()).collect();
+        encode_str(items.join(", "))
+    }
+}
+
+/// Entity tag, RFC 9110 section 8.8.3
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EntityTag {
+    /// Weak validator
+    pub weak: bool,
+    /// Opaque tag, without quotes
+    pub tag: String,
+}
+
+impl EntityTag {
+    /// Create strong entity tag
+    pub fn strong(tag: &str) -> Self {
+        EntityTag {
+            weak: false,
+            tag: tag.to_string(),
+        }
+    }
+
+    /// Create weak entity tag
+    pub fn weak(tag: &str) -> Self {
+        EntityTag {
+            weak: true,
+            tag: tag.to_string(),
+        }
+    }
+
+    /// Strong comparison, both tags must be strong
+    pub fn strong_eq(&self, other: &EntityTag) -> bool {
+        !self.weak && !other.weak && self.tag == other.tag
+    }
+
+    /// Weak comparison, ignores weakness of tags
+    pub fn weak_eq(&self, other: &EntityTag) -> bool {
+        self.tag == other.tag
+    }
+}
+
+impl fmt::Display for EntityTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.weak {
+            write!(f, "W/\"{}\"", self.tag)
+        } else {
+            write!(f, "\"{}\"", self.tag)
+        }
+    }
+}
+
+impl FromStr for EntityTag {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        let s = s.trim();
+        let (weak, s) = match s.strip_prefix("W/") {
+            Some(s) => (true, s),
+            None => (false, s),
+        };
+        let tag = s
+            .strip_prefix('"')
+            .and_then(|s| s.strip_suffix('"'))
+            .ok_or(())?;
+
+        if tag.contains('"') {
+            Err(())
+        } else {
+            Ok(EntityTag {
+                weak,
+                tag: tag.to_string(),
+            })
+        }
+    }
+}
+
+/// `ETag` header, RFC 9110 section 8.8.3
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ETag(pub EntityTag);
+
+impl TypedHeader for ETag {
+    fn name() -> HeaderName {
+        header::ETAG
+    }
+
+    fn decode<'a, I>(values: I) -> Result<Self, TypedHeaderError>
+    where
+        I: Iterator<Item = &'a HeaderValue>,
+    {
+        single::<Self, _>(values)?
+            .parse()
+            .map(ETag)
+            .map_err(|_| invalid::<Self>())
+    }
+
+    fn encode(&self) -> Result<HeaderValue, InvalidHeaderValue> {
+        encode_str(self.0.to_string())
+    }
+}
+
+macro_rules! etag_list {
+    ($(#[$meta:meta])* $name:ident, $header:ident, $cmp:ident) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        pub enum $name {
+            /// `*`, matches any representation
+            Any,
+            /// List of entity tags
+            Tags(Vec<EntityTag>),
+        }
+
+        impl $name {
+            /// Check if entity tag matches the condition
+            pub fn matches(&self, tag: &EntityTag) -> bool {
+                match self {
+                    $name::Any => true,
+                    $name::Tags(tags) => tags.iter().any(|t| t.$cmp(tag)),
+                }
+            }
+        }
+
+        impl TypedHeader for $name {
+            fn name() -> HeaderName {
+                header::$header
+            }
+
+            fn decode<'a, I>(values: I) -> Result<Self, TypedHeaderError>
+            where
+                I: Iterator<Item = &'a HeaderValue>,
+            {
+                let items = list::<Self, _>(values)?;
+                if items == ["*"] {
+                    Ok($name::Any)
+                } else {
+                    items
+                        .into_iter()
+                        .map(|s| s.parse().map_err(|_| invalid::<Self>()))
+                        .collect::<Result<_, _>>()
+                        .map($name::Tags)
+                }
+            }
+
+            fn encode(&self) -> Result<HeaderValue, InvalidHeaderValue> {
+                match self {
+                    $name::Any => Ok(HeaderValue::from_static("*")),
+                    $name::Tags(tags) => {
+                        let items: Vec<_> = tags.iter().map(|t| t.to_string()).collect();
+                        encode_str(items.join(", "))
+                    }
+                }
+            }
+        }
+    };
+}
+
+etag_list!(
+    /// `If-Match` header, RFC 9110 section 13.1.1
+    ///
+    /// Uses strong comparison.
+    IfMatch,
+    IF_MATCH,
+    strong_eq
+);
+
+etag_list!(
+    /// `If-None-Match` header, RFC 9110 section 13.1.2
+    ///
+    /// Uses weak comparison.
+    IfNoneMatch,
+    IF_NONE_MATCH,
+    weak_eq
+);
+
+/// Byte range specifier
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RangeSpec {
+    /// `first-last`, inclusive
+    FromTo(u64, u64),
+    /// `first-`, from position till the end
+    From(u64),
+    /// `-length`, last bytes
+    Last(u64),
+}
+
+impl fmt::Display for RangeSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RangeSpec::FromTo(first, last) => write!(f, "{}-{}", first, last),
+            RangeSpec::From(first) => write!(f, "{}-", first),
+            RangeSpec::Last(length) => write!(f, "-{}", length),
+        }
+    }
+}
+
+/// Max number of ranges in `Range` header
+const MAX_RANGES: usize = 64;
+
+/// `Range` header with `bytes` unit, RFC 9110 section 14.2
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Range(pub Vec<RangeSpec>);
+
+impl FromStr for Range {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        let s = s.trim();
+        let specs = match s.get(..6) {
+            Some(unit) if unit.eq_ignore_ascii_case("bytes=") => &s[6..],
+            _ => return Err(()),
+        };
+
+        let parse = |s: &str| s.trim().parse::<u64>().map_err(|_| ());
+        let mut ranges = Vec::new();
+        for spec in split(specs, b',') {
+            if ranges.len() >= MAX_RANGES {
+                return Err(());
+            }
+            let (first, last) = spec.split_once('-').ok_or(())?;
+            ranges.push(match (first.trim().is_empty(), last.trim().is_empty()) {
+                (true, false) => RangeSpec::Last(parse(last)?),
+                (false, true) => RangeSpec::From(parse(first)?),
+                (false, false) => {
+                    let (first, last) = (parse(first)?, parse(last)?);
+                    if first > last {
+                        return Err(());
+                    }
+                    RangeSpec::FromTo(first, last)
+                }
+                (true, true) => return Err(()),
+            });
+        }
+
+        if ranges.is_empty() {
+            Err(())
+        } else {
+            Ok(Range(ranges))
+        }
+    }
+}
+
+impl TypedHeader for Range {
+    fn name() -> HeaderName {
+        header::RANGE
+    }
+
+    fn decode<'a, I>(values: I) -> Result<Self, TypedHeaderError>
+    where
+        I: Iterator<Item = &'a HeaderValue>,
+    {
+        single::<Self, _>(values)?
+            .parse()
+            .map_err(|_| invalid::<Self>())
+    }
+
+    fn encode(&self) -> Result<HeaderValue, InvalidHeaderValue> {
+        let specs: Vec<_> = self.0.iter().map(|s| s.to_string()).collect();
+        encode_str(format!("bytes={}", specs.join(",")))
+    }
+}
+
+/// `Content-Type` header, RFC 9110 section 8.3
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContentType {
+    media_type: String,
+    params: Vec<(String, String)>,
+}
+
+impl ContentType {
+    /// Create content type for media type, e.g. `application/json`
+    pub fn new(media_type: &str) -> Self {
+        ContentType {
+            media_type: media_type.to_ascii_lowercase(),
+            params: Vec::new(),
+        }
+    }
+
+    /// Add media type parameter
+    pub fn with_param(mut self, name: &str, value: &str) -> Self {
+        self.params
+            .push((name.to_ascii_lowercase(), value.to_string()));
+        self
+    }
+
+    /// Media type without parameters, in lower case
+    pub fn media_type(&self) -> &str {
+        &self.media_type
+    }
+
+    /// Value of media type parameter
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Value of `charset` parameter
+    pub fn charset(&self) -> Option<&str> {
+        self.param("charset")
+    }
+}
+
+impl fmt::Display for ContentType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.media_type)?;
+        for (name, value) in &self.params {
+            write!(f, "; {}={}", name, quote(value))?;
+        }
+        Ok(())
+    }
+}
+
+impl TypedHeader for ContentType {
+    fn name() -> HeaderName {
+        header::CONTENT_TYPE
+    }
+
+    fn decode<'a, I>(values: I) -> Result<Self, TypedHeaderError>
+    where
+        I: Iterator<Item = &'a HeaderValue>,
+    {
+        let value = single::<Self, _>(values)?;
+        let mut parts = split(value, b';');
+        let media_type = parts.next().unwrap_or("");
+        if !media_type
+            .split_once('/')
+            .is_some_and(|(t, s)| is_token(t) && is_token(s))
+        {
+            return Err(invalid::<Self>());
+        }
+
+        let mut ct = ContentType::new(media_type);
+        for part in parts {
+            match param(part) {
+                Some((name, Some(value))) if is_token(name) => {
+                    ct = ct.with_param(name, &value);
+                }
+                _ => return Err(invalid::<Self>()),
+            }
+        }
+        Ok(ct)
+    }
+
+    fn encode(&self) -> Result<HeaderValue, InvalidHeaderValue> {
+        encode_str(self.to_string())
+    }
+}
+
+/// Element of `Forwarded` header
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ForwardedElement {
+    /// Interface where the request came in to the proxy server, `by` parameter
+    pub by: Option<String>,
+    /// Client that initiated the request, `for` parameter
+    pub forwarded_for: Option<String>,
+    /// Host request header as received by the proxy, `host` parameter
+    pub host: Option<String>,
+    /// Protocol used to make the request, `proto` parameter
+    pub proto: Option<String>,
+}
+
+/// `Forwarded` header, RFC 7239
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Forwarded(pub Vec<ForwardedElement>);
+
+impl TypedHeader for Forwarded {
+    fn name() -> HeaderName {
+        header::FORWARDED
+    }
+
+    fn decode<'a, I>(values: I) -> Result<Self, TypedHeaderError>
+    where
+        I: Iterator<Item = &'a HeaderValue>,
+    {
+        let mut elements = Vec::new();
+        for item in list::<Self, _>(values)? {
+            let mut el = ForwardedElement::default();
+            for pair in split(item, b';') {
+                let (name, value) = match param(pair) {
+                    Some((name, Some(value))) if is_token(name) => (name, value),
+                    _ => return Err(invalid::<Self>()),
+                };
+                let value = Some(value.into_owned());
+                if name.eq_ignore_ascii_case("by") {
+                    el.by = value;
+                } else if name.eq_ignore_ascii_case("for") {
+                    el.forwarded_for = value;
+                } else if name.eq_ignore_ascii_case("host") {
+                    el.host = value;
+                } else if name.eq_ignore_ascii_case("proto") {
+                    el.proto = value;
+                }
+            }
+            elements.push(el);
+        }
+        Ok(Forwarded(elements))
+    }
+
+    fn encode(&self) -> Result<HeaderValue, InvalidHeaderValue> {
+        let mut elements = Vec::new();
+        for el in &self.0 {
+            let pairs: Vec<_> = [
+                ("by", &el.by),
+                ("for", &el.forwarded_for),
+                ("host", &el.host),
+                ("proto", &el.proto),
+            ]
+            .into_iter()
+            .filter_map(|(name, value)| {
+                value
+                    .as_ref()
+                    .map(|value| format!("{}={}", name, quote(value)))
+            })
+            .collect();
+            elements.push(pairs.join(";"));
+        }
+        encode_str(elements.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HeaderMap;
+
+    fn decode<H: TypedHeader>(values: &[&'static str]) -> Result<H, TypedHeaderError> {
+        let values: Vec<_> = values
+            .iter()
+            .map(|v| HeaderValue::from_static(*v))
+            .collect();
+        H::decode(values.iter())
+    }
+
+    fn encode<H: TypedHeader>(h: H) -> String {
+        h.encode().unwrap().to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_accept() {
+        let accept: Accept = decode(&[
+            "text/html, application/*;q=0.5",
+            "*/*; q=0.1, Text/Plain;q=0",
+        ])
+        .unwrap();
+        assert_eq!(accept.0.len(), 4);
+        assert_eq!(accept.quality("text/html"), 1000);
+        assert_eq!(accept.quality("text/plain; charset=utf-8"), 0);
+        assert_eq!(accept.quality("application/json"), 500);
+        assert_eq!(accept.quality("image/png"), 100);
+        assert_eq!(accept.ranges().next().unwrap().item, "text/html");
+        assert_eq!(
+            encode(accept),
+            "text/html, application/*;q=0.5, */*;q=0.1, text/plain;q=0"
+        );
+
+        assert!(decode::<Accept>(&["text/html;q=2"]).is_err());
+        assert!(decode::<Accept>(&["text"]).is_err());
+        assert_eq!(
+            decode::<Accept>(&[]),
+            Err(TypedHeaderError::Missing(header::ACCEPT))
+        );
+    }
+
+    #[test]
+    fn test_authorization() {
+        let auth: Authorization = decode(&["Basic dXNlcjpwYXNzOndvcmQ="]).unwrap();
+        assert_eq!(auth, Authorization::basic("user", "pass:word"));
+        assert!(!format!("{:?}", auth).contains("pass"));
+        let value = auth.encode().unwrap();
+        assert!(value.is_sensitive());
+        assert_eq!(value, "Basic dXNlcjpwYXNzOndvcmQ=");
+
+        let auth: Authorization = decode(&["bearer  token"]).unwrap();
+        assert_eq!(auth, Authorization::bearer("token"));
+        assert_eq!(encode(auth), "Bearer token");
+
+        let auth: Authorization = decode(&["Digest username=\"u\""]).unwrap();
+        assert_eq!(encode(auth), "Digest username=\"u\"");
+
+        assert!(decode::<Authorization>(&["Basic"]).is_err());
+        assert!(decode::<Authorization>(&["Basic !!!"]).is_err());
+        assert!(decode::<Authorization>(&["Basic dXNlcg=="]).is_err());
+    }
+
+    #[test]
+    fn test_cache_control() {
+        let cc: CacheControl =
+            decode(&["no-cache, max-age=60", "max-stale, x-ext=\"a, b\""]).unwrap();
+        assert_eq!(
+            cc.0,
+            vec![
+                CacheDirective::NoCache,
+                CacheDirective::MaxAge(60),
+                CacheDirective::MaxStale(None),
+                CacheDirective::Extension("x-ext".to_string(), Some("a, b".to_string())),
+            ]
+        );
+        assert_eq!(cc.max_age(), Some(60));
+        assert!(cc.contains(&CacheDirective::NoCache));
+        assert_eq!(
+            encode(cc),
+            "no-cache, max-age=60, max-stale, x-ext=\"a, b\""
+        );
+        assert!(decode::<CacheControl>(&["max-age=abc"]).is_err());
+    }
+
+    #[test]
+    fn test_etag() {
+        let etag: ETag = decode(&["W/\"xyz\""]).unwrap();
+        assert_eq!(etag.0, EntityTag::weak("xyz"));
+        assert_eq!(encode(etag), "W/\"xyz\"");
+        assert!(decode::<ETag>(&["xyz"]).is_err());
+
+        let m: IfMatch = decode(&["\"a\", W/\"b\"", "\"c,d\""]).unwrap();
+        assert!(m.matches(&EntityTag::strong("a")));
+        assert!(!m.matches(&EntityTag::strong("b")));
+        assert!(m.matches(&EntityTag::strong("c,d")));
+        assert_eq!(encode(m), "\"a\", W/\"b\", \"c,d\"");
+
+        let m: IfNoneMatch = decode(&["W/\"b\""]).unwrap();
+        assert!(m.matches(&EntityTag::strong("b")));
+        let m: IfNoneMatch = decode(&["*"]).unwrap();
+        assert_eq!(m, IfNoneMatch::Any);
+        assert!(m.matches(&EntityTag::strong("x")));
+    }
+
+    #[test]
+    fn test_range() {
+        let range: Range = decode(&["bytes=0-99, 200-, -50"]).unwrap();
+        assert_eq!(
+            range.0,
+            vec![
+                RangeSpec::FromTo(0, 99),
+                RangeSpec::From(200),
+                RangeSpec::Last(50)
+            ]
+        );
+        assert_eq!(encode(range), "bytes=0-99,200-,-50");
+        assert!(decode::<Range>(&["bytes=10-5"]).is_err());
+        assert!(decode::<Range>(&["items=0-1"]).is_err());
+        assert!(decode::<Range>(&["bytes=-"]).is_err());
+        assert!(decode::<Range>(&["bytes="]).is_err());
+
+        let many = vec!["0-1"; MAX_RANGES + 1].join(",");
+        assert!(format!("bytes={}", &many[4..]).parse::<Range>().is_ok());
+        assert!(format!("bytes={}", many).parse::<Range>().is_err());
+    }
+
+    #[test]
+    fn test_content_type() {
+        let ct: ContentType = decode(&["Text/HTML; Charset=\"utf-8\""]).unwrap();
+        assert_eq!(ct.media_type(), "text/html");
+        assert_eq!(ct.charset(), Some("utf-8"));
+        assert_eq!(encode(ct), "text/html; charset=utf-8");
+        assert!(decode::<ContentType>(&["text"]).is_err());
+        assert!(decode::<ContentType>(&["text/html; charset"]).is_err());
+
+        let ct = ContentType::new("multipart/form-data").with_param("boundary", "a b");
+        assert_eq!(encode(ct), "multipart/form-data; boundary=\"a b\"");
+    }
+
+    #[test]
+    fn test_forwarded() {
+        let fwd: Forwarded =
+            decode(&["for=192.0.2.43;proto=https, for=\"[2001:db8::1]:4711\";by=10.0.0.1"])
+                .unwrap();
+        assert_eq!(fwd.0.len(), 2);
+        assert_eq!(fwd.0[0].forwarded_for.as_deref(), Some("192.0.2.43"));
+        assert_eq!(fwd.0[0].proto.as_deref(), Some("https"));
+        assert_eq!(
+            fwd.0[1].forwarded_for.as_deref(),
+            Some("[2001:db8::1]:4711")
+        );
+        assert_eq!(fwd.0[1].by.as_deref(), Some("10.0.0.1"));
+        assert_eq!(
+            encode(fwd),
+            "for=192.0.2.43;proto=https, by=10.0.0.1;for=\"[2001:db8::1]:4711\""
+        );
+        assert!(decode::<Forwarded>(&["for"]).is_err());
+    }
+
+    #[test]
+    fn test_header_map() {
+        let mut map = HeaderMap::new();
+        assert_eq!(
+            map.typed_get::<ETag>(),
+            Err(TypedHeaderError::Missing(header::ETAG))
+        );
+        map.typed_insert(ETag(EntityTag::strong("v1"))).unwrap();
+        assert_eq!(map.get(header::ETAG).unwrap(), "\"v1\"");
+        assert_eq!(map.typed_get::<ETag>().unwrap().0.tag, "v1");
+        assert_eq!(
+            TypedHeaderError::Invalid(header::ETAG).to_string(),
+            "Header etag is malformed"
+        );
+    }
+}
@@ -5,6 +5,7 @@
 use std::io::{Error as IoError, Result as IoResult};
 use std::{any::Any, any::TypeId, fmt, task::Context, task::Poll};
 
+pub mod proxy;
 pub mod testing;
 pub mod types;
 
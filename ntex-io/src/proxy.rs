@@ -0,0 +1,556 @@
+//! HAProxy PROXY protocol support
+//!
+//! [`ProxyAcceptor`] reads PROXY protocol v1 or v2 header from incoming
+//! connection and adds [`ProxyFilter`] layer to the io stream. Filter
+//! exposes parsed [`ProxyHeader`] via `IoRef::query()` and reports
+//! connection's source address as [`PeerAddr`].
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::{any, fmt, io, str};
+
+use ntex_bytes::{Buf, Bytes, BytesMut};
+use ntex_codec::Decoder;
+use ntex_service::{Service, ServiceCtx, ServiceFactory};
+use ntex_util::{future::Either, time, time::Millis};
+
+use crate::{types::PeerAddr, Filter, FilterLayer, Io, Layer, ReadBuf, WriteBuf};
+
+/// Application-Layer Protocol Negotiation TLV
+pub const PP2_TYPE_ALPN: u8 = 0x01;
+/// Host name TLV
+pub const PP2_TYPE_AUTHORITY: u8 = 0x02;
+/// CRC32c checksum TLV
+pub const PP2_TYPE_CRC32C: u8 = 0x03;
+/// Unique connection id TLV
+pub const PP2_TYPE_UNIQUE_ID: u8 = 0x05;
+/// SSL information TLV
+pub const PP2_TYPE_SSL: u8 = 0x20;
+/// Network namespace TLV
+pub const PP2_TYPE_NETNS: u8 = 0x30;
+
+const V1_PREFIX: &[u8] = b"PROXY ";
+const V1_MAX_LENGTH: usize = 107;
+const V2_SIGNATURE: &[u8] = b"\r\n\r\n\0\r\nQUIT\n";
+const V2_HEADER_LENGTH: usize = 16;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// Parsed PROXY protocol header
+pub struct ProxyHeader {
+    version: u8,
+    source: Option<SocketAddr>,
+    destination: Option<SocketAddr>,
+    tlvs: Vec<(u8, Bytes)>,
+}
+
+impl ProxyHeader {
+    /// PROXY protocol version, 1 or 2
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// Source address of the proxied connection
+    ///
+    /// Address is not available for `LOCAL` and `UNKNOWN` connections
+    /// and for unix sockets.
+    pub fn source(&self) -> Option<SocketAddr> {
+        self.source
+    }
+
+    /// Destination address of the proxied connection
+    pub fn destination(&self) -> Option<SocketAddr> {
+        self.destination
+    }
+
+    /// Get value of the TLV by type
+    pub fn tlv(&self, kind: u8) -> Option<&Bytes> {
+        self.tlvs.iter().find(|(k, _)| *k == kind).map(|(_, v)| v)
+    }
+
+    /// List of all TLVs, available for v2 headers only
+    pub fn tlvs(&self) -> &[(u8, Bytes)] {
+        &self.tlvs
+    }
+}
+
+#[derive(Debug)]
+/// Io filter that carries PROXY protocol header of the connection
+pub struct ProxyFilter {
+    header: ProxyHeader,
+}
+
+impl ProxyFilter {
+    /// Get PROXY protocol header
+    pub fn header(&self) -> &ProxyHeader {
+        &self.header
+    }
+}
+
+impl FilterLayer for ProxyFilter {
+    fn query(&self, id: any::TypeId) -> Option<Box<dyn any::Any>> {
+        if id == any::TypeId::of::<ProxyHeader>() {
+            Some(Box::new(self.header.clone()))
+        } else if id == any::TypeId::of::<PeerAddr>() {
+            self.header
+                .source
+                .map(|addr| Box::new(PeerAddr(addr)) as Box<dyn any::Any>)
+        } else {
+            None
+        }
+    }
+
+    fn process_read_buf(&self, buf: &ReadBuf<'_>) -> io::Result<usize> {
+        if let Some(src) = buf.take_src() {
+            let nbytes = src.len();
+            buf.set_dst(Some(src));
+            Ok(nbytes)
+        } else {
+            Ok(0)
+        }
+    }
+
+    fn process_write_buf(&self, buf: &WriteBuf<'_>) -> io::Result<()> {
+        if let Some(src) = buf.take_src() {
+            buf.set_dst(Some(src));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+/// Service factory for PROXY protocol acceptor
+///
+/// Acceptor supports both v1 and v2 versions of the protocol.
+/// Connections without valid PROXY header are rejected.
+pub struct ProxyAcceptor {
+    timeout: Millis,
+}
+
+impl Default for ProxyAcceptor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProxyAcceptor {
+    /// Create PROXY protocol acceptor
+    pub fn new() -> Self {
+        ProxyAcceptor {
+            timeout: Millis(5_000),
+        }
+    }
+
+    /// Set timeout for reading PROXY header.
+    ///
+    /// Default is set to 5 seconds.
+    pub fn timeout<U: Into<Millis>>(mut self, timeout: U) -> Self {
+        self.timeout = timeout.into();
+        self
+    }
+}
+
+impl<F: Filter, C> ServiceFactory<Io<F>, C> for ProxyAcceptor {
+    type Response = Io<Layer<ProxyFilter, F>>;
+    type Error = io::Error;
+    type Service = ProxyAcceptorService;
+    type InitError = ();
+
+    async fn create(&self, _: C) -> Result<Self::Service, Self::InitError> {
+        Ok(ProxyAcceptorService {
+            timeout: self.timeout,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+/// PROXY protocol acceptor service
+pub struct ProxyAcceptorService {
+    timeout: Millis,
+}
+
+impl<F: Filter> Service<Io<F>> for ProxyAcceptorService {
+    type Response = Io<Layer<ProxyFilter, F>>;
+    type Error = io::Error;
+
+    async fn call(
+        &self,
+        io: Io<F>,
+        _: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        let result = time::timeout(self.timeout, io.recv(&HeaderCodec))
+            .await
+            .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "proxy header timeout"))?;
+
+        match result {
+            Ok(Some(header)) => {
+                log::trace!("{}: Proxy protocol header {:?}", io.tag(), header);
+                Ok(io.add_filter(ProxyFilter { header }))
+            }
+            Ok(None) => Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "peer is disconnected",
+            )),
+            Err(Either::Left(err)) | Err(Either::Right(err)) => Err(err),
+        }
+    }
+}
+
+/// PROXY header decoder
+struct HeaderCodec;
+
+impl Decoder for HeaderCodec {
+    type Item = ProxyHeader;
+    type Error = io::Error;
+
+    fn decode(&self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        let len = src.len();
+        if len >= V2_SIGNATURE.len() && src.starts_with(V2_SIGNATURE) {
+            decode_v2(src)
+        } else if len >= V1_PREFIX.len() && src.starts_with(V1_PREFIX) {
+            decode_v1(src)
+        } else if V2_SIGNATURE.starts_with(&src[..]) || V1_PREFIX.starts_with(&src[..]) {
+            Ok(None)
+        } else {
+            Err(invalid("unknown header"))
+        }
+    }
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("invalid proxy protocol header: {}", msg),
+    )
+}
+
+fn decode_v1(src: &mut BytesMut) -> io::Result<Option<ProxyHeader>> {
+    let end = if let Some(pos) = src.windows(2).position(|w| w == b"\r\n") {
+        pos
+    } else if src.len() >= V1_MAX_LENGTH {
+        return Err(invalid("line is too long"));
+    } else {
+        return Ok(None);
+    };
+    if end + 2 > V1_MAX_LENGTH {
+        return Err(invalid("line is too long"));
+    }
+
+    let line = src.split_to(end + 2);
+    let line = str::from_utf8(&line[V1_PREFIX.len()..end])
+        .map_err(|_| invalid("non-ascii characters"))?;
+    let mut parts = line.split(' ');
+
+    let (source, destination) = match parts.next() {
+        Some("TCP4") | Some("TCP6") => {
+            let mut next = || parts.next().ok_or_else(|| invalid("missing address"));
+            let src_ip: IpAddr = next()?.parse().map_err(|_| invalid("source address"))?;
+            let dst_ip: IpAddr = next()?.parse().map_err(|_| invalid("dest address"))?;
+            let src_port: u16 = next()?.parse().map_err(|_| invalid("source port"))?;
+            let dst_port: u16 = next()?.parse().map_err(|_| invalid("dest port"))?;
+            if parts.next().is_some() {
+                return Err(invalid("unexpected data"));
+            }
+            (
+                Some(SocketAddr::new(src_ip, src_port)),
+                Some(SocketAddr::new(dst_ip, dst_port)),
+            )
+        }
+        Some("UNKNOWN") => (None, None),
+        _ => return Err(invalid("unknown protocol")),
+    };
+
+    Ok(Some(ProxyHeader {
+        source,
+        destination,
+        version: 1,
+        tlvs: Vec::new(),
+    }))
+}
+
+fn decode_v2(src: &mut BytesMut) -> io::Result<Option<ProxyHeader>> {
+    if src.len() < V2_HEADER_LENGTH {
+        return Ok(None);
+    }
+    let ver_cmd = src[12];
+    let family = src[13];
+    let len = u16::from_be_bytes([src[14], src[15]]) as usize;
+
+    if ver_cmd >> 4 != 2 {
+        return Err(invalid("unsupported version"));
+    }
+    let local = match ver_cmd & 0x0f {
+        0 => true,
+        1 => false,
+        _ => return Err(invalid("unsupported command")),
+    };
+    if src.len() < V2_HEADER_LENGTH + len {
+        return Ok(None);
+    }
+
+    src.advance(V2_HEADER_LENGTH);
+    let mut data = src.split_to(len).freeze();
+
+    let (addr_len, addrs) = match family >> 4 {
+        // AF_UNSPEC
+        0 => (0, None),
+        // AF_INET
+        1 => {
+            if data.len() < 12 {
+                return Err(invalid("address is too short"));
+            }
+            let src_ip = Ipv4Addr::new(data[0], data[1], data[2], data[3]);
+            let dst_ip = Ipv4Addr::new(data[4], data[5], data[6], data[7]);
+            let src_port = u16::from_be_bytes([data[8], data[9]]);
+            let dst_port = u16::from_be_bytes([data[10], data[11]]);
+            (
+                12,
+                Some((
+                    SocketAddr::new(src_ip.into(), src_port),
+                    SocketAddr::new(dst_ip.into(), dst_port),
+                )),
+            )
+        }
+        // AF_INET6
+        2 => {
+            if data.len() < 36 {
+                return Err(invalid("address is too short"));
+            }
+            let mut ip = [0u8; 16];
+            ip.copy_from_slice(&data[0..16]);
+            let src_ip = Ipv6Addr::from(ip);
+            ip.copy_from_slice(&data[16..32]);
+            let dst_ip = Ipv6Addr::from(ip);
+            let src_port = u16::from_be_bytes([data[32], data[33]]);
+            let dst_port = u16::from_be_bytes([data[34], data[35]]);
+            (
+                36,
+                Some((
+                    SocketAddr::new(src_ip.into(), src_port),
+                    SocketAddr::new(dst_ip.into(), dst_port),
+                )),
+            )
+        }
+        // AF_UNIX
+        3 => {
+            if data.len() < 216 {
+                return Err(invalid("address is too short"));
+            }
+            (216, None)
+        }
+        _ => return Err(invalid("unsupported address family")),
+    };
+    data.advance(addr_len);
+
+    let mut tlvs = Vec::new();
+    while !data.is_empty() {
+        if data.len() < 3 {
+            return Err(invalid("tlv is too short"));
+        }
+        let kind = data[0];
+        let len = u16::from_be_bytes([data[1], data[2]]) as usize;
+        if data.len() < 3 + len {
+            return Err(invalid("tlv is too short"));
+        }
+        data.advance(3);
+        tlvs.push((kind, data.split_to(len)));
+    }
+
+    let (source, destination) = match addrs {
+        Some((src, dst)) if !local => (Some(src), Some(dst)),
+        _ => (None, None),
+    };
+    Ok(Some(ProxyHeader {
+        source,
+        destination,
+        tlvs,
+        version: 2,
+    }))
+}
+
+impl fmt::Display for ProxyHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.source, self.destination) {
+            (Some(src), Some(dst)) => {
+                write!(f, "PROXY v{} {} -> {}", self.version, src, dst)
+            }
+            _ => write!(f, "PROXY v{} LOCAL", self.version),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ntex_service::Pipeline;
+
+    use super::*;
+    use crate::testing::IoTest;
+
+    fn v2_header(cmd: u8, family: u8, addr: &[u8], tlvs: &[(u8, &[u8])]) -> Vec<u8> {
+        let mut payload = addr.to_vec();
+        for (kind, val) in tlvs {
+            payload.push(*kind);
+            payload.extend_from_slice(&(val.len() as u16).to_be_bytes());
+            payload.extend_from_slice(val);
+        }
+        let mut buf = V2_SIGNATURE.to_vec();
+        buf.push(0x20 | cmd);
+        buf.push(family);
+        buf.extend_from_slice(&(payload.len() as u16).to_be_bytes());
+        buf.extend_from_slice(&payload);
+        buf
+    }
+
+    #[test]
+    fn decode_v1_headers() {
+        let mut buf =
+            BytesMut::from(&b"PROXY TCP4 192.168.0.1 192.168.0.11 56324 443\r\nGET"[..]);
+        let hdr = HeaderCodec.decode(&mut buf).unwrap().unwrap();
+        assert_eq!(hdr.version(), 1);
+        assert_eq!(hdr.source(), Some("192.168.0.1:56324".parse().unwrap()));
+        assert_eq!(hdr.destination(), Some("192.168.0.11:443".parse().unwrap()));
+        assert!(hdr.tlvs().is_empty());
+        assert_eq!(&buf[..], b"GET");
+        assert_eq!(
+            hdr.to_string(),
+            "PROXY v1 192.168.0.1:56324 -> 192.168.0.11:443"
+        );
+
+        let mut buf = BytesMut::from(&b"PROXY TCP6 ::1 ::2 1 2\r\n"[..]);
+        let hdr = HeaderCodec.decode(&mut buf).unwrap().unwrap();
+        assert_eq!(hdr.source(), Some("[::1]:1".parse().unwrap()));
+
+        let mut buf = BytesMut::from(&b"PROXY UNKNOWN ffff::1 ffff::2 1 2\r\n"[..]);
+        let hdr = HeaderCodec.decode(&mut buf).unwrap().unwrap();
+        assert_eq!(hdr.source(), None);
+        assert_eq!(hdr.to_string(), "PROXY v1 LOCAL");
+
+        // partial header
+        let mut buf = BytesMut::from(&b"PRO"[..]);
+        assert!(HeaderCodec.decode(&mut buf).unwrap().is_none());
+        let mut buf = BytesMut::from(&b"PROXY TCP4 192.168.0.1"[..]);
+        assert!(HeaderCodec.decode(&mut buf).unwrap().is_none());
+
+        // invalid headers
+        for item in [
+            "GET / HTTP/1.1\r\n",
+            "PROXY UDP4 1.1.1.1 1.1.1.2 1 2\r\n",
+            "PROXY TCP4 1.1.1.1 1.1.1.2 1\r\n",
+            "PROXY TCP4 1.1.1.1 1.1.1.2 1 70000\r\n",
+            "PROXY TCP4 1.1.1.1 1.1.1.2 1 2 3\r\n",
+            "PROXY TCP4 1.1.1.x 1.1.1.2 1 2\r\n",
+        ] {
+            let mut buf = BytesMut::from(item.as_bytes());
+            assert!(HeaderCodec.decode(&mut buf).is_err(), "{:?}", item);
+        }
+        let mut buf = BytesMut::from(format!("PROXY {}", "1".repeat(120)).as_bytes());
+        assert!(HeaderCodec.decode(&mut buf).is_err());
+    }
+
+    #[test]
+    fn decode_v2_headers() {
+        let addr = [127, 0, 0, 1, 127, 0, 0, 2, 0x1f, 0x90, 0x00, 0x50];
+        let data = v2_header(1, 0x11, &addr, &[(PP2_TYPE_AUTHORITY, b"example.com")]);
+        let mut buf = BytesMut::from(&data[..]);
+        buf.extend_from_slice(b"GET");
+
+        let hdr = HeaderCodec.decode(&mut buf).unwrap().unwrap();
+        assert_eq!(hdr.version(), 2);
+        assert_eq!(hdr.source(), Some("127.0.0.1:8080".parse().unwrap()));
+        assert_eq!(hdr.destination(), Some("127.0.0.2:80".parse().unwrap()));
+        assert_eq!(
+            hdr.tlv(PP2_TYPE_AUTHORITY),
+            Some(&Bytes::from_static(b"example.com"))
+        );
+        assert!(hdr.tlv(PP2_TYPE_ALPN).is_none());
+        assert_eq!(&buf[..], b"GET");
+
+        // ipv6
+        let mut addr = [0u8; 36];
+        addr[15] = 1;
+        addr[31] = 2;
+        addr[33] = 1;
+        addr[35] = 2;
+        let data = v2_header(1, 0x21, &addr, &[]);
+        let hdr = HeaderCodec
+            .decode(&mut BytesMut::from(&data[..]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(hdr.source(), Some("[::1]:1".parse().unwrap()));
+        assert_eq!(hdr.destination(), Some("[::2]:2".parse().unwrap()));
+
+        // local command
+        let data = v2_header(0, 0x11, &[0; 12], &[]);
+        let hdr = HeaderCodec
+            .decode(&mut BytesMut::from(&data[..]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(hdr.source(), None);
+
+        // partial header
+        for len in [5, 14, data.len() - 1] {
+            let mut buf = BytesMut::from(&data[..len]);
+            assert!(HeaderCodec.decode(&mut buf).unwrap().is_none());
+        }
+
+        // invalid headers
+        let mut data = v2_header(1, 0x11, &[0; 12], &[]);
+        data[12] = 0x11;
+        assert!(HeaderCodec.decode(&mut BytesMut::from(&data[..])).is_err());
+        let data = v2_header(2, 0x11, &[0; 12], &[]);
+        assert!(HeaderCodec.decode(&mut BytesMut::from(&data[..])).is_err());
+        let data = v2_header(1, 0x11, &[0; 8], &[]);
+        assert!(HeaderCodec.decode(&mut BytesMut::from(&data[..])).is_err());
+        let data = v2_header(1, 0x41, &[0; 12], &[]);
+        assert!(HeaderCodec.decode(&mut BytesMut::from(&data[..])).is_err());
+        let data = v2_header(1, 0x11, &[0; 14], &[]);
+        assert!(HeaderCodec.decode(&mut BytesMut::from(&data[..])).is_err());
+    }
+
+    #[ntex::test]
+    async fn acceptor() {
+        let (client, server) = IoTest::create();
+        client.remote_buffer_cap(1024);
+        client.write("PROXY TCP4 10.0.0.1 10.0.0.2 1000 80\r\nhello");
+
+        let factory = ProxyAcceptor::new().timeout(Millis(1_000));
+        let acceptor = Pipeline::new(
+            ServiceFactory::<Io, ()>::create(&factory, ())
+                .await
+                .unwrap(),
+        );
+        let io = acceptor.call(Io::new(server)).await.unwrap();
+        assert_eq!(
+            io.query::<PeerAddr>().get(),
+            Some(PeerAddr("10.0.0.1:1000".parse().unwrap()))
+        );
+        let hdr = io.query::<ProxyHeader>();
+        assert_eq!(
+            hdr.as_ref().unwrap().destination(),
+            Some("10.0.0.2:80".parse().unwrap())
+        );
+        assert_eq!(io.filter().header().version(), 1);
+
+        assert_eq!(
+            io.recv(&ntex_codec::BytesCodec).await.unwrap().unwrap(),
+            "hello"
+        );
+        io.send(Bytes::from_static(b"world"), &ntex_codec::BytesCodec)
+            .await
+            .unwrap();
+        assert_eq!(client.read().await.unwrap(), "world");
+        assert!(format!("{:?}", ProxyAcceptor::default()).contains("ProxyAcceptor"));
+
+        // invalid header
+        let (client, server) = IoTest::create();
+        client.remote_buffer_cap(1024);
+        client.write("GET / HTTP/1.1\r\n\r\n");
+        assert!(acceptor.call(Io::new(server)).await.is_err());
+
+        // timeout
+        let (client, server) = IoTest::create();
+        client.remote_buffer_cap(1024);
+        let acceptor = Pipeline::new(ProxyAcceptorService {
+            timeout: Millis(50),
+        });
+        let err = acceptor.call(Io::new(server)).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    }
+}
@@ -0,0 +1,855 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::{cell::RefCell, cmp, collections::HashMap, fmt, fs, io, iter, rc::Rc};
+use std::{time::Duration, time::Instant};
+
+use ntex_util::future::{join, BoxFuture};
+use ntex_util::time::{now, timeout, Millis};
+
+use super::Resolve;
+use crate::tcp_connect;
+
+const DNS_PORT: u16 = 53;
+const MAX_UDP_SIZE: usize = 512;
+const MAX_NDOTS: usize = 15;
+
+const TYPE_A: u16 = 1;
+const TYPE_AAAA: u16 = 28;
+const CLASS_IN: u16 = 1;
+
+const FLAG_RESPONSE: u16 = 0x8000;
+const FLAG_TRUNCATED: u16 = 0x0200;
+const RCODE_NXDOMAIN: u16 = 3;
+
+/// Non-blocking dns resolver
+///
+/// Resolver loads nameservers, search domains and options from
+/// `/etc/resolv.conf` and static entries from `/etc/hosts`, sends `A` and
+/// `AAAA` queries in parallel and caches answers according to records ttl.
+/// Truncated udp responses are retried over tcp.
+///
+/// Queries are sent with datagram socket of the async runtime, runtimes
+/// without datagram sockets support (neon) send queries over tcp.
+/// Blocking thread pool is not used.
+#[derive(Clone)]
+pub struct DnsResolver {
+    nameservers: Vec<SocketAddr>,
+    search: Vec<String>,
+    ndots: usize,
+    timeout: Millis,
+    attempts: usize,
+    hosts: Rc<HashMap<String, Vec<IpAddr>>>,
+    cache: Rc<RefCell<HashMap<String, Entry>>>,
+    cache_size: usize,
+}
+
+struct Entry {
+    addrs: Vec<IpAddr>,
+    valid_until: Instant,
+}
+
+enum Answer {
+    Records(Vec<IpAddr>, u32),
+    Truncated,
+}
+
+impl DnsResolver {
+    /// Create resolver with system configuration
+    ///
+    /// Reads `/etc/resolv.conf` and `/etc/hosts`. If no nameservers are
+    /// configured, local nameserver is used.
+    pub fn new() -> Self {
+        let conf = fs::read_to_string("/etc/resolv.conf").unwrap_or_default();
+        let hosts = fs::read_to_string("/etc/hosts").unwrap_or_default();
+
+        let mut resolver = DnsResolver::with_nameservers(Vec::new());
+        resolver.parse_resolv_conf(&conf);
+        if resolver.nameservers.is_empty() {
+            resolver
+                .nameservers
+                .push(SocketAddr::new(Ipv4Addr::LOCALHOST.into(), DNS_PORT));
+        }
+        resolver.hosts = Rc::new(parse_hosts(&hosts));
+        resolver
+    }
+
+    /// Create resolver with provided nameservers
+    ///
+    /// System configuration files are not used.
+    pub fn with_nameservers(nameservers: Vec<SocketAddr>) -> Self {
+        DnsResolver {
+            nameservers,
+            search: Vec::new(),
+            ndots: 1,
+            timeout: Millis(5_000),
+            attempts: 2,
+            hosts: Rc::new(HashMap::new()),
+            cache: Rc::new(RefCell::new(HashMap::new())),
+            cache_size: 1024,
+        }
+    }
+
+    /// Set query timeout
+    ///
+    /// By default timeout is set to 5 seconds.
+    pub fn timeout<T: Into<Millis>>(mut self, timeout: T) -> Self {
+        self.timeout = timeout.into();
+        self
+    }
+
+    /// Set number of attempts for each nameserver
+    ///
+    /// By default 2 attempts are made.
+    pub fn attempts(mut self, attempts: usize) -> Self {
+        self.attempts = cmp::max(attempts, 1);
+        self
+    }
+
+    /// Add search domain
+    ///
+    /// Relative names are resolved within search domains, in order of
+    /// addition. See [`DnsResolver::ndots()`].
+    pub fn search<T: AsRef<str>>(mut self, domain: T) -> Self {
+        let domain = domain.as_ref().trim_matches('.').to_ascii_lowercase();
+        if !domain.is_empty() {
+            self.search.push(domain);
+        }
+        self
+    }
+
+    /// Set number of dots in a name to try it as absolute name first
+    ///
+    /// Names with fewer dots are looked up in search domains first.
+    /// By default is set to 1.
+    pub fn ndots(mut self, ndots: usize) -> Self {
+        self.ndots = cmp::min(ndots, MAX_NDOTS);
+        self
+    }
+
+    /// Set max number of cached host names
+    ///
+    /// By default 1024 names are cached. Zero disables caching.
+    pub fn cache_size(mut self, size: usize) -> Self {
+        self.cache_size = size;
+        self
+    }
+
+    /// Add static host entry
+    pub fn host<T: AsRef<str>>(mut self, name: T, addr: IpAddr) -> Self {
+        Rc::make_mut(&mut self.hosts)
+            .entry(name.as_ref().to_ascii_lowercase())
+            .or_default()
+            .push(addr);
+        self
+    }
+
+    /// Clear cached answers
+    pub fn clear_cache(&self) {
+        self.cache.borrow_mut().clear();
+    }
+
+    /// Resolve host name to ip addresses
+    ///
+    /// Names that end with dot are absolute, search domains are not used.
+    pub async fn resolve(&self, host: &str) -> io::Result<Vec<IpAddr>> {
+        let absolute = host.ends_with('.');
+        let name = host.trim_end_matches('.').to_ascii_lowercase();
+
+        if let Some(addrs) = self.hosts.get(&name) {
+            return Ok(addrs.clone());
+        }
+
+        let mut error = None;
+        for name in self.candidates(&name, absolute) {
+            match self.resolve_name(name).await {
+                Ok(addrs) if !addrs.is_empty() => return Ok(addrs),
+                Ok(_) => (),
+                Err(err) => error = Some(err),
+            }
+        }
+        error.map_or(Ok(Vec::new()), Err)
+    }
+
+    /// Names to query, according to search domains and ndots option
+    fn candidates(&self, name: &str, absolute: bool) -> Vec<String> {
+        if absolute || self.search.is_empty() {
+            return vec![name.to_string()];
+        }
+
+        let searched = self.search.iter().map(|d| format!("{}.{}", name, d));
+        if name.matches('.').count() >= self.ndots {
+            iter::once(name.to_string()).chain(searched).collect()
+        } else {
+            searched.chain(iter::once(name.to_string())).collect()
+        }
+    }
+
+    async fn resolve_name(&self, name: String) -> io::Result<Vec<IpAddr>> {
+        if let Some(entry) = self.cache.borrow().get(&name) {
+            if entry.valid_until > now() {
+                return Ok(entry.addrs.clone());
+            }
+        }
+
+        let (v6, v4) = join(self.query(&name, TYPE_AAAA), self.query(&name, TYPE_A)).await;
+
+        let mut addrs = Vec::new();
+        let mut ttl = u32::MAX;
+        let mut error = None;
+        for result in [v6, v4] {
+            match result {
+                Ok((records, record_ttl)) => {
+                    if !records.is_empty() {
+                        addrs.extend(records);
+                        ttl = cmp::min(ttl, record_ttl);
+                    }
+                }
+                Err(err) => error = Some(err),
+            }
+        }
+
+        if addrs.is_empty() {
+            if let Some(err) = error {
+                return Err(err);
+            }
+        } else if ttl > 0 && self.cache_size > 0 {
+            self.store(name, addrs.clone(), ttl);
+        }
+        Ok(addrs)
+    }
+
+    fn store(&self, name: String, addrs: Vec<IpAddr>, ttl: u32) {
+        let now = now();
+        let mut cache = self.cache.borrow_mut();
+
+        if cache.len() >= self.cache_size {
+            cache.retain(|_, entry| entry.valid_until > now);
+            if cache.len() >= self.cache_size {
+                if let Some(key) = cache.keys().next().cloned() {
+                    cache.remove(&key);
+                }
+            }
+        }
+        cache.insert(
+            name,
+            Entry {
+                addrs,
+                valid_until: now + Duration::from_secs(ttl as u64),
+            },
+        );
+    }
+
+    async fn query(&self, name: &str, qtype: u16) -> io::Result<(Vec<IpAddr>, u32)> {
+        let mut error =
+            io::Error::new(io::ErrorKind::NotFound, "No nameservers configured");
+
+        for _ in 0..self.attempts {
+            for ns in &self.nameservers {
+                let msg = encode_query(query_id()?, name, qtype)?;
+                let result = self.exchange(*ns, msg).await;
+
+                match result {
+                    Ok(Answer::Records(addrs, ttl)) => return Ok((addrs, ttl)),
+                    Ok(Answer::Truncated) => error = invalid("Truncated dns response"),
+                    Err(err) => {
+                        log::trace!("DNS Resolver - query to {} failed: {}", ns, err);
+                        error = err;
+                    }
+                }
+            }
+        }
+        Err(error)
+    }
+
+    async fn exchange(&self, ns: SocketAddr, msg: Vec<u8>) -> io::Result<Answer> {
+        if udp::SUPPORTED {
+            let answer = timeout(self.timeout, udp_exchange(ns, &msg))
+                .await
+                .map_err(|_| timeout_error())??;
+            if !matches!(answer, Answer::Truncated) {
+                return Ok(answer);
+            }
+            log::trace!("DNS Resolver - truncated response from {}, use tcp", ns);
+        }
+
+        let data = timeout(self.timeout, tcp_exchange(ns, &msg))
+            .await
+            .map_err(|_| timeout_error())??;
+        parse_response(&msg, &data)?
+            .ok_or_else(|| invalid("Dns response does not match query"))
+    }
+
+    fn parse_resolv_conf(&mut self, conf: &str) {
+        for line in conf.lines() {
+            let mut parts = line.split_whitespace();
+            match parts.next() {
+                Some("nameserver") => {
+                    if let Some(Ok(ip)) = parts.next().map(|s| s.parse::<IpAddr>()) {
+                        self.nameservers.push(SocketAddr::new(ip, DNS_PORT));
+                    }
+                }
+                Some("search") | Some("domain") => {
+                    // last search or domain entry wins
+                    self.search.clear();
+                    for domain in parts {
+                        let domain = domain.trim_matches('.').to_ascii_lowercase();
+                        if !domain.is_empty() {
+                            self.search.push(domain);
+                        }
+                    }
+                }
+                Some("options") => {
+                    for opt in parts {
+                        if let Some(Ok(val)) =
+                            opt.strip_prefix("ndots:").map(|s| s.parse::<usize>())
+                        {
+                            self.ndots = cmp::min(val, MAX_NDOTS);
+                        } else if let Some(Ok(val)) =
+                            opt.strip_prefix("timeout:").map(|s| s.parse::<u32>())
+                        {
+                            self.timeout = Millis::from_secs(cmp::max(val, 1));
+                        } else if let Some(Ok(val)) =
+                            opt.strip_prefix("attempts:").map(|s| s.parse::<usize>())
+                        {
+                            self.attempts = cmp::max(val, 1);
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+impl Default for DnsResolver {
+    fn default() -> Self {
+        DnsResolver::new()
+    }
+}
+
+impl fmt::Debug for DnsResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DnsResolver")
+            .field("nameservers", &self.nameservers)
+            .field("search", &self.search)
+            .field("ndots", &self.ndots)
+            .field("timeout", &self.timeout)
+            .field("attempts", &self.attempts)
+            .field("cache_size", &self.cache_size)
+            .finish()
+    }
+}
+
+impl Resolve for DnsResolver {
+    fn lookup<'a>(
+        &'a self,
+        host: &'a str,
+        port: u16,
+    ) -> BoxFuture<'a, io::Result<Vec<SocketAddr>>> {
+        Box::pin(async move {
+            let addrs = self.resolve(host).await?;
+            Ok(addrs
+                .into_iter()
+                .map(|ip| SocketAddr::new(ip, port))
+                .collect())
+        })
+    }
+}
+
+fn parse_hosts(hosts: &str) -> HashMap<String, Vec<IpAddr>> {
+    let mut result: HashMap<String, Vec<IpAddr>> = HashMap::new();
+    for line in hosts.lines() {
+        let line = line.split('#').next().unwrap_or("");
+        let mut parts = line.split_whitespace();
+        if let Some(Ok(ip)) = parts.next().map(|s| s.parse::<IpAddr>()) {
+            for name in parts {
+                let addrs = result.entry(name.to_ascii_lowercase()).or_default();
+                if !addrs.contains(&ip) {
+                    addrs.push(ip);
+                }
+            }
+        }
+    }
+    result
+}
+
+/// Random query id, os random number generator is used
+fn query_id() -> io::Result<u16> {
+    let mut id = [0; 2];
+    getrandom::fill(&mut id).map_err(io::Error::other)?;
+    Ok(u16::from_be_bytes(id))
+}
+
+fn encode_query(id: u16, name: &str, qtype: u16) -> io::Result<Vec<u8>> {
+    if name.is_empty() || name.len() > 253 {
+        return Err(invalid_name());
+    }
+
+    let mut buf = Vec::with_capacity(name.len() + 18);
+    buf.extend_from_slice(&id.to_be_bytes());
+    // recursion desired, one question
+    buf.extend_from_slice(&[0x01, 0x00, 0x00, 0x01, 0, 0, 0, 0, 0, 0]);
+    for label in name.split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return Err(invalid_name());
+        }
+        buf.push(label.len() as u8);
+        buf.extend_from_slice(label.as_bytes());
+    }
+    buf.push(0);
+    buf.extend_from_slice(&qtype.to_be_bytes());
+    buf.extend_from_slice(&CLASS_IN.to_be_bytes());
+    Ok(buf)
+}
+
+/// Parse response to the query
+///
+/// Returns `None` if response id or question does not match query.
+fn parse_response(query: &[u8], data: &[u8]) -> io::Result<Option<Answer>> {
+    let question = &query[12..];
+    let qtype = read_u16(query, query.len() - 4);
+    if data.len() < 12 + question.len()
+        || data[..2] != query[..2]
+        || read_u16(data, 4) != 1
+        || !data[12..12 + question.len()].eq_ignore_ascii_case(question)
+    {
+        return Ok(None);
+    }
+
+    let flags = read_u16(data, 2);
+    if flags & FLAG_RESPONSE == 0 {
+        return Err(invalid("Dns message is not a response"));
+    }
+    if flags & FLAG_TRUNCATED != 0 {
+        return Ok(Some(Answer::Truncated));
+    }
+    match flags & 0x000f {
+        0 => (),
+        RCODE_NXDOMAIN => return Ok(Some(Answer::Records(Vec::new(), 0))),
+        code => {
+            return Err(io::Error::other(format!(
+                "Dns server responded with error code {}",
+                code
+            )))
+        }
+    }
+
+    let answers = read_u16(data, 6);
+
+    let mut pos = 12 + question.len();
+
+    let mut addrs = Vec::new();
+    let mut ttl = u32::MAX;
+    for _ in 0..answers {
+        pos = skip_name(data, pos)?;
+        if data.len() < pos + 10 {
+            return Err(invalid("Dns response is too short"));
+        }
+        let rtype = read_u16(data, pos);
+        let class = read_u16(data, pos + 2);
+        let record_ttl = u32::from_be_bytes([
+            data[pos + 4],
+            data[pos + 5],
+            data[pos + 6],
+            data[pos + 7],
+        ]);
+        let len = read_u16(data, pos + 8) as usize;
+        pos += 10;
+
+        let rdata = data
+            .get(pos..pos + len)
+            .ok_or_else(|| invalid("Dns response is too short"))?;
+        pos += len;
+
+        if class != CLASS_IN || rtype != qtype {
+            continue;
+        }
+        let addr = match rdata.len() {
+            4 if rtype == TYPE_A => {
+                IpAddr::from(Ipv4Addr::new(rdata[0], rdata[1], rdata[2], rdata[3]))
+            }
+            16 if rtype == TYPE_AAAA => {
+                let mut octets = [0; 16];
+                octets.copy_from_slice(rdata);
+                IpAddr::from(Ipv6Addr::from(octets))
+            }
+            _ => continue,
+        };
+        addrs.push(addr);
+        ttl = cmp::min(ttl, record_ttl);
+    }
+
+    if addrs.is_empty() {
+        ttl = 0;
+    }
+    Ok(Some(Answer::Records(addrs, ttl)))
+}
+
+fn skip_name(data: &[u8], mut pos: usize) -> io::Result<usize> {
+    loop {
+        let len = *data
+            .get(pos)
+            .ok_or_else(|| invalid("Dns response is too short"))?
+            as usize;
+
+        if len == 0 {
+            return Ok(pos + 1);
+        } else if len & 0xc0 == 0xc0 {
+            // compressed name, pointer terminates the name
+            return Ok(pos + 2);
+        }
+        pos += len + 1;
+    }
+}
+
+fn read_u16(data: &[u8], pos: usize) -> u16 {
+    u16::from_be_bytes([data[pos], data[pos + 1]])
+}
+
+async fn udp_exchange(ns: SocketAddr, msg: &[u8]) -> io::Result<Answer> {
+    let sock = udp::UdpSocket::connect(ns).await?;
+    sock.send(msg).await?;
+
+    loop {
+        // responses that do not match query are ignored
+        if let Some(answer) = parse_response(msg, &sock.recv().await?)? {
+            return Ok(answer);
+        }
+    }
+}
+
+async fn tcp_exchange(ns: SocketAddr, msg: &[u8]) -> io::Result<Vec<u8>> {
+    let io = tcp_connect(ns).await?;
+
+    let mut buf = Vec::with_capacity(msg.len() + 2);
+    buf.extend_from_slice(&(msg.len() as u16).to_be_bytes());
+    buf.extend_from_slice(msg);
+    io.write(&buf)?;
+    io.flush(true).await?;
+
+    loop {
+        let data = io.with_read_buf(|buf| {
+            if buf.len() >= 2 {
+                let size = read_u16(buf, 0) as usize + 2;
+                if buf.len() >= size {
+                    return Some(buf.split_to(size)[2..].to_vec());
+                }
+            }
+            None
+        });
+        if let Some(data) = data {
+            return Ok(data);
+        }
+
+        if io.read_ready().await?.is_none() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "Nameserver closed connection",
+            ));
+        }
+    }
+}
+
+/// Local address for the datagram socket connected to `ns`
+fn local_addr(ns: SocketAddr) -> SocketAddr {
+    if ns.is_ipv4() {
+        (Ipv4Addr::UNSPECIFIED, 0).into()
+    } else {
+        (Ipv6Addr::UNSPECIFIED, 0).into()
+    }
+}
+
+#[cfg(all(feature = "tokio", not(all(unix, feature = "neon"))))]
+mod udp {
+    use std::{io, net::SocketAddr};
+
+    use super::MAX_UDP_SIZE;
+
+    pub(super) const SUPPORTED: bool = true;
+
+    pub(super) struct UdpSocket(tok_io::net::UdpSocket);
+
+    impl UdpSocket {
+        pub(super) async fn connect(ns: SocketAddr) -> io::Result<Self> {
+            let sock = tok_io::net::UdpSocket::bind(super::local_addr(ns)).await?;
+            sock.connect(ns).await?;
+            Ok(UdpSocket(sock))
+        }
+
+        pub(super) async fn send(&self, msg: &[u8]) -> io::Result<()> {
+            self.0.send(msg).await.map(|_| ())
+        }
+
+        pub(super) async fn recv(&self) -> io::Result<Vec<u8>> {
+            let mut buf = vec![0; MAX_UDP_SIZE];
+            let size = self.0.recv(&mut buf).await?;
+            buf.truncate(size);
+            Ok(buf)
+        }
+    }
+}
+
+#[cfg(all(
+    feature = "compio",
+    not(feature = "tokio"),
+    not(all(unix, feature = "neon"))
+))]
+mod udp {
+    use std::{io, net::SocketAddr};
+
+    use compio_buf::BufResult;
+
+    use super::MAX_UDP_SIZE;
+
+    pub(super) const SUPPORTED: bool = true;
+
+    pub(super) struct UdpSocket(compio_net::UdpSocket);
+
+    impl UdpSocket {
+        pub(super) async fn connect(ns: SocketAddr) -> io::Result<Self> {
+            let sock = compio_net::UdpSocket::bind(super::local_addr(ns)).await?;
+            sock.connect(ns).await?;
+            Ok(UdpSocket(sock))
+        }
+
+        pub(super) async fn send(&self, msg: &[u8]) -> io::Result<()> {
+            let BufResult(result, _) = self.0.send(msg.to_vec()).await;
+            result.map(|_| ())
+        }
+
+        pub(super) async fn recv(&self) -> io::Result<Vec<u8>> {
+            let BufResult(result, buf) =
+                self.0.recv(Vec::with_capacity(MAX_UDP_SIZE)).await;
+            result.map(|_| buf)
+        }
+    }
+}
+
+/// Runtime does not provide datagram sockets, queries are sent over tcp
+#[cfg(not(any(
+    all(feature = "tokio", not(all(unix, feature = "neon"))),
+    all(feature = "compio", not(all(unix, feature = "neon")))
+)))]
+mod udp {
+    use std::{io, net::SocketAddr};
+
+    pub(super) const SUPPORTED: bool = false;
+
+    pub(super) struct UdpSocket;
+
+    impl UdpSocket {
+        pub(super) async fn connect(_: SocketAddr) -> io::Result<Self> {
+            Err(io::Error::from(io::ErrorKind::Unsupported))
+        }
+
+        pub(super) async fn send(&self, _: &[u8]) -> io::Result<()> {
+            Err(io::Error::from(io::ErrorKind::Unsupported))
+        }
+
+        pub(super) async fn recv(&self) -> io::Result<Vec<u8>> {
+            Err(io::Error::from(io::ErrorKind::Unsupported))
+        }
+    }
+}
+
+fn invalid(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn invalid_name() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, "Invalid dns name")
+}
+
+fn timeout_error() -> io::Error {
+    io::Error::new(io::ErrorKind::TimedOut, "Dns query timed out")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, UdpSocket};
+    use std::thread;
+
+    use super::*;
+
+    fn response(query: &[u8], truncated: bool, ttl: u32) -> Vec<u8> {
+        let qtype = read_u16(query, query.len() - 4);
+
+        let mut buf = query.to_vec();
+        buf[2] = if truncated { 0x83 } else { 0x81 };
+        buf[3] = 0x80;
+        if truncated {
+            return buf;
+        }
+
+        let rdata: &[u8] = if qtype == TYPE_A {
+            &[127, 0, 0, 1]
+        } else {
+            &[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]
+        };
+        buf[7] = 1;
+        buf.extend_from_slice(&[0xc0, 12]);
+        buf.extend_from_slice(&qtype.to_be_bytes());
+        buf.extend_from_slice(&CLASS_IN.to_be_bytes());
+        buf.extend_from_slice(&ttl.to_be_bytes());
+        buf.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+        buf.extend_from_slice(rdata);
+        buf
+    }
+
+    #[test]
+    fn config() {
+        let mut resolver = DnsResolver::with_nameservers(Vec::new());
+        resolver.parse_resolv_conf(
+            "# comment\nnameserver 10.0.0.1\nnameserver ::1\nsearch local\n\
+             options ndots:2 timeout:3 attempts:4\nnameserver invalid\n",
+        );
+        assert_eq!(
+            resolver.nameservers,
+            vec!["10.0.0.1:53".parse().unwrap(), "[::1]:53".parse().unwrap()]
+        );
+        assert_eq!(resolver.search, vec!["local".to_string()]);
+        assert_eq!(resolver.ndots, 2);
+
+        let resolver = DnsResolver::with_nameservers(Vec::new())
+            .search("corp.example.")
+            .search("example");
+        assert_eq!(
+            resolver.candidates("db", false),
+            vec!["db.corp.example", "db.example", "db"]
+        );
+        assert_eq!(
+            resolver.candidates("www.rust-lang.org", false),
+            vec![
+                "www.rust-lang.org",
+                "www.rust-lang.org.corp.example",
+                "www.rust-lang.org.example"
+            ]
+        );
+        assert_eq!(resolver.candidates("db", true), vec!["db"]);
+        assert_eq!(
+            resolver.ndots(2).candidates("db.corp", false),
+            vec!["db.corp.corp.example", "db.corp.example", "db.corp"]
+        );
+        assert_eq!(resolver.timeout, Millis(3_000));
+        assert_eq!(resolver.attempts, 4);
+        assert!(format!("{:?}", resolver).contains("DnsResolver"));
+
+        let hosts = parse_hosts(
+            "127.0.0.1 localhost Local # comment\n::1 localhost\n# 10.0.0.1 skip\n",
+        );
+        let localhost: Vec<IpAddr> =
+            vec!["127.0.0.1".parse().unwrap(), "::1".parse().unwrap()];
+        assert_eq!(hosts.get("localhost"), Some(&localhost));
+        assert_eq!(hosts.get("local").map(|v| v.len()), Some(1));
+        assert!(!hosts.contains_key("skip"));
+    }
+
+    #[test]
+    fn messages() {
+        assert!(encode_query(1, "", TYPE_A).is_err());
+        assert!(encode_query(1, "a..b", TYPE_A).is_err());
+
+        let query = encode_query(10, "example.com", TYPE_A).unwrap();
+        assert_eq!(&query[12..], b"\x07example\x03com\x00\x00\x01\x00\x01");
+
+        let resp = response(&query, false, 300);
+        match parse_response(&query, &resp).unwrap() {
+            Some(Answer::Records(addrs, ttl)) => {
+                assert_eq!(addrs, vec![IpAddr::from(Ipv4Addr::LOCALHOST)]);
+                assert_eq!(ttl, 300);
+            }
+            _ => panic!(),
+        }
+        assert!(parse_response(&query, &resp[..resp.len() - 2]).is_err());
+        assert!(matches!(
+            parse_response(&query, &response(&query, true, 0)),
+            Ok(Some(Answer::Truncated))
+        ));
+
+        // id or question mismatch
+        let other = encode_query(11, "example.com", TYPE_A).unwrap();
+        assert!(matches!(parse_response(&other, &resp), Ok(None)));
+        let other = encode_query(10, "example.org", TYPE_A).unwrap();
+        assert!(matches!(parse_response(&other, &resp), Ok(None)));
+        let other = encode_query(10, "example.com", TYPE_AAAA).unwrap();
+        assert!(matches!(parse_response(&other, &resp), Ok(None)));
+        assert!(matches!(parse_response(&query, &resp[..12]), Ok(None)));
+        let other = encode_query(10, "EXAMPLE.com", TYPE_A).unwrap();
+        assert!(parse_response(&other, &resp).unwrap().is_some());
+
+        let mut resp = resp;
+        resp[3] = 0x83;
+        assert!(matches!(
+            parse_response(&query, &resp),
+            Ok(Some(Answer::Records(addrs, 0))) if addrs.is_empty()
+        ));
+        resp[3] = 0x82;
+        assert!(parse_response(&query, &resp).is_err());
+    }
+
+    /// Test nameserver, serves queries over udp and tcp
+    fn nameserver() -> SocketAddr {
+        let sock = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let ns = sock.local_addr().unwrap();
+        let listener = TcpListener::bind(ns).unwrap();
+
+        thread::spawn(move || {
+            let mut buf = [0; MAX_UDP_SIZE];
+            loop {
+                let (size, peer) = sock.recv_from(&mut buf).unwrap();
+                // unrelated response must be ignored
+                let mut other = response(&buf[..size], false, 60);
+                other[0] = !other[0];
+                sock.send_to(&other, peer).unwrap();
+                sock.send_to(&response(&buf[..size], false, 60), peer)
+                    .unwrap();
+            }
+        });
+        thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut len = [0; 2];
+                stream.read_exact(&mut len).unwrap();
+                let mut buf = vec![0; u16::from_be_bytes(len) as usize];
+                stream.read_exact(&mut buf).unwrap();
+                let resp = response(&buf, false, 60);
+                stream
+                    .write_all(&(resp.len() as u16).to_be_bytes())
+                    .unwrap();
+                stream.write_all(&resp).unwrap();
+            }
+        });
+        ns
+    }
+
+    #[ntex::test]
+    async fn resolve() {
+        let resolver = DnsResolver::with_nameservers(vec![nameserver()])
+            .timeout(Millis(1_000))
+            .attempts(1)
+            .host("static.local", "10.0.0.1".parse().unwrap());
+
+        let addrs = resolver.lookup("example.com", 80).await.unwrap();
+        assert_eq!(addrs.len(), 2);
+        assert!(addrs.contains(&"127.0.0.1:80".parse().unwrap()));
+        assert!(addrs.contains(&"[::1]:80".parse().unwrap()));
+
+        // cached
+        let addrs = resolver.resolve("Example.com.").await.unwrap();
+        assert_eq!(addrs.len(), 2);
+
+        let addrs = resolver.resolve("static.local").await.unwrap();
+        assert_eq!(addrs, vec![IpAddr::from(Ipv4Addr::new(10, 0, 0, 1))]);
+
+        resolver.clear_cache();
+        let addrs = resolver.resolve("example.com").await.unwrap();
+        assert_eq!(addrs.len(), 2);
+
+        // nameserver does not respond
+        let sock = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let _listener = TcpListener::bind(sock.local_addr().unwrap()).unwrap();
+        let resolver = DnsResolver::with_nameservers(vec![sock.local_addr().unwrap()])
+            .timeout(Millis(100))
+            .attempts(1);
+        assert!(resolver.resolve("example.com").await.is_err());
+    }
+}
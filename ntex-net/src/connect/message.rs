@@ -270,7 +270,7 @@ impl ExactSizeIterator for ConnectTakeAddrsIter {}
 
 impl FusedIterator for ConnectTakeAddrsIter {}
 
-fn parse(host: &str) -> (&str, Option<u16>) {
+pub(super) fn parse(host: &str) -> (&str, Option<u16>) {
     let mut parts_iter = host.splitn(2, ':');
     if let Some(host) = parts_iter.next() {
         let port_str = parts_iter.next().unwrap_or("");
@@ -1,14 +1,18 @@
 //! Tcp connector service
+mod dns;
 mod error;
 mod message;
 mod resolve;
 mod service;
+mod socks;
 mod uri;
 
+pub use self::dns::DnsResolver;
 pub use self::error::ConnectError;
 pub use self::message::{Address, Connect};
-pub use self::resolve::Resolver;
+pub use self::resolve::{Resolve, Resolver, SystemResolver};
 pub use self::service::Connector;
+pub use self::socks::Socks5Connector;
 
 use ntex_io::Io;
 
@@ -1,24 +1,89 @@
-use std::{fmt, io, marker, net};
+use std::{fmt, io, marker, net, rc::Rc};
 
 use ntex_rt::spawn_blocking;
 use ntex_service::{Service, ServiceCtx, ServiceFactory};
-use ntex_util::future::Either;
+use ntex_util::future::{BoxFuture, Either};
 
 use super::{Address, Connect, ConnectError};
 
+/// Dns resolver
+///
+/// Resolves host name to the list of socket addresses.
+pub trait Resolve: fmt::Debug + 'static {
+    /// Lookup ip addresses for provided host name
+    fn lookup<'a>(
+        &'a self,
+        host: &'a str,
+        port: u16,
+    ) -> BoxFuture<'a, io::Result<Vec<net::SocketAddr>>>;
+}
+
+impl<R: Resolve + ?Sized> Resolve for Rc<R> {
+    fn lookup<'a>(
+        &'a self,
+        host: &'a str,
+        port: u16,
+    ) -> BoxFuture<'a, io::Result<Vec<net::SocketAddr>>> {
+        (**self).lookup(host, port)
+    }
+}
+
+/// System dns resolver
+///
+/// Uses `getaddrinfo` on the blocking thread pool.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct SystemResolver;
+
+impl Resolve for SystemResolver {
+    fn lookup<'a>(
+        &'a self,
+        host: &'a str,
+        port: u16,
+    ) -> BoxFuture<'a, io::Result<Vec<net::SocketAddr>>> {
+        let host = host.to_string();
+
+        Box::pin(async move {
+            let fut = spawn_blocking(move || {
+                net::ToSocketAddrs::to_socket_addrs(&(host.as_str(), port))
+            });
+            match fut.await {
+                Ok(Ok(ips)) => Ok(ips.collect()),
+                Ok(Err(e)) => Err(e),
+                Err(e) => Err(io::Error::other(e)),
+            }
+        })
+    }
+}
+
 /// DNS Resolver Service
-pub struct Resolver<T>(marker::PhantomData<T>);
+pub struct Resolver<T, R = SystemResolver> {
+    inner: R,
+    _t: marker::PhantomData<T>,
+}
 
 impl<T> Resolver<T> {
-    /// Create new resolver instance with custom configuration and options.
+    /// Create new resolver instance with system resolver.
     pub fn new() -> Self {
-        Resolver(marker::PhantomData)
+        Resolver::with(SystemResolver)
     }
 }
 
-impl<T> Copy for Resolver<T> {}
+impl<T, R> Resolver<T, R> {
+    /// Create new resolver instance with custom dns resolver.
+    ///
+    /// Resolver is cloned with the service, resolvers that keep state
+    /// must share it between clones, i.e. `DnsResolver` or `Rc<R>`.
+    pub fn with(resolver: R) -> Self {
+        Resolver {
+            inner: resolver,
+            _t: marker::PhantomData,
+        }
+    }
+}
 
-impl<T: Address> Resolver<T> {
+impl<T, R: Copy> Copy for Resolver<T, R> {}
+
+impl<T: Address, R: Resolve> Resolver<T, R> {
     /// Lookup ip addresses for provided host
     pub async fn lookup(&self, req: Connect<T>) -> Result<Connect<T>, ConnectError> {
         self.lookup_with_tag(req, "TCP-CLIENT").await
@@ -33,26 +98,16 @@ impl<T: Address> Resolver<T> {
     ) -> Result<Connect<T>, ConnectError> {
         if req.addr.is_some() || req.req.addr().is_some() {
             Ok(req)
-        } else if let Ok(ip) = req.host().parse() {
+        } else if let Ok(ip) = host_name(req.host()).parse() {
             req.addr = Some(Either::Left(net::SocketAddr::new(ip, req.port())));
             Ok(req)
         } else {
             log::trace!("{}: DNS Resolver - resolving host {:?}", tag, req.host());
 
-            let host = if req.host().contains(':') {
-                req.host().to_string()
-            } else {
-                format!("{}:{}", req.host(), req.port())
-            };
-
-            let fut = spawn_blocking(move || net::ToSocketAddrs::to_socket_addrs(&host));
-            match fut.await {
-                Ok(Ok(ips)) => {
-                    let port = req.port();
-                    let req = req.set_addrs(ips.map(|mut ip| {
-                        ip.set_port(port);
-                        ip
-                    }));
+            let result = self.inner.lookup(host_name(req.host()), req.port()).await;
+            match result {
+                Ok(ips) => {
+                    let req = req.set_addrs(interleave(ips));
 
                     log::trace!(
                         "{}: DNS Resolver - host {:?} resolved to {:?}",
@@ -67,15 +122,6 @@ impl<T: Address> Resolver<T> {
                         Ok(req)
                     }
                 }
-                Ok(Err(e)) => {
-                    log::trace!(
-                        "{}: DNS Resolver - failed to resolve host {:?} err: {}",
-                        tag,
-                        req.host(),
-                        e
-                    );
-                    Err(ConnectError::Resolver(e))
-                }
                 Err(e) => {
                     log::trace!(
                         "{}: DNS Resolver - failed to resolve host {:?} err: {}",
@@ -83,43 +129,78 @@ impl<T: Address> Resolver<T> {
                         req.host(),
                         e
                     );
-                    Err(ConnectError::Resolver(io::Error::other(e)))
+                    Err(ConnectError::Resolver(e))
                 }
             }
         }
     }
 }
 
+/// Strip port and brackets from the host
+fn host_name(host: &str) -> &str {
+    let host = match host.rsplit_once(':') {
+        Some((h, port))
+            if port.bytes().all(|b| b.is_ascii_digit())
+                && (!h.contains(':') || h.ends_with(']')) =>
+        {
+            h
+        }
+        _ => host,
+    };
+    host.strip_prefix('[')
+        .and_then(|h| h.strip_suffix(']'))
+        .unwrap_or(host)
+}
+
+/// Order addresses according to rfc8305, alternate ipv6 and ipv4 addresses
+/// starting with ipv6.
+fn interleave(addrs: Vec<net::SocketAddr>) -> Vec<net::SocketAddr> {
+    let (v6, v4): (Vec<_>, Vec<_>) = addrs.into_iter().partition(|a| a.is_ipv6());
+    let mut v6 = v6.into_iter();
+    let mut v4 = v4.into_iter();
+
+    let mut result = Vec::with_capacity(v6.len() + v4.len());
+    loop {
+        match (v6.next(), v4.next()) {
+            (None, None) => break,
+            (a, b) => result.extend(a.into_iter().chain(b)),
+        }
+    }
+    result
+}
+
 impl<T> Default for Resolver<T> {
     fn default() -> Resolver<T> {
         Resolver::new()
     }
 }
 
-impl<T> Clone for Resolver<T> {
+impl<T, R: Clone> Clone for Resolver<T, R> {
     fn clone(&self) -> Self {
-        *self
+        Resolver::with(self.inner.clone())
     }
 }
 
-impl<T> fmt::Debug for Resolver<T> {
+impl<T, R: fmt::Debug> fmt::Debug for Resolver<T, R> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("Resolver").finish()
+        f.debug_struct("Resolver")
+            .field("resolver", &self.inner)
+            .finish()
     }
 }
 
-impl<T: Address, C> ServiceFactory<Connect<T>, C> for Resolver<T> {
+impl<T: Address, R: Resolve + Clone, C> ServiceFactory<Connect<T>, C> for Resolver<T, R> {
     type Response = Connect<T>;
     type Error = ConnectError;
-    type Service = Resolver<T>;
+    type Service = Resolver<T, R>;
     type InitError = ();
 
     async fn create(&self, _: C) -> Result<Self::Service, Self::InitError> {
-        Ok(*self)
+        Ok(self.clone())
     }
 }
 
-impl<T: Address> Service<Connect<T>> for Resolver<T> {
+impl<T: Address, R: Resolve> Service<Connect<T>> for Resolver<T, R> {
     type Response = Connect<T>;
     type Error = ConnectError;
 
@@ -137,7 +218,6 @@ mod tests {
     use super::*;
     use ntex_util::future::lazy;
 
-    #[allow(clippy::clone_on_copy)]
     #[ntex::test]
     async fn resolver() {
         let resolver = Resolver::default().clone();
@@ -160,4 +240,56 @@ mod tests {
         assert_eq!(addrs.len(), 1);
         assert!(addrs.contains(&addr));
     }
+
+    #[test]
+    fn host_names() {
+        assert_eq!(host_name("example.com"), "example.com");
+        assert_eq!(host_name("example.com:8080"), "example.com");
+        assert_eq!(host_name("[::1]:8080"), "::1");
+        assert_eq!(host_name("[::1]"), "::1");
+        assert_eq!(host_name("::1"), "::1");
+    }
+
+    #[derive(Debug)]
+    struct Static(Vec<net::SocketAddr>);
+
+    impl Resolve for Static {
+        fn lookup<'a>(
+            &'a self,
+            _: &'a str,
+            port: u16,
+        ) -> BoxFuture<'a, io::Result<Vec<net::SocketAddr>>> {
+            let addrs = self
+                .0
+                .iter()
+                .map(|a| net::SocketAddr::new(a.ip(), port))
+                .collect();
+            Box::pin(async move { Ok(addrs) })
+        }
+    }
+
+    #[ntex::test]
+    async fn custom_resolver() {
+        let addrs = vec![
+            "127.0.0.1:0".parse().unwrap(),
+            "127.0.0.2:0".parse().unwrap(),
+            "[::1]:0".parse().unwrap(),
+        ];
+        let resolver = Resolver::with(Rc::new(Static(addrs)));
+        assert!(format!("{:?}", resolver).contains("Static"));
+
+        let res = resolver
+            .lookup(Connect::new("example.com:8080"))
+            .await
+            .unwrap();
+        let addrs: Vec<_> = res.addrs().map(|a| a.to_string()).collect();
+        assert_eq!(
+            addrs,
+            vec!["[::1]:8080", "127.0.0.1:8080", "127.0.0.2:8080"]
+        );
+
+        let resolver = Resolver::with(Static(Vec::new()));
+        let res = resolver.lookup(Connect::new("example.com:8080")).await;
+        assert!(matches!(res, Err(ConnectError::NoRecords)));
+    }
 }
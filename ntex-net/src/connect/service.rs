@@ -5,18 +5,20 @@ use ntex_bytes::{PoolId, PoolRef};
 use ntex_io::{types, Io};
 use ntex_service::{Service, ServiceCtx, ServiceFactory};
 use ntex_util::future::{BoxFuture, Either};
+use ntex_util::time::{Millis, Sleep};
 
-use super::{Address, Connect, ConnectError, Resolver};
+use super::{Address, Connect, ConnectError, Resolve, Resolver, SystemResolver};
 use crate::tcp_connect_in;
 
 /// Basic tcp stream connector
-pub struct Connector<T> {
-    resolver: Resolver<T>,
+pub struct Connector<T, R = SystemResolver> {
+    resolver: Resolver<T, R>,
     pool: PoolRef,
     tag: &'static str,
+    attempt_delay: Millis,
 }
 
-impl<T> Copy for Connector<T> {}
+impl<T, R: Copy> Copy for Connector<T, R> {}
 
 impl<T> Connector<T> {
     /// Construct new connect service with default dns resolver
@@ -25,8 +27,35 @@ impl<T> Connector<T> {
             resolver: Resolver::new(),
             pool: PoolId::P0.pool_ref(),
             tag: "TCP-CLIENT",
+            attempt_delay: Millis(250),
         }
     }
+}
+
+impl<T, R> Connector<T, R> {
+    /// Use custom dns resolver
+    ///
+    /// By default system resolver is used. Resolver is cloned with
+    /// the connector, see [`Resolver::with()`].
+    pub fn resolver<U: Resolve>(self, resolver: U) -> Connector<T, U> {
+        Connector {
+            resolver: Resolver::with(resolver),
+            pool: self.pool,
+            tag: self.tag,
+            attempt_delay: self.attempt_delay,
+        }
+    }
+
+    /// Set connection attempt delay
+    ///
+    /// If host resolves to multiple addresses, connector starts next
+    /// connection attempt if previous one is not completed within
+    /// specified delay (rfc8305 happy eyeballs). By default delay is
+    /// set to 250 milliseconds.
+    pub fn attempt_delay<U: Into<Millis>>(mut self, delay: U) -> Self {
+        self.attempt_delay = delay.into();
+        self
+    }
 
     /// Set memory pool
     ///
@@ -46,7 +75,7 @@ impl<T> Connector<T> {
     }
 }
 
-impl<T: Address> Connector<T> {
+impl<T: Address, R: Resolve> Connector<T, R> {
     /// Resolve and connect to remote host
     pub async fn connect<U>(&self, message: U) -> Result<Io, ConnectError>
     where
@@ -62,16 +91,9 @@ impl<T: Address> Connector<T> {
         let Connect { req, addr, .. } = address;
 
         if let Some(addr) = addr {
-            TcpConnectorResponse::new(req, port, addr, self.tag, self.pool).await
+            TcpConnectorResponse::new(req, port, addr, self).await
         } else if let Some(addr) = req.addr() {
-            TcpConnectorResponse::new(
-                req,
-                addr.port(),
-                Either::Left(addr),
-                self.tag,
-                self.pool,
-            )
-            .await
+            TcpConnectorResponse::new(req, addr.port(), Either::Left(addr), self).await
         } else {
             log::error!("{}: TCP connector: got unresolved address", self.tag);
             Err(ConnectError::Unresolved)
@@ -85,34 +107,40 @@ impl<T> Default for Connector<T> {
     }
 }
 
-impl<T> Clone for Connector<T> {
+impl<T, R: Clone> Clone for Connector<T, R> {
     fn clone(&self) -> Self {
-        *self
+        Connector {
+            resolver: self.resolver.clone(),
+            pool: self.pool,
+            tag: self.tag,
+            attempt_delay: self.attempt_delay,
+        }
     }
 }
 
-impl<T> fmt::Debug for Connector<T> {
+impl<T, R: fmt::Debug> fmt::Debug for Connector<T, R> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Connector")
             .field("tag", &self.tag)
             .field("resolver", &self.resolver)
             .field("memory_pool", &self.pool)
+            .field("attempt_delay", &self.attempt_delay)
             .finish()
     }
 }
 
-impl<T: Address, C> ServiceFactory<Connect<T>, C> for Connector<T> {
+impl<T: Address, R: Resolve + Clone, C> ServiceFactory<Connect<T>, C> for Connector<T, R> {
     type Response = Io;
     type Error = ConnectError;
-    type Service = Connector<T>;
+    type Service = Connector<T, R>;
     type InitError = ();
 
     async fn create(&self, _: C) -> Result<Self::Service, Self::InitError> {
-        Ok(*self)
+        Ok(self.clone())
     }
 }
 
-impl<T: Address> Service<Connect<T>> for Connector<T> {
+impl<T: Address, R: Resolve> Service<Connect<T>> for Connector<T, R> {
     type Response = Io;
     type Error = ConnectError;
 
@@ -125,62 +153,58 @@ impl<T: Address> Service<Connect<T>> for Connector<T> {
     }
 }
 
+type ConnectFn = fn(SocketAddr, PoolRef) -> BoxFuture<'static, Result<Io, io::Error>>;
+
 /// Tcp stream connector response future
+///
+/// Connection attempts are raced according to rfc8305, next attempt starts
+/// if previous one fails or does not complete within attempt delay.
 struct TcpConnectorResponse<T> {
     req: Option<T>,
     port: u16,
-    addrs: Option<VecDeque<SocketAddr>>,
+    addrs: VecDeque<SocketAddr>,
     #[allow(clippy::type_complexity)]
-    stream: Option<BoxFuture<'static, Result<Io, io::Error>>>,
+    attempts: Vec<BoxFuture<'static, Result<Io, io::Error>>>,
+    delay: Sleep,
+    attempt_delay: Millis,
+    error: Option<io::Error>,
     tag: &'static str,
     pool: PoolRef,
+    connect: ConnectFn,
 }
 
 impl<T: Address> TcpConnectorResponse<T> {
-    fn new(
+    fn new<R>(
         req: T,
         port: u16,
         addr: Either<SocketAddr, VecDeque<SocketAddr>>,
-        tag: &'static str,
-        pool: PoolRef,
+        connector: &Connector<T, R>,
     ) -> TcpConnectorResponse<T> {
         log::trace!(
             "{}: TCP connector - connecting to {:?} addr:{:?} port:{}",
-            tag,
+            connector.tag,
             req.host(),
             addr,
             port
         );
 
-        match addr {
-            Either::Left(addr) => TcpConnectorResponse {
-                req: Some(req),
-                addrs: None,
-                stream: Some(Box::pin(tcp_connect_in(addr, pool))),
-                tag,
-                pool,
-                port,
-            },
-            Either::Right(addrs) => TcpConnectorResponse {
-                tag,
-                port,
-                pool,
-                req: Some(req),
-                addrs: Some(addrs),
-                stream: None,
-            },
-        }
-    }
+        let addrs = match addr {
+            Either::Left(addr) => VecDeque::from([addr]),
+            Either::Right(addrs) => addrs,
+        };
 
-    fn can_continue(&self, err: &io::Error) -> bool {
-        log::trace!(
-            "{}: TCP connector - failed to connect to {:?} port: {} err: {:?}",
-            self.tag,
-            self.req.as_ref().unwrap().host(),
-            self.port,
-            err
-        );
-        !(self.addrs.is_none() || self.addrs.as_ref().unwrap().is_empty())
+        TcpConnectorResponse {
+            port,
+            addrs,
+            req: Some(req),
+            attempts: Vec::new(),
+            delay: Sleep::new(connector.attempt_delay),
+            attempt_delay: connector.attempt_delay,
+            error: None,
+            tag: connector.tag,
+            pool: connector.pool,
+            connect: |addr, pool| Box::pin(tcp_connect_in(addr, pool)),
+        }
     }
 }
 
@@ -190,10 +214,11 @@ impl<T: Address> Future for TcpConnectorResponse<T> {
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.get_mut();
 
-        // connect
         loop {
-            if let Some(new) = this.stream.as_mut() {
-                match new.as_mut().poll(cx) {
+            let mut failed = false;
+            let mut idx = 0;
+            while idx < this.attempts.len() {
+                match this.attempts[idx].as_mut().poll(cx) {
                     Poll::Ready(Ok(sock)) => {
                         let req = this.req.take().unwrap();
                         log::trace!(
@@ -205,18 +230,43 @@ impl<T: Address> Future for TcpConnectorResponse<T> {
                         sock.set_tag(this.tag);
                         return Poll::Ready(Ok(sock));
                     }
-                    Poll::Pending => return Poll::Pending,
                     Poll::Ready(Err(err)) => {
-                        if !this.can_continue(&err) {
-                            return Poll::Ready(Err(err.into()));
-                        }
+                        log::trace!(
+                            "{}: TCP connector - failed to connect to {:?} port: {} err: {:?}",
+                            this.tag,
+                            this.req.as_ref().unwrap().host(),
+                            this.port,
+                            err
+                        );
+                        this.attempts.swap_remove(idx);
+                        this.error = Some(err);
+                        failed = true;
                     }
+                    Poll::Pending => idx += 1,
                 }
             }
 
-            // try to connect
-            let addr = this.addrs.as_mut().unwrap().pop_front().unwrap();
-            this.stream = Some(Box::pin(tcp_connect_in(addr, this.pool)));
+            if this.addrs.is_empty() {
+                return if this.attempts.is_empty() {
+                    Poll::Ready(Err(this
+                        .error
+                        .take()
+                        .map(ConnectError::from)
+                        .unwrap_or(ConnectError::Unresolved)))
+                } else {
+                    Poll::Pending
+                };
+            }
+
+            // start next connection attempt
+            if failed || this.attempts.is_empty() || this.delay.poll_elapsed(cx).is_ready()
+            {
+                let addr = this.addrs.pop_front().unwrap();
+                this.attempts.push((this.connect)(addr, this.pool));
+                this.delay.reset(this.attempt_delay);
+            } else {
+                return Poll::Pending;
+            }
         }
     }
 }
@@ -225,6 +275,14 @@ impl<T: Address> Future for TcpConnectorResponse<T> {
 mod tests {
     use super::*;
 
+    /// Address of closed port
+    fn closed_addr() -> SocketAddr {
+        std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+    }
+
     #[ntex::test]
     async fn test_connect() {
         let server = ntex::server::test_server(|| {
@@ -255,4 +313,39 @@ mod tests {
         let result = crate::connect::connect(msg).await;
         assert!(result.is_ok());
     }
+
+    #[ntex::test]
+    async fn test_happy_eyeballs() {
+        let server = ntex::server::test_server(|| {
+            ntex_service::fn_service(|_| async { Ok::<_, ()>(()) })
+        });
+
+        // first attempt never completes, next attempt must win
+        let srv = Connector::<String>::default().attempt_delay(Millis(50));
+        assert!(format!("{:?}", srv).contains("attempt_delay"));
+        let stalled: SocketAddr = "127.0.0.1:1".parse().unwrap();
+        let mut fut = TcpConnectorResponse::new(
+            format!("{}", server.addr()),
+            server.addr().port(),
+            Either::Right(VecDeque::from([stalled, server.addr()])),
+            &srv,
+        );
+        fut.connect = |addr, pool| {
+            if addr.port() == 1 {
+                Box::pin(std::future::pending())
+            } else {
+                Box::pin(tcp_connect_in(addr, pool))
+            }
+        };
+        let sock = fut.await.unwrap();
+        assert_eq!(
+            sock.query::<types::PeerAddr>().get().map(|a| a.0),
+            Some(server.addr())
+        );
+
+        let msg = Connect::new(format!("{}", server.addr()))
+            .set_addrs(vec![closed_addr(), closed_addr()]);
+        let result = srv.connect(msg).await;
+        assert!(matches!(result, Err(ConnectError::Io(_))));
+    }
 }
@@ -0,0 +1,412 @@
+use std::{fmt, io, marker::PhantomData, net::IpAddr, net::SocketAddr};
+
+use ntex_bytes::{BufMut, ByteString, Bytes, BytesMut, PoolId};
+use ntex_io::Io;
+use ntex_service::{Service, ServiceCtx, ServiceFactory};
+
+use super::{message::parse, Address, Connect, ConnectError, Connector};
+
+const VERSION: u8 = 0x05;
+const AUTH_VERSION: u8 = 0x01;
+const METHOD_NO_AUTH: u8 = 0x00;
+const METHOD_PASSWORD: u8 = 0x02;
+const METHOD_NONE: u8 = 0xff;
+const CMD_CONNECT: u8 = 0x01;
+const ATYP_V4: u8 = 0x01;
+const ATYP_DOMAIN: u8 = 0x03;
+const ATYP_V6: u8 = 0x04;
+
+/// Socks5 proxy connector
+///
+/// Connects to the remote host through socks5 proxy. Host names are
+/// resolved by the proxy, pre-resolved addresses are passed as is.
+pub struct Socks5Connector<T> {
+    proxy: ByteString,
+    auth: Option<(ByteString, ByteString)>,
+    connector: Connector<ByteString>,
+    _t: PhantomData<T>,
+}
+
+impl<T> Socks5Connector<T> {
+    /// Construct new socks5 connector for proxy address, i.e. `127.0.0.1:1080`
+    pub fn new<U: Into<ByteString>>(proxy: U) -> Self {
+        Socks5Connector {
+            proxy: proxy.into(),
+            auth: None,
+            connector: Connector::new().tag("SOCKS5-CLIENT"),
+            _t: PhantomData,
+        }
+    }
+
+    /// Use username/password authentication
+    pub fn auth<U, P>(mut self, username: U, password: P) -> Self
+    where
+        U: Into<ByteString>,
+        P: Into<ByteString>,
+    {
+        self.auth = Some((username.into(), password.into()));
+        self
+    }
+
+    /// Set memory pool
+    ///
+    /// Use specified memory pool for memory allocations. By default P0
+    /// memory pool is used.
+    pub fn memory_pool(mut self, id: PoolId) -> Self {
+        self.connector = self.connector.memory_pool(id);
+        self
+    }
+
+    /// Set io tag
+    ///
+    /// Set tag to opened io object.
+    pub fn tag(mut self, tag: &'static str) -> Self {
+        self.connector = self.connector.tag(tag);
+        self
+    }
+}
+
+impl<T: Address> Socks5Connector<T> {
+    /// Connect to remote host through the proxy
+    pub async fn connect<U>(&self, message: U) -> Result<Io, ConnectError>
+    where
+        Connect<T>: From<U>,
+    {
+        let req = Connect::from(message);
+
+        // prefer pre-resolved address, otherwise proxy resolves host name
+        let (addr, port) = if let Some(addr) = req.addrs().next() {
+            (Target::Addr(addr.ip()), addr.port())
+        } else {
+            let port = req.port();
+            if port == 0 {
+                return Err(ConnectError::Unresolved);
+            }
+            let host = parse(req.host()).0;
+            let host = host.trim_start_matches('[').trim_end_matches(']');
+            match host.parse::<IpAddr>() {
+                Ok(ip) => (Target::Addr(ip), port),
+                Err(_) if host.is_empty() || host.len() > 255 => {
+                    return Err(ConnectError::InvalidInput)
+                }
+                Err(_) => (Target::Domain(host), port),
+            }
+        };
+
+        let io = self.connector.connect(self.proxy.clone()).await?;
+        log::trace!(
+            "{}: Socks5 connector - connecting to {:?} port: {} via {:?}",
+            io.tag(),
+            req.host(),
+            port,
+            self.proxy
+        );
+
+        self.handshake(&io).await?;
+        request(&io, addr, port).await?;
+
+        log::trace!(
+            "{}: Socks5 connector - successfully connected to {:?}",
+            io.tag(),
+            req.host()
+        );
+        Ok(io)
+    }
+
+    async fn handshake(&self, io: &Io) -> Result<(), ConnectError> {
+        if self.auth.is_some() {
+            write(io, &[VERSION, 2, METHOD_NO_AUTH, METHOD_PASSWORD]).await?;
+        } else {
+            write(io, &[VERSION, 1, METHOD_NO_AUTH]).await?;
+        }
+
+        let reply = read_exact(io, 2).await?;
+        if reply[0] != VERSION {
+            return Err(protocol_error("Invalid socks version"));
+        }
+        match (reply[1], &self.auth) {
+            (METHOD_NO_AUTH, _) => Ok(()),
+            (METHOD_PASSWORD, Some((username, password))) => {
+                if username.len() > 255 || password.len() > 255 {
+                    return Err(ConnectError::InvalidInput);
+                }
+                let mut buf = BytesMut::with_capacity(3 + username.len() + password.len());
+                buf.put_u8(AUTH_VERSION);
+                buf.put_u8(username.len() as u8);
+                buf.extend_from_slice(username.as_bytes());
+                buf.put_u8(password.len() as u8);
+                buf.extend_from_slice(password.as_bytes());
+                write(io, &buf).await?;
+
+                let reply = read_exact(io, 2).await?;
+                if reply[1] == 0 {
+                    Ok(())
+                } else {
+                    Err(io::Error::new(
+                        io::ErrorKind::PermissionDenied,
+                        "Socks5 proxy authentication failed",
+                    )
+                    .into())
+                }
+            }
+            (METHOD_NONE, _) => Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "Socks5 proxy does not accept authentication methods",
+            )
+            .into()),
+            _ => Err(protocol_error("Unsupported socks5 authentication method")),
+        }
+    }
+}
+
+enum Target<'a> {
+    Addr(IpAddr),
+    Domain(&'a str),
+}
+
+/// Send connect command and read proxy reply
+async fn request(io: &Io, target: Target<'_>, port: u16) -> Result<(), ConnectError> {
+    let mut buf = BytesMut::with_capacity(262);
+    buf.extend_from_slice(&[VERSION, CMD_CONNECT, 0x00]);
+    match target {
+        Target::Addr(IpAddr::V4(ip)) => {
+            buf.put_u8(ATYP_V4);
+            buf.extend_from_slice(&ip.octets());
+        }
+        Target::Addr(IpAddr::V6(ip)) => {
+            buf.put_u8(ATYP_V6);
+            buf.extend_from_slice(&ip.octets());
+        }
+        Target::Domain(host) => {
+            buf.put_u8(ATYP_DOMAIN);
+            buf.put_u8(host.len() as u8);
+            buf.extend_from_slice(host.as_bytes());
+        }
+    }
+    buf.put_u16(port);
+    write(io, &buf).await?;
+
+    // ver, rep, rsv, atyp and first byte of bound address
+    let reply = read_exact(io, 5).await?;
+    if reply[0] != VERSION {
+        return Err(protocol_error("Invalid socks version"));
+    }
+    if reply[1] != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::ConnectionRefused,
+            reply_error(reply[1]),
+        )
+        .into());
+    }
+    let remaining = match reply[3] {
+        ATYP_V4 => 4 - 1 + 2,
+        ATYP_V6 => 16 - 1 + 2,
+        ATYP_DOMAIN => reply[4] as usize + 2,
+        _ => return Err(protocol_error("Invalid socks5 address type")),
+    };
+    read_exact(io, remaining).await?;
+    Ok(())
+}
+
+async fn write(io: &Io, buf: &[u8]) -> Result<(), ConnectError> {
+    io.write(buf)?;
+    io.flush(true).await?;
+    Ok(())
+}
+
+async fn read_exact(io: &Io, size: usize) -> Result<Bytes, ConnectError> {
+    loop {
+        let data = io.with_read_buf(|buf| {
+            if buf.len() >= size {
+                Some(buf.split_to(size).freeze())
+            } else {
+                None
+            }
+        });
+        if let Some(data) = data {
+            return Ok(data);
+        }
+
+        if io.read_ready().await?.is_none() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "Socks5 proxy closed connection",
+            )
+            .into());
+        }
+    }
+}
+
+fn protocol_error(msg: &'static str) -> ConnectError {
+    io::Error::new(io::ErrorKind::InvalidData, msg).into()
+}
+
+fn reply_error(code: u8) -> &'static str {
+    match code {
+        0x01 => "General socks server failure",
+        0x02 => "Connection not allowed by ruleset",
+        0x03 => "Network unreachable",
+        0x04 => "Host unreachable",
+        0x05 => "Connection refused",
+        0x06 => "TTL expired",
+        0x07 => "Command not supported",
+        0x08 => "Address type not supported",
+        _ => "Unknown socks5 error",
+    }
+}
+
+impl<T> Clone for Socks5Connector<T> {
+    fn clone(&self) -> Self {
+        Socks5Connector {
+            proxy: self.proxy.clone(),
+            auth: self.auth.clone(),
+            connector: self.connector,
+            _t: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for Socks5Connector<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Socks5Connector")
+            .field("proxy", &self.proxy)
+            .field("auth", &self.auth.is_some())
+            .field("connector", &self.connector)
+            .finish()
+    }
+}
+
+impl<T: Address, C> ServiceFactory<Connect<T>, C> for Socks5Connector<T> {
+    type Response = Io;
+    type Error = ConnectError;
+    type Service = Socks5Connector<T>;
+    type InitError = ();
+
+    async fn create(&self, _: C) -> Result<Self::Service, Self::InitError> {
+        Ok(self.clone())
+    }
+}
+
+impl<T: Address> Service<Connect<T>> for Socks5Connector<T> {
+    type Response = Io;
+    type Error = ConnectError;
+
+    async fn call(
+        &self,
+        req: Connect<T>,
+        _: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        self.connect(req).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ntex::{codec::BytesCodec, io::Io as NIo, service::fn_service, util::Bytes};
+
+    use super::*;
+
+    fn proxy(auth: bool) -> ntex::server::TestServer {
+        ntex::server::test_server(move || {
+            fn_service(move |io: NIo| async move {
+                let greeting = read_exact(&io, 2).await.unwrap();
+                assert_eq!(greeting[0], VERSION);
+                let methods = read_exact(&io, greeting[1] as usize).await.unwrap();
+
+                if auth {
+                    if !methods.contains(&METHOD_PASSWORD) {
+                        write(&io, &[VERSION, METHOD_NONE]).await.unwrap();
+                        return Ok(());
+                    }
+                    write(&io, &[VERSION, METHOD_PASSWORD]).await.unwrap();
+                    let head = read_exact(&io, 2).await.unwrap();
+                    let user = read_exact(&io, head[1] as usize).await.unwrap();
+                    let len = read_exact(&io, 1).await.unwrap();
+                    let pass = read_exact(&io, len[0] as usize).await.unwrap();
+                    if &user[..] == b"user" && &pass[..] == b"pass" {
+                        write(&io, &[AUTH_VERSION, 0]).await.unwrap();
+                    } else {
+                        write(&io, &[AUTH_VERSION, 1]).await.unwrap();
+                        return Ok(());
+                    }
+                } else {
+                    write(&io, &[VERSION, METHOD_NO_AUTH]).await.unwrap();
+                }
+
+                let req = read_exact(&io, 4).await.unwrap();
+                assert_eq!(&req[..3], &[VERSION, CMD_CONNECT, 0]);
+                let host = match req[3] {
+                    ATYP_DOMAIN => {
+                        let len = read_exact(&io, 1).await.unwrap();
+                        let host = read_exact(&io, len[0] as usize).await.unwrap();
+                        String::from_utf8(host.to_vec()).unwrap()
+                    }
+                    ATYP_V4 => {
+                        let ip = read_exact(&io, 4).await.unwrap();
+                        format!("{}.{}.{}.{}", ip[0], ip[1], ip[2], ip[3])
+                    }
+                    _ => panic!(),
+                };
+                let port = read_exact(&io, 2).await.unwrap();
+                let port = u16::from_be_bytes([port[0], port[1]]);
+
+                if host == "example.com" || host == "127.0.0.1" {
+                    write(&io, &[VERSION, 0, 0, ATYP_V4, 127, 0, 0, 1, 0, 80])
+                        .await
+                        .unwrap();
+                    io.send(Bytes::from(format!("{}:{}", host, port)), &BytesCodec)
+                        .await
+                        .unwrap();
+                } else {
+                    write(&io, &[VERSION, 0x04, 0, ATYP_V4, 0, 0, 0, 0, 0, 0])
+                        .await
+                        .unwrap();
+                }
+                Ok::<_, ()>(())
+            })
+        })
+    }
+
+    #[ntex::test]
+    async fn test_socks5_connect() {
+        let srv = proxy(false);
+
+        let conn = Socks5Connector::new(srv.addr().to_string());
+        let io = conn.connect("example.com:8080").await.unwrap();
+        let data = io.recv(&BytesCodec).await.unwrap().unwrap();
+        assert_eq!(&data[..], b"example.com:8080");
+
+        let msg = Connect::new("example.com:8080")
+            .set_addr(Some("127.0.0.1:9090".parse::<SocketAddr>().unwrap()));
+        let io = conn.connect(msg).await.unwrap();
+        let data = io.recv(&BytesCodec).await.unwrap().unwrap();
+        assert_eq!(&data[..], b"127.0.0.1:9090");
+
+        let err = conn.connect("unknown.com:80").await.err().unwrap();
+        assert!(format!("{}", err).contains("Host unreachable"));
+
+        assert!(matches!(
+            conn.connect("example.com").await,
+            Err(ConnectError::Unresolved)
+        ));
+        assert!(format!("{:?}", conn).contains("Socks5Connector"));
+    }
+
+    #[ntex::test]
+    async fn test_socks5_auth() {
+        let srv = proxy(true);
+
+        let conn = Socks5Connector::new(srv.addr().to_string()).auth("user", "pass");
+        let io = conn.connect("example.com:443").await.unwrap();
+        let data = io.recv(&BytesCodec).await.unwrap().unwrap();
+        assert_eq!(&data[..], b"example.com:443");
+
+        let conn = Socks5Connector::new(srv.addr().to_string()).auth("user", "wrong");
+        let err = conn.connect("example.com:443").await.err().unwrap();
+        assert!(format!("{}", err).contains("authentication failed"));
+
+        // proxy requires authentication
+        let conn = Socks5Connector::<&'static str>::new(srv.addr().to_string());
+        assert!(conn.connect("example.com:443").await.is_err());
+    }
+}
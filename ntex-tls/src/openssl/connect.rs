@@ -1,4 +1,4 @@
-use std::{fmt, io};
+use std::{fmt, io, marker::PhantomData};
 
 use ntex_bytes::PoolId;
 use ntex_io::{Io, Layer};
@@ -8,9 +8,10 @@ use tls_openssl::ssl::SslConnector as BaseSslConnector;
 
 use super::{connect as connect_io, SslFilter};
 
-pub struct SslConnector<T> {
-    connector: Pipeline<BaseConnector<T>>,
+pub struct SslConnector<T, S = BaseConnector<T>> {
+    connector: Pipeline<S>,
     openssl: BaseSslConnector,
+    _t: PhantomData<T>,
 }
 
 impl<T: Address> SslConnector<T> {
@@ -19,6 +20,7 @@ impl<T: Address> SslConnector<T> {
         SslConnector {
             connector: BaseConnector::default().into(),
             openssl: connector,
+            _t: PhantomData,
         }
     }
 
@@ -32,11 +34,33 @@ impl<T: Address> SslConnector<T> {
         Self {
             connector,
             openssl: self.openssl,
+            _t: PhantomData,
         }
     }
 }
 
-impl<T: Address> SslConnector<T> {
+impl<T, S> SslConnector<T, S> {
+    /// Use custom connector for opening underlying connections.
+    ///
+    /// For example `Socks5Connector` could be used for connecting
+    /// through socks5 proxy.
+    pub fn connector<U>(self, connector: U) -> SslConnector<T, U>
+    where
+        U: Service<Connect<T>, Response = Io, Error = ConnectError>,
+    {
+        SslConnector {
+            connector: connector.into(),
+            openssl: self.openssl,
+            _t: PhantomData,
+        }
+    }
+}
+
+impl<T, S> SslConnector<T, S>
+where
+    T: Address,
+    S: Service<Connect<T>, Response = Io, Error = ConnectError>,
+{
     /// Resolve and connect to remote host
     pub async fn connect<U>(&self, message: U) -> Result<Io<Layer<SslFilter>>, ConnectError>
     where
@@ -75,16 +99,17 @@ impl<T: Address> SslConnector<T> {
     }
 }
 
-impl<T> Clone for SslConnector<T> {
+impl<T, S> Clone for SslConnector<T, S> {
     fn clone(&self) -> Self {
         Self {
             connector: self.connector.clone(),
             openssl: self.openssl.clone(),
+            _t: PhantomData,
         }
     }
 }
 
-impl<T> fmt::Debug for SslConnector<T> {
+impl<T, S: fmt::Debug> fmt::Debug for SslConnector<T, S> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("SslConnector(openssl)")
             .field("connector", &self.connector)
@@ -93,10 +118,14 @@ impl<T> fmt::Debug for SslConnector<T> {
     }
 }
 
-impl<T: Address, C> ServiceFactory<Connect<T>, C> for SslConnector<T> {
+impl<T, S, C> ServiceFactory<Connect<T>, C> for SslConnector<T, S>
+where
+    T: Address,
+    S: Service<Connect<T>, Response = Io, Error = ConnectError>,
+{
     type Response = Io<Layer<SslFilter>>;
     type Error = ConnectError;
-    type Service = SslConnector<T>;
+    type Service = SslConnector<T, S>;
     type InitError = ();
 
     async fn create(&self, _: C) -> Result<Self::Service, Self::InitError> {
@@ -104,7 +133,11 @@ impl<T: Address, C> ServiceFactory<Connect<T>, C> for SslConnector<T> {
     }
 }
 
-impl<T: Address> Service<Connect<T>> for SslConnector<T> {
+impl<T, S> Service<Connect<T>> for SslConnector<T, S>
+where
+    T: Address,
+    S: Service<Connect<T>, Response = Io, Error = ConnectError>,
+{
     type Response = Io<Layer<SslFilter>>;
     type Error = ConnectError;
 
@@ -140,5 +173,16 @@ mod tests {
             .await;
         assert!(result.is_err());
         assert!(format!("{:?}", srv).contains("SslConnector"));
+
+        let ssl = BaseSslConnector::builder(SslMethod::tls()).unwrap();
+        let srv = SslConnector::new(ssl.build())
+            .connector(BaseConnector::default().tag("CUSTOM"))
+            .pipeline(&())
+            .await
+            .unwrap();
+        let result = srv
+            .call(Connect::new("").set_addr(Some(server.addr())))
+            .await;
+        assert!(result.is_err());
     }
 }
@@ -1,4 +1,4 @@
-use std::{fmt, io, sync::Arc};
+use std::{fmt, io, marker::PhantomData, sync::Arc};
 
 use ntex_bytes::PoolId;
 use ntex_io::{Io, Layer};
@@ -9,9 +9,10 @@ use tls_rust::{pki_types::ServerName, ClientConfig};
 use super::TlsClientFilter;
 
 /// Rustls connector factory
-pub struct TlsConnector<T> {
-    connector: Pipeline<BaseConnector<T>>,
+pub struct TlsConnector<T, S = BaseConnector<T>> {
+    connector: Pipeline<S>,
     config: Arc<ClientConfig>,
+    _t: PhantomData<T>,
 }
 
 impl<T: Address> From<Arc<ClientConfig>> for TlsConnector<T> {
@@ -19,6 +20,7 @@ impl<T: Address> From<Arc<ClientConfig>> for TlsConnector<T> {
         TlsConnector {
             config,
             connector: BaseConnector::default().into(),
+            _t: PhantomData,
         }
     }
 }
@@ -28,6 +30,7 @@ impl<T: Address> TlsConnector<T> {
         TlsConnector {
             config: Arc::new(config),
             connector: BaseConnector::default().into(),
+            _t: PhantomData,
         }
     }
 
@@ -40,11 +43,33 @@ impl<T: Address> TlsConnector<T> {
         Self {
             connector,
             config: self.config,
+            _t: PhantomData,
         }
     }
 }
 
-impl<T: Address> TlsConnector<T> {
+impl<T, S> TlsConnector<T, S> {
+    /// Use custom connector for opening underlying connections.
+    ///
+    /// For example `Socks5Connector` could be used for connecting
+    /// through socks5 proxy.
+    pub fn connector<U>(self, connector: U) -> TlsConnector<T, U>
+    where
+        U: Service<Connect<T>, Response = Io, Error = ConnectError>,
+    {
+        TlsConnector {
+            connector: connector.into(),
+            config: self.config,
+            _t: PhantomData,
+        }
+    }
+}
+
+impl<T, S> TlsConnector<T, S>
+where
+    T: Address,
+    S: Service<Connect<T>, Response = Io, Error = ConnectError>,
+{
     /// Resolve and connect to remote host
     pub async fn connect<U>(
         &self,
@@ -77,16 +102,17 @@ impl<T: Address> TlsConnector<T> {
     }
 }
 
-impl<T> Clone for TlsConnector<T> {
+impl<T, S> Clone for TlsConnector<T, S> {
     fn clone(&self) -> Self {
         Self {
             config: self.config.clone(),
             connector: self.connector.clone(),
+            _t: PhantomData,
         }
     }
 }
 
-impl<T> fmt::Debug for TlsConnector<T> {
+impl<T, S: fmt::Debug> fmt::Debug for TlsConnector<T, S> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("TlsConnector(rustls)")
             .field("connector", &self.connector)
@@ -94,10 +120,14 @@ impl<T> fmt::Debug for TlsConnector<T> {
     }
 }
 
-impl<T: Address, C> ServiceFactory<Connect<T>, C> for TlsConnector<T> {
+impl<T, S, C> ServiceFactory<Connect<T>, C> for TlsConnector<T, S>
+where
+    T: Address,
+    S: Service<Connect<T>, Response = Io, Error = ConnectError>,
+{
     type Response = Io<Layer<TlsClientFilter>>;
     type Error = ConnectError;
-    type Service = TlsConnector<T>;
+    type Service = TlsConnector<T, S>;
     type InitError = ();
 
     async fn create(&self, _: C) -> Result<Self::Service, Self::InitError> {
@@ -105,7 +135,11 @@ impl<T: Address, C> ServiceFactory<Connect<T>, C> for TlsConnector<T> {
     }
 }
 
-impl<T: Address> Service<Connect<T>> for TlsConnector<T> {
+impl<T, S> Service<Connect<T>> for TlsConnector<T, S>
+where
+    T: Address,
+    S: Service<Connect<T>, Response = Io, Error = ConnectError>,
+{
     type Response = Io<Layer<TlsClientFilter>>;
     type Error = ConnectError;
 
@@ -137,6 +171,9 @@ mod tests {
             .with_root_certificates(cert_store)
             .with_no_client_auth();
         let _ = TlsConnector::<&'static str>::new(config.clone()).clone();
+        let _ = TlsConnector::<&'static str>::new(config.clone())
+            .connector(BaseConnector::default().tag("CUSTOM"))
+            .clone();
         let factory = TlsConnector::from(Arc::new(config))
             .memory_pool(PoolId::P5)
             .clone();
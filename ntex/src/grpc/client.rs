@@ -0,0 +1,228 @@
+use std::{error::Error, pin::Pin, rc::Rc, task::ready, task::Context, task::Poll};
+
+use crate::http::body::{Body, BodySize, MessageBody};
+use crate::http::header::{HeaderMap, HeaderValue, CONTENT_TYPE, TE};
+use crate::http::{client, StatusCode};
+use crate::util::{Bytes, Stream};
+
+use super::codec::{encode, Codec, DEFAULT_MAX_MESSAGE_SIZE};
+use super::request::{encode_timeout, Request, Response, Streaming};
+use super::{Code, Status, GRPC_CONTENT_TYPE, GRPC_TIMEOUT};
+
+/// gRPC client
+///
+/// Client sends calls with provided http client. gRPC requires
+/// http/2 transport, for secure connections http/2 is negotiated
+/// with tls alpn.
+///
+/// ```rust,no_run
+/// use ntex::grpc::{Client, RawCodec, Request};
+/// use ntex::{http, util::Bytes};
+///
+/// #[ntex::main]
+/// async fn main() {
+///     let client = Client::new(http::client::Client::new(), "https://localhost:50051");
+///     let res = client
+///         .unary("/echo.Echo/Call", RawCodec, Request::new(Bytes::from_static(b"test")))
+///         .await;
+///     println!("Response: {:?}", res);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Client {
+    client: client::Client,
+    base: Rc<str>,
+    max_message_size: usize,
+}
+
+impl Client {
+    /// Create grpc client for the remote host
+    ///
+    /// `base` is a scheme and authority of the remote host,
+    /// for example `https://localhost:50051`.
+    pub fn new(client: client::Client, base: &str) -> Self {
+        Client {
+            client,
+            base: base.trim_end_matches('/').into(),
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+        }
+    }
+
+    /// Set max size of incoming and outgoing messages
+    ///
+    /// By default max size is 4Mb
+    pub fn max_message_size(mut self, size: usize) -> Self {
+        self.max_message_size = size;
+        self
+    }
+
+    /// Send unary call
+    pub async fn unary<C: Codec>(
+        &self,
+        path: &str,
+        codec: C,
+        req: Request<C::Encode>,
+    ) -> Result<Response<C::Decode>, Status> {
+        let (msg, metadata, timeout) = req.into_parts();
+        let msg = encode(&codec, msg, self.max_message_size)?;
+
+        let mut res = self
+            .send(path, Rc::new(codec), metadata, timeout, msg.into())
+            .await?;
+        let stream = res.message_mut();
+        let msg = match stream.recv().await {
+            Some(msg) => msg?,
+            None => return Err(Status::internal("Missing response message")),
+        };
+        if let Some(item) = stream.recv().await {
+            item?;
+            return Err(Status::internal(
+                "Unary call received more than one message",
+            ));
+        }
+        Ok(Response::new(msg, res.metadata().clone()))
+    }
+
+    /// Send streaming call
+    ///
+    /// Request messages are sent from the provided stream,
+    /// response messages are received with `Streaming` stream.
+    pub async fn streaming<C, S>(
+        &self,
+        path: &str,
+        codec: C,
+        req: Request<S>,
+    ) -> Result<Response<Streaming<C::Decode>>, Status>
+    where
+        C: Codec,
+        S: Stream<Item = C::Encode> + Unpin + 'static,
+    {
+        let (stream, metadata, timeout) = req.into_parts();
+        let codec = Rc::new(codec);
+        let body = Body::from_message(RequestBody {
+            stream,
+            codec: codec.clone(),
+            max_size: self.max_message_size,
+        });
+        self.send(path, codec, metadata, timeout, body).await
+    }
+
+    async fn send<C: Codec>(
+        &self,
+        path: &str,
+        codec: Rc<C>,
+        metadata: HeaderMap,
+        timeout: Option<crate::time::Millis>,
+        body: Body,
+    ) -> Result<Response<Streaming<C::Decode>>, Status> {
+        let mut req = self
+            .client
+            .post(format!("{}{}", self.base, path))
+            .no_decompress();
+        for (name, value) in &metadata {
+            req.headers_mut().append(name.clone(), value.clone());
+        }
+        req.headers_mut().insert(CONTENT_TYPE, GRPC_CONTENT_TYPE);
+        req.headers_mut()
+            .insert(TE, HeaderValue::from_static("trailers"));
+        if let Some(timeout) = timeout {
+            if let Ok(value) = HeaderValue::try_from(encode_timeout(timeout)) {
+                req.headers_mut().insert(GRPC_TIMEOUT, value);
+            }
+            req = req.timeout(timeout);
+        }
+
+        let mut res = req.send_body(body).await?;
+        if res.status() != StatusCode::OK {
+            return Err(Status::new(
+                Code::from_http_status(res.status()),
+                format!("Unexpected http status: {}", res.status()),
+            ));
+        }
+
+        // trailers-only response
+        let status = Status::from_headers(res.headers());
+        if let Some(ref status) = status {
+            if !status.is_ok() {
+                return Err(status.clone());
+            }
+        }
+
+        let stream = Streaming::new(
+            res.take_payload(),
+            self.max_message_size,
+            Rc::new(move |msg| codec.decode(msg)),
+            status.is_none(),
+        );
+        Ok(Response::new(stream, res.headers().clone()))
+    }
+}
+
+/// Request body of streaming call
+struct RequestBody<S, C> {
+    stream: S,
+    codec: Rc<C>,
+    max_size: usize,
+}
+
+impl<S, C> MessageBody for RequestBody<S, C>
+where
+    C: Codec,
+    S: Stream<Item = C::Encode> + Unpin + 'static,
+{
+    fn size(&self) -> BodySize {
+        BodySize::Stream
+    }
+
+    fn poll_next_chunk(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Box<dyn Error>>>> {
+        match ready!(Pin::new(&mut self.stream).poll_next(cx)) {
+            Some(item) => Poll::Ready(Some(
+                encode(&*self.codec, item, self.max_size).map_err(|e| e.into()),
+            )),
+            None => Poll::Ready(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::poll_fn;
+
+    use super::*;
+    use crate::grpc::RawCodec;
+
+    #[crate::rt_test]
+    async fn request_body() {
+        let items = vec![Bytes::from_static(b"a"), Bytes::from_static(b"bc")];
+        let mut body = RequestBody {
+            stream: futures_util::stream::iter(items),
+            codec: Rc::new(RawCodec),
+            max_size: 1,
+        };
+        assert_eq!(body.size(), BodySize::Stream);
+        assert_eq!(
+            poll_fn(|cx| body.poll_next_chunk(cx))
+                .await
+                .unwrap()
+                .unwrap(),
+            Bytes::from_static(b"\x00\x00\x00\x00\x01a")
+        );
+        assert!(poll_fn(|cx| body.poll_next_chunk(cx))
+            .await
+            .unwrap()
+            .is_err());
+        assert!(poll_fn(|cx| body.poll_next_chunk(cx)).await.is_none());
+    }
+
+    #[crate::rt_test]
+    async fn client() {
+        let client =
+            Client::new(client::Client::new(), "http://localhost/").max_message_size(1024);
+        assert_eq!(&*client.base, "http://localhost");
+        assert_eq!(client.max_message_size, 1024);
+        assert!(format!("{:?}", client).contains("Client"));
+    }
+}
@@ -0,0 +1,226 @@
+use std::fmt;
+#[cfg(feature = "prost")]
+use std::marker::PhantomData;
+
+use crate::util::{Buf, BufMut, Bytes, BytesMut};
+
+use super::Status;
+
+/// Size of message frame header, compression flag and message length
+const HEADER_SIZE: usize = 5;
+
+/// Default max size of message
+pub(super) const DEFAULT_MAX_MESSAGE_SIZE: usize = 4 * 1024 * 1024;
+
+/// Message serialization for gRPC calls
+///
+/// Codec converts messages to and from their wire representation,
+/// length-prefixed framing is handled by ntex.
+pub trait Codec: 'static {
+    /// Type of outgoing messages
+    type Encode;
+    /// Type of incoming messages
+    type Decode;
+
+    /// Serialize message to the buffer
+    fn encode(&self, item: Self::Encode, dst: &mut BytesMut) -> Result<(), Status>;
+
+    /// Deserialize message
+    fn decode(&self, src: Bytes) -> Result<Self::Decode, Status>;
+}
+
+/// Codec for raw binary messages
+#[derive(Copy, Clone, Debug, Default)]
+pub struct RawCodec;
+
+impl Codec for RawCodec {
+    type Encode = Bytes;
+    type Decode = Bytes;
+
+    fn encode(&self, item: Bytes, dst: &mut BytesMut) -> Result<(), Status> {
+        dst.extend_from_slice(&item);
+        Ok(())
+    }
+
+    fn decode(&self, src: Bytes) -> Result<Bytes, Status> {
+        Ok(src)
+    }
+}
+
+#[cfg(feature = "prost")]
+/// Codec for `prost` generated messages
+pub struct ProstCodec<E, D>(PhantomData<(E, D)>);
+
+#[cfg(feature = "prost")]
+impl<E, D> ProstCodec<E, D> {
+    /// Create new prost codec
+    pub fn new() -> Self {
+        ProstCodec(PhantomData)
+    }
+}
+
+#[cfg(feature = "prost")]
+impl<E, D> Default for ProstCodec<E, D> {
+    fn default() -> Self {
+        ProstCodec(PhantomData)
+    }
+}
+
+#[cfg(feature = "prost")]
+impl<E, D> Clone for ProstCodec<E, D> {
+    fn clone(&self) -> Self {
+        ProstCodec(PhantomData)
+    }
+}
+
+#[cfg(feature = "prost")]
+impl<E, D> fmt::Debug for ProstCodec<E, D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProstCodec")
+            .field("encode", &std::any::type_name::<E>())
+            .field("decode", &std::any::type_name::<D>())
+            .finish()
+    }
+}
+
+#[cfg(feature = "prost")]
+impl<E, D> Codec for ProstCodec<E, D>
+where
+    E: prost::Message + 'static,
+    D: prost::Message + Default + 'static,
+{
+    type Encode = E;
+    type Decode = D;
+
+    fn encode(&self, item: E, dst: &mut BytesMut) -> Result<(), Status> {
+        dst.extend_from_slice(&item.encode_to_vec());
+        Ok(())
+    }
+
+    fn decode(&self, src: Bytes) -> Result<D, Status> {
+        D::decode(src.as_ref()).map_err(|e| Status::internal(e.to_string()))
+    }
+}
+
+/// Encode length-prefixed message
+pub(super) fn encode<C: Codec>(
+    codec: &C,
+    item: C::Encode,
+    max_size: usize,
+) -> Result<Bytes, Status> {
+    let mut dst = BytesMut::with_capacity(64);
+    dst.put_slice(&[0; HEADER_SIZE]);
+    codec.encode(item, &mut dst)?;
+
+    let len = dst.len() - HEADER_SIZE;
+    if len > max_size {
+        return Err(Status::new(
+            super::Code::ResourceExhausted,
+            format!("Message is too large: {} > {}", len, max_size),
+        ));
+    }
+    dst[1..HEADER_SIZE].copy_from_slice(&(len as u32).to_be_bytes());
+    Ok(dst.freeze())
+}
+
+/// Decoder for length-prefixed messages
+pub(super) struct FrameDecoder {
+    buf: BytesMut,
+    max_size: usize,
+}
+
+impl FrameDecoder {
+    pub(super) fn new(max_size: usize) -> Self {
+        FrameDecoder {
+            max_size,
+            buf: BytesMut::new(),
+        }
+    }
+
+    /// Add data to the decoder
+    pub(super) fn feed(&mut self, data: Bytes) {
+        self.buf.extend_from_slice(&data);
+    }
+
+    /// Check if decoder contains unprocessed data
+    pub(super) fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    /// Decode next message
+    pub(super) fn decode(&mut self) -> Result<Option<Bytes>, Status> {
+        if self.buf.len() < HEADER_SIZE {
+            return Ok(None);
+        }
+        if self.buf[0] != 0 {
+            return Err(Status::unimplemented(
+                "Message compression is not supported",
+            ));
+        }
+
+        let len = u32::from_be_bytes([self.buf[1], self.buf[2], self.buf[3], self.buf[4]])
+            as usize;
+        if len > self.max_size {
+            Err(Status::new(
+                super::Code::ResourceExhausted,
+                format!("Message is too large: {} > {}", len, self.max_size),
+            ))
+        } else if self.buf.len() < HEADER_SIZE + len {
+            Ok(None)
+        } else {
+            self.buf.advance(HEADER_SIZE);
+            Ok(Some(self.buf.split_to(len).freeze()))
+        }
+    }
+}
+
+impl fmt::Debug for FrameDecoder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FrameDecoder")
+            .field("buffered", &self.buf.len())
+            .field("max_size", &self.max_size)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grpc::Code;
+
+    #[test]
+    fn frames() {
+        let msg = encode(&RawCodec, Bytes::from_static(b"test"), 1024).unwrap();
+        assert_eq!(msg, Bytes::from_static(b"\x00\x00\x00\x00\x04test"));
+
+        let err = encode(&RawCodec, Bytes::from_static(b"test"), 2).unwrap_err();
+        assert_eq!(err.code(), Code::ResourceExhausted);
+
+        let mut decoder = FrameDecoder::new(1024);
+        assert!(decoder.is_empty());
+        decoder.feed(msg.slice(..3));
+        assert_eq!(decoder.decode().unwrap(), None);
+        decoder.feed(msg.slice(3..));
+        decoder.feed(msg.slice(..7));
+        assert_eq!(decoder.decode().unwrap(), Some(Bytes::from_static(b"test")));
+        assert_eq!(decoder.decode().unwrap(), None);
+        assert!(!decoder.is_empty());
+        decoder.feed(msg.slice(7..));
+        assert_eq!(decoder.decode().unwrap(), Some(Bytes::from_static(b"test")));
+        assert!(decoder.is_empty());
+
+        decoder.feed(Bytes::from_static(b"\x00\x00\x00\x00\x00"));
+        assert_eq!(decoder.decode().unwrap(), Some(Bytes::new()));
+
+        decoder.feed(Bytes::from_static(b"\x01\x00\x00\x00\x00"));
+        assert_eq!(decoder.decode().unwrap_err().code(), Code::Unimplemented);
+
+        let mut decoder = FrameDecoder::new(2);
+        decoder.feed(msg);
+        assert_eq!(
+            decoder.decode().unwrap_err().code(),
+            Code::ResourceExhausted
+        );
+        assert!(format!("{:?}", decoder).contains("FrameDecoder"));
+    }
+}
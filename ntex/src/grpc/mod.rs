@@ -0,0 +1,63 @@
+//! gRPC server and client
+//!
+//! gRPC calls are carried over http/2 streams, messages are
+//! length-prefixed and call status is sent with `grpc-status`
+//! and `grpc-message` trailers.
+//!
+//! ```rust,no_run
+//! use ntex::grpc::{self, RawCodec, Request, Status};
+//! use ntex::{http, service::fn_service, util::Bytes};
+//!
+//! #[ntex::main]
+//! async fn main() -> std::io::Result<()> {
+//!     ntex::server::build()
+//!         .bind("grpc", "127.0.0.1:50051", |_| {
+//!             http::HttpService::build().h2(
+//!                 grpc::Server::new().unary(
+//!                     "/echo.Echo/Call",
+//!                     RawCodec,
+//!                     fn_service(|req: Request<Bytes>| async move {
+//!                         Ok::<_, Status>(req.into_inner())
+//!                     }),
+//!                 ),
+//!             )
+//!         })?
+//!         .run()
+//!         .await
+//! }
+//! ```
+mod client;
+mod codec;
+mod request;
+mod server;
+mod status;
+
+pub use self::client::Client;
+#[cfg(feature = "prost")]
+pub use self::codec::ProstCodec;
+pub use self::codec::{Codec, RawCodec};
+pub use self::request::{Request, Response, Streaming};
+pub use self::server::{Server, ServerService};
+pub use self::status::{Code, Status};
+
+use crate::http::header::{HeaderName, HeaderValue};
+
+/// Content type of grpc requests and responses
+#[allow(clippy::declare_interior_mutable_const)]
+pub const GRPC_CONTENT_TYPE: HeaderValue = HeaderValue::from_static("application/grpc");
+
+/// `grpc-status` trailer
+#[allow(clippy::declare_interior_mutable_const)]
+pub const GRPC_STATUS: HeaderName = HeaderName::from_static("grpc-status");
+
+/// `grpc-message` trailer
+#[allow(clippy::declare_interior_mutable_const)]
+pub const GRPC_MESSAGE: HeaderName = HeaderName::from_static("grpc-message");
+
+/// `grpc-timeout` header
+#[allow(clippy::declare_interior_mutable_const)]
+pub const GRPC_TIMEOUT: HeaderName = HeaderName::from_static("grpc-timeout");
+
+/// `grpc-encoding` header
+#[allow(clippy::declare_interior_mutable_const)]
+pub const GRPC_ENCODING: HeaderName = HeaderName::from_static("grpc-encoding");
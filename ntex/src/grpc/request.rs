@@ -0,0 +1,336 @@
+use std::task::{ready, Context, Poll};
+use std::{fmt, future::poll_fn, pin::Pin, rc::Rc};
+
+use crate::http::{header::HeaderMap, Payload};
+use crate::{time::Millis, util::Bytes, util::Stream};
+
+use super::{codec::FrameDecoder, Status};
+
+/// gRPC request
+#[derive(Debug)]
+pub struct Request<T> {
+    message: T,
+    metadata: HeaderMap,
+    timeout: Option<Millis>,
+}
+
+impl<T> Request<T> {
+    /// Create new request
+    pub fn new(message: T) -> Self {
+        Request {
+            message,
+            metadata: HeaderMap::new(),
+            timeout: None,
+        }
+    }
+
+    pub(super) fn with_parts(
+        message: T,
+        metadata: HeaderMap,
+        timeout: Option<Millis>,
+    ) -> Self {
+        Request {
+            message,
+            metadata,
+            timeout,
+        }
+    }
+
+    /// Request message
+    pub fn message(&self) -> &T {
+        &self.message
+    }
+
+    /// Mutable reference to request message
+    pub fn message_mut(&mut self) -> &mut T {
+        &mut self.message
+    }
+
+    /// Consume request and return message
+    pub fn into_inner(self) -> T {
+        self.message
+    }
+
+    /// Request metadata
+    pub fn metadata(&self) -> &HeaderMap {
+        &self.metadata
+    }
+
+    /// Mutable reference to request metadata
+    pub fn metadata_mut(&mut self) -> &mut HeaderMap {
+        &mut self.metadata
+    }
+
+    /// Call deadline, sent with `grpc-timeout` header
+    pub fn timeout(&self) -> Option<Millis> {
+        self.timeout
+    }
+
+    /// Set call deadline
+    pub fn set_timeout<U: Into<Millis>>(&mut self, timeout: U) {
+        self.timeout = Some(timeout.into());
+    }
+
+    pub(super) fn into_parts(self) -> (T, HeaderMap, Option<Millis>) {
+        (self.message, self.metadata, self.timeout)
+    }
+}
+
+/// gRPC response
+#[derive(Debug)]
+pub struct Response<T> {
+    message: T,
+    metadata: HeaderMap,
+}
+
+impl<T> Response<T> {
+    pub(super) fn new(message: T, metadata: HeaderMap) -> Self {
+        Response { message, metadata }
+    }
+
+    /// Response message
+    pub fn message(&self) -> &T {
+        &self.message
+    }
+
+    /// Mutable reference to response message
+    pub fn message_mut(&mut self) -> &mut T {
+        &mut self.message
+    }
+
+    /// Consume response and return message
+    pub fn into_inner(self) -> T {
+        self.message
+    }
+
+    /// Response metadata
+    pub fn metadata(&self) -> &HeaderMap {
+        &self.metadata
+    }
+}
+
+/// Stream of incoming gRPC messages
+pub struct Streaming<T> {
+    payload: Payload,
+    decoder: FrameDecoder,
+    decode: Rc<dyn Fn(Bytes) -> Result<T, Status>>,
+    trailers: Option<HeaderMap>,
+    status: bool,
+    eof: bool,
+}
+
+impl<T> Streaming<T> {
+    pub(super) fn new(
+        payload: Payload,
+        max_size: usize,
+        decode: Rc<dyn Fn(Bytes) -> Result<T, Status>>,
+        status: bool,
+    ) -> Self {
+        Streaming {
+            payload,
+            decode,
+            status,
+            decoder: FrameDecoder::new(max_size),
+            trailers: None,
+            eof: false,
+        }
+    }
+
+    /// Receive next message
+    pub async fn recv(&mut self) -> Option<Result<T, Status>> {
+        poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Attempt to receive next message
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<T, Status>>> {
+        loop {
+            if self.eof {
+                return Poll::Ready(None);
+            }
+
+            match self.decoder.decode() {
+                Ok(Some(msg)) => return Poll::Ready(Some((self.decode)(msg))),
+                Ok(None) => (),
+                Err(err) => {
+                    self.eof = true;
+                    return Poll::Ready(Some(Err(err)));
+                }
+            }
+
+            match ready!(self.payload.poll_recv(cx)) {
+                Some(Ok(chunk)) => self.decoder.feed(chunk),
+                Some(Err(err)) => {
+                    self.eof = true;
+                    return Poll::Ready(Some(Err(err.into())));
+                }
+                None => {
+                    self.eof = true;
+                    self.trailers = self.payload.take_trailers();
+                    if !self.decoder.is_empty() {
+                        return Poll::Ready(Some(Err(Status::internal(
+                            "Incomplete message",
+                        ))));
+                    }
+                    if self.status {
+                        let status = self
+                            .trailers
+                            .as_ref()
+                            .and_then(Status::from_headers)
+                            .unwrap_or_else(|| Status::internal("Missing grpc-status"));
+                        if !status.is_ok() {
+                            return Poll::Ready(Some(Err(status)));
+                        }
+                    }
+                    return Poll::Ready(None);
+                }
+            }
+        }
+    }
+
+    /// Trailing metadata, available after stream is exhausted
+    pub fn trailers(&self) -> Option<&HeaderMap> {
+        self.trailers.as_ref()
+    }
+}
+
+impl<T> Stream for Streaming<T> {
+    type Item = Result<T, Status>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().poll_recv(cx)
+    }
+}
+
+impl<T> fmt::Debug for Streaming<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Streaming")
+            .field("payload", &self.payload)
+            .field("decoder", &self.decoder)
+            .field("eof", &self.eof)
+            .finish()
+    }
+}
+
+/// Parse `grpc-timeout` header value
+///
+/// Sub-millisecond timeouts are rounded down, zero timeout means
+/// deadline is already exceeded.
+pub(super) fn parse_timeout(val: &[u8]) -> Option<Millis> {
+    if val.len() < 2 || val.len() > 9 {
+        return None;
+    }
+    let (digits, unit) = val.split_at(val.len() - 1);
+    let value = std::str::from_utf8(digits).ok()?.parse::<u64>().ok()?;
+
+    let millis = match unit[0] {
+        b'H' => value.saturating_mul(3_600_000),
+        b'M' => value.saturating_mul(60_000),
+        b'S' => value.saturating_mul(1000),
+        b'm' => value,
+        b'u' => value / 1000,
+        b'n' => value / 1_000_000,
+        _ => return None,
+    };
+    Some(Millis(u32::try_from(millis).unwrap_or(u32::MAX)))
+}
+
+/// Encode `grpc-timeout` header value
+pub(super) fn encode_timeout(timeout: Millis) -> String {
+    if timeout.0 < 100_000_000 {
+        format!("{}m", timeout.0)
+    } else {
+        format!("{}S", timeout.0.div_ceil(1000))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::h1;
+
+    #[test]
+    fn timeouts() {
+        assert_eq!(parse_timeout(b"10m"), Some(Millis(10)));
+        assert_eq!(parse_timeout(b"2S"), Some(Millis(2000)));
+        assert_eq!(parse_timeout(b"1M"), Some(Millis(60_000)));
+        assert_eq!(parse_timeout(b"1H"), Some(Millis(3_600_000)));
+        assert_eq!(parse_timeout(b"1500u"), Some(Millis(1)));
+        assert_eq!(parse_timeout(b"999u"), Some(Millis::ZERO));
+        assert_eq!(parse_timeout(b"1n"), Some(Millis::ZERO));
+        assert_eq!(parse_timeout(b"0m"), Some(Millis::ZERO));
+        assert_eq!(parse_timeout(b"99999999H"), Some(Millis(u32::MAX)));
+        assert_eq!(parse_timeout(b"123456789m"), None);
+        assert_eq!(parse_timeout(b"10"), None);
+        assert_eq!(parse_timeout(b"m"), None);
+        assert_eq!(parse_timeout(b"-1m"), None);
+        assert_eq!(parse_timeout(b"10x"), None);
+
+        assert_eq!(encode_timeout(Millis(250)), "250m");
+        assert_eq!(encode_timeout(Millis(100_000_001)), "100001S");
+        assert_eq!(
+            parse_timeout(encode_timeout(Millis(u32::MAX)).as_bytes()),
+            Some(Millis(u32::MAX))
+        );
+    }
+
+    #[test]
+    fn request() {
+        let mut req = Request::new(1);
+        assert_eq!(*req.message(), 1);
+        *req.message_mut() = 2;
+        assert!(req.timeout().is_none());
+        req.set_timeout(Millis(10));
+        assert_eq!(req.timeout(), Some(Millis(10)));
+        assert!(req.metadata().is_empty());
+        req.metadata_mut().insert(
+            crate::http::header::HeaderName::from_static("x-test"),
+            crate::http::header::HeaderValue::from_static("1"),
+        );
+        assert!(req.metadata().contains_key("x-test"));
+        assert_eq!(req.into_inner(), 2);
+
+        let res = Response::new("test", HeaderMap::new());
+        assert_eq!(*res.message(), "test");
+        assert!(res.metadata().is_empty());
+        assert_eq!(res.into_inner(), "test");
+    }
+
+    #[crate::rt_test]
+    async fn streaming() {
+        let data = Bytes::from_static(b"\x00\x00\x00\x00\x01a\x00\x00\x00\x00\x01b");
+        let (tx, rx) = h1::Payload::create();
+        tx.feed_data(data);
+        tx.feed_trailers(Status::not_found("test").to_trailers());
+
+        let mut stream = Streaming::new(rx.into(), 1024, Rc::new(Ok), true);
+        assert_eq!(
+            stream.recv().await.unwrap().unwrap(),
+            Bytes::from_static(b"a")
+        );
+        assert_eq!(
+            stream.recv().await.unwrap().unwrap(),
+            Bytes::from_static(b"b")
+        );
+        assert_eq!(stream.recv().await.unwrap().unwrap_err().message(), "test");
+        assert!(stream.recv().await.is_none());
+        assert!(stream.trailers().is_some());
+        assert!(format!("{:?}", stream).contains("Streaming"));
+
+        let (tx, rx) = h1::Payload::create();
+        tx.feed_data(Bytes::from_static(b"\x00\x00\x00\x00\x02a"));
+        tx.feed_eof();
+        let mut stream = Streaming::new(rx.into(), 1024, Rc::new(Ok), false);
+        assert_eq!(
+            stream.recv().await.unwrap().unwrap_err().message(),
+            "Incomplete message"
+        );
+
+        let (tx, rx) = h1::Payload::create();
+        tx.feed_eof();
+        let mut stream = Streaming::new(rx.into(), 1024, Rc::new(Ok), true);
+        assert_eq!(
+            stream.recv().await.unwrap().unwrap_err().message(),
+            "Missing grpc-status"
+        );
+    }
+}
@@ -0,0 +1,673 @@
+use std::{fmt, future::Future, pin::Pin, rc::Rc, task::ready, task::Context, task::Poll};
+
+use crate::http::body::{Body, BodySize, MessageBody, WithTrailers};
+use crate::http::header::{HeaderMap, CONTENT_TYPE};
+use crate::http::{Method, Request as HttpRequest, Response as HttpResponse, StatusCode};
+use crate::service::boxed::{self, BoxService, BoxServiceFactory};
+use crate::service::{IntoServiceFactory, Service, ServiceCtx, ServiceFactory};
+use crate::time::{Deadline, Millis};
+use crate::util::{select, Bytes, Either, HashMap, Stream};
+
+use super::codec::{encode, Codec, DEFAULT_MAX_MESSAGE_SIZE};
+use super::request::{parse_timeout, Request, Streaming};
+use super::{Status, GRPC_CONTENT_TYPE, GRPC_ENCODING, GRPC_TIMEOUT};
+
+type MethodFactory = BoxServiceFactory<(), Call, HttpResponse, Status, ()>;
+type MethodService = BoxService<Call, HttpResponse, Status>;
+
+/// gRPC server
+///
+/// Server is a http service factory, it dispatches calls to the
+/// registered method services by request path (`/package.Service/Method`).
+/// Server is supposed to be used with http/2 transport.
+pub struct Server {
+    methods: Vec<(String, MethodFactory)>,
+    max_message_size: usize,
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Server::new()
+    }
+}
+
+impl Server {
+    /// Create new grpc server
+    pub fn new() -> Self {
+        Server {
+            methods: Vec::new(),
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+        }
+    }
+
+    /// Set max size of incoming and outgoing messages
+    ///
+    /// By default max size is 4Mb
+    pub fn max_message_size(mut self, size: usize) -> Self {
+        self.max_message_size = size;
+        self
+    }
+
+    /// Register unary method
+    ///
+    /// Method service receives single request message and
+    /// responds with single message.
+    pub fn unary<C, F, U>(mut self, path: &str, codec: C, factory: U) -> Self
+    where
+        C: Codec,
+        F: ServiceFactory<Request<C::Decode>, Response = C::Encode, Error = Status>
+            + 'static,
+        F::InitError: fmt::Debug,
+        U: IntoServiceFactory<F, Request<C::Decode>>,
+    {
+        self.methods.push((
+            path.to_string(),
+            boxed::factory(UnaryFactory {
+                codec: Rc::new(codec),
+                factory: factory.into_factory(),
+            }),
+        ));
+        self
+    }
+
+    /// Register streaming method
+    ///
+    /// Method service receives stream of request messages and
+    /// responds with stream of messages. Client, server and bidirectional
+    /// streaming calls are handled by this kind of methods.
+    pub fn streaming<C, F, U, R>(mut self, path: &str, codec: C, factory: U) -> Self
+    where
+        C: Codec,
+        F: ServiceFactory<Request<Streaming<C::Decode>>, Response = R, Error = Status>
+            + 'static,
+        F::InitError: fmt::Debug,
+        U: IntoServiceFactory<F, Request<Streaming<C::Decode>>>,
+        R: Stream<Item = Result<C::Encode, Status>> + Unpin + 'static,
+    {
+        self.methods.push((
+            path.to_string(),
+            boxed::factory(StreamingFactory {
+                codec: Rc::new(codec),
+                factory: factory.into_factory(),
+            }),
+        ));
+        self
+    }
+}
+
+impl fmt::Debug for Server {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("grpc::Server")
+            .field(
+                "methods",
+                &self.methods.iter().map(|(p, _)| p).collect::<Vec<_>>(),
+            )
+            .field("max_message_size", &self.max_message_size)
+            .finish()
+    }
+}
+
+impl ServiceFactory<HttpRequest> for Server {
+    type Response = HttpResponse;
+    type Error = Status;
+    type Service = ServerService;
+    type InitError = ();
+
+    async fn create(&self, _: ()) -> Result<Self::Service, Self::InitError> {
+        let mut methods = HashMap::default();
+        for (path, factory) in &self.methods {
+            methods.insert(path.clone(), factory.create(()).await?);
+        }
+        Ok(ServerService {
+            methods,
+            max_message_size: self.max_message_size,
+        })
+    }
+}
+
+/// gRPC server service
+pub struct ServerService {
+    methods: HashMap<String, MethodService>,
+    max_message_size: usize,
+}
+
+impl fmt::Debug for ServerService {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("grpc::ServerService")
+            .field("methods", &self.methods.keys().collect::<Vec<_>>())
+            .field("max_message_size", &self.max_message_size)
+            .finish()
+    }
+}
+
+impl Service<HttpRequest> for ServerService {
+    type Response = HttpResponse;
+    type Error = Status;
+
+    async fn call(
+        &self,
+        req: HttpRequest,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<HttpResponse, Status> {
+        if req.method() != Method::POST {
+            return Ok(HttpResponse::new(StatusCode::METHOD_NOT_ALLOWED));
+        }
+        let is_grpc = req
+            .headers()
+            .get(CONTENT_TYPE)
+            .map(|v| v.as_bytes().starts_with(GRPC_CONTENT_TYPE.as_bytes()))
+            .unwrap_or(false);
+        if !is_grpc {
+            return Ok(HttpResponse::new(StatusCode::UNSUPPORTED_MEDIA_TYPE));
+        }
+        if let Some(enc) = req.headers().get(GRPC_ENCODING) {
+            if enc.as_bytes() != b"identity" {
+                return Err(Status::unimplemented(format!(
+                    "Message encoding {:?} is not supported",
+                    enc
+                )));
+            }
+        }
+
+        let method = if let Some(method) = self.methods.get(req.path()) {
+            method
+        } else {
+            return Err(Status::unimplemented(format!(
+                "Method {} is not implemented",
+                req.path()
+            )));
+        };
+        let timeout = req
+            .headers()
+            .get(GRPC_TIMEOUT)
+            .and_then(|v| parse_timeout(v.as_bytes()));
+
+        // zero deadline never elapses, so expired call must be rejected here
+        if timeout == Some(Millis::ZERO) {
+            return Err(Status::deadline_exceeded("Deadline exceeded"));
+        }
+
+        ctx.call(
+            method,
+            Call {
+                req,
+                timeout,
+                deadline: Deadline::new(timeout.unwrap_or(Millis::ZERO)),
+                max_size: self.max_message_size,
+            },
+        )
+        .await
+    }
+}
+
+/// Call passed to method service
+struct Call {
+    req: HttpRequest,
+    timeout: Option<Millis>,
+    // started when request is received, covers whole call
+    deadline: Deadline,
+    max_size: usize,
+}
+
+impl Call {
+    fn into_request<C: Codec>(
+        self,
+        codec: &Rc<C>,
+    ) -> (Request<Streaming<C::Decode>>, Deadline, usize) {
+        let Call {
+            mut req,
+            timeout,
+            deadline,
+            max_size,
+        } = self;
+
+        let codec = codec.clone();
+        let stream = Streaming::new(
+            req.take_payload(),
+            max_size,
+            Rc::new(move |msg| codec.decode(msg)),
+            false,
+        );
+        let request = Request::with_parts(stream, req.headers().clone(), timeout);
+        (request, deadline, max_size)
+    }
+}
+
+/// Run future within call deadline
+async fn with_deadline<F, T>(deadline: &Deadline, fut: F) -> Result<T, Status>
+where
+    F: Future<Output = Result<T, Status>>,
+{
+    match select(deadline.wait(), fut).await {
+        Either::Left(_) => Err(Status::deadline_exceeded("Deadline exceeded")),
+        Either::Right(res) => res,
+    }
+}
+
+fn response(body: Body) -> HttpResponse {
+    let mut res = HttpResponse::new(StatusCode::OK).set_body(body);
+    res.headers_mut().insert(CONTENT_TYPE, GRPC_CONTENT_TYPE);
+    res
+}
+
+struct UnaryFactory<C, F> {
+    codec: Rc<C>,
+    factory: F,
+}
+
+impl<C, F> ServiceFactory<Call> for UnaryFactory<C, F>
+where
+    C: Codec,
+    F: ServiceFactory<Request<C::Decode>, Response = C::Encode, Error = Status>,
+    F::InitError: fmt::Debug,
+{
+    type Response = HttpResponse;
+    type Error = Status;
+    type Service = UnaryService<C, F::Service>;
+    type InitError = ();
+
+    async fn create(&self, _: ()) -> Result<Self::Service, Self::InitError> {
+        let service = self.factory.create(()).await.map_err(|e| {
+            log::error!("Cannot construct grpc method service: {:?}", e);
+        })?;
+        Ok(UnaryService {
+            service,
+            codec: self.codec.clone(),
+        })
+    }
+}
+
+struct UnaryService<C, S> {
+    codec: Rc<C>,
+    service: S,
+}
+
+impl<C, S> Service<Call> for UnaryService<C, S>
+where
+    C: Codec,
+    S: Service<Request<C::Decode>, Response = C::Encode, Error = Status>,
+{
+    type Response = HttpResponse;
+    type Error = Status;
+
+    async fn call(
+        &self,
+        call: Call,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<HttpResponse, Status> {
+        let (req, deadline, max_size) = call.into_request(&self.codec);
+
+        let msg = with_deadline(&deadline, async {
+            let (mut stream, metadata, timeout) = req.into_parts();
+            let msg = match stream.recv().await {
+                Some(msg) => msg?,
+                None => return Err(Status::internal("Missing request message")),
+            };
+            if let Some(item) = stream.recv().await {
+                item?;
+                return Err(Status::internal(
+                    "Unary call received more than one message",
+                ));
+            }
+
+            let res = ctx
+                .call(&self.service, Request::with_parts(msg, metadata, timeout))
+                .await?;
+            encode(&*self.codec, res, max_size)
+        })
+        .await?;
+
+        Ok(response(
+            WithTrailers::new(msg, Status::ok().to_trailers()).into(),
+        ))
+    }
+}
+
+struct StreamingFactory<C, F> {
+    codec: Rc<C>,
+    factory: F,
+}
+
+impl<C, F, R> ServiceFactory<Call> for StreamingFactory<C, F>
+where
+    C: Codec,
+    F: ServiceFactory<Request<Streaming<C::Decode>>, Response = R, Error = Status>,
+    F::InitError: fmt::Debug,
+    R: Stream<Item = Result<C::Encode, Status>> + Unpin + 'static,
+{
+    type Response = HttpResponse;
+    type Error = Status;
+    type Service = StreamingService<C, F::Service>;
+    type InitError = ();
+
+    async fn create(&self, _: ()) -> Result<Self::Service, Self::InitError> {
+        let service = self.factory.create(()).await.map_err(|e| {
+            log::error!("Cannot construct grpc method service: {:?}", e);
+        })?;
+        Ok(StreamingService {
+            service,
+            codec: self.codec.clone(),
+        })
+    }
+}
+
+struct StreamingService<C, S> {
+    codec: Rc<C>,
+    service: S,
+}
+
+impl<C, S, R> Service<Call> for StreamingService<C, S>
+where
+    C: Codec,
+    S: Service<Request<Streaming<C::Decode>>, Response = R, Error = Status>,
+    R: Stream<Item = Result<C::Encode, Status>> + Unpin + 'static,
+{
+    type Response = HttpResponse;
+    type Error = Status;
+
+    async fn call(
+        &self,
+        call: Call,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<HttpResponse, Status> {
+        let (req, deadline, max_size) = call.into_request(&self.codec);
+        let stream = with_deadline(&deadline, ctx.call(&self.service, req)).await?;
+
+        Ok(response(Body::from_message(StreamingBody {
+            stream,
+            max_size,
+            codec: self.codec.clone(),
+            deadline,
+            status: None,
+            eof: false,
+        })))
+    }
+}
+
+/// Response body of streaming call
+///
+/// Call status is sent with body trailers.
+struct StreamingBody<S, C> {
+    stream: S,
+    codec: Rc<C>,
+    max_size: usize,
+    deadline: Deadline,
+    status: Option<Status>,
+    eof: bool,
+}
+
+impl<S, C> StreamingBody<S, C> {
+    fn finish(
+        &mut self,
+        status: Status,
+    ) -> Poll<Option<Result<Bytes, Box<dyn std::error::Error>>>> {
+        self.eof = true;
+        self.status = Some(status);
+        Poll::Ready(None)
+    }
+}
+
+impl<S, C> MessageBody for StreamingBody<S, C>
+where
+    C: Codec,
+    S: Stream<Item = Result<C::Encode, Status>> + Unpin + 'static,
+{
+    fn size(&self) -> BodySize {
+        BodySize::Stream
+    }
+
+    fn poll_next_chunk(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Box<dyn std::error::Error>>>> {
+        if self.eof {
+            return Poll::Ready(None);
+        }
+        if self.deadline.poll_elapsed(cx).is_ready() {
+            return self.finish(Status::deadline_exceeded("Deadline exceeded"));
+        }
+
+        match ready!(Pin::new(&mut self.stream).poll_next(cx)) {
+            Some(Ok(item)) => match encode(&*self.codec, item, self.max_size) {
+                Ok(msg) => Poll::Ready(Some(Ok(msg))),
+                Err(status) => self.finish(status),
+            },
+            Some(Err(status)) => self.finish(status),
+            None => self.finish(Status::ok()),
+        }
+    }
+
+    fn poll_trailers(&mut self, _: &mut Context<'_>) -> Poll<Option<HeaderMap>> {
+        let status = self.status.take().unwrap_or_else(Status::ok);
+        Poll::Ready(Some(status.to_trailers()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::poll_fn;
+
+    use super::*;
+    use crate::channel::bstream;
+    use crate::grpc::{codec::FrameDecoder, Code, RawCodec, GRPC_STATUS};
+    use crate::http::{error::PayloadError, test::TestRequest};
+    use crate::service::{fn_service, Pipeline};
+
+    fn request(path: &str, data: &'static [u8]) -> HttpRequest {
+        let (tx, rx) = bstream::channel::<PayloadError>();
+        tx.feed_data(Bytes::from_static(data));
+        tx.feed_eof();
+
+        let mut req = TestRequest::with_uri(path)
+            .method(Method::POST)
+            .header(CONTENT_TYPE, GRPC_CONTENT_TYPE)
+            .finish();
+        req.replace_payload(rx.into());
+        req
+    }
+
+    async fn server() -> Pipeline<ServerService> {
+        let srv = Server::new()
+            .max_message_size(16)
+            .unary(
+                "/test.Test/Unary",
+                RawCodec,
+                fn_service(|req: Request<Bytes>| async move {
+                    if req.message().is_empty() {
+                        Err(Status::invalid_argument("empty"))
+                    } else {
+                        Ok(req.into_inner())
+                    }
+                }),
+            )
+            .streaming(
+                "/test.Test/Stream",
+                RawCodec,
+                fn_service(|req: Request<Streaming<Bytes>>| async move {
+                    let mut stream = req.into_inner();
+                    let mut items = Vec::new();
+                    while let Some(item) = stream.recv().await {
+                        items.push(item);
+                    }
+                    Ok::<_, Status>(futures_util::stream::iter(items))
+                }),
+            );
+        assert!(format!("{:?}", srv).contains("/test.Test/Unary"));
+        Pipeline::new(srv.create(()).await.unwrap())
+    }
+
+    async fn read_body(mut res: HttpResponse) -> (Vec<Bytes>, Status) {
+        let mut body = res.take_body();
+        let mut decoder = FrameDecoder::new(1024);
+        let mut msgs = Vec::new();
+        while let Some(chunk) = poll_fn(|cx| body.poll_next_chunk(cx)).await {
+            decoder.feed(chunk.unwrap());
+            while let Some(msg) = decoder.decode().unwrap() {
+                msgs.push(msg);
+            }
+        }
+        let trailers = poll_fn(|cx| body.poll_trailers(cx)).await.unwrap();
+        (msgs, Status::from_headers(&trailers).unwrap())
+    }
+
+    #[crate::rt_test]
+    async fn unary() {
+        let srv = server().await;
+
+        let res = srv
+            .call(request("/test.Test/Unary", b"\x00\x00\x00\x00\x02ab"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), "application/grpc");
+        let (msgs, status) = read_body(res).await;
+        assert_eq!(msgs, vec![Bytes::from_static(b"ab")]);
+        assert!(status.is_ok());
+
+        let err = srv
+            .call(request("/test.Test/Unary", b"\x00\x00\x00\x00\x00"))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+
+        let err = srv
+            .call(request("/test.Test/Unary", b""))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::Internal);
+
+        let err = srv
+            .call(request("/test.Test/Unary", b"\x00\x00\x00\x00\x20"))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::ResourceExhausted);
+
+        let err = srv
+            .call(request("/test.Test/Unknown", b""))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::Unimplemented);
+        let res = err.error_response();
+        assert_eq!(res.headers().get(GRPC_STATUS).unwrap(), "12");
+    }
+
+    #[crate::rt_test]
+    async fn streaming() {
+        let srv = server().await;
+
+        let res = srv
+            .call(request(
+                "/test.Test/Stream",
+                b"\x00\x00\x00\x00\x01a\x00\x00\x00\x00\x01b",
+            ))
+            .await
+            .unwrap();
+        let (msgs, status) = read_body(res).await;
+        assert_eq!(
+            msgs,
+            vec![Bytes::from_static(b"a"), Bytes::from_static(b"b")]
+        );
+        assert!(status.is_ok());
+
+        let res = srv
+            .call(request(
+                "/test.Test/Stream",
+                b"\x00\x00\x00\x00\x01a\x01\x00\x00\x00\x01b",
+            ))
+            .await
+            .unwrap();
+        let (msgs, status) = read_body(res).await;
+        assert_eq!(msgs, vec![Bytes::from_static(b"a")]);
+        assert_eq!(status.code(), Code::Unimplemented);
+    }
+
+    #[crate::rt_test]
+    async fn invalid_requests() {
+        let srv = server().await;
+
+        let req = TestRequest::with_uri("/test.Test/Unary").finish();
+        let res = srv.call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+
+        let req = TestRequest::with_uri("/test.Test/Unary")
+            .method(Method::POST)
+            .header(CONTENT_TYPE, "application/json")
+            .finish();
+        let res = srv.call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let mut req = request("/test.Test/Unary", b"");
+        req.headers_mut().insert(
+            GRPC_ENCODING,
+            crate::http::header::HeaderValue::from_static("gzip"),
+        );
+        let err = srv.call(req).await.unwrap_err();
+        assert_eq!(err.code(), Code::Unimplemented);
+    }
+
+    #[crate::rt_test]
+    async fn deadline() {
+        let srv = Pipeline::new(
+            Server::new()
+                .unary(
+                    "/test.Test/Slow",
+                    RawCodec,
+                    fn_service(|req: Request<Bytes>| async move {
+                        crate::time::sleep(Millis(500)).await;
+                        Ok::<_, Status>(req.into_inner())
+                    }),
+                )
+                .create(())
+                .await
+                .unwrap(),
+        );
+
+        let mut req = request("/test.Test/Slow", b"\x00\x00\x00\x00\x00");
+        req.headers_mut().insert(
+            GRPC_TIMEOUT,
+            crate::http::header::HeaderValue::from_static("10m"),
+        );
+        let err = srv.call(req).await.unwrap_err();
+        assert_eq!(err.code(), Code::DeadlineExceeded);
+
+        let mut req = request("/test.Test/Slow", b"\x00\x00\x00\x00\x00");
+        req.headers_mut().insert(
+            GRPC_TIMEOUT,
+            crate::http::header::HeaderValue::from_static("500u"),
+        );
+        let err = srv.call(req).await.unwrap_err();
+        assert_eq!(err.code(), Code::DeadlineExceeded);
+    }
+
+    #[crate::rt_test]
+    async fn streaming_deadline() {
+        let srv = Pipeline::new(
+            Server::new()
+                .streaming(
+                    "/test.Test/Slow",
+                    RawCodec,
+                    fn_service(|_: Request<Streaming<Bytes>>| async move {
+                        crate::time::sleep(Millis(150)).await;
+                        Ok::<_, Status>(futures_util::stream::once(Box::pin(async {
+                            crate::time::sleep(Millis(150)).await;
+                            Ok(Bytes::from_static(b"a"))
+                        })))
+                    }),
+                )
+                .create(())
+                .await
+                .unwrap(),
+        );
+
+        // deadline starts when request is received and covers response body
+        let mut req = request("/test.Test/Slow", b"");
+        req.headers_mut().insert(
+            GRPC_TIMEOUT,
+            crate::http::header::HeaderValue::from_static("200m"),
+        );
+        let res = srv.call(req).await.unwrap();
+        let (msgs, status) = read_body(res).await;
+        assert!(msgs.is_empty());
+        assert_eq!(status.code(), Code::DeadlineExceeded);
+    }
+}
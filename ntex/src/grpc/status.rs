@@ -0,0 +1,352 @@
+use std::{borrow::Cow, fmt};
+
+use percent_encoding::{percent_decode, utf8_percent_encode, AsciiSet, CONTROLS};
+
+use crate::http::error::{PayloadError, ResponseError};
+use crate::http::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+use crate::http::{client::error::SendRequestError, Response, StatusCode};
+
+use super::{GRPC_CONTENT_TYPE, GRPC_MESSAGE, GRPC_STATUS};
+
+/// Characters that must be percent-encoded in `grpc-message` header
+const MESSAGE_SET: &AsciiSet = &CONTROLS.add(b'%');
+
+/// gRPC status codes
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
+pub enum Code {
+    /// The operation completed successfully.
+    Ok,
+    /// The operation was cancelled.
+    Cancelled,
+    /// Unknown error.
+    Unknown,
+    /// Client specified an invalid argument.
+    InvalidArgument,
+    /// Deadline expired before operation could complete.
+    DeadlineExceeded,
+    /// Some requested entity was not found.
+    NotFound,
+    /// Some entity that we attempted to create already exists.
+    AlreadyExists,
+    /// The caller does not have permission to execute the specified operation.
+    PermissionDenied,
+    /// Some resource has been exhausted.
+    ResourceExhausted,
+    /// The system is not in a state required for the operation's execution.
+    FailedPrecondition,
+    /// The operation was aborted.
+    Aborted,
+    /// Operation was attempted past the valid range.
+    OutOfRange,
+    /// Operation is not implemented or not supported.
+    Unimplemented,
+    /// Internal error.
+    Internal,
+    /// The service is currently unavailable.
+    Unavailable,
+    /// Unrecoverable data loss or corruption.
+    DataLoss,
+    /// The request does not have valid authentication credentials.
+    Unauthenticated,
+}
+
+impl Code {
+    /// Get code from numeric value, unknown values are mapped to `Code::Unknown`
+    pub fn from_i32(code: i32) -> Code {
+        match code {
+            0 => Code::Ok,
+            1 => Code::Cancelled,
+            3 => Code::InvalidArgument,
+            4 => Code::DeadlineExceeded,
+            5 => Code::NotFound,
+            6 => Code::AlreadyExists,
+            7 => Code::PermissionDenied,
+            8 => Code::ResourceExhausted,
+            9 => Code::FailedPrecondition,
+            10 => Code::Aborted,
+            11 => Code::OutOfRange,
+            12 => Code::Unimplemented,
+            13 => Code::Internal,
+            14 => Code::Unavailable,
+            15 => Code::DataLoss,
+            16 => Code::Unauthenticated,
+            _ => Code::Unknown,
+        }
+    }
+
+    /// Numeric value of the code
+    pub fn as_i32(&self) -> i32 {
+        match self {
+            Code::Ok => 0,
+            Code::Cancelled => 1,
+            Code::Unknown => 2,
+            Code::InvalidArgument => 3,
+            Code::DeadlineExceeded => 4,
+            Code::NotFound => 5,
+            Code::AlreadyExists => 6,
+            Code::PermissionDenied => 7,
+            Code::ResourceExhausted => 8,
+            Code::FailedPrecondition => 9,
+            Code::Aborted => 10,
+            Code::OutOfRange => 11,
+            Code::Unimplemented => 12,
+            Code::Internal => 13,
+            Code::Unavailable => 14,
+            Code::DataLoss => 15,
+            Code::Unauthenticated => 16,
+        }
+    }
+
+    /// Map http status of non-grpc response to grpc code
+    pub fn from_http_status(status: StatusCode) -> Code {
+        match status {
+            StatusCode::BAD_REQUEST => Code::Internal,
+            StatusCode::UNAUTHORIZED => Code::Unauthenticated,
+            StatusCode::FORBIDDEN => Code::PermissionDenied,
+            StatusCode::NOT_FOUND => Code::Unimplemented,
+            StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT => Code::Unavailable,
+            _ => Code::Unknown,
+        }
+    }
+}
+
+impl fmt::Display for Code {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Code::Ok => "OK",
+            Code::Cancelled => "CANCELLED",
+            Code::Unknown => "UNKNOWN",
+            Code::InvalidArgument => "INVALID_ARGUMENT",
+            Code::DeadlineExceeded => "DEADLINE_EXCEEDED",
+            Code::NotFound => "NOT_FOUND",
+            Code::AlreadyExists => "ALREADY_EXISTS",
+            Code::PermissionDenied => "PERMISSION_DENIED",
+            Code::ResourceExhausted => "RESOURCE_EXHAUSTED",
+            Code::FailedPrecondition => "FAILED_PRECONDITION",
+            Code::Aborted => "ABORTED",
+            Code::OutOfRange => "OUT_OF_RANGE",
+            Code::Unimplemented => "UNIMPLEMENTED",
+            Code::Internal => "INTERNAL",
+            Code::Unavailable => "UNAVAILABLE",
+            Code::DataLoss => "DATA_LOSS",
+            Code::Unauthenticated => "UNAUTHENTICATED",
+        };
+        f.write_str(s)
+    }
+}
+
+/// gRPC call status
+///
+/// Status is sent to the peer with `grpc-status` and `grpc-message` trailers.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub struct Status {
+    code: Code,
+    message: String,
+}
+
+impl Status {
+    /// Create new status
+    pub fn new<T: Into<String>>(code: Code, message: T) -> Status {
+        Status {
+            code,
+            message: message.into(),
+        }
+    }
+
+    /// Successful status
+    pub fn ok() -> Status {
+        Status::new(Code::Ok, "")
+    }
+
+    /// Operation was cancelled
+    pub fn cancelled<T: Into<String>>(message: T) -> Status {
+        Status::new(Code::Cancelled, message)
+    }
+
+    /// Unknown error
+    pub fn unknown<T: Into<String>>(message: T) -> Status {
+        Status::new(Code::Unknown, message)
+    }
+
+    /// Client specified an invalid argument
+    pub fn invalid_argument<T: Into<String>>(message: T) -> Status {
+        Status::new(Code::InvalidArgument, message)
+    }
+
+    /// Deadline expired before operation could complete
+    pub fn deadline_exceeded<T: Into<String>>(message: T) -> Status {
+        Status::new(Code::DeadlineExceeded, message)
+    }
+
+    /// Requested entity was not found
+    pub fn not_found<T: Into<String>>(message: T) -> Status {
+        Status::new(Code::NotFound, message)
+    }
+
+    /// Operation is not implemented
+    pub fn unimplemented<T: Into<String>>(message: T) -> Status {
+        Status::new(Code::Unimplemented, message)
+    }
+
+    /// Internal error
+    pub fn internal<T: Into<String>>(message: T) -> Status {
+        Status::new(Code::Internal, message)
+    }
+
+    /// Service is unavailable
+    pub fn unavailable<T: Into<String>>(message: T) -> Status {
+        Status::new(Code::Unavailable, message)
+    }
+
+    /// Status code
+    pub fn code(&self) -> Code {
+        self.code
+    }
+
+    /// Status message
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Check if status is `Code::Ok`
+    pub fn is_ok(&self) -> bool {
+        self.code == Code::Ok
+    }
+
+    /// Read status from `grpc-status` and `grpc-message` headers
+    pub fn from_headers(headers: &HeaderMap) -> Option<Status> {
+        let code = headers
+            .get(GRPC_STATUS)?
+            .to_str()
+            .ok()
+            .and_then(|s| s.parse::<i32>().ok())
+            .map(Code::from_i32)
+            .unwrap_or(Code::Unknown);
+        let message = headers
+            .get(GRPC_MESSAGE)
+            .map(|v| {
+                percent_decode(v.as_bytes())
+                    .decode_utf8()
+                    .map(Cow::into_owned)
+                    .unwrap_or_else(|_| String::from_utf8_lossy(v.as_bytes()).into_owned())
+            })
+            .unwrap_or_default();
+
+        Some(Status { code, message })
+    }
+
+    /// Add `grpc-status` and `grpc-message` headers
+    pub fn add_headers(&self, headers: &mut HeaderMap) {
+        headers.insert(GRPC_STATUS, HeaderValue::from(self.code.as_i32()));
+        if !self.message.is_empty() {
+            let msg = utf8_percent_encode(&self.message, MESSAGE_SET).to_string();
+            if let Ok(value) = HeaderValue::try_from(msg) {
+                headers.insert(GRPC_MESSAGE, value);
+            }
+        }
+    }
+
+    /// Convert status to trailers map
+    pub fn to_trailers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        self.add_headers(&mut headers);
+        headers
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.message.is_empty() {
+            write!(f, "grpc status: {}", self.code)
+        } else {
+            write!(f, "grpc status: {}, message: {}", self.code, self.message)
+        }
+    }
+}
+
+/// Errors are sent as "trailers-only" grpc responses
+impl ResponseError for Status {
+    fn error_response(&self) -> Response {
+        let mut res = Response::new(StatusCode::OK);
+        res.headers_mut().insert(CONTENT_TYPE, GRPC_CONTENT_TYPE);
+        self.add_headers(res.headers_mut());
+        res
+    }
+}
+
+impl From<PayloadError> for Status {
+    fn from(err: PayloadError) -> Status {
+        Status::internal(err.to_string())
+    }
+}
+
+impl From<SendRequestError> for Status {
+    fn from(err: SendRequestError) -> Status {
+        match err {
+            SendRequestError::Timeout => Status::deadline_exceeded(err.to_string()),
+            err => Status::unavailable(err.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes() {
+        for code in 0..17 {
+            assert_eq!(Code::from_i32(code).as_i32(), code);
+        }
+        assert_eq!(Code::from_i32(100), Code::Unknown);
+        assert_eq!(Code::Unimplemented.to_string(), "UNIMPLEMENTED");
+        assert_eq!(
+            Code::from_http_status(StatusCode::NOT_FOUND),
+            Code::Unimplemented
+        );
+        assert_eq!(
+            Code::from_http_status(StatusCode::SERVICE_UNAVAILABLE),
+            Code::Unavailable
+        );
+        assert_eq!(
+            Code::from_http_status(StatusCode::IM_A_TEAPOT),
+            Code::Unknown
+        );
+    }
+
+    #[test]
+    fn status_headers() {
+        let st = Status::not_found("not found: 100%\nnext");
+        let hdrs = st.to_trailers();
+        assert_eq!(hdrs.get(GRPC_STATUS).unwrap(), "5");
+        assert_eq!(hdrs.get(GRPC_MESSAGE).unwrap(), "not found: 100%25%0Anext");
+        assert_eq!(Status::from_headers(&hdrs).unwrap(), st);
+
+        let hdrs = Status::ok().to_trailers();
+        assert!(!hdrs.contains_key(GRPC_MESSAGE));
+        assert!(Status::from_headers(&hdrs).unwrap().is_ok());
+        assert!(Status::from_headers(&HeaderMap::new()).is_none());
+
+        let mut hdrs = HeaderMap::new();
+        hdrs.insert(GRPC_STATUS, HeaderValue::from_static("invalid"));
+        assert_eq!(Status::from_headers(&hdrs).unwrap().code(), Code::Unknown);
+    }
+
+    #[test]
+    fn status_response() {
+        let st = Status::unimplemented("test");
+        assert_eq!(st.to_string(), "grpc status: UNIMPLEMENTED, message: test");
+        assert_eq!(Status::ok().to_string(), "grpc status: OK");
+
+        let res = st.error_response();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), "application/grpc");
+        assert_eq!(res.headers().get(GRPC_STATUS).unwrap(), "12");
+
+        let st: Status = SendRequestError::Timeout.into();
+        assert_eq!(st.code(), Code::DeadlineExceeded);
+    }
+}
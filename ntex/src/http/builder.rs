@@ -136,6 +136,54 @@ where
         self
     }
 
+    /// Set max number of request headers for http/1.
+    ///
+    /// Requests with more headers are rejected with `431 Request Header Fields Too Large`
+    /// response.
+    ///
+    /// By default max number of headers is set to 96.
+    pub fn max_headers(mut self, num: usize) -> Self {
+        self.config.max_headers(num);
+        self
+    }
+
+    /// Set max size of request head for http/1.
+    ///
+    /// Size includes request line and all request headers. Requests with larger head
+    /// are rejected with `431 Request Header Fields Too Large` response.
+    ///
+    /// By default max size is set to 32Kb.
+    pub fn max_headers_size(mut self, size: usize) -> Self {
+        self.config.max_headers_size(size);
+        self
+    }
+
+    /// Set max length of request uri for http/1.
+    ///
+    /// Requests with longer uri are rejected with `414 URI Too Long` response.
+    ///
+    /// By default max length is set to 32Kb.
+    pub fn max_uri_length(mut self, size: usize) -> Self {
+        self.config.max_uri_length(size);
+        self
+    }
+
+    /// Set max size of chunk extension for http/1 chunked payloads.
+    ///
+    /// By default max size is set to 16Kb.
+    pub fn max_chunk_extension_size(mut self, size: usize) -> Self {
+        self.config.max_chunk_extension_size(size);
+        self
+    }
+
+    /// Set max number of pipelined http/1 requests processed concurrently.
+    ///
+    /// By default pipeline depth is set to 1, requests are processed one at a time.
+    pub fn pipeline_depth(mut self, depth: usize) -> Self {
+        self.config.pipeline_depth(depth);
+        self
+    }
+
     /// Provide control service for http/1.
     pub fn h1_control<CF, CT>(self, control: CF) -> HttpServiceBuilder<F, S, CT, C2>
     where
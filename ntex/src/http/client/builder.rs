@@ -4,11 +4,17 @@ use base64::{engine::general_purpose::STANDARD as base64, Engine};
 
 use crate::http::error::HttpError;
 use crate::http::header::{self, HeaderMap, HeaderName, HeaderValue};
-use crate::{service::Service, time::Millis};
+use crate::service::{Middleware, Service};
+use crate::time::Millis;
 
 use super::connect::ConnectorWrapper;
-use super::error::ConnectError;
-use super::{Client, ClientConfig, Connect, Connection, Connector};
+use super::error::{ConnectError, SendRequestError};
+use super::middleware::{ClientMiddlewares, ClientService, ClientServiceRequest};
+use super::retry::RetryConnect;
+use super::RetryPolicy;
+#[cfg(feature = "cookie")]
+use super::{cookie::CookieConnect, CookieJar};
+use super::{Client, ClientConfig, ClientResponse, Connect, Connection, Connector};
 
 /// An HTTP Client builder
 ///
@@ -20,6 +26,10 @@ pub struct ClientBuilder {
     default_headers: bool,
     allow_redirects: bool,
     max_redirects: usize,
+    retry: Option<RetryPolicy>,
+    #[cfg(feature = "cookie")]
+    cookie_jar: Option<CookieJar>,
+    middlewares: ClientMiddlewares,
 }
 
 impl Default for ClientBuilder {
@@ -34,6 +44,10 @@ impl ClientBuilder {
             default_headers: true,
             allow_redirects: true,
             max_redirects: 10,
+            retry: None,
+            #[cfg(feature = "cookie")]
+            cookie_jar: None,
+            middlewares: ClientMiddlewares::default(),
             config: ClientConfig {
                 headers: HeaderMap::new(),
                 timeout: Millis(5_000),
@@ -98,6 +112,44 @@ impl ClientBuilder {
         self
     }
 
+    /// Retry failed requests.
+    ///
+    /// Only requests with idempotent methods and non-streaming
+    /// bodies are retried. Requests are not retried by default.
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    #[cfg(feature = "cookie")]
+    /// Use cookie jar.
+    ///
+    /// Cookies from responses are stored in the jar, and sent
+    /// with subsequent requests.
+    pub fn cookie_jar(mut self, jar: CookieJar) -> Self {
+        self.cookie_jar = Some(jar);
+        self
+    }
+
+    /// Register client middleware.
+    ///
+    /// Middleware could inspect and modify outgoing requests and incoming
+    /// responses, or respond without sending request. Middlewares are
+    /// applied to every request sent by the client, the last registered
+    /// middleware is called first.
+    pub fn wrap<M>(mut self, mw: M) -> Self
+    where
+        M: Middleware<ClientService> + 'static,
+        M::Service: Service<
+                ClientServiceRequest,
+                Response = ClientResponse,
+                Error = SendRequestError,
+            > + 'static,
+    {
+        self.middlewares.add(mw);
+        self
+    }
+
     /// Do not add default request headers.
     /// By default `Date` and `User-Agent` headers are set.
     pub fn no_default_headers(mut self) -> Self {
@@ -166,7 +218,18 @@ impl ClientBuilder {
     }
 
     /// Finish build process and create `Client` instance.
-    pub fn finish(self) -> Client {
+    pub fn finish(mut self) -> Client {
+        // each retry attempt uses and updates cookie jar
+        #[cfg(feature = "cookie")]
+        if let Some(jar) = self.cookie_jar {
+            self.config.connector =
+                Box::new(CookieConnect::new(self.config.connector, jar));
+        }
+        if let Some(policy) = self.retry {
+            self.config.connector =
+                Box::new(RetryConnect::new(self.config.connector, policy));
+        }
+        self.config.connector = self.middlewares.apply(self.config.connector);
         Client(Rc::new(self.config))
     }
 }
@@ -185,6 +248,10 @@ mod tests {
         assert!(!builder.allow_redirects);
         assert!(!builder.default_headers);
         assert_eq!(builder.max_redirects, 10);
+        assert!(builder.retry.is_none());
+
+        let builder = ClientBuilder::new().retry(RetryPolicy::new(2));
+        assert!(builder.retry.is_some());
     }
 
     #[crate::rt_test]
@@ -0,0 +1,913 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::task::{Context, Poll};
+use std::{cell::RefCell, fmt, pin::Pin, rc::Rc, time::Duration, time::SystemTime};
+
+use crate::channel::bstream;
+use crate::http::error::PayloadError;
+use crate::http::header::{self, HeaderMap, HeaderName, HeaderValue};
+use crate::http::{Method, Payload, ResponseHead, StatusCode, Version};
+use crate::service::{Middleware, Pipeline, Service, ServiceCtx};
+use crate::util::{Bytes, BytesMut, Stream};
+
+use super::error::SendRequestError;
+use super::{ClientResponse, ClientServiceRequest};
+
+/// Storage for cached responses
+pub trait CacheStore: fmt::Debug + 'static {
+    /// Get cached response
+    fn get(&self, key: &str) -> Option<CachedResponse>;
+
+    /// Store response
+    fn put(&self, key: String, response: CachedResponse);
+
+    /// Remove cached response
+    fn remove(&self, key: &str);
+}
+
+/// Client response cache middleware
+///
+/// Private cache according to rfc9111. Fresh responses are served from
+/// the store, stale responses are revalidated with `ETag` and
+/// `Last-Modified` validators. Responses with `stale-while-revalidate`
+/// directive are served stale while revalidation runs in background.
+///
+/// ```rust
+/// use ntex::http::client::{Cache, Client, MemoryStore};
+///
+/// let client = Client::build()
+///     .wrap(Cache::new(MemoryStore::new(16 * 1024 * 1024)))
+///     .finish();
+/// ```
+#[derive(Debug)]
+pub struct Cache {
+    inner: Rc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    store: Box<dyn CacheStore>,
+    max_entry_size: usize,
+    revalidating: RefCell<HashSet<String>>,
+}
+
+impl Cache {
+    /// Create cache middleware with provided store
+    pub fn new<T: CacheStore>(store: T) -> Self {
+        Cache {
+            inner: Rc::new(Inner {
+                store: Box::new(store),
+                max_entry_size: 1_048_576,
+                revalidating: RefCell::new(HashSet::new()),
+            }),
+        }
+    }
+
+    /// Set max size of cached response body
+    ///
+    /// Larger responses are not cached. By default max size is 1Mb.
+    pub fn max_entry_size(mut self, size: usize) -> Self {
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple copies exist")
+            .max_entry_size = size;
+        self
+    }
+}
+
+impl<S> Middleware<S> for Cache {
+    type Service = CacheService<S>;
+
+    fn create(&self, service: S) -> Self::Service {
+        CacheService {
+            service: Pipeline::new(service),
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+/// Client service that serves responses from the cache store
+///
+/// Service is created by [`Cache`] middleware, requests that cannot be
+/// served from the store are forwarded to the inner service.
+#[derive(Debug)]
+pub struct CacheService<S> {
+    service: Pipeline<S>,
+    inner: Rc<Inner>,
+}
+
+impl<S> Service<ClientServiceRequest> for CacheService<S>
+where
+    S: Service<ClientServiceRequest, Response = ClientResponse, Error = SendRequestError>
+        + 'static,
+{
+    type Response = ClientResponse;
+    type Error = SendRequestError;
+
+    async fn call(
+        &self,
+        mut req: ClientServiceRequest,
+        _: ServiceCtx<'_, Self>,
+    ) -> Result<ClientResponse, SendRequestError> {
+        let key = req.uri().to_string();
+
+        if !matches!(*req.method(), Method::GET | Method::HEAD) {
+            // unsafe methods invalidate cached response
+            let invalidate = !matches!(*req.method(), Method::OPTIONS | Method::TRACE);
+            let res = self.service.call(req).await?;
+            if invalidate && (res.status().is_success() || res.status().is_redirection()) {
+                self.inner.store.remove(&key);
+            }
+            return Ok(res);
+        }
+
+        let req_cc = CacheControl::parse(req.headers());
+        if req_cc.no_store
+            || req.headers().contains_key(header::IF_NONE_MATCH)
+            || req.headers().contains_key(header::IF_MODIFIED_SINCE)
+            || req.headers().contains_key(header::RANGE)
+        {
+            return self.service.call(req).await;
+        }
+
+        let is_head = *req.method() == Method::HEAD;
+        let now = SystemTime::now();
+        let entry = self
+            .inner
+            .store
+            .get(&key)
+            .filter(|entry| entry.matches(req.headers()));
+
+        if let Some(ref entry) = entry {
+            let cc = CacheControl::parse(&entry.headers);
+            let age = entry.current_age(now);
+            let lifetime = entry.freshness_lifetime(&cc);
+
+            if !req_cc.no_cache && !cc.no_cache {
+                let acceptable = req_cc
+                    .max_age
+                    .map(|max| age.as_secs() <= max)
+                    .unwrap_or(true);
+                if acceptable && age < lifetime {
+                    log::trace!("Cached response for {:?}", key);
+                    let res = req.response(ResponseHead::new(entry.status), Payload::None);
+                    return Ok(entry.apply(res, age, is_head));
+                }
+
+                let swr = cc.stale_while_revalidate.map(Duration::from_secs);
+                if acceptable
+                    && !cc.must_revalidate
+                    && swr.map(|swr| age < lifetime + swr).unwrap_or(false)
+                {
+                    log::trace!("Stale response for {:?}, revalidate", key);
+                    self.revalidate(key, &req, entry.clone());
+                    let res = req.response(ResponseHead::new(entry.status), Payload::None);
+                    return Ok(entry.apply(res, age, is_head));
+                }
+            }
+            entry.add_conditions(req.headers_mut());
+        }
+
+        let req_headers = req.headers().clone();
+        let request_time = SystemTime::now();
+        let mut res = self.service.call(req).await?;
+        let response_time = SystemTime::now();
+
+        if let Some(entry) = entry {
+            if res.status() == StatusCode::NOT_MODIFIED {
+                log::trace!("Cached response for {:?} is revalidated", key);
+                let entry = entry.refresh(res.headers(), request_time, response_time);
+                let age = entry.current_age(response_time);
+                let res = entry.apply(res, age, is_head);
+                self.inner.store.put(key, entry);
+                return Ok(res);
+            }
+        }
+
+        if !is_head {
+            if let Some(entry) =
+                self.inner
+                    .storable(&res, &req_headers, request_time, response_time)
+            {
+                let payload = res.take_payload();
+                res.set_payload(Payload::from_stream(CacheStream {
+                    payload,
+                    key,
+                    body: BytesMut::new(),
+                    entry: Some(entry),
+                    inner: self.inner.clone(),
+                }));
+            }
+        }
+        Ok(res)
+    }
+}
+
+impl<S> CacheService<S>
+where
+    S: Service<ClientServiceRequest, Response = ClientResponse, Error = SendRequestError>
+        + 'static,
+{
+    /// Revalidate cached response in background
+    fn revalidate(&self, key: String, req: &ClientServiceRequest, entry: CachedResponse) {
+        if !self.inner.revalidating.borrow_mut().insert(key.clone()) {
+            return;
+        }
+        let Some(mut req) = req.try_clone() else {
+            self.inner.revalidating.borrow_mut().remove(&key);
+            return;
+        };
+        req.head_mut().method = Method::GET;
+        entry.add_conditions(req.headers_mut());
+
+        let svc = self.service.clone();
+        let inner = self.inner.clone();
+        let _ = crate::rt::spawn(async move {
+            let req_headers = req.headers().clone();
+            let request_time = SystemTime::now();
+            let result = svc.call(req).await;
+            let response_time = SystemTime::now();
+
+            match result {
+                Ok(res) if res.status() == StatusCode::NOT_MODIFIED => {
+                    let entry = entry.refresh(res.headers(), request_time, response_time);
+                    inner.store.put(key.clone(), entry);
+                }
+                Ok(mut res) => {
+                    if let Some(mut entry) =
+                        inner.storable(&res, &req_headers, request_time, response_time)
+                    {
+                        if let Ok(body) = res.body().limit(inner.max_entry_size).await {
+                            entry.body = body;
+                            inner.store.put(key.clone(), entry);
+                        }
+                    }
+                }
+                Err(err) => log::trace!("Cannot revalidate {:?}: {}", key, err),
+            }
+            inner.revalidating.borrow_mut().remove(&key);
+        });
+    }
+}
+
+impl Inner {
+    /// Create cache entry if response could be stored
+    fn storable(
+        &self,
+        res: &ClientResponse,
+        req_headers: &HeaderMap,
+        request_time: SystemTime,
+        response_time: SystemTime,
+    ) -> Option<CachedResponse> {
+        let cc = CacheControl::parse(res.headers());
+        if cc.no_store {
+            return None;
+        }
+
+        let explicit = cc.max_age.is_some() || res.headers().contains_key(header::EXPIRES);
+        let validators = res.headers().contains_key(header::ETAG)
+            || res.headers().contains_key(header::LAST_MODIFIED);
+        if !((explicit && is_final(res.status()))
+            || (is_heuristic(res.status()) && validators))
+        {
+            return None;
+        }
+
+        if let Some(len) = res
+            .headers()
+            .get(header::CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<usize>().ok())
+        {
+            if len > self.max_entry_size {
+                return None;
+            }
+        }
+
+        let mut vary = Vec::new();
+        for value in res.headers().get_all(header::VARY) {
+            for name in value.to_str().ok()?.split(',') {
+                let name = name.trim();
+                if name == "*" {
+                    return None;
+                } else if let Ok(name) = HeaderName::try_from(name) {
+                    let values = req_headers.get_all(&name).cloned().collect();
+                    vary.push((name, values));
+                }
+            }
+        }
+
+        Some(CachedResponse {
+            vary,
+            request_time,
+            response_time,
+            status: res.status(),
+            version: res.version(),
+            headers: res.headers().clone(),
+            body: Bytes::new(),
+        })
+    }
+}
+
+/// Response stored in the cache
+#[derive(Clone, Debug)]
+pub struct CachedResponse {
+    status: StatusCode,
+    version: Version,
+    headers: HeaderMap,
+    body: Bytes,
+    vary: Vec<(HeaderName, Vec<HeaderValue>)>,
+    request_time: SystemTime,
+    response_time: SystemTime,
+}
+
+impl CachedResponse {
+    /// Response status
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Response headers
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Response body
+    pub fn body(&self) -> &Bytes {
+        &self.body
+    }
+
+    /// Approximate size of the response in bytes
+    pub fn size(&self) -> usize {
+        self.body.len()
+            + self
+                .headers
+                .iter()
+                .map(|(k, v)| k.as_str().len() + v.len())
+                .sum::<usize>()
+    }
+
+    /// Check if request matches response's `Vary` headers
+    fn matches(&self, headers: &HeaderMap) -> bool {
+        self.vary
+            .iter()
+            .all(|(name, values)| headers.get_all(name).eq(values.iter()))
+    }
+
+    fn date(&self) -> SystemTime {
+        http_date(&self.headers, header::DATE).unwrap_or(self.response_time)
+    }
+
+    /// Current age of the response, rfc9111 section 4.2.3
+    fn current_age(&self, now: SystemTime) -> Duration {
+        let apparent_age = self
+            .response_time
+            .duration_since(self.date())
+            .unwrap_or(Duration::ZERO);
+        let age_value = self
+            .headers
+            .get(header::AGE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(Duration::ZERO);
+        let response_delay = self
+            .response_time
+            .duration_since(self.request_time)
+            .unwrap_or(Duration::ZERO);
+        let resident_time = now
+            .duration_since(self.response_time)
+            .unwrap_or(Duration::ZERO);
+
+        apparent_age.max(age_value + response_delay) + resident_time
+    }
+
+    /// Freshness lifetime, rfc9111 section 4.2.1
+    fn freshness_lifetime(&self, cc: &CacheControl) -> Duration {
+        if let Some(max_age) = cc.max_age {
+            Duration::from_secs(max_age)
+        } else if self.headers.contains_key(header::EXPIRES) {
+            http_date(&self.headers, header::EXPIRES)
+                .and_then(|expires| expires.duration_since(self.date()).ok())
+                .unwrap_or(Duration::ZERO)
+        } else if let Some(modified) = http_date(&self.headers, header::LAST_MODIFIED) {
+            // heuristic freshness, 10% of time since last modification
+            self.date()
+                .duration_since(modified)
+                .map(|d| d / 10)
+                .unwrap_or(Duration::ZERO)
+        } else {
+            Duration::ZERO
+        }
+    }
+
+    /// Add conditional headers for revalidation
+    fn add_conditions(&self, headers: &mut HeaderMap) {
+        if let Some(etag) = self.headers.get(header::ETAG) {
+            headers.insert(header::IF_NONE_MATCH, etag.clone());
+        }
+        if let Some(modified) = self.headers.get(header::LAST_MODIFIED) {
+            headers.insert(header::IF_MODIFIED_SINCE, modified.clone());
+        }
+    }
+
+    /// Update stored response with headers from `304 Not Modified` response
+    fn refresh(
+        &self,
+        headers: &HeaderMap,
+        request_time: SystemTime,
+        response_time: SystemTime,
+    ) -> CachedResponse {
+        let mut entry = self.clone();
+        for name in headers.keys() {
+            if *name != header::CONTENT_LENGTH {
+                entry.headers.remove(name);
+            }
+        }
+        for (name, value) in headers.iter() {
+            if *name != header::CONTENT_LENGTH {
+                entry.headers.append(name.clone(), value.clone());
+            }
+        }
+        entry.request_time = request_time;
+        entry.response_time = response_time;
+        entry
+    }
+
+    /// Replace response with cached one
+    fn apply(
+        &self,
+        mut res: ClientResponse,
+        age: Duration,
+        is_head: bool,
+    ) -> ClientResponse {
+        let head = res.head_mut();
+        head.status = self.status;
+        head.version = self.version;
+        head.headers = self.headers.clone();
+        head.headers
+            .insert(header::AGE, HeaderValue::from(age.as_secs()));
+
+        let body = if is_head {
+            None
+        } else {
+            Some(self.body.clone())
+        };
+        res.set_payload(bstream::empty(body).into());
+        res
+    }
+}
+
+/// Stores response body while it is read
+struct CacheStream {
+    key: String,
+    payload: Payload,
+    body: BytesMut,
+    entry: Option<CachedResponse>,
+    inner: Rc<Inner>,
+}
+
+impl Stream for CacheStream {
+    type Item = Result<Bytes, PayloadError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        let result = this.payload.poll_recv(cx);
+        match result {
+            Poll::Ready(Some(Ok(ref chunk))) => {
+                if this.entry.is_some() {
+                    if this.body.len() + chunk.len() > this.inner.max_entry_size {
+                        this.entry = None;
+                        this.body = BytesMut::new();
+                    } else {
+                        this.body.extend_from_slice(chunk);
+                    }
+                }
+            }
+            Poll::Ready(Some(Err(_))) => this.entry = None,
+            Poll::Ready(None) => {
+                if let Some(mut entry) = this.entry.take() {
+                    log::trace!("Store response for {:?}", this.key);
+                    entry.body = this.body.split().freeze();
+                    this.inner.store.put(this.key.clone(), entry);
+                }
+            }
+            Poll::Pending => (),
+        }
+        result
+    }
+}
+
+/// In-memory lru cache store
+///
+/// Store size is limited by total size of cached responses in bytes.
+#[derive(Clone, Debug)]
+pub struct MemoryStore(Rc<RefCell<Lru>>);
+
+#[derive(Debug)]
+struct Lru {
+    entries: HashMap<String, (CachedResponse, u64)>,
+    order: BTreeMap<u64, String>,
+    tick: u64,
+    size: usize,
+    capacity: usize,
+}
+
+impl MemoryStore {
+    /// Create store with capacity in bytes
+    pub fn new(capacity: usize) -> Self {
+        MemoryStore(Rc::new(RefCell::new(Lru {
+            capacity,
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            tick: 0,
+            size: 0,
+        })))
+    }
+
+    /// Total size of stored responses
+    pub fn size(&self) -> usize {
+        self.0.borrow().size
+    }
+
+    /// Number of stored responses
+    pub fn len(&self) -> usize {
+        self.0.borrow().entries.len()
+    }
+
+    /// Check if store is empty
+    pub fn is_empty(&self) -> bool {
+        self.0.borrow().entries.is_empty()
+    }
+
+    /// Remove all stored responses
+    pub fn clear(&self) {
+        let mut lru = self.0.borrow_mut();
+        lru.entries.clear();
+        lru.order.clear();
+        lru.size = 0;
+    }
+}
+
+impl Lru {
+    fn remove(&mut self, key: &str) {
+        if let Some((entry, tick)) = self.entries.remove(key) {
+            self.order.remove(&tick);
+            self.size -= entry.size() + key.len();
+        }
+    }
+}
+
+impl CacheStore for MemoryStore {
+    fn get(&self, key: &str) -> Option<CachedResponse> {
+        let mut lru = self.0.borrow_mut();
+        lru.tick += 1;
+        let tick = lru.tick;
+
+        let Lru {
+            ref mut entries,
+            ref mut order,
+            ..
+        } = *lru;
+        let (entry, entry_tick) = entries.get_mut(key)?;
+        if let Some(key) = order.remove(&*entry_tick) {
+            order.insert(tick, key);
+        }
+        *entry_tick = tick;
+        Some(entry.clone())
+    }
+
+    fn put(&self, key: String, response: CachedResponse) {
+        let mut lru = self.0.borrow_mut();
+        lru.remove(&key);
+
+        let size = response.size() + key.len();
+        if size > lru.capacity {
+            return;
+        }
+        while lru.size + size > lru.capacity {
+            if let Some((_, key)) = lru.order.pop_first() {
+                if let Some((entry, _)) = lru.entries.remove(&key) {
+                    lru.size -= entry.size() + key.len();
+                }
+            } else {
+                break;
+            }
+        }
+
+        lru.tick += 1;
+        let tick = lru.tick;
+        lru.size += size;
+        lru.order.insert(tick, key.clone());
+        lru.entries.insert(key, (response, tick));
+    }
+
+    fn remove(&self, key: &str) {
+        self.0.borrow_mut().remove(key);
+    }
+}
+
+#[derive(Default, Debug)]
+struct CacheControl {
+    no_store: bool,
+    no_cache: bool,
+    must_revalidate: bool,
+    max_age: Option<u64>,
+    stale_while_revalidate: Option<u64>,
+}
+
+impl CacheControl {
+    fn parse(headers: &HeaderMap) -> Self {
+        let mut cc = CacheControl::default();
+        for value in headers.get_all(header::CACHE_CONTROL) {
+            let Ok(value) = value.to_str() else {
+                continue;
+            };
+            for directive in value.split(',') {
+                let (name, arg) = match directive.split_once('=') {
+                    Some((name, arg)) => (name.trim(), Some(arg.trim().trim_matches('"'))),
+                    None => (directive.trim(), None),
+                };
+                let seconds = || arg.and_then(|a| a.parse::<u64>().ok());
+
+                match name.to_ascii_lowercase().as_str() {
+                    "no-store" => cc.no_store = true,
+                    "no-cache" => cc.no_cache = true,
+                    "must-revalidate" => cc.must_revalidate = true,
+                    "max-age" => cc.max_age = Some(seconds().unwrap_or(0)),
+                    "stale-while-revalidate" => cc.stale_while_revalidate = seconds(),
+                    _ => (),
+                }
+            }
+        }
+        cc
+    }
+}
+
+fn http_date(headers: &HeaderMap, name: HeaderName) -> Option<SystemTime> {
+    let value = headers.get(name)?.to_str().ok()?;
+    httpdate::parse_http_date(value).ok()
+}
+
+/// Statuses that are cacheable by default, rfc9110 section 15.1
+fn is_heuristic(status: StatusCode) -> bool {
+    matches!(
+        status.as_u16(),
+        200 | 203 | 204 | 300 | 301 | 308 | 404 | 405 | 410 | 414 | 501
+    )
+}
+
+fn is_final(status: StatusCode) -> bool {
+    status.as_u16() >= 200 && status != StatusCode::PARTIAL_CONTENT
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::http::client::{Client, TestConnect};
+    use crate::time::{sleep, Millis};
+
+    fn test_client(
+        headers: Vec<(&'static str, &'static str)>,
+    ) -> (Client, MemoryStore, Rc<Cell<usize>>) {
+        let calls = Rc::new(Cell::new(0));
+        let store = MemoryStore::new(1024);
+        let counter = calls.clone();
+        let client = Client::build()
+            .connection(TestConnect::new(move |head, _| {
+                counter.set(counter.get() + 1);
+
+                let status = if head.as_ref().headers.get(header::IF_NONE_MATCH)
+                    == Some(&HeaderValue::from_static("\"v1\""))
+                {
+                    StatusCode::NOT_MODIFIED
+                } else {
+                    StatusCode::OK
+                };
+                let mut res = ResponseHead::new(status);
+                for (name, value) in &headers {
+                    res.headers.insert(
+                        HeaderName::from_static(*name),
+                        HeaderValue::from_static(*value),
+                    );
+                }
+                let body = if status == StatusCode::OK {
+                    Some(Bytes::from_static(b"body"))
+                } else {
+                    None
+                };
+                Ok((res, body))
+            }))
+            .wrap(Cache::new(store.clone()))
+            .finish();
+        (client, store, calls)
+    }
+
+    #[crate::rt_test]
+    async fn test_fresh() {
+        let (client, store, calls) = test_client(vec![("cache-control", "max-age=60")]);
+
+        let mut res = client.get("http://localhost/test").send().await.unwrap();
+        assert_eq!(res.body().await.unwrap(), "body");
+        assert_eq!(store.len(), 1);
+
+        let mut res = client.get("http://localhost/test").send().await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(res.headers().contains_key(header::AGE));
+        assert_eq!(res.body().await.unwrap(), "body");
+        assert_eq!(calls.get(), 1);
+
+        // request no-cache
+        let res = client
+            .get("http://localhost/test")
+            .header(header::CACHE_CONTROL, "no-cache")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(calls.get(), 2);
+
+        // unsafe method invalidates response
+        client.post("http://localhost/test").send().await.unwrap();
+        assert_eq!(calls.get(), 3);
+        assert!(store.is_empty());
+    }
+
+    #[crate::rt_test]
+    async fn test_not_storable() {
+        let (client, store, calls) =
+            test_client(vec![("cache-control", "no-store, max-age=60")]);
+        let mut res = client.get("http://localhost/test").send().await.unwrap();
+        assert_eq!(res.body().await.unwrap(), "body");
+        assert!(store.is_empty());
+
+        let (client, store, _) =
+            test_client(vec![("cache-control", "max-age=60"), ("vary", "*")]);
+        let mut res = client.get("http://localhost/test").send().await.unwrap();
+        assert_eq!(res.body().await.unwrap(), "body");
+        assert!(store.is_empty());
+
+        // no freshness and no validators
+        let (client, store, _) = test_client(vec![]);
+        let mut res = client.get("http://localhost/test").send().await.unwrap();
+        assert_eq!(res.body().await.unwrap(), "body");
+        assert!(store.is_empty());
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[crate::rt_test]
+    async fn test_vary() {
+        let (client, _, calls) =
+            test_client(vec![("cache-control", "max-age=60"), ("vary", "accept")]);
+
+        let req = client
+            .get("http://localhost/test")
+            .header(header::ACCEPT, "text/plain");
+        let mut res = req.send().await.unwrap();
+        assert_eq!(res.body().await.unwrap(), "body");
+
+        let req = client
+            .get("http://localhost/test")
+            .header(header::ACCEPT, "text/plain");
+        req.send().await.unwrap();
+        assert_eq!(calls.get(), 1);
+
+        let req = client
+            .get("http://localhost/test")
+            .header(header::ACCEPT, "text/html");
+        req.send().await.unwrap();
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[crate::rt_test]
+    async fn test_revalidate() {
+        let (client, store, calls) =
+            test_client(vec![("cache-control", "no-cache"), ("etag", "\"v1\"")]);
+
+        let mut res = client.get("http://localhost/test").send().await.unwrap();
+        assert_eq!(res.body().await.unwrap(), "body");
+        assert_eq!(store.len(), 1);
+
+        // server responds with 304, cached body is used
+        let mut res = client.get("http://localhost/test").send().await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.body().await.unwrap(), "body");
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[crate::rt_test]
+    async fn test_stale_while_revalidate() {
+        let (client, _, calls) = test_client(vec![
+            ("cache-control", "max-age=0, stale-while-revalidate=60"),
+            ("etag", "\"v1\""),
+        ]);
+
+        let mut res = client.get("http://localhost/test").send().await.unwrap();
+        assert_eq!(res.body().await.unwrap(), "body");
+
+        let mut res = client.get("http://localhost/test").send().await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.body().await.unwrap(), "body");
+
+        sleep(Millis(50)).await;
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn test_cache_control() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static("Max-Age=\"10\", no-cache"),
+        );
+        headers.append(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static("must-revalidate, stale-while-revalidate=5"),
+        );
+        let cc = CacheControl::parse(&headers);
+        assert_eq!(cc.max_age, Some(10));
+        assert_eq!(cc.stale_while_revalidate, Some(5));
+        assert!(cc.no_cache);
+        assert!(cc.must_revalidate);
+        assert!(!cc.no_store);
+    }
+
+    #[test]
+    fn test_freshness() {
+        let now = SystemTime::now();
+        let mut entry = CachedResponse {
+            status: StatusCode::OK,
+            version: Version::HTTP_11,
+            headers: HeaderMap::new(),
+            body: Bytes::new(),
+            vary: Vec::new(),
+            request_time: now,
+            response_time: now,
+        };
+        entry
+            .headers
+            .insert(header::AGE, HeaderValue::from_static("30"));
+        let age = entry.current_age(now + Duration::from_secs(10));
+        assert_eq!(age.as_secs(), 40);
+
+        let cc = CacheControl::default();
+        assert_eq!(entry.freshness_lifetime(&cc), Duration::ZERO);
+
+        let date = now - Duration::from_secs(100);
+        entry.headers.insert(
+            header::DATE,
+            HeaderValue::try_from(httpdate::fmt_http_date(date)).unwrap(),
+        );
+        entry.headers.insert(
+            header::LAST_MODIFIED,
+            HeaderValue::try_from(httpdate::fmt_http_date(
+                date - Duration::from_secs(1000),
+            ))
+            .unwrap(),
+        );
+        assert_eq!(entry.freshness_lifetime(&cc).as_secs(), 100);
+
+        entry.headers.insert(
+            header::EXPIRES,
+            HeaderValue::try_from(httpdate::fmt_http_date(date + Duration::from_secs(60)))
+                .unwrap(),
+        );
+        assert_eq!(entry.freshness_lifetime(&cc).as_secs(), 60);
+        assert!(entry.current_age(now).as_secs() >= 100);
+    }
+
+    #[test]
+    fn test_memory_store() {
+        let entry = |size: usize| CachedResponse {
+            status: StatusCode::OK,
+            version: Version::HTTP_11,
+            headers: HeaderMap::new(),
+            body: Bytes::from(vec![0; size]),
+            vary: Vec::new(),
+            request_time: SystemTime::now(),
+            response_time: SystemTime::now(),
+        };
+
+        let store = MemoryStore::new(100);
+        store.put("a".to_string(), entry(39));
+        store.put("b".to_string(), entry(39));
+        assert_eq!(store.size(), 80);
+
+        // "a" is recently used
+        assert!(store.get("a").is_some());
+        store.put("c".to_string(), entry(39));
+        assert_eq!(store.len(), 2);
+        assert!(store.get("b").is_none());
+        assert!(store.get("a").is_some());
+
+        // too large
+        store.put("d".to_string(), entry(100));
+        assert!(store.get("d").is_none());
+
+        store.remove("a");
+        assert_eq!(store.size(), 40);
+        store.clear();
+        assert!(store.is_empty());
+    }
+}
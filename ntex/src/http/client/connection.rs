@@ -1,4 +1,4 @@
-use std::{fmt, time};
+use std::{fmt, rc::Rc, time};
 
 use crate::http::body::MessageBody;
 use crate::http::message::{RequestHeadType, ResponseHead};
@@ -6,7 +6,7 @@ use crate::http::payload::Payload;
 use crate::io::{types::HttpProtocol, IoBoxed};
 use crate::time::Millis;
 
-use super::{error::SendRequestError, h1proto, h2proto, pool::Acquired};
+use super::{error::SendRequestError, h1proto, h2proto, pool::Acquired, proxy::Proxy};
 
 pub(super) enum ConnectionType {
     H1(IoBoxed),
@@ -28,6 +28,7 @@ pub struct Connection {
     io: Option<ConnectionType>,
     created: time::Instant,
     pool: Option<Acquired>,
+    proxy: Option<Rc<Proxy>>,
 }
 
 impl fmt::Debug for Connection {
@@ -50,9 +51,16 @@ impl Connection {
             pool,
             created,
             io: Some(io),
+            proxy: None,
         }
     }
 
+    /// Forward requests through the proxy
+    pub(super) fn forward(mut self, proxy: Rc<Proxy>) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
     pub(super) fn release(self, close: bool) {
         if let Some(mut pool) = self.pool {
             pool.release(
@@ -60,6 +68,7 @@ impl Connection {
                     io: self.io,
                     created: self.created,
                     pool: None,
+                    proxy: None,
                 },
                 close,
             );
@@ -93,6 +102,7 @@ impl Connection {
                     self.created,
                     timeout,
                     self.pool,
+                    self.proxy,
                 )
                 .await
             }
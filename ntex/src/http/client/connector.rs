@@ -1,13 +1,15 @@
-use std::{fmt, task::Context, time::Duration};
+use std::{fmt, rc::Rc, task::Context, time::Duration};
 
 use ntex_h2::{self as h2};
 
 use crate::connect::{Connect as TcpConnect, Connector as TcpConnector};
-use crate::service::{apply_fn, boxed, Service, ServiceCtx};
+use crate::http::Uri;
+use crate::io::IoBoxed;
+use crate::service::{boxed, fn_service, Pipeline, Service, ServiceCtx};
 use crate::time::{Millis, Seconds};
-use crate::util::{join, timeout::TimeoutError, timeout::TimeoutService};
-use crate::{http::Uri, io::IoBoxed};
+use crate::util::{join, timeout::TimeoutError, timeout::TimeoutService, BoxFuture};
 
+use super::proxy::{Proxy, ProxyConfig};
 use super::{connection::Connection, error::ConnectError, pool::ConnectionPool, Connect};
 
 #[cfg(feature = "openssl")]
@@ -41,6 +43,8 @@ pub struct Connector {
     h2config: h2::Config,
     connector: BoxedConnector,
     ssl_connector: Option<BoxedConnector>,
+    tls: Option<TlsUpgrade>,
+    proxy: ProxyConfig,
 }
 
 impl Default for Connector {
@@ -58,6 +62,8 @@ impl Connector {
                     .map_err(ConnectError::from),
             ),
             ssl_connector: None,
+            tls: None,
+            proxy: ProxyConfig::default(),
             timeout: Millis(1_000),
             conn_lifetime: Duration::from_secs(75),
             conn_keep_alive: Duration::from_secs(15),
@@ -114,7 +120,10 @@ impl Connector {
     pub fn openssl(self, connector: OpensslConnector) -> Self {
         use crate::connect::openssl::SslConnector;
 
-        self.secure_connector(SslConnector::new(connector))
+        let tls = TlsUpgrade::openssl(connector.clone());
+        let mut conn = self.secure_connector(SslConnector::new(connector));
+        conn.tls = Some(tls);
+        conn
     }
 
     #[cfg(feature = "rustls")]
@@ -122,7 +131,59 @@ impl Connector {
     pub fn rustls(self, connector: ClientConfig) -> Self {
         use crate::connect::rustls::TlsConnector;
 
-        self.secure_connector(TlsConnector::new(connector))
+        let tls = TlsUpgrade::rustls(connector.clone());
+        let mut conn = self.secure_connector(TlsConnector::new(connector));
+        conn.tls = Some(tls);
+        conn
+    }
+
+    /// Use http proxy for all requests.
+    ///
+    /// Plain `http` requests are forwarded to the proxy in absolute-form,
+    /// secure requests are tunneled through the proxy with `CONNECT` method.
+    /// Tunneling requires `openssl` or `rustls` secure connector.
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        let proxy = Rc::new(proxy);
+        self.proxy.http = Some(proxy.clone());
+        self.proxy.https = Some(proxy);
+        self
+    }
+
+    /// Use http proxy for plain `http` requests.
+    pub fn http_proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy.http = Some(Rc::new(proxy));
+        self
+    }
+
+    /// Use http proxy for secure `https` requests.
+    pub fn https_proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy.https = Some(Rc::new(proxy));
+        self
+    }
+
+    /// Set list of hosts that are connected directly, bypassing the proxy.
+    ///
+    /// Host matches if it is equal to the entry or is a subdomain of it,
+    /// `*` matches all hosts.
+    pub fn no_proxy<I, T>(mut self, hosts: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        self.proxy.no_proxy = hosts
+            .into_iter()
+            .map(|h| h.as_ref().trim().to_ascii_lowercase())
+            .collect();
+        self
+    }
+
+    /// Configure proxies from environment variables.
+    ///
+    /// `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY` variables are used,
+    /// lowercase variants take precedence. Invalid values are ignored.
+    pub fn proxy_from_env(mut self) -> Self {
+        self.proxy = ProxyConfig::from_env();
+        self
     }
 
     /// Set total number of simultaneous connections per type of scheme.
@@ -198,6 +259,7 @@ impl Connector {
         self.ssl_connector = Some(boxed::service(
             connector.map(IoBoxed::from).map_err(ConnectError::from),
         ));
+        self.tls = None;
         self
     }
 
@@ -208,10 +270,28 @@ impl Connector {
         self,
     ) -> impl Service<Connect, Response = Connection, Error = ConnectError> + fmt::Debug
     {
-        let tcp_service = connector(self.connector, self.timeout, self.disconnect_timeout);
+        let proxy = Rc::new(self.proxy);
+        let tcp = Pipeline::new(self.connector);
+        let tcp_service = connector(
+            tcp.clone(),
+            None,
+            proxy.clone(),
+            self.timeout,
+            self.disconnect_timeout,
+        );
 
         let ssl_pool = if let Some(ssl_connector) = self.ssl_connector {
-            let srv = connector(ssl_connector, self.timeout, self.disconnect_timeout);
+            let tunnel = Tunnel {
+                connector: tcp,
+                tls: self.tls,
+            };
+            let srv = connector(
+                Pipeline::new(ssl_connector),
+                Some(tunnel),
+                proxy.clone(),
+                self.timeout,
+                self.disconnect_timeout,
+            );
             Some(ConnectionPool::new(
                 srv,
                 self.conn_lifetime,
@@ -219,6 +299,7 @@ impl Connector {
                 self.disconnect_timeout,
                 self.limit,
                 self.h2config.clone(),
+                proxy.clone(),
             ))
         } else {
             None
@@ -232,6 +313,7 @@ impl Connector {
                 self.disconnect_timeout,
                 self.limit,
                 self.h2config.clone(),
+                proxy,
             ),
             ssl_pool,
         }
@@ -239,14 +321,31 @@ impl Connector {
 }
 
 fn connector(
-    connector: BoxedConnector,
+    connector: Pipeline<BoxedConnector>,
+    tunnel: Option<Tunnel>,
+    proxy: Rc<ProxyConfig>,
     timeout: Millis,
     disconnect_timeout: Seconds,
 ) -> impl Service<Connect, Response = IoBoxed, Error = ConnectError> + fmt::Debug {
     TimeoutService::new(
         timeout,
-        apply_fn(connector, |msg: Connect, svc| async move {
-            svc.call(TcpConnect::new(msg.uri).set_addr(msg.addr)).await
+        fn_service(move |msg: Connect| {
+            let connector = connector.clone();
+            let tunnel = tunnel.clone();
+            let proxy = proxy.clone();
+            async move {
+                match (proxy.select(&msg.uri), tunnel) {
+                    (Some(proxy), Some(tunnel)) => tunnel.connect(proxy, &msg.uri).await,
+                    (Some(proxy), None) => {
+                        connector.call(TcpConnect::new(proxy.uri().clone())).await
+                    }
+                    (None, _) => {
+                        connector
+                            .call(TcpConnect::new(msg.uri).set_addr(msg.addr))
+                            .await
+                    }
+                }
+            }
         })
         .map(move |io: IoBoxed| {
             io.set_disconnect_timeout(disconnect_timeout);
@@ -260,6 +359,89 @@ fn connector(
     })
 }
 
+type TlsUpgradeFn =
+    dyn Fn(IoBoxed, Uri) -> BoxFuture<'static, Result<IoBoxed, ConnectError>>;
+
+#[derive(Clone)]
+/// Starts tls session over established connection
+struct TlsUpgrade(Rc<TlsUpgradeFn>);
+
+impl TlsUpgrade {
+    #[cfg(feature = "openssl")]
+    fn openssl(connector: OpensslConnector) -> Self {
+        use crate::io::{Io, Sealed};
+        use tls_openssl::ssl::Error as SslError;
+
+        TlsUpgrade(Rc::new(move |io, uri| {
+            let connector = connector.clone();
+            Box::pin(async move {
+                let host = uri.host().unwrap_or("");
+                let ssl = connector
+                    .configure()
+                    .and_then(|config| config.into_ssl(host))
+                    .map_err(SslError::from)?;
+                let io = ntex_tls::openssl::connect(Io::<Sealed>::from(io), ssl)
+                    .await
+                    .map_err(|e| ConnectError::SslHandshakeError(format!("{}", e)))?;
+                Ok(IoBoxed::from(io))
+            })
+        }))
+    }
+
+    #[cfg(feature = "rustls")]
+    fn rustls(config: ClientConfig) -> Self {
+        use std::{io, sync::Arc};
+        use tls_rustls::pki_types::ServerName;
+
+        use crate::io::{Io, Sealed};
+
+        let config = Arc::new(config);
+        TlsUpgrade(Rc::new(move |io, uri| {
+            let config = config.clone();
+            Box::pin(async move {
+                let host = ServerName::try_from(uri.host().unwrap_or("").to_owned())
+                    .map_err(|e| ConnectError::Disconnected(Some(io::Error::other(e))))?;
+                let io = ntex_tls::rustls::TlsClientFilter::create(
+                    Io::<Sealed>::from(io),
+                    config,
+                    host,
+                )
+                .await
+                .map_err(|e| ConnectError::Disconnected(Some(e)))?;
+                Ok(IoBoxed::from(io))
+            })
+        }))
+    }
+}
+
+impl fmt::Debug for TlsUpgrade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsUpgrade").finish()
+    }
+}
+
+#[derive(Clone, Debug)]
+/// Opens secure connections through the proxy
+struct Tunnel {
+    connector: Pipeline<BoxedConnector>,
+    tls: Option<TlsUpgrade>,
+}
+
+impl Tunnel {
+    async fn connect(&self, proxy: &Proxy, uri: &Uri) -> Result<IoBoxed, ConnectError> {
+        let Some(ref tls) = self.tls else {
+            return Err(ConnectError::SslIsNotSupported);
+        };
+
+        let io = self
+            .connector
+            .call(TcpConnect::new(proxy.uri().clone()))
+            .await?;
+        proxy.tunnel(&io, uri).await?;
+        (tls.0)(io, uri.clone()).await
+    }
+}
+
 #[derive(Debug)]
 struct InnerConnector<T> {
     tcp_pool: ConnectionPool<T>,
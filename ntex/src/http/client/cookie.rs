@@ -0,0 +1,539 @@
+use std::fmt::{self, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{cell::RefCell, net, rc::Rc};
+
+use coo_kie::Cookie;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::http::header::{self, HeaderMap, HeaderValue};
+use crate::http::{body::Body, RequestHeadType, Uri};
+use crate::{time::Millis, util::BoxFuture};
+
+use super::connect::Connect;
+use super::error::SendRequestError;
+use super::{ClientConfig, ClientResponse};
+
+/// Client cookie storage
+///
+/// Cookie jar stores cookies received in `Set-Cookie` response headers and
+/// sends matching cookies with subsequent requests. `Domain`, `Path`,
+/// `Expires`, `Max-Age` and `Secure` attributes are respected. Cookies with
+/// `Domain` attribute set to a public suffix are rejected, single-label
+/// domains like `com` are always treated as public suffixes, public suffix
+/// list could be provided with [`CookieJar::public_suffix()`].
+/// Jar is cheap to clone, clones share storage.
+///
+/// Jar could be serialized with serde, for example to prepare test fixtures.
+///
+/// ```rust
+/// use ntex::http::client::{Client, CookieJar};
+///
+/// let jar = CookieJar::new();
+/// let client = Client::build().cookie_jar(jar.clone()).finish();
+/// ```
+#[derive(Clone, Default)]
+pub struct CookieJar(Rc<JarInner>);
+
+#[derive(Default)]
+struct JarInner {
+    cookies: RefCell<Vec<StoredCookie>>,
+    public_suffix: RefCell<Option<Rc<dyn Fn(&str) -> bool>>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct StoredCookie {
+    name: String,
+    value: String,
+    domain: String,
+    host_only: bool,
+    path: String,
+    secure: bool,
+    http_only: bool,
+    expires: Option<u64>,
+}
+
+impl CookieJar {
+    /// Create empty cookie jar
+    pub fn new() -> Self {
+        CookieJar::default()
+    }
+
+    /// Set public suffix predicate.
+    ///
+    /// Predicate receives lowercased domain without leading dot, for example
+    /// `co.uk`, and must return `true` if domain is a public suffix. Cookies
+    /// could not be set for public suffixes, except if it is equal to the
+    /// request's host, in that case cookie is sent to this host only.
+    ///
+    /// Predicate is shared by all clones of the jar.
+    pub fn public_suffix<F>(self, f: F) -> Self
+    where
+        F: Fn(&str) -> bool + 'static,
+    {
+        *self.0.public_suffix.borrow_mut() = Some(Rc::new(f));
+        self
+    }
+
+    /// Store cookies from `Set-Cookie` headers of the response for `uri`
+    pub fn store(&self, uri: &Uri, headers: &HeaderMap) {
+        for hdr in headers.get_all(header::SET_COOKIE) {
+            if let Ok(s) = hdr.to_str() {
+                match Cookie::parse(s) {
+                    Ok(cookie) => self.insert(uri, cookie),
+                    Err(e) => log::trace!("Cannot parse cookie {:?}: {}", s, e),
+                }
+            }
+        }
+    }
+
+    /// Add cookie, as if it is received in response for `uri`
+    pub fn insert(&self, uri: &Uri, cookie: Cookie<'_>) {
+        let Some(host) = uri.host().map(|h| h.to_ascii_lowercase()) else {
+            return;
+        };
+
+        let (domain, host_only) = match cookie.domain() {
+            Some(domain) if !domain.is_empty() => {
+                let domain = domain.trim_start_matches('.').to_ascii_lowercase();
+                if !domain_match(&host, &domain) {
+                    log::trace!("Cookie domain {:?} does not match {:?}", domain, host);
+                    return;
+                }
+                if !self.is_public_suffix(&domain) {
+                    (domain, false)
+                } else if domain == host {
+                    (host, true)
+                } else {
+                    log::trace!("Cookie domain {:?} is a public suffix", domain);
+                    return;
+                }
+            }
+            _ => (host, true),
+        };
+        let path = match cookie.path() {
+            Some(path) if path.starts_with('/') => path.to_string(),
+            _ => default_path(uri.path()),
+        };
+        // secure cookies could be set only by secure origin
+        if cookie.secure() == Some(true) && !is_secure(uri) {
+            return;
+        }
+
+        let now = now();
+        let expires = if let Some(max_age) = cookie.max_age() {
+            Some(now.saturating_add_signed(max_age.whole_seconds()))
+        } else {
+            cookie
+                .expires_datetime()
+                .map(|dt| u64::try_from(dt.unix_timestamp()).unwrap_or(0))
+        };
+        let expired = expires.is_some_and(|exp| exp <= now);
+
+        let mut cookies = self.0.cookies.borrow_mut();
+        let idx = cookies
+            .iter()
+            .position(|c| c.name == cookie.name() && c.domain == domain && c.path == path);
+
+        if expired {
+            if let Some(idx) = idx {
+                cookies.remove(idx);
+            }
+            return;
+        }
+
+        let item = StoredCookie {
+            domain,
+            host_only,
+            path,
+            expires,
+            name: cookie.name().to_string(),
+            value: cookie.value().to_string(),
+            secure: cookie.secure().unwrap_or(false),
+            http_only: cookie.http_only().unwrap_or(false),
+        };
+        if let Some(idx) = idx {
+            cookies[idx] = item;
+        } else {
+            cookies.push(item);
+        }
+    }
+
+    /// Cookies that must be sent with request to `uri`
+    ///
+    /// Cookies with longer paths are listed first.
+    pub fn cookies(&self, uri: &Uri) -> Vec<Cookie<'static>> {
+        let Some(host) = uri.host().map(|h| h.to_ascii_lowercase()) else {
+            return Vec::new();
+        };
+        let path = if uri.path().is_empty() {
+            "/"
+        } else {
+            uri.path()
+        };
+        let secure = is_secure(uri);
+
+        let now = now();
+        let mut cookies = self.0.cookies.borrow_mut();
+        cookies.retain(|c| c.expires.map_or(true, |exp| exp > now));
+
+        let mut matched: Vec<_> = cookies
+            .iter()
+            .filter(|c| {
+                (if c.host_only {
+                    c.domain == host
+                } else {
+                    domain_match(&host, &c.domain)
+                }) && path_match(path, &c.path)
+                    && (secure || !c.secure)
+            })
+            .collect();
+        // stable sort, preserves creation order for equal paths
+        matched.sort_by(|a, b| b.path.len().cmp(&a.path.len()));
+
+        matched
+            .into_iter()
+            .map(|c| Cookie::new(c.name.clone(), c.value.clone()))
+            .collect()
+    }
+
+    /// Remove all cookies
+    pub fn clear(&self) {
+        self.0.cookies.borrow_mut().clear();
+    }
+
+    /// Number of stored cookies
+    pub fn len(&self) -> usize {
+        self.0.cookies.borrow().len()
+    }
+
+    /// Check if jar is empty
+    pub fn is_empty(&self) -> bool {
+        self.0.cookies.borrow().is_empty()
+    }
+
+    fn is_public_suffix(&self, domain: &str) -> bool {
+        let f = self.0.public_suffix.borrow().clone();
+        !domain.contains('.') || f.is_some_and(|f| f(domain))
+    }
+
+    /// Value for `Cookie` request header
+    fn header(&self, uri: &Uri) -> Option<String> {
+        let cookies = self.cookies(uri);
+        if cookies.is_empty() {
+            return None;
+        }
+
+        // cookie values are sent as received
+        let mut hdr = String::new();
+        for c in cookies {
+            let _ = write!(hdr, "; {}={}", c.name(), c.value());
+        }
+        Some(hdr.split_off(2))
+    }
+}
+
+impl fmt::Debug for CookieJar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CookieJar")
+            .field("cookies", &self.0.cookies.borrow().len())
+            .finish()
+    }
+}
+
+impl Serialize for CookieJar {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.cookies.borrow().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for CookieJar {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let cookies = Vec::<StoredCookie>::deserialize(deserializer)?;
+        Ok(CookieJar(Rc::new(JarInner {
+            cookies: RefCell::new(cookies),
+            public_suffix: RefCell::new(None),
+        })))
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn is_secure(uri: &Uri) -> bool {
+    matches!(uri.scheme_str(), Some("https") | Some("wss"))
+}
+
+fn domain_match(host: &str, domain: &str) -> bool {
+    if host == domain {
+        true
+    } else {
+        host.parse::<net::IpAddr>().is_err()
+            && host.len() > domain.len()
+            && host.ends_with(domain)
+            && host.as_bytes()[host.len() - domain.len() - 1] == b'.'
+    }
+}
+
+fn default_path(path: &str) -> String {
+    if !path.starts_with('/') {
+        return "/".to_string();
+    }
+    match path.rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(idx) => path[..idx].to_string(),
+    }
+}
+
+fn path_match(path: &str, cookie_path: &str) -> bool {
+    path == cookie_path
+        || (path.starts_with(cookie_path)
+            && (cookie_path.ends_with('/') || path[cookie_path.len()..].starts_with('/')))
+}
+
+/// Connector wrapper that sends and stores cookies
+pub(super) struct CookieConnect {
+    connector: Box<dyn Connect>,
+    jar: CookieJar,
+}
+
+impl CookieConnect {
+    pub(super) fn new(connector: Box<dyn Connect>, jar: CookieJar) -> Self {
+        CookieConnect { connector, jar }
+    }
+}
+
+impl fmt::Debug for CookieConnect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CookieConnect")
+            .field("connector", &self.connector)
+            .field("jar", &self.jar)
+            .finish()
+    }
+}
+
+impl Connect for CookieConnect {
+    fn send_request(
+        &self,
+        mut head: RequestHeadType,
+        body: Body,
+        addr: Option<net::SocketAddr>,
+        timeout: Millis,
+        cfg: Rc<ClientConfig>,
+    ) -> BoxFuture<'_, Result<ClientResponse, SendRequestError>> {
+        let uri = head.as_ref().uri.clone();
+
+        // cookies set explicitly for the request are preserved
+        if let Some(cookies) = self.jar.header(&uri) {
+            let current = head
+                .extra_headers()
+                .and_then(|h| h.get(header::COOKIE))
+                .or_else(|| head.as_ref().headers.get(header::COOKIE))
+                .and_then(|h| h.to_str().ok());
+            let value = if let Some(current) = current {
+                format!("{}; {}", current, cookies)
+            } else {
+                cookies
+            };
+
+            if let Ok(value) = HeaderValue::try_from(value) {
+                match head {
+                    RequestHeadType::Owned(ref mut head) => {
+                        head.headers.insert(header::COOKIE, value);
+                    }
+                    RequestHeadType::Rc(_, ref mut extra_headers) => {
+                        extra_headers
+                            .get_or_insert_with(HeaderMap::new)
+                            .insert(header::COOKIE, value);
+                    }
+                }
+            }
+        }
+
+        Box::pin(async move {
+            let res = self
+                .connector
+                .send_request(head, body, addr, timeout, cfg)
+                .await?;
+            self.jar.store(&uri, res.headers());
+            Ok(res)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::client::{Client, TestConnect};
+    use crate::http::{ResponseHead, StatusCode};
+
+    fn store(jar: &CookieJar, uri: &'static str, cookie: &'static str) {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::SET_COOKIE, HeaderValue::from_static(cookie));
+        jar.store(&Uri::from_static(uri), &headers);
+    }
+
+    fn names(jar: &CookieJar, uri: &'static str) -> Vec<String> {
+        jar.cookies(&Uri::from_static(uri))
+            .into_iter()
+            .map(|c| c.name().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_domain_path() {
+        let jar = CookieJar::new();
+        store(&jar, "http://example.com/a/b", "host=1");
+        store(&jar, "http://example.com/", "domain=1; Domain=.Example.com");
+        store(&jar, "http://example.com/", "other=1; Domain=other.com");
+        store(&jar, "http://example.com/", "path=1; Path=/docs");
+        assert_eq!(jar.len(), 3);
+
+        assert_eq!(
+            names(&jar, "http://example.com/a/c"),
+            vec!["host", "domain"]
+        );
+        assert_eq!(names(&jar, "http://example.com/"), vec!["domain"]);
+        assert_eq!(names(&jar, "http://www.example.com/a/c"), vec!["domain"]);
+        assert_eq!(
+            names(&jar, "http://example.com/docs/1"),
+            vec!["path", "domain"]
+        );
+        assert_eq!(names(&jar, "http://example.com/docsx"), vec!["domain"]);
+        assert!(names(&jar, "http://notexample.com/").is_empty());
+
+        // replace
+        store(&jar, "http://example.com/a/", "host=2");
+        assert_eq!(jar.len(), 3);
+        let cookies = jar.cookies(&Uri::from_static("http://example.com/a"));
+        assert_eq!(cookies[0].value(), "2");
+    }
+
+    #[test]
+    fn test_public_suffix() {
+        let jar = CookieJar::new().public_suffix(|domain| domain == "co.uk");
+        store(&jar, "http://example.com/", "tld=1; Domain=com");
+        store(&jar, "http://example.com/", "tld=2; Domain=.com");
+        store(&jar, "http://example.co.uk/", "suffix=1; Domain=co.uk");
+        store(
+            &jar,
+            "http://example.co.uk/",
+            "domain=1; Domain=example.co.uk",
+        );
+        assert_eq!(jar.len(), 1);
+        assert!(names(&jar, "http://other.com/").is_empty());
+        assert!(names(&jar, "http://other.co.uk/").is_empty());
+        assert_eq!(names(&jar, "http://www.example.co.uk/"), vec!["domain"]);
+
+        // public suffix equal to host, host-only cookie
+        store(&jar, "http://localhost/", "local=1; Domain=localhost");
+        assert_eq!(names(&jar, "http://localhost/"), vec!["local"]);
+        assert!(names(&jar, "http://www.localhost/").is_empty());
+    }
+
+    #[test]
+    fn test_public_suffix_shared() {
+        let jar = CookieJar::new();
+        let jar2 = jar.clone().public_suffix(|domain| domain == "co.uk");
+        store(&jar, "http://example.co.uk/", "suffix=1; Domain=co.uk");
+        assert!(jar2.is_empty());
+    }
+
+    #[test]
+    fn test_raw_value() {
+        let jar = CookieJar::new();
+        store(&jar, "http://example.com/", "token=a%2Fb+c==");
+        store(&jar, "http://example.com/", "quoted=\"v\"");
+        assert_eq!(
+            jar.header(&Uri::from_static("http://example.com/"))
+                .unwrap(),
+            "token=a%2Fb+c==; quoted=\"v\""
+        );
+    }
+
+    #[test]
+    fn test_expires_secure() {
+        let jar = CookieJar::new();
+        store(&jar, "http://example.com/", "a=1; Max-Age=100");
+        store(
+            &jar,
+            "http://example.com/",
+            "b=1; Expires=Wed, 21 Oct 2015 07:28:00 GMT",
+        );
+        store(&jar, "http://example.com/", "c=1; Secure");
+        store(&jar, "https://example.com/", "d=1; Secure");
+        assert_eq!(names(&jar, "http://example.com/"), vec!["a"]);
+        assert_eq!(names(&jar, "https://example.com/"), vec!["a", "d"]);
+
+        // remove
+        store(&jar, "http://example.com/", "a=1; Max-Age=0");
+        assert_eq!(names(&jar, "https://example.com/"), vec!["d"]);
+
+        jar.clear();
+        assert!(jar.is_empty());
+    }
+
+    #[test]
+    fn test_serialize() {
+        let jar = CookieJar::new();
+        store(&jar, "http://example.com/", "a=1; Max-Age=100");
+        store(&jar, "http://example.com/", "b=2; Domain=example.com");
+
+        let data = serde_json::to_string(&jar).unwrap();
+        let jar2: CookieJar = serde_json::from_str(&data).unwrap();
+        assert_eq!(*jar.0.cookies.borrow(), *jar2.0.cookies.borrow());
+        assert_eq!(names(&jar2, "http://www.example.com/"), vec!["b"]);
+    }
+
+    #[crate::rt_test]
+    async fn test_client() {
+        let jar = CookieJar::new();
+        let client = Client::build()
+            .connection(TestConnect::new(|head, _| {
+                let cookie = head
+                    .extra_headers()
+                    .and_then(|h| h.get(header::COOKIE))
+                    .or_else(|| head.as_ref().headers.get(header::COOKIE))
+                    .map(|h| h.to_str().unwrap().to_string())
+                    .unwrap_or_default();
+
+                let mut res = ResponseHead::new(StatusCode::OK);
+                res.headers.insert(
+                    header::SET_COOKIE,
+                    HeaderValue::from_static("session=abc; Path=/"),
+                );
+                res.headers.insert(
+                    header::HeaderName::from_static("x-cookie"),
+                    HeaderValue::try_from(cookie).unwrap(),
+                );
+                Ok((res, None))
+            }))
+            .cookie_jar(jar.clone())
+            .finish();
+
+        let res = client.get("http://localhost/").send().await.unwrap();
+        assert_eq!(res.headers().get("x-cookie").unwrap(), "");
+        assert_eq!(names(&jar, "http://localhost/"), vec!["session"]);
+
+        let res = client.get("http://localhost/test").send().await.unwrap();
+        assert_eq!(res.headers().get("x-cookie").unwrap(), "session=abc");
+
+        let res = client
+            .get("http://localhost/test")
+            .cookie(Cookie::new("user", "1"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(
+            res.headers().get("x-cookie").unwrap(),
+            "user=1; session=abc"
+        );
+
+        let req = client.get("http://localhost/").freeze().unwrap();
+        let res = req.send().await.unwrap();
+        assert_eq!(res.headers().get("x-cookie").unwrap(), "session=abc");
+    }
+}
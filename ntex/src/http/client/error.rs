@@ -8,6 +8,7 @@ use thiserror::Error;
 use tls_openssl::ssl::{Error as SslError, HandshakeError};
 
 use crate::http::error::{DecodeError, EncodeError, HttpError, PayloadError};
+use crate::http::StatusCode;
 use crate::util::Either;
 
 /// A set of errors that can occur during parsing json payloads
@@ -26,6 +27,7 @@ pub enum JsonPayloadError {
 
 /// A set of errors that can occur while connecting to an HTTP host
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum ConnectError {
     /// SSL feature is not enabled
     #[error("SSL is not supported")]
@@ -60,6 +62,10 @@ pub enum ConnectError {
     /// Unresolved host name
     #[error("Connector received `Connect` method with unresolved host")]
     Unresolved,
+
+    /// Proxy refused to open tunnel
+    #[error("Proxy tunnel request failed with status: {0}")]
+    Proxy(StatusCode),
 }
 
 impl Clone for ConnectError {
@@ -88,6 +94,7 @@ impl Clone for ConnectError {
                 }
             }
             ConnectError::Unresolved => ConnectError::Unresolved,
+            ConnectError::Proxy(status) => ConnectError::Proxy(*status),
         }
     }
 }
@@ -1,11 +1,11 @@
 use std::task::{ready, Poll};
-use std::{future::poll_fn, io, io::Write, pin::Pin, task, time::Instant};
+use std::{future::poll_fn, io, io::Write, pin::Pin, rc::Rc, task, time::Instant};
 
 use crate::http::body::{BodySize, MessageBody};
 use crate::http::error::PayloadError;
 use crate::http::header::{HeaderMap, HeaderValue, HOST};
 use crate::http::message::{RequestHeadType, ResponseHead};
-use crate::http::payload::{Payload, PayloadStream};
+use crate::http::payload::Payload;
 use crate::http::{h1, Version};
 use crate::io::{IoBoxed, RecvError};
 use crate::time::{timeout_checked, Millis};
@@ -13,7 +13,7 @@ use crate::util::{BufMut, Bytes, BytesMut, Stream};
 
 use super::connection::{Connection, ConnectionType};
 use super::error::{ConnectError, SendRequestError};
-use super::pool::Acquired;
+use super::{pool::Acquired, proxy::Proxy};
 
 pub(super) async fn send_request<B>(
     io: IoBoxed,
@@ -22,6 +22,7 @@ pub(super) async fn send_request<B>(
     created: Instant,
     timeout: Millis,
     pool: Option<Acquired>,
+    proxy: Option<Rc<Proxy>>,
 ) -> Result<(ResponseHead, Payload), SendRequestError>
 where
     B: MessageBody,
@@ -53,6 +54,11 @@ where
         }
     }
 
+    // request goes through the proxy
+    if let Some(proxy) = proxy {
+        head = proxy.forward(head);
+    }
+
     log::trace!(
         "sending http1 request {:?} body size: {:?}",
         head,
@@ -100,14 +106,16 @@ where
             Ok((head, Payload::None))
         }
         _ => {
-            let pl: PayloadStream = Box::pin(PlStream::new(
+            let trailers = h1::Trailers::default();
+            let pl = PlStream::new(
                 io,
                 codec,
                 created,
                 pool,
                 head.version == Version::HTTP_10,
-            ));
-            Ok((head, pl.into()))
+                trailers.clone(),
+            );
+            Ok((head, h1::Payload::from_stream(pl, trailers).into()))
         }
     }
 }
@@ -128,7 +136,10 @@ where
                 io.flush(false).await?;
             }
             None => {
-                io.encode(h1::Message::Chunk(None), codec)?;
+                match poll_fn(|cx| body.poll_trailers(cx)).await {
+                    Some(trailers) => io.encode(trailers, &h1::TrailersEncoder(codec))?,
+                    None => io.encode(h1::Message::Chunk(None), codec)?,
+                }
                 break;
             }
         }
@@ -144,6 +155,7 @@ pub(super) struct PlStream {
     created: Instant,
     http_10: bool,
     pool: Option<Acquired>,
+    trailers: h1::Trailers,
 }
 
 impl PlStream {
@@ -153,6 +165,7 @@ impl PlStream {
         created: Instant,
         pool: Option<Acquired>,
         http_10: bool,
+        trailers: h1::Trailers,
     ) -> Self {
         PlStream {
             io: Some(io),
@@ -160,6 +173,7 @@ impl PlStream {
             created,
             pool,
             http_10,
+            trailers,
         }
     }
 }
@@ -179,6 +193,9 @@ impl Stream for PlStream {
                     if let Some(chunk) = chunk {
                         Ok(chunk)
                     } else {
+                        if let Some(trailers) = this.codec.take_trailers() {
+                            this.trailers.set(trailers);
+                        }
                         release_connection(
                             this.io.take().unwrap(),
                             !this.codec.keepalive(),
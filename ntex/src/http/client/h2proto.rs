@@ -5,7 +5,7 @@ use ntex_h2::{self as h2, client::RecvStream, client::SimpleClient, frame};
 use crate::http::body::{BodySize, MessageBody};
 use crate::http::header::{self, HeaderMap, HeaderValue};
 use crate::http::message::{RequestHeadType, ResponseHead};
-use crate::http::{h2::payload, payload::Payload, Method, Version};
+use crate::http::{h2::payload, helpers, payload::Payload, Method, Version};
 use crate::time::{timeout_checked, Millis};
 use crate::util::{select, ByteString, Bytes, Either};
 
@@ -172,8 +172,8 @@ async fn get_response(
                                             h2::StreamEof::Data(data) => {
                                                 pl.feed_eof(data);
                                             }
-                                            h2::StreamEof::Trailers(_) => {
-                                                pl.feed_eof(Bytes::new());
+                                            h2::StreamEof::Trailers(trailers) => {
+                                                pl.feed_trailers(trailers);
                                             }
                                             h2::StreamEof::Error(err) => {
                                                 pl.set_error(err.into())
@@ -243,7 +243,11 @@ async fn send_body<B: MessageBody>(
             Some(Err(e)) => return Err(e.into()),
             None => {
                 log::trace!("{}: {:?} eof of send stream ", stream.tag(), stream.id());
-                stream.send_payload(Bytes::new(), true).await?;
+                if let Some(trailers) = poll_fn(|cx| body.poll_trailers(cx)).await {
+                    stream.send_trailers(helpers::filter_trailers(trailers));
+                } else {
+                    stream.send_payload(Bytes::new(), true).await?;
+                }
                 return Ok(());
             }
         }
@@ -0,0 +1,343 @@
+use std::{fmt, net, rc::Rc};
+
+use crate::http::{body::Body, HeaderMap, Method, Payload, RequestHead, RequestHeadType};
+use crate::http::{ResponseHead, Uri};
+use crate::service::{Middleware, Pipeline, Service, ServiceCtx};
+use crate::{time::Millis, util::BoxFuture};
+
+use super::connect::Connect;
+use super::error::SendRequestError;
+use super::{ClientConfig, ClientResponse};
+
+/// Client request passed through client middlewares
+pub struct ClientServiceRequest {
+    head: RequestHead,
+    body: Body,
+    addr: Option<net::SocketAddr>,
+    timeout: Millis,
+    config: Rc<ClientConfig>,
+}
+
+impl ClientServiceRequest {
+    /// Request head
+    pub fn head(&self) -> &RequestHead {
+        &self.head
+    }
+
+    /// Mutable reference to the request head
+    pub fn head_mut(&mut self) -> &mut RequestHead {
+        &mut self.head
+    }
+
+    /// Request's uri
+    pub fn uri(&self) -> &Uri {
+        &self.head.uri
+    }
+
+    /// Request's method
+    pub fn method(&self) -> &Method {
+        &self.head.method
+    }
+
+    /// Request's headers
+    pub fn headers(&self) -> &HeaderMap {
+        &self.head.headers
+    }
+
+    /// Mutable reference to the request's headers
+    pub fn headers_mut(&mut self) -> &mut HeaderMap {
+        &mut self.head.headers
+    }
+
+    /// Request's body
+    pub fn body(&self) -> &Body {
+        &self.body
+    }
+
+    /// Set request's body
+    pub fn set_body(&mut self, body: Body) {
+        self.body = body;
+    }
+
+    /// Request timeout
+    pub fn timeout(&self) -> Millis {
+        self.timeout
+    }
+
+    /// Set request timeout
+    pub fn set_timeout(&mut self, timeout: Millis) {
+        self.timeout = timeout;
+    }
+
+    /// Create response for the request
+    ///
+    /// Could be used for responding without sending request to the remote host.
+    pub fn response(&self, head: ResponseHead, payload: Payload) -> ClientResponse {
+        ClientResponse::new(head, payload, self.config.clone())
+    }
+
+    /// Copy request, streaming bodies could not be copied
+    pub(super) fn try_clone(&self) -> Option<ClientServiceRequest> {
+        let body = match self.body {
+            Body::None => Body::None,
+            Body::Empty => Body::Empty,
+            Body::Bytes(ref b) => Body::Bytes(b.clone()),
+            Body::Message(_) => return None,
+        };
+
+        let mut head = RequestHead::default();
+        head.uri = self.head.uri.clone();
+        head.method = self.head.method.clone();
+        head.version = self.head.version;
+        head.headers = self.head.headers.clone();
+        head.flags = self.head.flags;
+
+        Some(ClientServiceRequest {
+            head,
+            body,
+            addr: self.addr,
+            timeout: self.timeout,
+            config: self.config.clone(),
+        })
+    }
+}
+
+impl fmt::Debug for ClientServiceRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientServiceRequest")
+            .field("head", &self.head)
+            .field("body", &self.body)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+/// Client service, sends requests to remote hosts
+///
+/// Client middlewares wrap this service.
+pub struct ClientService(Box<dyn Connect>);
+
+impl fmt::Debug for ClientService {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ClientService").field(&self.0).finish()
+    }
+}
+
+impl Service<ClientServiceRequest> for ClientService {
+    type Response = ClientResponse;
+    type Error = SendRequestError;
+
+    async fn call(
+        &self,
+        req: ClientServiceRequest,
+        _: ServiceCtx<'_, Self>,
+    ) -> Result<ClientResponse, SendRequestError> {
+        self.0
+            .send_request(req.head.into(), req.body, req.addr, req.timeout, req.config)
+            .await
+    }
+}
+
+type Wrap = Box<dyn FnOnce(Box<dyn Connect>) -> Box<dyn Connect>>;
+
+/// Registered client middlewares
+#[derive(Default)]
+pub(super) struct ClientMiddlewares(Vec<Wrap>);
+
+impl ClientMiddlewares {
+    pub(super) fn add<M>(&mut self, mw: M)
+    where
+        M: Middleware<ClientService> + 'static,
+        M::Service: Service<
+                ClientServiceRequest,
+                Response = ClientResponse,
+                Error = SendRequestError,
+            > + 'static,
+    {
+        self.0.push(Box::new(move |connector| {
+            Box::new(MiddlewareConnect(Pipeline::new(
+                mw.create(ClientService(connector)),
+            )))
+        }));
+    }
+
+    /// Wrap connector with middlewares, last registered middleware is called first
+    pub(super) fn apply(self, mut connector: Box<dyn Connect>) -> Box<dyn Connect> {
+        for wrap in self.0 {
+            connector = wrap(connector);
+        }
+        connector
+    }
+}
+
+impl fmt::Debug for ClientMiddlewares {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientMiddlewares")
+            .field("len", &self.0.len())
+            .finish()
+    }
+}
+
+/// Connector that passes requests through middleware service
+struct MiddlewareConnect<S>(Pipeline<S>);
+
+impl<S> fmt::Debug for MiddlewareConnect<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MiddlewareConnect").finish()
+    }
+}
+
+impl<S> Connect for MiddlewareConnect<S>
+where
+    S: Service<ClientServiceRequest, Response = ClientResponse, Error = SendRequestError>
+        + 'static,
+{
+    fn send_request(
+        &self,
+        head: RequestHeadType,
+        body: Body,
+        addr: Option<net::SocketAddr>,
+        timeout: Millis,
+        config: Rc<ClientConfig>,
+    ) -> BoxFuture<'_, Result<ClientResponse, SendRequestError>> {
+        Box::pin(self.0.call(ClientServiceRequest {
+            head: head.into_owned(),
+            body,
+            addr,
+            timeout,
+            config,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::http::client::{Client, TestConnect};
+    use crate::http::{header, StatusCode};
+
+    struct Trace;
+
+    impl<S> Middleware<S> for Trace {
+        type Service = TraceService<S>;
+
+        fn create(&self, service: S) -> Self::Service {
+            TraceService(service)
+        }
+    }
+
+    struct TraceService<S>(S);
+
+    impl<S> Service<ClientServiceRequest> for TraceService<S>
+    where
+        S: Service<
+            ClientServiceRequest,
+            Response = ClientResponse,
+            Error = SendRequestError,
+        >,
+    {
+        type Response = ClientResponse;
+        type Error = SendRequestError;
+
+        async fn call(
+            &self,
+            mut req: ClientServiceRequest,
+            ctx: ServiceCtx<'_, Self>,
+        ) -> Result<ClientResponse, SendRequestError> {
+            req.headers_mut().insert(
+                header::HeaderName::from_static("x-trace"),
+                header::HeaderValue::from_static("1"),
+            );
+            let mut res = ctx.call(&self.0, req).await?;
+            res.headers_mut().insert(
+                header::HeaderName::from_static("x-seen"),
+                header::HeaderValue::from_static("1"),
+            );
+            Ok(res)
+        }
+    }
+
+    struct Cached;
+
+    impl<S> Middleware<S> for Cached {
+        type Service = CachedService<S>;
+
+        fn create(&self, service: S) -> Self::Service {
+            CachedService(service)
+        }
+    }
+
+    struct CachedService<S>(S);
+
+    impl<S> Service<ClientServiceRequest> for CachedService<S>
+    where
+        S: Service<
+            ClientServiceRequest,
+            Response = ClientResponse,
+            Error = SendRequestError,
+        >,
+    {
+        type Response = ClientResponse;
+        type Error = SendRequestError;
+
+        async fn call(
+            &self,
+            req: ClientServiceRequest,
+            ctx: ServiceCtx<'_, Self>,
+        ) -> Result<ClientResponse, SendRequestError> {
+            if req.uri().path() == "/cached" {
+                Ok(
+                    req.response(
+                        ResponseHead::new(StatusCode::NOT_MODIFIED),
+                        Payload::None,
+                    ),
+                )
+            } else {
+                ctx.call(&self.0, req).await
+            }
+        }
+    }
+
+    #[crate::rt_test]
+    async fn test_middlewares() {
+        let calls = Rc::new(Cell::new(0));
+        let counter = calls.clone();
+        let client = Client::build()
+            .connection(TestConnect::new(move |head, _| {
+                counter.set(counter.get() + 1);
+                let mut res = ResponseHead::new(StatusCode::OK);
+                if let Some(value) = head.as_ref().headers.get("x-trace") {
+                    res.headers
+                        .insert(header::HeaderName::from_static("x-trace"), value.clone());
+                }
+                Ok((res, None))
+            }))
+            .wrap(Trace)
+            .wrap(Cached)
+            .finish();
+
+        let res = client.get("http://localhost/test").send().await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(res.headers().contains_key("x-trace"));
+        assert!(res.headers().contains_key("x-seen"));
+        assert_eq!(calls.get(), 1);
+
+        let res = client.get("http://localhost/cached").send().await.unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+        assert!(!res.headers().contains_key("x-seen"));
+        assert_eq!(calls.get(), 1);
+
+        let res = client
+            .get("http://localhost/test")
+            .freeze()
+            .unwrap()
+            .extra_header("x-extra", "1")
+            .send()
+            .await
+            .unwrap();
+        assert!(res.headers().contains_key("x-trace"));
+        assert_eq!(calls.get(), 2);
+    }
+}
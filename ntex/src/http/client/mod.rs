@@ -19,25 +19,37 @@
 use std::rc::Rc;
 
 mod builder;
+mod cache;
 pub mod connect;
 mod connection;
 mod connector;
+#[cfg(feature = "cookie")]
+mod cookie;
 pub mod error;
 mod frozen;
 mod h1proto;
 mod h2proto;
+mod middleware;
 mod pool;
+mod proxy;
 mod request;
 mod response;
+mod retry;
 mod sender;
 mod test;
 
 pub use self::builder::ClientBuilder;
+pub use self::cache::{Cache, CacheService, CacheStore, CachedResponse, MemoryStore};
 pub use self::connection::Connection;
 pub use self::connector::Connector;
+#[cfg(feature = "cookie")]
+pub use self::cookie::CookieJar;
 pub use self::frozen::{FrozenClientRequest, FrozenSendBuilder};
+pub use self::middleware::{ClientService, ClientServiceRequest};
+pub use self::proxy::Proxy;
 pub use self::request::ClientRequest;
 pub use self::response::{ClientResponse, JsonBody, MessageBody};
+pub use self::retry::RetryPolicy;
 pub use self::sender::SendClientRequest;
 pub use self::test::TestResponse;
 
@@ -198,3 +210,65 @@ impl Client {
         self.request(Method::OPTIONS, url)
     }
 }
+
+#[cfg(test)]
+mod testing {
+    use std::{fmt, net, rc::Rc};
+
+    use crate::http::{body::Body, Payload, RequestHeadType, ResponseHead};
+    use crate::{channel::bstream, time::Millis, util::BoxFuture, util::Bytes};
+
+    use super::connect::Connect;
+    use super::error::SendRequestError;
+    use super::{ClientConfig, ClientResponse};
+
+    /// Mock connection, response head and body are built by provided function
+    pub(super) struct TestConnect<F>(F);
+
+    impl<F> TestConnect<F>
+    where
+        F: Fn(
+            RequestHeadType,
+            Body,
+        ) -> Result<(ResponseHead, Option<Bytes>), SendRequestError>,
+    {
+        pub(super) fn new(f: F) -> Self {
+            TestConnect(f)
+        }
+    }
+
+    impl<F> fmt::Debug for TestConnect<F> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("TestConnect").finish()
+        }
+    }
+
+    impl<F> Connect for TestConnect<F>
+    where
+        F: Fn(
+            RequestHeadType,
+            Body,
+        ) -> Result<(ResponseHead, Option<Bytes>), SendRequestError>,
+    {
+        fn send_request(
+            &self,
+            head: RequestHeadType,
+            body: Body,
+            _: Option<net::SocketAddr>,
+            _: Millis,
+            cfg: Rc<ClientConfig>,
+        ) -> BoxFuture<'_, Result<ClientResponse, SendRequestError>> {
+            let result = (self.0)(head, body).map(|(head, body)| {
+                let payload = match body {
+                    Some(body) => bstream::empty(Some(body)).into(),
+                    None => Payload::None,
+                };
+                ClientResponse::new(head, payload, cfg)
+            });
+            Box::pin(async move { result })
+        }
+    }
+}
+
+#[cfg(test)]
+use self::testing::TestConnect;
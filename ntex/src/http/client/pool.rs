@@ -14,16 +14,21 @@ use crate::util::{ByteString, HashMap, HashSet};
 use crate::{channel::pool, rt::spawn, task::LocalWaker};
 
 use super::connection::{Connection, ConnectionType};
+use super::proxy::{is_tunnel, Proxy, ProxyConfig};
 use super::{error::ConnectError, h2proto::H2Client, Connect};
 
 #[derive(Hash, Eq, PartialEq, Clone, Debug)]
 pub(super) struct Key {
     authority: Authority,
+    proxy: Option<Authority>,
 }
 
-impl From<Authority> for Key {
-    fn from(authority: Authority) -> Key {
-        Key { authority }
+impl Key {
+    fn new(authority: Authority, proxy: Option<&Rc<Proxy>>) -> Key {
+        Key {
+            authority,
+            proxy: proxy.map(|p| p.authority().clone()),
+        }
     }
 }
 
@@ -47,6 +52,7 @@ struct AvailableConnection {
 #[derive(Debug)]
 pub(super) struct ConnectionPool<T> {
     connector: Pipeline<T>,
+    proxy: Rc<ProxyConfig>,
     inner: Rc<RefCell<Inner>>,
     waiters: Rc<RefCell<Waiters>>,
 }
@@ -62,6 +68,7 @@ where
         disconnect_timeout: Seconds,
         limit: usize,
         h2config: h2::Config,
+        proxy: Rc<ProxyConfig>,
     ) -> Self {
         let connector = Pipeline::new(connector);
         let waiters = Rc::new(RefCell::new(Waiters {
@@ -90,6 +97,7 @@ where
 
         ConnectionPool {
             connector,
+            proxy,
             inner,
             waiters,
         }
@@ -106,6 +114,7 @@ impl<T> Clone for ConnectionPool<T> {
     fn clone(&self) -> Self {
         ConnectionPool {
             connector: self.connector.clone(),
+            proxy: self.proxy.clone(),
             inner: self.inner.clone(),
             waiters: self.waiters.clone(),
         }
@@ -142,15 +151,19 @@ where
         let inner = self.inner.clone();
         let waiters = self.waiters.clone();
 
+        let proxy = self.proxy.select(&req.uri).cloned();
         let key = if let Some(authority) = req.uri.authority() {
-            authority.clone().into()
+            Key::new(authority.clone(), proxy.as_ref())
         } else {
             return Err(ConnectError::Unresolved);
         };
 
+        // plain http requests are forwarded to the proxy
+        let forward = proxy.filter(|_| !is_tunnel(&req.uri));
+
         // acquire connection
         let result = inner.borrow_mut().acquire(&key);
-        match result {
+        let conn = match result {
             // use existing connection
             Acquire::Acquired(io, created) => {
                 log::trace!("Use existing {:?} connection for {:?}", io, req.uri);
@@ -178,12 +191,18 @@ where
                     "Pool is full, waiting for available connections for {:?}",
                     req.uri
                 );
-                let rx = waiters.borrow_mut().wait_for(req);
+                let rx = waiters.borrow_mut().wait_for(key, req);
                 match rx.await {
                     Err(_) => Err(ConnectError::Disconnected(None)),
                     Ok(res) => res,
                 }
             }
+        }?;
+
+        if let Some(proxy) = forward {
+            Ok(conn.forward(proxy))
+        } else {
+            Ok(conn)
         }
     }
 }
@@ -210,9 +229,8 @@ struct Waiters {
 
 impl Waiters {
     /// connection is not available, wait
-    fn wait_for(&mut self, connect: Connect) -> WaiterReceiver {
+    fn wait_for(&mut self, key: Key, connect: Connect) -> WaiterReceiver {
         let (tx, rx) = self.pool.channel();
-        let key: Key = connect.uri.authority().unwrap().clone().into();
         self.waiters
             .entry(key)
             .or_default()
@@ -645,6 +663,7 @@ mod tests {
                 Seconds::ZERO,
                 1,
                 h2::Config::client(),
+                Rc::default(),
             )
             .clone(),
         )
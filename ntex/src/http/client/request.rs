@@ -8,20 +8,13 @@ use serde::Serialize;
 use crate::http::body::Body;
 use crate::http::error::HttpError;
 use crate::http::header::{self, HeaderMap, HeaderName, HeaderValue};
-use crate::http::{
-    uri, ConnectionType, Method, RequestHead, RequestHeadType, Uri, Version,
-};
+use crate::http::{ConnectionType, Method, RequestHead, RequestHeadType, Uri, Version};
 use crate::{time::Millis, util::Bytes, util::Stream};
 
 use super::error::{FreezeRequestError, InvalidUrl};
 use super::sender::{PrepForSendingError, SendClientRequest};
 use super::{frozen::FrozenClientRequest, ClientConfig};
 
-#[cfg(feature = "compress")]
-const HTTPS_ENCODING: &str = "br, gzip, deflate";
-#[cfg(not(feature = "compress"))]
-const HTTPS_ENCODING: &str = "br";
-
 /// An HTTP Client request builder
 ///
 /// This type can be used to construct an instance of `ClientRequest` through a
@@ -526,27 +519,15 @@ impl ClientRequest {
             }
         }
 
-        let mut slf = self;
-
-        if slf.response_decompress {
-            let https = slf
-                .head
-                .uri
-                .scheme()
-                .map(|s| s == &uri::Scheme::HTTPS)
-                .unwrap_or(true);
-
-            if https {
-                slf = slf.set_header_if_none(header::ACCEPT_ENCODING, HTTPS_ENCODING)
-            } else {
-                #[cfg(feature = "compress")]
-                {
-                    slf = slf.set_header_if_none(header::ACCEPT_ENCODING, "gzip, deflate")
-                }
-            };
+        #[cfg(feature = "compress")]
+        if self.response_decompress {
+            self = self.set_header_if_none(
+                header::ACCEPT_ENCODING,
+                crate::http::encoding::ACCEPT_ENCODING,
+            );
         }
 
-        Ok(slf)
+        Ok(self)
     }
 }
 
@@ -0,0 +1,383 @@
+use std::{fmt, net, rc::Rc, time::Duration, time::SystemTime};
+
+use nanorand::{Rng, WyRand};
+
+use crate::http::{body::Body, header, HeaderMap, Method, StatusCode};
+use crate::http::{RequestHead, RequestHeadType};
+use crate::time::{sleep, Millis};
+use crate::util::BoxFuture;
+
+use super::connect::Connect;
+use super::error::{ConnectError, SendRequestError};
+use super::{ClientConfig, ClientResponse};
+
+/// Client request retry policy
+///
+/// Policy applies only to requests with idempotent methods and replayable
+/// bodies, streaming request bodies are never retried. Requests are retried
+/// on connect errors, on errors caused by stale pooled connections and
+/// on configured response statuses.
+///
+/// ```rust
+/// use ntex::http::client::{Client, RetryPolicy};
+/// use ntex::time::Millis;
+///
+/// let client = Client::build()
+///     .retry(RetryPolicy::new(3).base_delay(Millis(50)))
+///     .finish();
+/// ```
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    max_retries: u16,
+    base_delay: Millis,
+    max_delay: Millis,
+    jitter: bool,
+    retry_after: bool,
+    statuses: Vec<StatusCode>,
+    methods: Vec<Method>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy::new(3)
+    }
+}
+
+impl RetryPolicy {
+    /// Create retry policy with max number of retries.
+    ///
+    /// By default `GET`, `HEAD`, `OPTIONS`, `TRACE`, `PUT` and `DELETE`
+    /// requests are retried on connect errors and on `502`, `503`, `504`
+    /// response statuses.
+    pub fn new(max_retries: u16) -> Self {
+        RetryPolicy {
+            max_retries,
+            base_delay: Millis(100),
+            max_delay: Millis(10_000),
+            jitter: true,
+            retry_after: true,
+            statuses: vec![
+                StatusCode::BAD_GATEWAY,
+                StatusCode::SERVICE_UNAVAILABLE,
+                StatusCode::GATEWAY_TIMEOUT,
+            ],
+            methods: vec![
+                Method::GET,
+                Method::HEAD,
+                Method::OPTIONS,
+                Method::TRACE,
+                Method::PUT,
+                Method::DELETE,
+            ],
+        }
+    }
+
+    /// Set initial backoff delay.
+    ///
+    /// Delay is doubled for each following attempt. Default is 100 millis.
+    pub fn base_delay<T: Into<Millis>>(mut self, delay: T) -> Self {
+        self.base_delay = delay.into();
+        self
+    }
+
+    /// Set max backoff delay.
+    ///
+    /// Default is 10 seconds.
+    pub fn max_delay<T: Into<Millis>>(mut self, delay: T) -> Self {
+        self.max_delay = delay.into();
+        self
+    }
+
+    /// Randomize backoff delay, enabled by default.
+    pub fn jitter(mut self, enabled: bool) -> Self {
+        self.jitter = enabled;
+        self
+    }
+
+    /// Respect `Retry-After` response header, enabled by default.
+    ///
+    /// Response is returned without retry if `Retry-After`
+    /// is greater than max backoff delay.
+    pub fn retry_after(mut self, enabled: bool) -> Self {
+        self.retry_after = enabled;
+        self
+    }
+
+    /// Set response statuses that trigger retry.
+    pub fn statuses<I: IntoIterator<Item = StatusCode>>(mut self, statuses: I) -> Self {
+        self.statuses = statuses.into_iter().collect();
+        self
+    }
+
+    /// Set request methods that could be retried.
+    ///
+    /// Non-idempotent methods must not be added, request could be
+    /// processed by server even if response is not received.
+    pub fn methods<I: IntoIterator<Item = Method>>(mut self, methods: I) -> Self {
+        self.methods = methods.into_iter().collect();
+        self
+    }
+
+    fn is_retriable_error(&self, err: &SendRequestError) -> bool {
+        match err {
+            SendRequestError::Connect(err) => matches!(
+                err,
+                ConnectError::Timeout
+                    | ConnectError::Disconnected(_)
+                    | ConnectError::Resolver(_)
+                    | ConnectError::NoRecords
+            ),
+            // pooled connection is closed by peer
+            SendRequestError::Send(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Backoff delay for the attempt
+    fn delay(&self, attempt: u16) -> Millis {
+        let delay = self
+            .base_delay
+            .0
+            .saturating_mul(1u32.checked_shl(attempt.into()).unwrap_or(u32::MAX))
+            .min(self.max_delay.0);
+
+        if self.jitter && delay > 1 {
+            Millis(WyRand::new().generate_range(delay / 2..=delay))
+        } else {
+            Millis(delay)
+        }
+    }
+
+    /// `Retry-After` delay, `None` if header is missing or invalid
+    fn retry_after_delay(&self, headers: &HeaderMap) -> Option<Duration> {
+        let value = headers.get(header::RETRY_AFTER)?.to_str().ok()?;
+        if let Ok(secs) = value.trim().parse::<u64>() {
+            Some(Duration::from_secs(secs))
+        } else {
+            let date = httpdate::parse_http_date(value).ok()?;
+            Some(
+                date.duration_since(SystemTime::now())
+                    .unwrap_or(Duration::ZERO),
+            )
+        }
+    }
+}
+
+/// Connector wrapper that retries failed requests
+pub(super) struct RetryConnect {
+    connector: Box<dyn Connect>,
+    policy: RetryPolicy,
+}
+
+impl RetryConnect {
+    pub(super) fn new(connector: Box<dyn Connect>, policy: RetryPolicy) -> Self {
+        RetryConnect { connector, policy }
+    }
+}
+
+impl fmt::Debug for RetryConnect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryConnect")
+            .field("connector", &self.connector)
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+impl Connect for RetryConnect {
+    fn send_request(
+        &self,
+        head: RequestHeadType,
+        body: Body,
+        addr: Option<net::SocketAddr>,
+        timeout: Millis,
+        cfg: Rc<ClientConfig>,
+    ) -> BoxFuture<'_, Result<ClientResponse, SendRequestError>> {
+        // only idempotent requests with replayable body are retried
+        let replayable = matches!(body, Body::None | Body::Empty | Body::Bytes(_));
+        if self.policy.max_retries == 0
+            || !replayable
+            || !self.policy.methods.contains(&head.as_ref().method)
+        {
+            return self.connector.send_request(head, body, addr, timeout, cfg);
+        }
+
+        let (head, extra_headers): (Rc<RequestHead>, Option<HeaderMap>) = match head {
+            RequestHeadType::Owned(head) => (Rc::new(head), None),
+            RequestHeadType::Rc(head, extra_headers) => (head, extra_headers),
+        };
+
+        Box::pin(async move {
+            let mut attempt = 0;
+            loop {
+                let req = RequestHeadType::Rc(head.clone(), extra_headers.clone());
+                let result = self
+                    .connector
+                    .send_request(req, replay(&body), addr, timeout, cfg.clone())
+                    .await;
+
+                if attempt >= self.policy.max_retries {
+                    return result;
+                }
+
+                let delay = match result {
+                    Ok(ref res) if self.policy.statuses.contains(&res.status()) => {
+                        let delay = self.policy.delay(attempt);
+                        if self.policy.retry_after {
+                            match self.policy.retry_after_delay(res.headers()) {
+                                Some(d) if d > Duration::from(self.policy.max_delay) => {
+                                    return result
+                                }
+                                Some(d) => Millis::from(d).max(delay),
+                                None => delay,
+                            }
+                        } else {
+                            delay
+                        }
+                    }
+                    Err(ref err) if self.policy.is_retriable_error(err) => {
+                        self.policy.delay(attempt)
+                    }
+                    _ => return result,
+                };
+                attempt += 1;
+
+                log::trace!(
+                    "Retry {} {:?}, attempt {} in {:?}",
+                    head.method,
+                    head.uri,
+                    attempt,
+                    delay
+                );
+                drop(result);
+                sleep(delay).await;
+            }
+        })
+    }
+}
+
+fn replay(body: &Body) -> Body {
+    match body {
+        Body::None => Body::None,
+        Body::Empty => Body::Empty,
+        Body::Bytes(b) => Body::Bytes(b.clone()),
+        Body::Message(_) => unreachable!("Streaming body cannot be replayed"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::http::client::{Client, TestConnect};
+    use crate::http::ResponseHead;
+
+    fn test_client(
+        failures: usize,
+        status: StatusCode,
+        policy: RetryPolicy,
+    ) -> (Client, Rc<Cell<usize>>) {
+        let attempts = Rc::new(Cell::new(0));
+        let counter = attempts.clone();
+        let client = Client::build()
+            .connection(TestConnect::new(move |head, body| {
+                let attempt = counter.get();
+                counter.set(attempt + 1);
+                assert_eq!(head.as_ref().uri.path(), "/test");
+                if let Body::Bytes(b) = body {
+                    assert_eq!(b, "body");
+                }
+
+                if attempt < failures {
+                    if status.is_success() {
+                        Err(ConnectError::Disconnected(None).into())
+                    } else {
+                        let mut head = ResponseHead::new(status);
+                        head.headers.insert(
+                            header::RETRY_AFTER,
+                            header::HeaderValue::from_static("0"),
+                        );
+                        Ok((head, None))
+                    }
+                } else {
+                    Ok((ResponseHead::new(StatusCode::OK), None))
+                }
+            }))
+            .retry(policy)
+            .finish();
+        (client, attempts)
+    }
+
+    #[test]
+    fn test_delay() {
+        let policy = RetryPolicy::new(3).jitter(false).max_delay(Millis(300));
+        assert_eq!(policy.delay(0), Millis(100));
+        assert_eq!(policy.delay(1), Millis(200));
+        assert_eq!(policy.delay(2), Millis(300));
+        assert_eq!(policy.delay(40), Millis(300));
+
+        let policy = RetryPolicy::new(3);
+        let delay = policy.delay(1);
+        assert!(delay >= Millis(100) && delay <= Millis(200));
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let policy = RetryPolicy::new(3);
+        let mut head = ResponseHead::new(StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(policy.retry_after_delay(&head.headers), None);
+
+        head.headers
+            .insert(header::RETRY_AFTER, header::HeaderValue::from_static("120"));
+        assert_eq!(
+            policy.retry_after_delay(&head.headers),
+            Some(Duration::from_secs(120))
+        );
+
+        head.headers.insert(
+            header::RETRY_AFTER,
+            header::HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(
+            policy.retry_after_delay(&head.headers),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[crate::rt_test]
+    async fn test_retry_connect_error() {
+        let policy = RetryPolicy::new(3).base_delay(Millis(1));
+        let (client, attempts) = test_client(2, StatusCode::OK, policy.clone());
+        let res = client.get("http://localhost/test").send().await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(attempts.get(), 3);
+
+        let (client, attempts) = test_client(5, StatusCode::OK, policy.clone());
+        assert!(client.get("http://localhost/test").send().await.is_err());
+        assert_eq!(attempts.get(), 4);
+
+        // non idempotent method
+        let (client, attempts) = test_client(2, StatusCode::OK, policy);
+        assert!(client.post("http://localhost/test").send().await.is_err());
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[crate::rt_test]
+    async fn test_retry_status() {
+        let policy = RetryPolicy::new(3).base_delay(Millis(1));
+        let (client, attempts) = test_client(2, StatusCode::SERVICE_UNAVAILABLE, policy);
+        let req = client.put("http://localhost/test").freeze().unwrap();
+        let res = req.send_body("body").await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(attempts.get(), 3);
+
+        let policy = RetryPolicy::new(3)
+            .base_delay(Millis(1))
+            .statuses([StatusCode::TOO_MANY_REQUESTS]);
+        let (client, attempts) = test_client(2, StatusCode::SERVICE_UNAVAILABLE, policy);
+        let res = client.get("http://localhost/test").send().await.unwrap();
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(attempts.get(), 1);
+    }
+}
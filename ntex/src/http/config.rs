@@ -48,9 +48,31 @@ pub struct ServiceConfig {
     pub(super) h2config: h2::Config,
     pub(super) headers_read_rate: Option<ReadRate>,
     pub(super) payload_read_rate: Option<ReadRate>,
+    pub(super) h1_limits: ParserLimits,
+    pub(super) pipeline_depth: usize,
     pub(super) timer: DateService,
 }
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+/// Http/1 message parser limits
+pub(super) struct ParserLimits {
+    pub(super) max_headers: usize,
+    pub(super) max_headers_size: usize,
+    pub(super) max_uri_length: usize,
+    pub(super) max_chunk_extension_size: usize,
+}
+
+impl Default for ParserLimits {
+    fn default() -> Self {
+        ParserLimits {
+            max_headers: 96,
+            max_headers_size: 32_768,
+            max_uri_length: 32_768,
+            max_chunk_extension_size: 16_384,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub(super) struct ReadRate {
     pub(super) rate: u16,
@@ -113,6 +135,8 @@ impl ServiceConfig {
                 max_timeout: client_timeout + Seconds(15),
             }),
             payload_read_rate: None,
+            h1_limits: ParserLimits::default(),
+            pipeline_depth: 1,
         }
     }
 
@@ -232,6 +256,61 @@ impl ServiceConfig {
         }
         self
     }
+
+    /// Set max number of request headers for http/1.
+    ///
+    /// Requests with more headers are rejected with `431 Request Header Fields Too Large`
+    /// response.
+    ///
+    /// By default max number of headers is set to 96.
+    pub fn max_headers(&mut self, num: usize) -> &mut Self {
+        self.h1_limits.max_headers = num;
+        self
+    }
+
+    /// Set max size of request head for http/1.
+    ///
+    /// Size includes request line and all request headers. Requests with larger head
+    /// are rejected with `431 Request Header Fields Too Large` response.
+    ///
+    /// By default max size is set to 32Kb.
+    pub fn max_headers_size(&mut self, size: usize) -> &mut Self {
+        self.h1_limits.max_headers_size = size;
+        self
+    }
+
+    /// Set max length of request uri for http/1.
+    ///
+    /// Requests with longer uri are rejected with `414 URI Too Long` response.
+    ///
+    /// By default max length is set to 32Kb.
+    pub fn max_uri_length(&mut self, size: usize) -> &mut Self {
+        self.h1_limits.max_uri_length = size;
+        self
+    }
+
+    /// Set max size of chunk extension for http/1 chunked payloads.
+    ///
+    /// Requests with larger chunk extensions are rejected with `400 Bad Request` response.
+    ///
+    /// By default max size is set to 16Kb.
+    pub fn max_chunk_extension_size(&mut self, size: usize) -> &mut Self {
+        self.h1_limits.max_chunk_extension_size = size;
+        self
+    }
+
+    /// Set max number of pipelined http/1 requests processed concurrently.
+    ///
+    /// Requests that are already received on a connection are dispatched to the
+    /// service while previous request is still in progress. Responses are sent in
+    /// the order of requests. Only `GET`, `HEAD` and `OPTIONS` requests without payload
+    /// are processed concurrently, other requests are processed sequentially.
+    ///
+    /// By default pipeline depth is set to 1, requests are processed one at a time.
+    pub fn pipeline_depth(&mut self, depth: usize) -> &mut Self {
+        self.pipeline_depth = std::cmp::max(depth, 1);
+        self
+    }
 }
 
 bitflags::bitflags! {
@@ -253,6 +332,8 @@ pub(super) struct DispatcherConfig<S, C> {
     pub(super) h2config: h2::Config,
     pub(super) headers_read_rate: Option<ReadRate>,
     pub(super) payload_read_rate: Option<ReadRate>,
+    pub(super) h1_limits: ParserLimits,
+    pub(super) pipeline_depth: usize,
     pub(super) timer: DateService,
 }
 
@@ -265,6 +346,8 @@ impl<S, C> DispatcherConfig<S, C> {
             client_disconnect: cfg.client_disconnect,
             headers_read_rate: cfg.headers_read_rate,
             payload_read_rate: cfg.payload_read_rate,
+            h1_limits: cfg.h1_limits,
+            pipeline_depth: cfg.pipeline_depth,
             h2config: cfg.h2config.clone(),
             timer: cfg.timer.clone(),
             flags: Cell::new(if cfg.ka_enabled {
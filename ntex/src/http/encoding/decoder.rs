@@ -1,6 +1,10 @@
 use std::{future::Future, io, io::Write, pin::Pin, task::Context, task::Poll};
 
+#[cfg(feature = "brotli")]
+use brotli::DecompressorWriter as BrotliDecoder;
 use flate2::write::{GzDecoder, ZlibDecoder};
+#[cfg(feature = "zstd")]
+use zstd::stream::write::Decoder as ZstdDecoder;
 
 use super::Writer;
 use crate::http::error::PayloadError;
@@ -10,6 +14,9 @@ use crate::util::{Bytes, Stream};
 
 const INPLACE: usize = 2049;
 
+#[cfg(feature = "brotli")]
+const BROTLI_BUFFER_SIZE: usize = 8192;
+
 pub struct Decoder<S> {
     decoder: Option<ContentDecoder>,
     stream: S,
@@ -31,6 +38,16 @@ where
             ContentEncoding::Gzip => Some(ContentDecoder::Gzip(Box::new(GzDecoder::new(
                 Writer::new(),
             )))),
+            #[cfg(feature = "brotli")]
+            ContentEncoding::Br => Some(ContentDecoder::Br(Box::new(BrotliDecoder::new(
+                Writer::new(),
+                BROTLI_BUFFER_SIZE,
+            )))),
+            #[cfg(feature = "zstd")]
+            ContentEncoding::Zstd => ZstdDecoder::new(Writer::new())
+                .map(|dec| ContentDecoder::Zstd(Box::new(dec)))
+                .map_err(|err| log::error!("Cannot create zstd decoder: {}", err))
+                .ok(),
             _ => None,
         };
         Decoder {
@@ -131,6 +148,10 @@ where
 enum ContentDecoder {
     Deflate(Box<ZlibDecoder<Writer>>),
     Gzip(Box<GzDecoder<Writer>>),
+    #[cfg(feature = "brotli")]
+    Br(Box<BrotliDecoder<Writer>>),
+    #[cfg(feature = "zstd")]
+    Zstd(Box<ZstdDecoder<'static, Writer>>),
 }
 
 impl ContentDecoder {
@@ -158,6 +179,30 @@ impl ContentDecoder {
                 }
                 Err(e) => Err(e),
             },
+            #[cfg(feature = "brotli")]
+            ContentDecoder::Br(ref mut decoder) => match decoder.close() {
+                Ok(_) => {
+                    let b = decoder.get_mut().take();
+                    if !b.is_empty() {
+                        Ok(Some(b))
+                    } else {
+                        Ok(None)
+                    }
+                }
+                Err(e) => Err(e),
+            },
+            #[cfg(feature = "zstd")]
+            ContentDecoder::Zstd(ref mut decoder) => match decoder.flush() {
+                Ok(_) => {
+                    let b = decoder.get_mut().take();
+                    if !b.is_empty() {
+                        Ok(Some(b))
+                    } else {
+                        Ok(None)
+                    }
+                }
+                Err(e) => Err(e),
+            },
         }
     }
 
@@ -187,6 +232,32 @@ impl ContentDecoder {
                 }
                 Err(e) => Err(e),
             },
+            #[cfg(feature = "brotli")]
+            ContentDecoder::Br(ref mut decoder) => match decoder.write_all(&data) {
+                Ok(_) => {
+                    decoder.flush()?;
+                    let b = decoder.get_mut().take();
+                    if !b.is_empty() {
+                        Ok(Some(b))
+                    } else {
+                        Ok(None)
+                    }
+                }
+                Err(e) => Err(e),
+            },
+            #[cfg(feature = "zstd")]
+            ContentDecoder::Zstd(ref mut decoder) => match decoder.write_all(&data) {
+                Ok(_) => {
+                    decoder.flush()?;
+                    let b = decoder.get_mut().take();
+                    if !b.is_empty() {
+                        Ok(Some(b))
+                    } else {
+                        Ok(None)
+                    }
+                }
+                Err(e) => Err(e),
+            },
         }
     }
 }
@@ -1,7 +1,11 @@
 //! Stream encoder
 use std::{fmt, future::Future, io, io::Write, pin::Pin, task::Context, task::Poll};
 
+#[cfg(feature = "brotli")]
+use brotli::CompressorWriter as BrotliEncoder;
 use flate2::write::{GzEncoder, ZlibEncoder};
+#[cfg(feature = "zstd")]
+use zstd::stream::write::Encoder as ZstdEncoder;
 
 use crate::http::body::{Body, BodySize, MessageBody, ResponseBody};
 use crate::http::header::{ContentEncoding, HeaderValue, CONTENT_ENCODING};
@@ -13,6 +17,16 @@ use super::Writer;
 
 const INPLACE: usize = 1024;
 
+#[cfg(feature = "brotli")]
+const BROTLI_BUFFER_SIZE: usize = 4096;
+#[cfg(feature = "brotli")]
+const BROTLI_QUALITY: u32 = 3;
+#[cfg(feature = "brotli")]
+const BROTLI_LGWIN: u32 = 22;
+
+#[cfg(feature = "zstd")]
+const ZSTD_LEVEL: i32 = 3;
+
 pub struct Encoder<B> {
     eof: bool,
     body: EncoderBody<B>,
@@ -34,8 +48,11 @@ impl<B: MessageBody> Encoder<B> {
                 || encoding == ContentEncoding::Auto);
 
         if !can_encode {
-            body
-        } else {
+            return body;
+        }
+
+        // Modify response body only if encoder is not None
+        if let Some(encoder) = ContentEncoder::encoder(encoding) {
             let body = match body {
                 ResponseBody::Other(b) => match b {
                     Body::None => return ResponseBody::Other(Body::None),
@@ -46,8 +63,6 @@ impl<B: MessageBody> Encoder<B> {
                 ResponseBody::Body(stream) => EncoderBody::Stream(stream),
             };
 
-            // Modify response body only if encoder is not None
-            let encoder = ContentEncoder::encoder(encoding).unwrap();
             update_head(encoding, head);
             head.no_chunking(false);
             ResponseBody::Other(Body::from_message(Encoder {
@@ -56,6 +71,8 @@ impl<B: MessageBody> Encoder<B> {
                 fut: None,
                 encoder: Some(encoder),
             }))
+        } else {
+            body
         }
     }
 }
@@ -189,11 +206,15 @@ fn update_head(encoding: ContentEncoding, head: &mut ResponseHead) {
 enum ContentEncoder {
     Deflate(ZlibEncoder<Writer>),
     Gzip(GzEncoder<Writer>),
+    #[cfg(feature = "brotli")]
+    Br(Box<BrotliEncoder<Writer>>),
+    #[cfg(feature = "zstd")]
+    Zstd(ZstdEncoder<'static, Writer>),
 }
 
 impl ContentEncoder {
     fn can_encode(encoding: ContentEncoding) -> bool {
-        matches!(encoding, ContentEncoding::Deflate | ContentEncoding::Gzip)
+        super::is_supported(encoding)
     }
 
     fn encoder(encoding: ContentEncoding) -> Option<Self> {
@@ -206,6 +227,18 @@ impl ContentEncoder {
                 Writer::new(),
                 flate2::Compression::fast(),
             ))),
+            #[cfg(feature = "brotli")]
+            ContentEncoding::Br => Some(ContentEncoder::Br(Box::new(BrotliEncoder::new(
+                Writer::new(),
+                BROTLI_BUFFER_SIZE,
+                BROTLI_QUALITY,
+                BROTLI_LGWIN,
+            )))),
+            #[cfg(feature = "zstd")]
+            ContentEncoding::Zstd => ZstdEncoder::new(Writer::new(), ZSTD_LEVEL)
+                .map(ContentEncoder::Zstd)
+                .map_err(|err| log::error!("Cannot create zstd encoder: {}", err))
+                .ok(),
             _ => None,
         }
     }
@@ -214,6 +247,10 @@ impl ContentEncoder {
         match *self {
             ContentEncoder::Deflate(ref mut encoder) => encoder.get_mut().take(),
             ContentEncoder::Gzip(ref mut encoder) => encoder.get_mut().take(),
+            #[cfg(feature = "brotli")]
+            ContentEncoder::Br(ref mut encoder) => encoder.get_mut().take(),
+            #[cfg(feature = "zstd")]
+            ContentEncoder::Zstd(ref mut encoder) => encoder.get_mut().take(),
         }
     }
 
@@ -227,6 +264,13 @@ impl ContentEncoder {
                 Ok(writer) => Ok(writer.buf.freeze()),
                 Err(err) => Err(err),
             },
+            #[cfg(feature = "brotli")]
+            ContentEncoder::Br(encoder) => Ok(encoder.into_inner().buf.freeze()),
+            #[cfg(feature = "zstd")]
+            ContentEncoder::Zstd(encoder) => match encoder.finish() {
+                Ok(writer) => Ok(writer.buf.freeze()),
+                Err(err) => Err(err),
+            },
         }
     }
 
@@ -246,6 +290,22 @@ impl ContentEncoder {
                     Err(err)
                 }
             },
+            #[cfg(feature = "brotli")]
+            ContentEncoder::Br(ref mut encoder) => match encoder.write_all(data) {
+                Ok(_) => Ok(()),
+                Err(err) => {
+                    log::trace!("Error decoding br encoding: {}", err);
+                    Err(err)
+                }
+            },
+            #[cfg(feature = "zstd")]
+            ContentEncoder::Zstd(ref mut encoder) => match encoder.write_all(data) {
+                Ok(_) => Ok(()),
+                Err(err) => {
+                    log::trace!("Error decoding zstd encoding: {}", err);
+                    Err(err)
+                }
+            },
         }
     }
 }
@@ -255,6 +315,10 @@ impl fmt::Debug for ContentEncoder {
         match self {
             ContentEncoder::Deflate(_) => write!(f, "ContentEncoder::Deflate"),
             ContentEncoder::Gzip(_) => write!(f, "ContentEncoder::Gzip"),
+            #[cfg(feature = "brotli")]
+            ContentEncoder::Br(_) => write!(f, "ContentEncoder::Br"),
+            #[cfg(feature = "zstd")]
+            ContentEncoder::Zstd(_) => write!(f, "ContentEncoder::Zstd"),
         }
     }
 }
@@ -1,6 +1,7 @@
 //! Content-Encoding support
 use std::io;
 
+use crate::http::header::ContentEncoding;
 use crate::util::{Bytes, BytesMut};
 
 mod decoder;
@@ -9,6 +10,32 @@ mod encoder;
 pub use self::decoder::Decoder;
 pub use self::encoder::Encoder;
 
+/// Value of `Accept-Encoding` header for encodings supported by `Decoder`
+#[cfg(all(feature = "brotli", feature = "zstd"))]
+pub const ACCEPT_ENCODING: &str = "br, zstd, gzip, deflate";
+/// Value of `Accept-Encoding` header for encodings supported by `Decoder`
+#[cfg(all(feature = "brotli", not(feature = "zstd")))]
+pub const ACCEPT_ENCODING: &str = "br, gzip, deflate";
+/// Value of `Accept-Encoding` header for encodings supported by `Decoder`
+#[cfg(all(not(feature = "brotli"), feature = "zstd"))]
+pub const ACCEPT_ENCODING: &str = "zstd, gzip, deflate";
+/// Value of `Accept-Encoding` header for encodings supported by `Decoder`
+#[cfg(all(not(feature = "brotli"), not(feature = "zstd")))]
+pub const ACCEPT_ENCODING: &str = "gzip, deflate";
+
+/// Check if content encoding is supported by current build.
+///
+/// Availability of `br` and `zstd` encodings depends on
+/// `brotli` and `zstd` cargo features.
+pub fn is_supported(encoding: ContentEncoding) -> bool {
+    match encoding {
+        ContentEncoding::Deflate | ContentEncoding::Gzip => true,
+        ContentEncoding::Br => cfg!(feature = "brotli"),
+        ContentEncoding::Zstd => cfg!(feature = "zstd"),
+        ContentEncoding::Identity | ContentEncoding::Auto => false,
+    }
+}
+
 struct Writer {
     buf: BytesMut,
 }
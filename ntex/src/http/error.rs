@@ -90,6 +90,9 @@ pub enum DecodeError {
     /// A message head is too large to be reasonable.
     #[error("Message head is too large")]
     TooLarge(usize),
+    /// Request uri is longer than configured limit
+    #[error("Uri is too long")]
+    UriTooLong,
     /// A message reached EOF, but is not complete.
     #[error("Message is incomplete")]
     Incomplete,
@@ -7,7 +7,7 @@ use crate::http::body::BodySize;
 use crate::http::config::DateService;
 use crate::http::error::{DecodeError, EncodeError, PayloadError};
 use crate::http::message::{ConnectionType, RequestHeadType, ResponseHead};
-use crate::http::{Method, Version};
+use crate::http::{HeaderMap, Method, Version};
 use crate::util::{Bytes, BytesMut};
 
 use super::decoder::{PayloadDecoder, PayloadItem, PayloadType};
@@ -39,6 +39,7 @@ struct ClientCodecInner {
     timer: DateService,
     decoder: decoder::MessageDecoder<ResponseHead>,
     payload: RefCell<Option<PayloadDecoder>>,
+    trailers: RefCell<Option<HeaderMap>>,
     version: Cell<Version>,
     ctype: Cell<ConnectionType>,
 
@@ -68,6 +69,7 @@ impl ClientCodec {
                 timer,
                 decoder: decoder::MessageDecoder::default(),
                 payload: RefCell::new(None),
+                trailers: RefCell::new(None),
                 version: Cell::new(Version::HTTP_11),
                 ctype: Cell::new(ConnectionType::Close),
                 flags: Cell::new(flags),
@@ -97,6 +99,18 @@ impl ClientCodec {
         }
     }
 
+    /// Encode request payload eof with trailers
+    ///
+    /// Trailers are sent only with chunked transfer encoding, fields that
+    /// are not allowed in trailer section are skipped.
+    pub fn encode_trailers(
+        &self,
+        trailers: &HeaderMap,
+        dst: &mut BytesMut,
+    ) -> Result<(), EncodeError> {
+        self.inner.encoder.encode_trailers(trailers, dst)
+    }
+
     /// Convert message codec to a payload codec
     pub fn into_payload_codec(self) -> ClientPayloadCodec {
         ClientPayloadCodec { inner: self.inner }
@@ -109,6 +123,13 @@ impl ClientPayloadCodec {
         self.inner.ctype.get() == ConnectionType::KeepAlive
     }
 
+    /// Take trailers of the last response payload
+    ///
+    /// Trailers are available after payload reached eof.
+    pub fn take_trailers(&self) -> Option<HeaderMap> {
+        self.inner.trailers.borrow_mut().take()
+    }
+
     /// Transform payload codec to a message codec
     pub fn into_message_codec(self) -> ClientCodec {
         ClientCodec { inner: self.inner }
@@ -126,6 +147,7 @@ impl Decoder for ClientCodec {
         );
 
         if let Some((req, payload)) = self.inner.decoder.decode(src)? {
+            self.inner.trailers.borrow_mut().take();
             if let Some(ctype) = req.ctype() {
                 // do not use peer's keep-alive
                 if ctype != ConnectionType::KeepAlive {
@@ -184,6 +206,10 @@ impl Decoder for ClientPayloadCodec {
                 self.inner.payload.borrow_mut().take();
                 Some(None)
             }
+            Some(PayloadItem::Trailers(trailers)) => {
+                *self.inner.trailers.borrow_mut() = Some(trailers);
+                return self.decode(src);
+            }
             None => None,
         })
     }
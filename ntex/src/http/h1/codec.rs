@@ -4,12 +4,12 @@ use bitflags::bitflags;
 
 use crate::codec::{Decoder, Encoder};
 use crate::http::body::BodySize;
-use crate::http::config::DateService;
+use crate::http::config::{DateService, ParserLimits};
 use crate::http::error::{DecodeError, EncodeError};
 use crate::http::message::ConnectionType;
 use crate::http::request::Request;
 use crate::http::response::Response;
-use crate::http::{Method, Version};
+use crate::http::{HeaderMap, Method, Version};
 use crate::util::BytesMut;
 
 use super::{decoder, decoder::PayloadType, encoder, Message};
@@ -23,6 +23,14 @@ bitflags! {
     }
 }
 
+/// State of the last decoded request, used for response encoding
+#[derive(Copy, Clone, Debug)]
+pub(super) struct MessageState {
+    flags: Flags,
+    version: Version,
+    ctype: ConnectionType,
+}
+
 /// HTTP/1 Codec
 pub struct Codec {
     timer: DateService,
@@ -87,6 +95,11 @@ impl Codec {
         }
     }
 
+    /// Set request parser limits
+    pub(super) fn set_limits(&mut self, limits: ParserLimits) {
+        self.decoder = decoder::MessageDecoder::new(limits);
+    }
+
     #[inline]
     /// Check if request is upgrade
     pub fn upgrade(&self) -> bool {
@@ -99,6 +112,18 @@ impl Codec {
         self.ctype.get() == ConnectionType::KeepAlive
     }
 
+    /// Encode payload eof with trailers
+    ///
+    /// Trailers are sent only with chunked transfer encoding, fields that
+    /// are not allowed in trailer section are skipped.
+    pub fn encode_trailers(
+        &self,
+        trailers: &HeaderMap,
+        dst: &mut BytesMut,
+    ) -> Result<(), EncodeError> {
+        self.encoder.encode_trailers(trailers, dst)
+    }
+
     #[inline]
     #[doc(hidden)]
     pub fn set_date_header(&self, dst: &mut BytesMut) {
@@ -111,6 +136,22 @@ impl Codec {
         self.flags.set(flags);
     }
 
+    /// Get state of the last decoded request
+    pub(super) fn message_state(&self) -> MessageState {
+        MessageState {
+            flags: self.flags.get(),
+            version: self.version.get(),
+            ctype: self.ctype.get(),
+        }
+    }
+
+    /// Restore state of previously decoded request
+    pub(super) fn set_message_state(&self, st: MessageState) {
+        self.flags.set(st.flags);
+        self.version.set(st.version);
+        self.ctype.set(st.ctype);
+    }
+
     pub(super) fn reset_upgrade(&self) {
         let mut flags = self.flags.get();
         flags.remove(Flags::STREAM);
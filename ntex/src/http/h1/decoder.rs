@@ -1,21 +1,25 @@
-use std::{cell::Cell, marker::PhantomData, mem, task::Poll};
+use std::{cell::Cell, cmp, marker::PhantomData, mem, task::Poll};
 
 use ntex_http::header::{HeaderName, HeaderValue};
 use ntex_http::{header, Method, StatusCode, Uri, Version};
 
 use crate::codec::Decoder;
+use crate::http::config::ParserLimits;
 use crate::http::error::DecodeError;
 use crate::http::header::HeaderMap;
 use crate::http::message::{ConnectionType, ResponseHead};
 use crate::http::request::Request;
 use crate::util::{Buf, Bytes, BytesMut};
 
+/// Number of headers that could be parsed without heap allocation
 const MAX_HEADERS: usize = 96;
-const MAX_BUFFER_SIZE: usize = 32_768;
 
 #[derive(Debug)]
 /// Incoming messagd decoder
-pub(super) struct MessageDecoder<T: MessageType>(PhantomData<T>);
+pub(super) struct MessageDecoder<T: MessageType> {
+    limits: ParserLimits,
+    _t: PhantomData<T>,
+}
 
 #[derive(Debug, PartialEq, Eq)]
 /// Incoming request type
@@ -25,15 +29,24 @@ pub enum PayloadType {
     Stream(PayloadDecoder),
 }
 
+impl<T: MessageType> MessageDecoder<T> {
+    pub(super) fn new(limits: ParserLimits) -> Self {
+        MessageDecoder {
+            limits,
+            _t: PhantomData,
+        }
+    }
+}
+
 impl<T: MessageType> Default for MessageDecoder<T> {
     fn default() -> Self {
-        MessageDecoder(PhantomData)
+        MessageDecoder::new(ParserLimits::default())
     }
 }
 
 impl<T: MessageType> Clone for MessageDecoder<T> {
     fn clone(&self) -> Self {
-        MessageDecoder(PhantomData)
+        MessageDecoder::new(self.limits)
     }
 }
 
@@ -42,7 +55,7 @@ impl<T: MessageType> Decoder for MessageDecoder<T> {
     type Error = DecodeError;
 
     fn decode(&self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-        T::decode(src)
+        T::decode(src, &self.limits)
     }
 }
 
@@ -53,11 +66,6 @@ pub(super) enum PayloadLength {
     None,
 }
 
-#[allow(clippy::declare_interior_mutable_const)]
-const ZERO: PayloadLength = PayloadLength::Payload(PayloadType::Payload(PayloadDecoder {
-    kind: Cell::new(Kind::Length(0)),
-}));
-
 impl PayloadLength {
     /// Returns true if variant is `None`.
     fn is_none(&self) -> bool {
@@ -66,7 +74,11 @@ impl PayloadLength {
 
     /// Returns true if variant is represents zero-length (not none) payload.
     fn is_zero(&self) -> bool {
-        self == &ZERO
+        if let PayloadLength::Payload(PayloadType::Payload(ref pl)) = self {
+            pl.kind.get() == Kind::Length(0)
+        } else {
+            false
+        }
     }
 }
 
@@ -77,13 +89,17 @@ pub(super) trait MessageType: Sized {
 
     fn headers_mut(&mut self) -> &mut HeaderMap;
 
-    fn decode(src: &mut BytesMut) -> Result<Option<(Self, PayloadType)>, DecodeError>;
+    fn decode(
+        src: &mut BytesMut,
+        limits: &ParserLimits,
+    ) -> Result<Option<(Self, PayloadType)>, DecodeError>;
 
     fn set_headers(
         &mut self,
         slice: &Bytes,
         version: Version,
         raw_headers: &[HeaderIndex],
+        limits: &ParserLimits,
     ) -> Result<PayloadLength, DecodeError> {
         let mut ka = None;
         let mut has_upgrade = false;
@@ -189,7 +205,7 @@ pub(super) trait MessageType: Sized {
         if chunked {
             // Chunked encoding
             Ok(PayloadLength::Payload(PayloadType::Payload(
-                PayloadDecoder::chunked(),
+                PayloadDecoder::chunked(*limits),
             )))
         } else if let Some(len) = content_length {
             // Content-Length
@@ -219,20 +235,35 @@ impl MessageType for Request {
         &mut self.head_mut().headers
     }
 
-    fn decode(src: &mut BytesMut) -> Result<Option<(Self, PayloadType)>, DecodeError> {
-        let mut headers: [mem::MaybeUninit<HeaderIndex>; MAX_HEADERS] = uninit_array();
+    fn decode(
+        src: &mut BytesMut,
+        limits: &ParserLimits,
+    ) -> Result<Option<(Self, PayloadType)>, DecodeError> {
+        let mut indices = HeadersBuf::<HeaderIndex>::new(limits.max_headers);
 
         let (len, method, uri, ver, headers) = {
-            let mut parsed: [mem::MaybeUninit<httparse::Header<'_>>; MAX_HEADERS] =
-                uninit_array();
+            let mut parsed = HeadersBuf::<httparse::Header<'_>>::new(limits.max_headers);
 
             let mut req = httparse::Request::new(&mut []);
 
-            match req.parse_with_uninit_headers(src, &mut parsed)? {
+            match req
+                .parse_with_uninit_headers(src, parsed.as_mut())
+                .map_err(|e| parse_error(e, src))?
+            {
                 httparse::Status::Complete(len) => {
+                    if len > limits.max_headers_size {
+                        log::trace!("Max headers size reached: {}", len);
+                        return Err(DecodeError::TooLarge(len));
+                    }
+                    let path = req.path.unwrap();
+                    if path.len() > limits.max_uri_length {
+                        log::trace!("Max uri length reached: {}", path.len());
+                        return Err(DecodeError::UriTooLong);
+                    }
+
                     let method = Method::from_bytes(req.method.unwrap().as_bytes())
                         .map_err(|_| DecodeError::Method)?;
-                    let uri = Uri::try_from(req.path.unwrap())?;
+                    let uri = Uri::try_from(path)?;
                     let version = if req.version.unwrap() == 1 {
                         Version::HTTP_11
                     } else {
@@ -244,12 +275,17 @@ impl MessageType for Request {
                         method,
                         uri,
                         version,
-                        HeaderIndex::record(src, req.headers, &mut headers),
+                        HeaderIndex::record(src, req.headers, indices.as_mut()),
                     )
                 }
                 httparse::Status::Partial => {
-                    if src.len() >= MAX_BUFFER_SIZE {
-                        log::trace!("MAX_BUFFER_SIZE unprocessed data reached, closing");
+                    let len = partial_uri_length(src);
+                    if len > limits.max_uri_length {
+                        log::trace!("Max uri length reached: {}", len);
+                        return Err(DecodeError::UriTooLong);
+                    }
+                    if src.len() > limits.max_headers_size {
+                        log::trace!("Max headers size reached, closing");
                         return Err(DecodeError::TooLarge(src.len()));
                     }
                     return Ok(None);
@@ -260,7 +296,8 @@ impl MessageType for Request {
         let mut msg = Request::new();
 
         // convert headers
-        let mut length = msg.set_headers(&src.split_to(len).freeze(), ver, headers)?;
+        let mut length =
+            msg.set_headers(&src.split_to(len).freeze(), ver, headers, limits)?;
 
         // disallow HTTP/1.0 POST requests that do not contain a Content-Length headers
         // see https://datatracker.ietf.org/doc/html/rfc1945#section-7.2.2
@@ -316,19 +353,20 @@ impl MessageType for ResponseHead {
         &mut self.headers
     }
 
-    fn decode(src: &mut BytesMut) -> Result<Option<(Self, PayloadType)>, DecodeError> {
-        let mut headers: [mem::MaybeUninit<HeaderIndex>; MAX_HEADERS] = uninit_array();
+    fn decode(
+        src: &mut BytesMut,
+        limits: &ParserLimits,
+    ) -> Result<Option<(Self, PayloadType)>, DecodeError> {
+        let mut indices = HeadersBuf::<HeaderIndex>::new(limits.max_headers);
 
         let (len, ver, status, headers) = {
-            let mut parsed: [mem::MaybeUninit<httparse::Header<'_>>; MAX_HEADERS] =
-                uninit_array();
+            let mut parsed = HeadersBuf::<httparse::Header<'_>>::new(limits.max_headers);
 
             let mut res = httparse::Response::new(&mut []);
-            match httparse::ParserConfig::default().parse_response_with_uninit_headers(
-                &mut res,
-                src,
-                &mut parsed,
-            )? {
+            match httparse::ParserConfig::default()
+                .parse_response_with_uninit_headers(&mut res, src, parsed.as_mut())
+                .map_err(|e| parse_error(e, src))?
+            {
                 httparse::Status::Complete(len) => {
                     let version = if res.version.unwrap() == 1 {
                         Version::HTTP_11
@@ -342,12 +380,12 @@ impl MessageType for ResponseHead {
                         len,
                         version,
                         status,
-                        HeaderIndex::record(src, res.headers, &mut headers),
+                        HeaderIndex::record(src, res.headers, indices.as_mut()),
                     )
                 }
                 httparse::Status::Partial => {
-                    return if src.len() >= MAX_BUFFER_SIZE {
-                        log::error!("MAX_BUFFER_SIZE unprocessed data reached, closing");
+                    return if src.len() > limits.max_headers_size {
+                        log::error!("Max headers size reached, closing");
                         Err(DecodeError::TooLarge(src.len()))
                     } else {
                         Ok(None)
@@ -360,7 +398,8 @@ impl MessageType for ResponseHead {
         msg.version = ver;
 
         // convert headers
-        let mut length = msg.set_headers(&src.split_to(len).freeze(), ver, headers)?;
+        let mut length =
+            msg.set_headers(&src.split_to(len).freeze(), ver, headers, limits)?;
 
         // Remove CL value if 0 now that all headers and HTTP/1.0 special cases are processed.
         // Protects against some request smuggling attacks.
@@ -482,10 +521,70 @@ impl HeaderIndex {
     }
 }
 
+/// Storage for parsed headers
+///
+/// Storage is allocated on stack, unless max number of headers exceeds `MAX_HEADERS`
+struct HeadersBuf<T> {
+    stack: [mem::MaybeUninit<T>; MAX_HEADERS],
+    heap: Vec<mem::MaybeUninit<T>>,
+    len: usize,
+}
+
+impl<T> HeadersBuf<T> {
+    fn new(len: usize) -> Self {
+        let mut heap = Vec::new();
+        if len > MAX_HEADERS {
+            heap.resize_with(len, mem::MaybeUninit::uninit);
+        }
+        HeadersBuf {
+            heap,
+            len,
+            stack: uninit_array(),
+        }
+    }
+
+    fn as_mut(&mut self) -> &mut [mem::MaybeUninit<T>] {
+        if self.len > MAX_HEADERS {
+            &mut self.heap
+        } else {
+            &mut self.stack[..self.len]
+        }
+    }
+}
+
+/// Convert parser error
+///
+/// Too many headers error is reported with size of the message head.
+fn parse_error(err: httparse::Error, src: &[u8]) -> DecodeError {
+    if err == httparse::Error::TooManyHeaders {
+        let len = src
+            .windows(4)
+            .position(|w| w == b"\r\n\r\n")
+            .map(|pos| pos + 4)
+            .unwrap_or(src.len());
+        DecodeError::TooLarge(len)
+    } else {
+        err.into()
+    }
+}
+
+/// Length of the uri of incomplete request line
+fn partial_uri_length(src: &[u8]) -> usize {
+    if let Some(start) = src.iter().position(|b| *b == b' ') {
+        let uri = &src[start + 1..];
+        uri.iter()
+            .position(|b| matches!(*b, b' ' | b'\r' | b'\n'))
+            .unwrap_or(uri.len())
+    } else {
+        0
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// Http payload item
 pub enum PayloadItem {
     Chunk(Bytes),
+    Trailers(HeaderMap),
     Eof,
 }
 
@@ -496,24 +595,28 @@ pub enum PayloadItem {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PayloadDecoder {
     kind: Cell<Kind>,
+    limits: ParserLimits,
 }
 
 impl PayloadDecoder {
     pub(super) fn length(x: u64) -> PayloadDecoder {
         PayloadDecoder {
             kind: Cell::new(Kind::Length(x)),
+            limits: ParserLimits::default(),
         }
     }
 
-    pub(super) fn chunked() -> PayloadDecoder {
+    pub(super) fn chunked(limits: ParserLimits) -> PayloadDecoder {
         PayloadDecoder {
-            kind: Cell::new(Kind::Chunked(ChunkedState::Size, 0)),
+            limits,
+            kind: Cell::new(Kind::Chunked(ChunkedState::Size, 0, 0)),
         }
     }
 
     pub(super) fn eof() -> PayloadDecoder {
         PayloadDecoder {
             kind: Cell::new(Kind::Eof),
+            limits: ParserLimits::default(),
         }
     }
 }
@@ -524,7 +627,9 @@ enum Kind {
     /// integer.
     Length(u64),
     /// A Reader used when Transfer-Encoding is `chunked`.
-    Chunked(ChunkedState, u64),
+    ///
+    /// Contains chunked state, remaining chunk size and size of chunk extension.
+    Chunked(ChunkedState, u64, usize),
     /// A Reader used for responses that don't indicate a length or chunked.
     ///
     /// Note: This should only used for `Response`s. It is illegal for a
@@ -585,11 +690,25 @@ impl Decoder for PayloadDecoder {
                     Ok(Some(PayloadItem::Chunk(buf.freeze())))
                 }
             }
-            Kind::Chunked(ref mut state, ref mut size) => {
+            Kind::Chunked(ref mut state, ref mut size, ref mut ext) => {
                 let result = loop {
+                    // trailer section follows last chunk
+                    if *state == ChunkedState::EndCr
+                        && src.first().is_some_and(|b| *b != b'\r')
+                    {
+                        break match parse_trailers(src, &self.limits) {
+                            Ok(Some(trailers)) => {
+                                *state = ChunkedState::End;
+                                Ok(Some(PayloadItem::Trailers(trailers)))
+                            }
+                            Ok(None) => Ok(None),
+                            Err(e) => Err(e),
+                        };
+                    }
+
                     let mut buf = None;
                     // advances the chunked state
-                    *state = match state.step(src, size, &mut buf) {
+                    *state = match state.step(src, size, ext, &self.limits, &mut buf) {
                         Poll::Pending => break Ok(None),
                         Poll::Ready(Ok(state)) => state,
                         Poll::Ready(Err(e)) => break Err(e),
@@ -638,13 +757,17 @@ impl ChunkedState {
         &self,
         body: &mut BytesMut,
         size: &mut u64,
+        ext: &mut usize,
+        limits: &ParserLimits,
         buf: &mut Option<Bytes>,
     ) -> Poll<Result<ChunkedState, DecodeError>> {
         use self::ChunkedState::*;
         match *self {
             Size => ChunkedState::read_size(body, size),
             SizeLws => ChunkedState::read_size_lws(body),
-            Extension => ChunkedState::read_extension(body),
+            Extension => {
+                ChunkedState::read_extension(body, ext, limits.max_chunk_extension_size)
+            }
             SizeLf => ChunkedState::read_size_lf(body, size),
             Body => ChunkedState::read_body(body, size, buf),
             BodyCr => ChunkedState::read_body_cr(body),
@@ -701,14 +824,28 @@ impl ChunkedState {
             ))),
         }
     }
-    fn read_extension(rdr: &mut BytesMut) -> Poll<Result<ChunkedState, DecodeError>> {
+    fn read_extension(
+        rdr: &mut BytesMut,
+        ext: &mut usize,
+        max_size: usize,
+    ) -> Poll<Result<ChunkedState, DecodeError>> {
         match byte!(rdr) {
-            b'\r' => Poll::Ready(Ok(ChunkedState::SizeLf)),
+            b'\r' => {
+                *ext = 0;
+                Poll::Ready(Ok(ChunkedState::SizeLf))
+            }
+            _ if *ext >= max_size => Poll::Ready(Err(DecodeError::InvalidInput(
+                "Chunk extension is too large",
+            ))),
             // strictly 0x20 (space) should be disallowed but we don't parse quoted strings here
             0x00..=0x08 | 0x0a..=0x1f | 0x7f => Poll::Ready(Err(
                 DecodeError::InvalidInput("Invalid character in chunk extension"),
             )),
-            _ => Poll::Ready(Ok(ChunkedState::Extension)), // no supported extensions
+            _ => {
+                // no supported extensions
+                *ext += 1;
+                Poll::Ready(Ok(ChunkedState::Extension))
+            }
         }
     }
     fn read_size_lf(
@@ -776,6 +913,42 @@ impl ChunkedState {
     }
 }
 
+/// Parse trailer section of chunked payload
+///
+/// Number of trailer fields is limited by `MAX_HEADERS`.
+fn parse_trailers(
+    src: &mut BytesMut,
+    limits: &ParserLimits,
+) -> Result<Option<HeaderMap>, DecodeError> {
+    let mut parsed = [httparse::EMPTY_HEADER; MAX_HEADERS];
+    let max = cmp::min(limits.max_headers, MAX_HEADERS);
+
+    match httparse::parse_headers(&src[..], &mut parsed[..max])
+        .map_err(|e| parse_error(e, src))?
+    {
+        httparse::Status::Complete((len, headers)) => {
+            let mut trailers = HeaderMap::with_capacity(headers.len());
+            for h in headers {
+                let name = HeaderName::from_bytes(h.name.as_bytes())
+                    .map_err(|_| DecodeError::Header)?;
+                let value =
+                    HeaderValue::from_bytes(h.value).map_err(|_| DecodeError::Header)?;
+                trailers.append(name, value);
+            }
+            src.advance(len);
+            Ok(Some(trailers))
+        }
+        httparse::Status::Partial => {
+            if src.len() > limits.max_headers_size {
+                log::trace!("Max trailers size reached, closing");
+                Err(DecodeError::TooLarge(src.len()))
+            } else {
+                Ok(None)
+            }
+        }
+    }
+}
+
 fn uninit_array<T, const LEN: usize>() -> [mem::MaybeUninit<T>; LEN] {
     // SAFETY: An uninitialized `[mem::MaybeUninit<_>; LEN]` is valid.
     unsafe { mem::MaybeUninit::uninit().assume_init() }
@@ -1415,6 +1588,47 @@ mod tests {
         assert!(msg.eof());
     }
 
+    #[test]
+    fn test_parse_chunked_payload_trailers() {
+        let mut buf = BytesMut::from(
+            "GET /test HTTP/1.1\r\n\
+              transfer-encoding: chunked\r\n\r\n",
+        );
+
+        let reader = MessageDecoder::<Request>::default();
+        let (_, pl) = reader.decode(&mut buf).unwrap().unwrap();
+        let pl = pl.unwrap();
+
+        buf.extend(b"4\r\ndata\r\n0\r\ngrpc-status: 0\r\n");
+        let chunk = pl.decode(&mut buf).unwrap().unwrap().chunk();
+        assert_eq!(chunk, Bytes::from_static(b"data"));
+        assert!(pl.decode(&mut buf).unwrap().is_none());
+
+        buf.extend(b"grpc-message: ok\r\n\r\nGET /test2 HTTP/1.1\r\n\r\n");
+        let msg = pl.decode(&mut buf).unwrap().unwrap();
+        let trailers = match msg {
+            PayloadItem::Trailers(trailers) => trailers,
+            _ => panic!(),
+        };
+        assert_eq!(trailers.len(), 2);
+        assert_eq!(trailers.get("grpc-status").unwrap(), "0");
+        assert_eq!(trailers.get("grpc-message").unwrap(), "ok");
+        assert!(pl.decode(&mut buf).unwrap().unwrap().eof());
+
+        let (req, _) = reader.decode(&mut buf).unwrap().unwrap();
+        assert_eq!(req.path(), "/test2");
+
+        let (_, pl) = reader
+            .decode(&mut BytesMut::from(
+                "GET /test HTTP/1.1\r\ntransfer-encoding: chunked\r\n\r\n",
+            ))
+            .unwrap()
+            .unwrap();
+        let pl = pl.unwrap();
+        let mut buf = BytesMut::from("0\r\ninvalid header\r\n\r\n");
+        assert!(pl.decode(&mut buf).is_err());
+    }
+
     #[test]
     fn test_response_http10_read_until_eof() {
         let mut buf = BytesMut::from("HTTP/1.0 200 Ok\r\n\r\ntest data");
@@ -1570,4 +1784,104 @@ mod tests {
         let chunk = pl.decode(&mut buf).unwrap().unwrap();
         assert_eq!(chunk, PayloadItem::Chunk(Bytes::from_static(b"0\r\n")));
     }
+
+    #[test]
+    fn test_parser_limits() {
+        let limits = ParserLimits {
+            max_headers: 2,
+            max_headers_size: 64,
+            max_uri_length: 10,
+            max_chunk_extension_size: 4,
+        };
+        let reader = MessageDecoder::<Request>::new(limits);
+
+        let mut buf = BytesMut::from("GET /test HTTP/1.1\r\na: 1\r\nb: 2\r\n\r\n");
+        assert!(reader.decode(&mut buf).unwrap().is_some());
+
+        let mut buf = BytesMut::from("GET /test HTTP/1.1\r\na: 1\r\nb: 2\r\nc: 3\r\n\r\n");
+        assert!(matches!(
+            reader.decode(&mut buf),
+            Err(DecodeError::TooLarge(40))
+        ));
+
+        // uri length limit is inclusive
+        let mut buf = BytesMut::from("GET /test/long HTTP/1.1\r\n\r\n");
+        assert!(reader.decode(&mut buf).unwrap().is_some());
+
+        let mut buf = BytesMut::from("GET /test/long");
+        assert!(reader.decode(&mut buf).unwrap().is_none());
+
+        let mut buf = BytesMut::from("GET /test/longe HTTP/1.1\r\n\r\n");
+        assert!(matches!(
+            reader.decode(&mut buf),
+            Err(DecodeError::UriTooLong)
+        ));
+
+        let mut buf = BytesMut::from("GET /test/longe");
+        assert!(matches!(
+            reader.decode(&mut buf),
+            Err(DecodeError::UriTooLong)
+        ));
+
+        // head size limit is inclusive
+        let mut buf = BytesMut::from(
+            "GET /test HTTP/1.1\r\na: 1111111111111111111111111111111111111\r\n\r\n",
+        );
+        assert_eq!(buf.len(), 64);
+        assert!(reader.decode(&mut buf).unwrap().is_some());
+
+        let mut buf = BytesMut::from(
+            "GET /test HTTP/1.1\r\na: 11111111111111111111111111111111111111\r\n\r\n",
+        );
+        assert!(matches!(
+            reader.decode(&mut buf),
+            Err(DecodeError::TooLarge(65))
+        ));
+
+        let mut buf =
+            BytesMut::from("GET /test HTTP/1.1\r\na: 1111111111111111111111111111111");
+        assert!(reader.decode(&mut buf).unwrap().is_none());
+        buf.extend(b"111111111111111111111111111111");
+        assert!(matches!(
+            reader.decode(&mut buf),
+            Err(DecodeError::TooLarge(_))
+        ));
+
+        // chunk extension
+        let mut buf =
+            BytesMut::from("GET /test HTTP/1.1\r\ntransfer-encoding: chunked\r\n\r\n");
+        let (_, pl) = reader.decode(&mut buf).unwrap().unwrap();
+        let pl = pl.unwrap();
+        buf.extend(b"4;ext\r\ndata\r\n4;extension\r\ndata\r\n");
+        let chunk = pl.decode(&mut buf).unwrap().unwrap().chunk();
+        assert_eq!(chunk, Bytes::from_static(b"data"));
+        assert!(pl.decode(&mut buf).is_err());
+    }
+
+    #[test]
+    fn test_parse_many_headers() {
+        let limits = ParserLimits {
+            max_headers: 200,
+            ..Default::default()
+        };
+        let mut data = "GET /test HTTP/1.1\r\n".to_string();
+        for idx in 0..150 {
+            data.push_str(&format!("x-header-{}: {}\r\n", idx, idx));
+        }
+        data.push_str("\r\n");
+
+        let mut buf = BytesMut::from(data.as_str());
+        assert!(matches!(
+            MessageDecoder::<Request>::default().decode(&mut buf),
+            Err(DecodeError::TooLarge(_))
+        ));
+
+        let mut buf = BytesMut::from(data.as_str());
+        let (req, _) = MessageDecoder::<Request>::new(limits)
+            .decode(&mut buf)
+            .unwrap()
+            .unwrap();
+        assert_eq!(req.headers().len(), 150);
+        assert_eq!(req.headers().get("x-header-149").unwrap(), "149");
+    }
 }
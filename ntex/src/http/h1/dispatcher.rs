@@ -1,6 +1,6 @@
 //! HTTP/1 protocol dispatcher
 use std::task::{ready, Context, Poll};
-use std::{error, future, io, marker, mem, pin::Pin, rc::Rc};
+use std::{collections::VecDeque, error, future, io, marker, mem, pin::Pin, rc::Rc};
 
 use crate::io::{Decoded, Filter, Io, IoStatusUpdate, RecvError};
 use crate::service::{PipelineCall, Service};
@@ -8,20 +8,25 @@ use crate::{channel::bstream, time::Seconds, util::Either};
 
 use crate::http::body::{BodySize, MessageBody, ResponseBody};
 use crate::http::error::{PayloadError, ResponseError};
-use crate::http::message::CurrentIo;
 use crate::http::{self, config::DispatcherConfig, request::Request, response::Response};
+use crate::http::{message::CurrentIo, Method};
 
+use super::codec::{Codec, MessageState};
 use super::control::{Control, ControlAck, ControlFlags, ControlResult};
 use super::decoder::{PayloadDecoder, PayloadItem, PayloadType};
-use super::{codec::Codec, Message, ProtocolError};
+use super::{Message, Payload, PayloadSender, ProtocolError, TrailersEncoder};
 
 bitflags::bitflags! {
     #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
     pub struct Flags: u8 {
+        /// In-flight request is not safe, pipelining is disabled
+        const SEQUENTIAL           = 0b0000_0001;
         /// Stopping
         const SENDPAYLOAD_AND_STOP = 0b0000_0010;
         /// Complete operation and disconnect
         const DISCONNECT           = 0b0000_0100;
+        /// Response body is complete, waiting for trailers
+        const SEND_TRAILERS        = 0b0000_1000;
         /// Keep-alive is enabled
         const READ_KA_TIMEOUT      = 0b0001_0000;
         /// Read headers timer is enabled
@@ -67,18 +72,62 @@ where
     },
 }
 
-struct DispatcherInner<F, C, S, B> {
+struct DispatcherInner<F, C, S, B>
+where
+    F: 'static,
+    S: Service<Request>,
+    S::Error: 'static,
+    C: Service<Control<F, S::Error>>,
+{
     io: Rc<Io<F>>,
     flags: Flags,
     codec: Codec,
     config: Rc<DispatcherConfig<S, C>>,
-    payload: Option<(PayloadDecoder, bstream::Sender<PayloadError>)>,
+    payload: Option<(PayloadDecoder, PayloadSender)>,
+    pipeline: VecDeque<Pipelined<F, C, S>>,
     read_remains: u32,
     read_consumed: u32,
     read_max_timeout: Seconds,
     _t: marker::PhantomData<(S, B)>,
 }
 
+/// Pipelined request, waits for responses of previous requests
+struct Pipelined<F, C, S>
+where
+    F: 'static,
+    S: Service<Request>,
+    S::Error: 'static,
+    C: Service<Control<F, S::Error>>,
+{
+    state: MessageState,
+    call: PipelinedCall<F, C, S>,
+}
+
+enum PipelinedCall<F, C, S>
+where
+    F: 'static,
+    S: Service<Request>,
+    S::Error: 'static,
+    C: Service<Control<F, S::Error>>,
+{
+    /// Request with payload, dispatched after previous requests are complete
+    Request(Request, PayloadType),
+    Control(PipelineCall<C, Control<F, S::Error>>),
+    Publish(PipelineCall<S, Request>),
+    ControlAck(Result<ControlAck, C::Error>),
+    Response(Result<S::Response, S::Error>),
+}
+
+/// Safe requests without payload could be processed concurrently
+fn is_safe(req: &Request, pl: &PayloadType, upgrade: bool) -> bool {
+    matches!(pl, PayloadType::None)
+        && !upgrade
+        && matches!(
+            req.head().method,
+            Method::GET | Method::HEAD | Method::OPTIONS
+        )
+}
+
 impl<F, S, B, C> Dispatcher<F, S, B, C>
 where
     F: Filter,
@@ -90,7 +139,8 @@ where
 {
     /// Construct new `Dispatcher` instance with outgoing messages stream.
     pub(in crate::http) fn new(io: Io<F>, config: Rc<DispatcherConfig<S, C>>) -> Self {
-        let codec = Codec::new(config.timer.clone(), config.keep_alive_enabled());
+        let mut codec = Codec::new(config.timer.clone(), config.keep_alive_enabled());
+        codec.set_limits(config.h1_limits);
         io.set_disconnect_timeout(config.client_disconnect);
 
         // slow-request timer
@@ -109,6 +159,7 @@ where
                 config,
                 io: Rc::new(io),
                 payload: None,
+                pipeline: VecDeque::new(),
                 read_remains: 0,
                 read_consumed: 0,
                 read_max_timeout: max_timeout,
@@ -144,6 +195,9 @@ where
                     }
                     Poll::Ready(Err(err)) => inner.control(Control::err(err)),
                     Poll::Pending => {
+                        // dispatch pipelined requests
+                        inner.poll_pipeline(cx);
+
                         // state changed because of error.
                         // spawn current publish future to runtime
                         // so it could complete error handling
@@ -160,32 +214,7 @@ where
                 },
                 // handle control service responses
                 State::CallControl { fut } => match Pin::new(fut).poll(cx) {
-                    Poll::Ready(Ok(ControlAck { result, flags })) => {
-                        if flags.contains(ControlFlags::CONTINUE) {
-                            let result = inner.io.with_write_buf(|buf| {
-                                buf.extend_from_slice(b"HTTP/1.1 100 Continue\r\n\r\n")
-                            });
-                            if let Err(err) = result {
-                                *this.st = inner.ctl_peer_gone(Some(err));
-                                continue;
-                            }
-                        }
-                        if flags.contains(ControlFlags::DISCONNECT) {
-                            inner.flags.insert(Flags::DISCONNECT);
-                        }
-
-                        match result {
-                            ControlResult::Publish(req) => inner.publish(req),
-                            ControlResult::Response(res, body) => {
-                                inner.send_response(res, body.into())
-                            }
-                            ControlResult::Expect(req) => {
-                                inner.control(Control::expect(req))
-                            }
-                            ControlResult::Upgrade(req) => inner.ctl_upgrade(req),
-                            ControlResult::Stop => inner.stop(),
-                        }
-                    }
+                    Poll::Ready(Ok(ack)) => inner.control_ack(ack),
                     Poll::Ready(Err(err)) => {
                         log::error!("{}: Control plain error: {}", inner.io.tag(), err);
                         return Poll::Ready(Err(Box::new(err)));
@@ -196,6 +225,18 @@ where
                 State::ReadRequest => {
                     if inner.flags.contains(Flags::SENDPAYLOAD_AND_STOP) {
                         inner.stop()
+                    } else if let Some(item) = inner.pipeline.pop_front() {
+                        match inner.pipelined(item) {
+                            Ok(st) => st,
+                            Err(err) => {
+                                log::error!(
+                                    "{}: Control plain error: {}",
+                                    inner.io.tag(),
+                                    err
+                                );
+                                return Poll::Ready(Err(Box::new(err)));
+                            }
+                        }
                     } else {
                         ready!(inner.poll_read_request(cx))
                     }
@@ -211,6 +252,7 @@ where
                 }
                 // send response body
                 State::SendPayload { body } => {
+                    inner.poll_pipeline(cx);
                     ready!(inner.poll_send_payload(cx, body))
                 }
                 // shutdown io
@@ -265,30 +307,14 @@ where
 
         // decode incoming bytes stream
         let st = match result {
-            Ok((mut req, pl)) => {
+            Ok((req, pl)) => {
                 log::trace!(
                     "{}: Http message is received: {:?} and payload {:?}",
                     self.io.tag(),
                     req,
                     pl
                 );
-                req.head_mut().io = CurrentIo::Ref(self.io.get_ref());
-
-                // configure request payload
-                match pl {
-                    PayloadType::None => (),
-                    PayloadType::Payload(decoder) => {
-                        let (ps, pl) = bstream::channel();
-                        req.replace_payload(http::Payload::H1(pl));
-                        self.payload = Some((decoder, ps));
-                    }
-                    PayloadType::Stream(decoder) => {
-                        let (ps, pl) = bstream::channel();
-                        req.replace_payload(http::Payload::H1(pl));
-                        self.payload = Some((decoder, ps));
-                    }
-                };
-                self.control(Control::new_req(req))
+                self.new_request(req, pl)
             }
             Err(RecvError::WriteBackpressure) => {
                 if let Err(err) = ready!(self.io.poll_flush(cx, false)) {
@@ -329,6 +355,165 @@ where
         Poll::Ready(st)
     }
 
+    fn new_request(&mut self, mut req: Request, pl: PayloadType) -> State<F, C, S, B> {
+        req.head_mut().io = CurrentIo::Ref(self.io.get_ref());
+        self.flags
+            .set(Flags::SEQUENTIAL, !is_safe(&req, &pl, self.codec.upgrade()));
+
+        // configure request payload
+        match pl {
+            PayloadType::None => (),
+            PayloadType::Payload(decoder) => {
+                let (ps, pl) = Payload::create();
+                req.replace_payload(http::Payload::H1(pl));
+                self.payload = Some((decoder, ps));
+            }
+            PayloadType::Stream(decoder) => {
+                let (ps, pl) = Payload::create();
+                req.replace_payload(http::Payload::H1(pl));
+                self.payload = Some((decoder, ps));
+            }
+        };
+        self.control(Control::new_req(req))
+    }
+
+    fn control_ack(&mut self, ack: ControlAck) -> State<F, C, S, B> {
+        let ControlAck { result, flags } = ack;
+
+        if flags.contains(ControlFlags::CONTINUE) {
+            let result = self.io.with_write_buf(|buf| {
+                buf.extend_from_slice(b"HTTP/1.1 100 Continue\r\n\r\n")
+            });
+            if let Err(err) = result {
+                return self.ctl_peer_gone(Some(err));
+            }
+        }
+        if flags.contains(ControlFlags::DISCONNECT) {
+            self.flags.insert(Flags::DISCONNECT);
+        }
+
+        match result {
+            ControlResult::Publish(req) => self.publish(req),
+            ControlResult::Response(res, body) => self.send_response(res, body.into()),
+            ControlResult::Expect(req) => self.control(Control::expect(req)),
+            ControlResult::Upgrade(req) => self.ctl_upgrade(req),
+            ControlResult::Stop => self.stop(),
+        }
+    }
+
+    /// Decode already received requests and drive their service calls
+    fn poll_pipeline(&mut self, cx: &mut Context<'_>) {
+        let current = self.codec.message_state();
+
+        while self.pipeline.len() + 1 < self.config.pipeline_depth
+            && self.payload.is_none()
+            && !self.flags.intersects(
+                Flags::DISCONNECT | Flags::SENDPAYLOAD_AND_STOP | Flags::SEQUENTIAL,
+            )
+        {
+            // non-idempotent requests are processed sequentially
+            if let Some(item) = self.pipeline.back() {
+                if let PipelinedCall::Request(..) = item.call {
+                    break;
+                }
+                self.codec.set_message_state(item.state);
+            }
+            if !self.codec.keepalive() {
+                break;
+            }
+
+            // decode errors are handled by regular request processing
+            let decoded = if let Ok(decoded) = self.io.decode_item(&self.codec) {
+                decoded
+            } else {
+                break;
+            };
+            if decoded.item.is_none() && decoded.remains == 0 {
+                break;
+            }
+
+            // update read timers, partially received request
+            // starts headers read timer
+            let _ = self.update_hdrs_timer(&decoded);
+            let (mut req, pl) = if let Some(item) = decoded.item {
+                item
+            } else {
+                break;
+            };
+            log::trace!(
+                "{}: Pipelined http message is received: {:?}",
+                self.io.tag(),
+                req
+            );
+
+            // only safe requests without payload are dispatched ahead
+            let call = if is_safe(&req, &pl, self.codec.upgrade()) {
+                req.head_mut().io = CurrentIo::Ref(self.io.get_ref());
+                PipelinedCall::Control(
+                    self.config.control.call_nowait(Control::new_req(req)),
+                )
+            } else {
+                PipelinedCall::Request(req, pl)
+            };
+            self.pipeline.push_back(Pipelined {
+                call,
+                state: self.codec.message_state(),
+            });
+        }
+        self.codec.set_message_state(current);
+
+        // poll service calls
+        for item in self.pipeline.iter_mut() {
+            loop {
+                item.call = match item.call {
+                    PipelinedCall::Control(ref mut fut) => match Pin::new(fut).poll(cx) {
+                        Poll::Ready(Ok(ControlAck {
+                            result: ControlResult::Publish(req),
+                            flags,
+                        })) if flags.is_empty() => {
+                            PipelinedCall::Publish(self.config.service.call_nowait(req))
+                        }
+                        Poll::Ready(res) => PipelinedCall::ControlAck(res),
+                        Poll::Pending => break,
+                    },
+                    PipelinedCall::Publish(ref mut fut) => match Pin::new(fut).poll(cx) {
+                        Poll::Ready(res) => PipelinedCall::Response(res),
+                        Poll::Pending => break,
+                    },
+                    _ => break,
+                }
+            }
+        }
+    }
+
+    /// Dispatch next pipelined request
+    fn pipelined(
+        &mut self,
+        item: Pipelined<F, C, S>,
+    ) -> Result<State<F, C, S, B>, C::Error> {
+        // previous response closes connection
+        if !self.codec.keepalive() {
+            self.pipeline.clear();
+            return Ok(self.stop());
+        }
+        self.codec.set_message_state(item.state);
+        if !matches!(item.call, PipelinedCall::Request(..)) {
+            self.flags.remove(Flags::SEQUENTIAL);
+        }
+
+        Ok(match item.call {
+            PipelinedCall::Request(req, pl) => self.new_request(req, pl),
+            PipelinedCall::Control(fut) => State::CallControl { fut },
+            PipelinedCall::Publish(fut) => State::CallPublish { fut },
+            PipelinedCall::ControlAck(res) => self.control_ack(res?),
+            PipelinedCall::Response(Ok(res)) => {
+                let (res, body) = res.into().into_parts();
+                self.send_response(res, body)
+            }
+            PipelinedCall::Response(Err(err)) => self.control(Control::err(err)),
+        })
+    }
+
     fn send_response(
         &mut self,
         msg: Response<()>,
@@ -393,7 +578,11 @@ where
         }
         loop {
             let _ = ready!(self.io.poll_flush(cx, false));
-            let item = ready!(body.poll_next_chunk(cx));
+            let item = if self.flags.contains(Flags::SEND_TRAILERS) {
+                None
+            } else {
+                ready!(body.poll_next_chunk(cx))
+            };
 
             let st = match item {
                 Some(Ok(item)) => {
@@ -404,8 +593,17 @@ where
                     }
                 }
                 None => {
+                    self.flags.insert(Flags::SEND_TRAILERS);
+                    let result = match ready!(body.poll_trailers(cx)) {
+                        Some(trailers) => {
+                            self.io.encode(trailers, &TrailersEncoder(&self.codec))
+                        }
+                        None => self.io.encode(Message::Chunk(None), &self.codec),
+                    };
+                    self.flags.remove(Flags::SEND_TRAILERS);
+
                     log::trace!("{}: Response payload eof {:?}", self.io.tag(), self.flags);
-                    if let Err(err) = self.io.encode(Message::Chunk(None), &self.codec) {
+                    if let Err(err) = result {
                         self.ctl_proto_err(err.into())
                     } else if self.flags.contains(Flags::DISCONNECT) {
                         self.stop()
@@ -440,7 +638,17 @@ where
         } else {
             // check for io changes, could close while waiting for service call
             match ready!(self.io.poll_status_update(cx)) {
-                IoStatusUpdate::KeepAlive => Poll::Pending,
+                IoStatusUpdate::KeepAlive => {
+                    // pipelined request is not received in time,
+                    // close connection after current response
+                    if self.flags.contains(Flags::READ_HDRS_TIMEOUT)
+                        && self.handle_timeout().is_err()
+                    {
+                        log::trace!("{}: Slow pipelined request timeout", self.io.tag());
+                        self.flags.insert(Flags::DISCONNECT);
+                    }
+                    Poll::Pending
+                }
                 IoStatusUpdate::Stop | IoStatusUpdate::PeerGone(_) => {
                     Poll::Ready(self.stop())
                 }
@@ -512,6 +720,9 @@ where
                         Ok(PayloadItem::Chunk(chunk)) => {
                             self.payload.as_mut().unwrap().1.feed_data(chunk);
                         }
+                        Ok(PayloadItem::Trailers(trailers)) => {
+                            self.payload.as_mut().unwrap().1.feed_trailers(trailers);
+                        }
                         Ok(PayloadItem::Eof) => {
                             self.flags.remove(Flags::READ_PL_TIMEOUT);
                             self.payload.as_mut().unwrap().1.feed_eof();
@@ -757,18 +968,19 @@ where
 #[cfg(test)]
 mod tests {
     use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
-    use std::{cell::Cell, future::poll_fn, future::Future, sync::Arc};
+    use std::{cell::Cell, cell::RefCell, future::poll_fn, future::Future, sync::Arc};
 
     use ntex_h2::Config;
     use rand::Rng;
 
     use super::*;
+    use crate::channel::{mpsc, oneshot};
     use crate::http::config::ServiceConfig;
     use crate::http::h1::{ClientCodec, DefaultControlService};
     use crate::http::{body, ResponseHead, StatusCode};
     use crate::io::{self as nio, Base};
     use crate::service::{fn_service, IntoService};
-    use crate::util::{lazy, stream_recv, Bytes, BytesMut};
+    use crate::util::{lazy, stream_recv, Bytes, BytesMut, HashMap};
     use crate::{codec::Decoder, testing::Io, time::sleep, time::Millis};
 
     const BUFFER_SIZE: usize = 32_768;
@@ -987,6 +1199,96 @@ mod tests {
         assert!(client.is_server_dropped());
     }
 
+    #[crate::rt_test]
+    async fn test_pipeline_concurrent() {
+        let (client, server) = Io::create();
+        client.remote_buffer_cap(4096);
+        let mut decoder = ClientCodec::default();
+
+        // service calls report start with number of in-flight calls
+        // and wait for release signal
+        let (started_tx, started) = mpsc::channel();
+        let waiters: Rc<RefCell<HashMap<String, oneshot::Receiver<()>>>> = Rc::default();
+        let inflight = Rc::new(Cell::new(0));
+        let (waiters2, inflight2) = (waiters.clone(), inflight.clone());
+
+        let mut config = ServiceConfig::default();
+        config.pipeline_depth(3);
+        crate::rt::spawn(Dispatcher::<Base, _, _, _>::new(
+            nio::Io::new(server),
+            Rc::new(DispatcherConfig::new(
+                config,
+                fn_service(move |req: Request| {
+                    let path = req.path().to_string();
+                    let rx = waiters2.borrow_mut().remove(&path).unwrap();
+                    let inflight = inflight2.clone();
+                    let _ = started_tx.send((path.clone(), inflight.get()));
+                    inflight.set(inflight.get() + 1);
+                    async move {
+                        let _ = rx.recv().await;
+                        inflight.set(inflight.get() - 1);
+                        let n: u16 = path[1..].parse().unwrap();
+                        Ok::<_, io::Error>(
+                            Response::build(StatusCode::from_u16(200 + n).unwrap())
+                                .finish(),
+                        )
+                    }
+                }),
+                DefaultControlService,
+            )),
+        ));
+        let wait = |path: &str| {
+            let (tx, rx) = oneshot::channel();
+            waiters.borrow_mut().insert(path.to_string(), rx);
+            tx
+        };
+
+        async fn read(client: &Io, decoder: &mut ClientCodec, mut num: usize) -> Vec<u16> {
+            let mut buf = BytesMut::new();
+            let mut statuses = Vec::new();
+            while num > 0 {
+                buf.extend_from_slice(&client.read().await.unwrap());
+                while let Some(item) = decoder.decode(&mut buf).unwrap() {
+                    statuses.push(item.status.as_u16());
+                    num -= 1;
+                }
+            }
+            statuses
+        }
+
+        // safe requests are processed concurrently, later requests complete first
+        let releases = [wait("/1"), wait("/2"), wait("/3")];
+        client
+            .write("GET /1 HTTP/1.1\r\n\r\nGET /2 HTTP/1.1\r\n\r\nGET /3 HTTP/1.1\r\n\r\n");
+        for path in ["/1", "/2", "/3"] {
+            assert_eq!(started.recv().await.unwrap().0, path);
+        }
+        assert_eq!(inflight.get(), 3);
+        for tx in releases.into_iter().rev() {
+            let _ = tx.send(());
+        }
+        assert_eq!(read(&client, &mut decoder, 3).await, vec![201, 202, 203]);
+        assert!(!client.is_server_dropped());
+
+        // non-idempotent requests are processed sequentially,
+        // request body is fully read before next request is received
+        let (rel4, rel5) = (wait("/4"), wait("/5"));
+        client.write("POST /4 HTTP/1.1\r\ncontent-length: 1\r\n\r\nx");
+        assert_eq!(started.recv().await.unwrap(), ("/4".to_string(), 0));
+        client.write("GET /5 HTTP/1.1\r\n\r\n");
+        sleep(Millis(50)).await;
+        let _ = rel4.send(());
+        assert_eq!(read(&client, &mut decoder, 1).await, vec![204]);
+
+        // safe request is not started while non-idempotent is in flight
+        assert_eq!(started.recv().await.unwrap(), ("/5".to_string(), 0));
+        let _ = rel5.send(());
+        assert_eq!(read(&client, &mut decoder, 1).await, vec![205]);
+
+        client.close().await;
+        assert!(client.is_server_dropped());
+    }
+
     #[crate::rt_test]
     /// h1 dispatcher still processes all incoming requests
     /// but it does not write any data to socket
@@ -5,7 +5,7 @@ use crate::http::body::BodySize;
 use crate::http::config::DateService;
 use crate::http::error::EncodeError;
 use crate::http::header::{Value, CONNECTION, CONTENT_LENGTH, DATE, TRANSFER_ENCODING};
-use crate::http::message::{ConnectionType, RequestHeadType};
+use crate::http::message::{ConnectionType, Flags, RequestHeadType};
 use crate::http::{helpers, HeaderMap, Response, StatusCode, Version};
 use crate::util::{BufMut, BytesMut};
 
@@ -246,20 +246,32 @@ impl MessageType for RequestHeadType {
     fn encode_status(&self, dst: &mut BytesMut) -> Result<(), EncodeError> {
         let head = self.as_ref();
         dst.reserve(256 + head.headers.len() * AVERAGE_HEADER_SIZE);
-        write!(
-            helpers::Writer(dst),
-            "{} {} {}",
-            head.method,
-            head.uri.path_and_query().map(|u| u.as_str()).unwrap_or("/"),
-            // only HTTP-0.9/1.1
-            match head.version {
-                Version::HTTP_09 => "HTTP/0.9",
-                Version::HTTP_10 => "HTTP/1.0",
-                Version::HTTP_11 => "HTTP/1.1",
-                _ => return Err(EncodeError::UnsupportedVersion(head.version)),
-            }
-        )
-        .map_err(EncodeError::Fmt)
+        // only HTTP-0.9/1.1
+        let version = match head.version {
+            Version::HTTP_09 => "HTTP/0.9",
+            Version::HTTP_10 => "HTTP/1.0",
+            Version::HTTP_11 => "HTTP/1.1",
+            _ => return Err(EncodeError::UnsupportedVersion(head.version)),
+        };
+        let result = if head.flags.contains(Flags::ABSOLUTE_FORM) {
+            // request to a proxy
+            write!(
+                helpers::Writer(dst),
+                "{} {} {}",
+                head.method,
+                head.uri,
+                version
+            )
+        } else {
+            write!(
+                helpers::Writer(dst),
+                "{} {} {}",
+                head.method,
+                head.uri.path_and_query().map(|u| u.as_str()).unwrap_or("/"),
+                version
+            )
+        };
+        result.map_err(EncodeError::Fmt)
     }
 }
 
@@ -284,6 +296,18 @@ impl<T: MessageType> MessageEncoder<T> {
         result
     }
 
+    /// Encode eof with trailers
+    pub(super) fn encode_trailers(
+        &self,
+        trailers: &HeaderMap,
+        buf: &mut BytesMut,
+    ) -> Result<(), EncodeError> {
+        let mut te = self.te.get();
+        let result = te.encode_trailers(trailers, buf);
+        self.te.set(te);
+        result
+    }
+
     pub(super) fn encode(
         &self,
         dst: &mut BytesMut,
@@ -440,6 +464,39 @@ impl TransferEncoding {
             }
         }
     }
+
+    /// Encode eof with trailers.
+    ///
+    /// Trailers are supported only by chunked transfer encoding,
+    /// for other encodings trailers are ignored. Fields that are not
+    /// allowed in trailer section are skipped.
+    pub(super) fn encode_trailers(
+        &mut self,
+        trailers: &HeaderMap,
+        buf: &mut BytesMut,
+    ) -> Result<(), EncodeError> {
+        match self.kind {
+            TransferEncodingKind::Chunked(false) => {
+                buf.extend_from_slice(b"0\r\n");
+                for (key, value) in trailers {
+                    if helpers::is_forbidden_trailer(key) {
+                        continue;
+                    }
+                    let k = key.as_str().as_bytes();
+                    let v = value.as_bytes();
+                    buf.reserve(k.len() + v.len() + 4);
+                    buf.extend_from_slice(k);
+                    buf.extend_from_slice(b": ");
+                    buf.extend_from_slice(v);
+                    buf.extend_from_slice(b"\r\n");
+                }
+                buf.extend_from_slice(b"\r\n");
+                self.kind = TransferEncodingKind::Chunked(true);
+                Ok(())
+            }
+            _ => self.encode_eof(buf),
+        }
+    }
 }
 
 const DEC_DIGITS_LUT: &[u8] = b"0001020304050607080910111213141516171819\
@@ -590,7 +647,7 @@ mod tests {
     use std::rc::Rc;
 
     use super::*;
-    use crate::http::header::{HeaderValue, AUTHORIZATION};
+    use crate::http::header::{HeaderName, HeaderValue, AUTHORIZATION};
     use crate::http::RequestHead;
     use crate::util::Bytes;
 
@@ -605,6 +662,37 @@ mod tests {
         assert_eq!(bytes.split(), Bytes::from_static(b"4\r\ntest\r\n0\r\n\r\n"));
     }
 
+    #[test]
+    fn test_chunked_trailers() {
+        let mut trailers = HeaderMap::new();
+        trailers.insert(
+            HeaderName::from_static("grpc-status"),
+            HeaderValue::from_static("0"),
+        );
+        trailers.insert(CONTENT_LENGTH, HeaderValue::from_static("4"));
+        trailers.insert(TRANSFER_ENCODING, HeaderValue::from_static("chunked"));
+        trailers.insert(
+            HeaderName::from_static("keep-alive"),
+            HeaderValue::from_static("timeout=5"),
+        );
+
+        let mut bytes = BytesMut::new();
+        let mut enc = TransferEncoding::chunked();
+        assert!(!enc.encode(b"test", &mut bytes).unwrap());
+        enc.encode_trailers(&trailers, &mut bytes).unwrap();
+        assert_eq!(
+            bytes.split(),
+            Bytes::from_static(b"4\r\ntest\r\n0\r\ngrpc-status: 0\r\n\r\n")
+        );
+        enc.encode_trailers(&trailers, &mut bytes).unwrap();
+        assert!(bytes.is_empty());
+
+        let mut enc = TransferEncoding::length(4);
+        assert!(enc.encode(b"test", &mut bytes).unwrap());
+        enc.encode_trailers(&trailers, &mut bytes).unwrap();
+        assert_eq!(bytes.split(), Bytes::from_static(b"test"));
+    }
+
     #[test]
     fn test_extra_headers() {
         let mut bytes = BytesMut::with_capacity(2048);
@@ -638,6 +726,29 @@ mod tests {
         assert!(data.contains("date: date\r\n"));
     }
 
+    #[test]
+    fn test_absolute_form() {
+        let mut bytes = BytesMut::with_capacity(2048);
+
+        let mut head = RequestHead::default();
+        head.uri = "http://example.com/index.html?q=1".parse().unwrap();
+        let mut head = RequestHeadType::Owned(head);
+        head.encode_status(&mut bytes).unwrap();
+        assert_eq!(
+            bytes.split(),
+            Bytes::from_static(b"GET /index.html?q=1 HTTP/1.1")
+        );
+
+        if let RequestHeadType::Owned(ref mut head) = head {
+            head.flags.insert(Flags::ABSOLUTE_FORM);
+        }
+        head.encode_status(&mut bytes).unwrap();
+        assert_eq!(
+            bytes.split(),
+            Bytes::from_static(b"GET http://example.com/index.html?q=1 HTTP/1.1")
+        );
+    }
+
     #[test]
     fn test_write_content_length() {
         let mut bytes = BytesMut::new();
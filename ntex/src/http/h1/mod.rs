@@ -1,4 +1,6 @@
 //! HTTP/1 implementation
+use crate::codec::Encoder;
+use crate::http::{error::EncodeError, header::HeaderMap};
 use crate::util::{Bytes, BytesMut};
 
 mod client;
@@ -7,6 +9,7 @@ mod decoder;
 mod default;
 mod dispatcher;
 mod encoder;
+mod payload;
 mod service;
 
 pub mod control;
@@ -16,12 +19,11 @@ pub use self::codec::Codec;
 pub use self::control::{Control, ControlAck};
 pub use self::decoder::{PayloadDecoder, PayloadItem, PayloadType};
 pub use self::default::DefaultControlService;
+pub use self::payload::{Payload, PayloadSender};
 pub use self::service::{H1Service, H1ServiceHandler};
 
 pub(super) use self::dispatcher::Dispatcher;
-use crate::channel::bstream::Receiver;
-
-pub type Payload = Receiver<super::error::PayloadError>;
+pub(super) use self::payload::Trailers;
 
 #[derive(Debug)]
 /// Codec message
@@ -38,6 +40,27 @@ impl<T> From<T> for Message<T> {
     }
 }
 
+/// Encoder for payload eof with trailers
+pub(crate) struct TrailersEncoder<'a, C>(pub(crate) &'a C);
+
+impl Encoder for TrailersEncoder<'_, Codec> {
+    type Item = HeaderMap;
+    type Error = EncodeError;
+
+    fn encode(&self, item: HeaderMap, dst: &mut BytesMut) -> Result<(), EncodeError> {
+        self.0.encode_trailers(&item, dst)
+    }
+}
+
+impl Encoder for TrailersEncoder<'_, ClientCodec> {
+    type Item = HeaderMap;
+    type Error = EncodeError;
+
+    fn encode(&self, item: HeaderMap, dst: &mut BytesMut) -> Result<(), EncodeError> {
+        self.0.encode_trailers(&item, dst)
+    }
+}
+
 /// Incoming request type
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MessageType {
@@ -83,6 +106,12 @@ pub enum ProtocolError {
 impl super::ResponseError for ProtocolError {
     fn error_response(&self) -> super::Response {
         match self {
+            ProtocolError::Decode(super::error::DecodeError::TooLarge(_)) => {
+                super::Response::RequestHeaderFieldsTooLarge().into()
+            }
+            ProtocolError::Decode(super::error::DecodeError::UriTooLong) => {
+                super::Response::UriTooLong().into()
+            }
             ProtocolError::Decode(_) => super::Response::BadRequest().into(),
             ProtocolError::SlowRequestTimeout | ProtocolError::SlowPayloadTimeout => {
                 super::Response::RequestTimeout().into()
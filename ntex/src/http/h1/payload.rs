@@ -0,0 +1,182 @@
+//! Http/1 payload stream
+use std::{cell::Cell, fmt, future::poll_fn, pin::Pin, rc::Rc, task::Context, task::Poll};
+
+use crate::channel::bstream;
+use crate::http::{error::PayloadError, header::HeaderMap};
+use crate::util::{Bytes, Stream};
+
+type BoxedStream = Pin<Box<dyn Stream<Item = Result<Bytes, PayloadError>>>>;
+
+/// Shared trailers of the payload
+#[derive(Clone, Default)]
+pub(crate) struct Trailers(Rc<Cell<Option<HeaderMap>>>);
+
+impl Trailers {
+    /// Store payload trailers
+    pub(crate) fn set(&self, trailers: HeaderMap) {
+        self.0.set(Some(trailers));
+    }
+
+    fn take(&self) -> Option<HeaderMap> {
+        self.0.take()
+    }
+}
+
+/// Http/1 payload stream
+///
+/// Trailers of chunked payload are available after payload reached eof.
+pub struct Payload {
+    inner: Inner,
+    trailers: Trailers,
+}
+
+enum Inner {
+    Channel(bstream::Receiver<PayloadError>),
+    Stream(BoxedStream),
+}
+
+impl Payload {
+    /// Create payload stream
+    ///
+    /// This method construct two objects responsible for bytes stream
+    /// generation.
+    pub fn create() -> (PayloadSender, Payload) {
+        let (tx, rx) = bstream::channel();
+        let trailers = Trailers::default();
+        (
+            PayloadSender {
+                tx,
+                trailers: trailers.clone(),
+            },
+            Payload {
+                inner: Inner::Channel(rx),
+                trailers,
+            },
+        )
+    }
+
+    /// Create payload from a stream, stream stores trailers to `trailers`
+    pub(crate) fn from_stream<S>(stream: S, trailers: Trailers) -> Self
+    where
+        S: Stream<Item = Result<Bytes, PayloadError>> + 'static,
+    {
+        Payload {
+            inner: Inner::Stream(Box::pin(stream)),
+            trailers,
+        }
+    }
+
+    #[inline]
+    /// Read next available bytes chunk
+    pub async fn read(&mut self) -> Option<Result<Bytes, PayloadError>> {
+        poll_fn(|cx| self.poll_read(cx)).await
+    }
+
+    #[inline]
+    /// Attempt to pull out the next value of this payload
+    pub fn poll_read(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, PayloadError>>> {
+        match self.inner {
+            Inner::Channel(ref rx) => rx.poll_read(cx),
+            Inner::Stream(ref mut stream) => stream.as_mut().poll_next(cx),
+        }
+    }
+
+    /// Take payload trailers
+    ///
+    /// Trailers are available after payload reached eof.
+    pub fn take_trailers(&self) -> Option<HeaderMap> {
+        self.trailers.take()
+    }
+}
+
+impl From<bstream::Receiver<PayloadError>> for Payload {
+    fn from(rx: bstream::Receiver<PayloadError>) -> Self {
+        Payload {
+            inner: Inner::Channel(rx),
+            trailers: Trailers::default(),
+        }
+    }
+}
+
+impl Stream for Payload {
+    type Item = Result<Bytes, PayloadError>;
+
+    #[inline]
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().poll_read(cx)
+    }
+}
+
+impl fmt::Debug for Payload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.inner {
+            Inner::Channel(ref rx) => f.debug_tuple("Payload").field(rx).finish(),
+            Inner::Stream(_) => f.debug_tuple("Payload").field(&"Stream(..)").finish(),
+        }
+    }
+}
+
+/// Sender part of the payload stream
+pub struct PayloadSender {
+    tx: bstream::Sender<PayloadError>,
+    trailers: Trailers,
+}
+
+impl PayloadSender {
+    /// Set stream error
+    pub fn set_error(&self, err: PayloadError) {
+        self.tx.set_error(err);
+    }
+
+    /// Set stream eof
+    pub fn feed_eof(&self) {
+        self.tx.feed_eof();
+    }
+
+    /// Set stream eof with trailers
+    pub fn feed_trailers(&self, trailers: HeaderMap) {
+        self.trailers.set(trailers);
+        self.tx.feed_eof();
+    }
+
+    /// Add chunk to the stream
+    pub fn feed_data(&self, data: Bytes) {
+        self.tx.feed_data(data);
+    }
+
+    /// Check stream readiness
+    pub fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<bstream::Status> {
+        self.tx.poll_ready(cx)
+    }
+}
+
+impl fmt::Debug for PayloadSender {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PayloadSender").field(&self.tx).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[crate::rt_test]
+    async fn test_trailers() {
+        let (tx, mut pl) = Payload::create();
+        tx.feed_data(Bytes::from("data"));
+        let mut trailers = HeaderMap::new();
+        trailers.insert("grpc-status".try_into().unwrap(), "0".try_into().unwrap());
+        tx.feed_trailers(trailers);
+
+        assert_eq!(pl.read().await.unwrap().unwrap(), Bytes::from("data"));
+        assert!(pl.read().await.is_none());
+        let trailers = pl.take_trailers().unwrap();
+        assert_eq!(trailers.get("grpc-status").unwrap(), "0");
+        assert!(pl.take_trailers().is_none());
+        assert!(format!("{:?}", pl).contains("Payload"));
+        assert!(format!("{:?}", tx).contains("PayloadSender"));
+    }
+}
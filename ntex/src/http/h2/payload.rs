@@ -5,8 +5,8 @@ use std::{cell::RefCell, future::poll_fn, pin::Pin, rc::Rc, rc::Weak};
 
 use ntex_h2::{self as h2};
 
-use crate::util::{Bytes, Stream};
-use crate::{http::error::PayloadError, task::LocalWaker};
+use crate::http::{error::PayloadError, header::HeaderMap};
+use crate::{task::LocalWaker, util::Bytes, util::Stream};
 
 bitflags::bitflags! {
     #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
@@ -60,6 +60,13 @@ impl Payload {
     ) -> Poll<Option<Result<Bytes, PayloadError>>> {
         self.inner.borrow_mut().readany(cx)
     }
+
+    /// Take request trailers
+    ///
+    /// Trailers are available after payload reached eof.
+    pub fn take_trailers(&self) -> Option<HeaderMap> {
+        self.inner.borrow_mut().trailers.take()
+    }
 }
 
 impl Drop for Payload {
@@ -108,6 +115,13 @@ impl PayloadSender {
         }
     }
 
+    pub fn feed_trailers(&mut self, trailers: HeaderMap) {
+        if let Some(shared) = self.inner.upgrade() {
+            shared.borrow_mut().feed_trailers(trailers);
+            self.inner = Weak::new();
+        }
+    }
+
     pub fn feed_data(&mut self, data: Bytes, cap: h2::Capacity) {
         if let Some(shared) = self.inner.upgrade() {
             shared.borrow_mut().feed_data(data, cap)
@@ -140,6 +154,7 @@ struct Inner {
     cap: h2::Capacity,
     err: Option<PayloadError>,
     items: VecDeque<Bytes>,
+    trailers: Option<HeaderMap>,
     task: LocalWaker,
     io_task: LocalWaker,
     stream: Option<h2::Stream>,
@@ -153,6 +168,7 @@ impl Inner {
             err: None,
             stream: None,
             items: VecDeque::new(),
+            trailers: None,
             task: LocalWaker::new(),
             io_task: LocalWaker::new(),
         }
@@ -171,6 +187,12 @@ impl Inner {
         self.task.wake()
     }
 
+    fn feed_trailers(&mut self, trailers: HeaderMap) {
+        self.flags.insert(Flags::EOF);
+        self.trailers = Some(trailers);
+        self.task.wake()
+    }
+
     fn feed_data(&mut self, data: Bytes, cap: h2::Capacity) {
         self.cap += cap;
         self.items.push_back(data);
@@ -9,7 +9,8 @@ use crate::http::config::{DispatcherConfig, ServiceConfig};
 use crate::http::error::{DispatchError, H2Error, ResponseError};
 use crate::http::header::{self, HeaderMap, HeaderName, HeaderValue};
 use crate::http::message::{CurrentIo, ResponseHead};
-use crate::http::{DateService, Method, Request, Response, StatusCode, Uri, Version};
+use crate::http::{helpers, DateService, Method, Request, Response, StatusCode};
+use crate::http::{Uri, Version};
 use crate::io::{types, Filter, Io, IoBoxed, IoRef};
 use crate::service::{IntoServiceFactory, Service, ServiceCtx, ServiceFactory};
 use crate::util::{Bytes, BytesMut, HashMap, HashSet};
@@ -397,8 +398,8 @@ where
                         h2::StreamEof::Data(data) => {
                             sender.feed_eof(data);
                         }
-                        h2::StreamEof::Trailers(_) => {
-                            sender.feed_eof(Bytes::new());
+                        h2::StreamEof::Trailers(trailers) => {
+                            sender.feed_trailers(trailers);
                         }
                         h2::StreamEof::Error(err) => sender.set_error(err.into()),
                     }
@@ -470,8 +471,13 @@ where
             loop {
                 match poll_fn(|cx| body.poll_next_chunk(cx)).await {
                     None => {
-                        log::debug!("{:?} closing payload stream", stream.id());
-                        stream.send_payload(Bytes::new(), true).await?;
+                        if let Some(trailers) = poll_fn(|cx| body.poll_trailers(cx)).await {
+                            log::debug!("{:?} sending trailers", stream.id());
+                            stream.send_trailers(helpers::filter_trailers(trailers));
+                        } else {
+                            log::debug!("{:?} closing payload stream", stream.id());
+                            stream.send_payload(Bytes::new(), true).await?;
+                        }
                         break;
                     }
                     Some(Ok(chunk)) => {
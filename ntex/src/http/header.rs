@@ -9,6 +9,7 @@ pub use ntex_http::HeaderMap;
 
 /// Represents supported types of content encodings
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[non_exhaustive]
 pub enum ContentEncoding {
     /// Automatically select encoding based on encoding negotiation
     Auto,
@@ -18,6 +19,8 @@ pub enum ContentEncoding {
     Deflate,
     /// Gzip algorithm
     Gzip,
+    /// A format using the Zstandard algorithm
+    Zstd,
     /// Indicates the identity function (i.e. no compression, nor modification)
     Identity,
 }
@@ -36,6 +39,7 @@ impl ContentEncoding {
             ContentEncoding::Br => "br",
             ContentEncoding::Gzip => "gzip",
             ContentEncoding::Deflate => "deflate",
+            ContentEncoding::Zstd => "zstd",
             ContentEncoding::Identity | ContentEncoding::Auto => "identity",
         }
     }
@@ -45,6 +49,7 @@ impl ContentEncoding {
     pub fn quality(self) -> f64 {
         match self {
             ContentEncoding::Br => 1.1,
+            ContentEncoding::Zstd => 1.05,
             ContentEncoding::Gzip => 1.0,
             ContentEncoding::Deflate => 0.9,
             ContentEncoding::Identity | ContentEncoding::Auto => 0.1,
@@ -62,6 +67,8 @@ impl<'a> From<&'a str> for ContentEncoding {
             ContentEncoding::Gzip
         } else if s.eq_ignore_ascii_case("deflate") {
             ContentEncoding::Deflate
+        } else if s.eq_ignore_ascii_case("zstd") {
+            ContentEncoding::Zstd
         } else {
             ContentEncoding::Identity
         }
@@ -75,6 +82,9 @@ mod tests {
     #[test]
     fn encoding() {
         assert!(ContentEncoding::Br.is_compressed());
+        assert!(ContentEncoding::Zstd.is_compressed());
+        assert_eq!(ContentEncoding::from(" zstd "), ContentEncoding::Zstd);
+        assert_eq!(ContentEncoding::Zstd.as_str(), "zstd");
         assert!(!ContentEncoding::Identity.is_compressed());
         assert!(!ContentEncoding::Auto.is_compressed());
         assert_eq!(format!("{:?}", ContentEncoding::Identity), "Identity");
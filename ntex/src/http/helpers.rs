@@ -2,6 +2,7 @@ use std::io;
 
 use percent_encoding::{AsciiSet, CONTROLS};
 
+use crate::http::header::{self, HeaderMap, HeaderName};
 use crate::util::BytesMut;
 
 pub(crate) struct Writer<'a>(pub(crate) &'a mut BytesMut);
@@ -35,3 +36,64 @@ pub(crate) const USERINFO: &AsciiSet = &PATH
     .add(b']')
     .add(b'^')
     .add(b'|');
+
+/// Fields that must not be sent in trailer section, rfc7230 4.1.2
+///
+/// Framing, routing, request modifiers, authentication, response control
+/// and payload processing fields.
+static FORBIDDEN_TRAILERS: [HeaderName; 33] = [
+    header::AGE,
+    header::AUTHORIZATION,
+    header::CACHE_CONTROL,
+    header::CONNECTION,
+    header::CONTENT_ENCODING,
+    header::CONTENT_LENGTH,
+    header::CONTENT_RANGE,
+    header::CONTENT_TYPE,
+    header::COOKIE,
+    header::DATE,
+    header::EXPECT,
+    header::EXPIRES,
+    header::HOST,
+    header::IF_MATCH,
+    header::IF_MODIFIED_SINCE,
+    header::IF_NONE_MATCH,
+    header::IF_RANGE,
+    header::IF_UNMODIFIED_SINCE,
+    header::LOCATION,
+    header::MAX_FORWARDS,
+    header::PRAGMA,
+    header::PROXY_AUTHENTICATE,
+    header::PROXY_AUTHORIZATION,
+    header::RANGE,
+    header::RETRY_AFTER,
+    header::SET_COOKIE,
+    header::TE,
+    header::TRAILER,
+    header::TRANSFER_ENCODING,
+    header::UPGRADE,
+    header::VARY,
+    header::WARNING,
+    header::WWW_AUTHENTICATE,
+];
+
+/// Check if header field is not allowed in trailer section
+pub(crate) fn is_forbidden_trailer(name: &HeaderName) -> bool {
+    FORBIDDEN_TRAILERS.contains(name)
+        || name.as_str() == "keep-alive"
+        || name.as_str() == "proxy-connection"
+}
+
+/// Remove fields that are not allowed in trailer section
+pub(crate) fn filter_trailers(mut trailers: HeaderMap) -> HeaderMap {
+    let forbidden: Vec<_> = trailers
+        .keys()
+        .filter(|name| is_forbidden_trailer(name))
+        .cloned()
+        .collect();
+    for name in forbidden {
+        log::debug!("Dropping forbidden trailer field: {:?}", name);
+        trailers.remove(name);
+    }
+    trailers
+}
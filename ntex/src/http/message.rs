@@ -25,6 +25,7 @@ bitflags! {
         const UPGRADE     = 0b0000_0100;
         const EXPECT      = 0b0000_1000;
         const NO_CHUNKING = 0b0001_0000;
+        const ABSOLUTE_FORM = 0b0010_0000;
     }
 }
 
@@ -236,6 +237,32 @@ impl RequestHeadType {
             RequestHeadType::Rc(_, headers) => headers.as_ref(),
         }
     }
+
+    /// Convert to owned request head
+    ///
+    /// Extra headers override request's headers with the same name.
+    pub(crate) fn into_owned(self) -> RequestHead {
+        match self {
+            RequestHeadType::Owned(head) => head,
+            RequestHeadType::Rc(rc, extra_headers) => {
+                let mut head = RequestHead::default();
+                head.uri = rc.uri.clone();
+                head.method = rc.method.clone();
+                head.version = rc.version;
+                head.flags = rc.flags;
+                head.headers = rc.headers.clone();
+                if let Some(extra) = extra_headers {
+                    for key in extra.keys() {
+                        head.headers.remove(key);
+                    }
+                    for (key, value) in extra.iter() {
+                        head.headers.append(key.clone(), value.clone());
+                    }
+                }
+                head
+            }
+        }
+    }
 }
 
 impl AsRef<RequestHead> for RequestHeadType {
@@ -17,6 +17,7 @@ pub mod error;
 pub mod h1;
 pub mod h2;
 pub mod header;
+pub mod range;
 pub mod test;
 
 pub(crate) use self::message::Message;
@@ -1,7 +1,7 @@
 use std::{fmt, future::poll_fn, mem, pin::Pin, task::Context, task::Poll};
 
-use super::{error::PayloadError, h1, h2};
-use crate::util::{Bytes, Stream};
+use super::{error::PayloadError, h1, h2, header::HeaderMap};
+use crate::{channel::bstream, util::Bytes, util::Stream};
 
 /// Type represent boxed payload
 pub type PayloadStream = Pin<Box<dyn Stream<Item = Result<Bytes, PayloadError>>>>;
@@ -22,6 +22,12 @@ impl From<h1::Payload> for Payload {
     }
 }
 
+impl From<bstream::Receiver<PayloadError>> for Payload {
+    fn from(v: bstream::Receiver<PayloadError>) -> Self {
+        Payload::H1(v.into())
+    }
+}
+
 impl From<h2::Payload> for Payload {
     fn from(v: h2::Payload) -> Self {
         Payload::H2(v)
@@ -80,6 +86,18 @@ impl Payload {
             Payload::Stream(ref mut pl) => Pin::new(pl).poll_next(cx),
         }
     }
+
+    /// Take payload trailers
+    ///
+    /// Trailers are available after payload reached eof,
+    /// streaming payloads do not support trailers.
+    pub fn take_trailers(&self) -> Option<HeaderMap> {
+        match self {
+            Payload::H1(ref pl) => pl.take_trailers(),
+            Payload::H2(ref pl) => pl.take_trailers(),
+            Payload::None | Payload::Stream(_) => None,
+        }
+    }
 }
 
 impl Stream for Payload {
@@ -99,13 +117,34 @@ mod tests {
     fn payload_debug() {
         assert!(format!("{:?}", Payload::None).contains("Payload::None"));
         assert!(
-            format!("{:?}", Payload::H1(crate::channel::bstream::channel().1))
-                .contains("Payload::H1")
+            format!("{:?}", Payload::H1(h1::Payload::create().1)).contains("Payload::H1")
+        );
+        assert!(
+            format!("{:?}", Payload::Stream(Box::pin(bstream::channel().1)))
+                .contains("Payload::Stream")
+        );
+    }
+
+    #[crate::rt_test]
+    async fn payload_trailers() {
+        let (tx, rx) = h1::Payload::create();
+        let mut pl = Payload::H1(rx);
+        assert!(pl.take_trailers().is_none());
+
+        let mut trailers = HeaderMap::new();
+        trailers.insert(
+            crate::http::header::HeaderName::from_static("grpc-status"),
+            crate::http::header::HeaderValue::from_static("0"),
+        );
+        tx.feed_data(Bytes::from_static(b"data"));
+        tx.feed_trailers(trailers);
+
+        assert_eq!(
+            pl.recv().await.unwrap().unwrap(),
+            Bytes::from_static(b"data")
         );
-        assert!(format!(
-            "{:?}",
-            Payload::Stream(Box::pin(crate::channel::bstream::channel().1))
-        )
-        .contains("Payload::Stream"));
+        assert!(pl.recv().await.is_none());
+        assert_eq!(pl.take_trailers().unwrap().get("grpc-status").unwrap(), "0");
+        assert!(Payload::None.take_trailers().is_none());
     }
 }
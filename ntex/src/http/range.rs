@@ -0,0 +1,553 @@
+//! Http range requests support, RFC 9110 section 14
+use std::collections::VecDeque;
+use std::{cmp, error::Error, fmt, fmt::Write, io, task::Context, task::Poll};
+
+use nanorand::{Rng, WyRand};
+
+use crate::http::body::{Body, BodySize, MessageBody};
+use crate::http::header::{self, HeaderMap, HeaderValue, Range, RangeSpec};
+use crate::http::{Method, RequestHead, Response, StatusCode};
+use crate::util::Bytes;
+
+/// Byte range of the representation
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ByteRange {
+    /// First byte position
+    pub start: u64,
+    /// Number of bytes in range
+    pub length: u64,
+}
+
+impl ByteRange {
+    /// Last byte position, inclusive
+    pub fn end(&self) -> u64 {
+        self.start + self.length - 1
+    }
+
+    /// `Content-Range` header value for this range
+    pub fn content_range(&self, size: u64) -> String {
+        format!("bytes {}-{}/{}", self.start, self.end(), size)
+    }
+}
+
+/// Errors which can occur during `Range` header processing
+#[derive(thiserror::Error, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RangeError {
+    /// Range header is malformed
+    #[error("Range header is malformed")]
+    Invalid,
+    /// None of the ranges overlap representation
+    #[error("Range is not satisfiable")]
+    Unsatisfiable,
+}
+
+/// Parse `Range` header value for representation of `size` bytes.
+///
+/// Ranges are sorted, overlapping and adjacent ranges are coalesced.
+pub fn parse_range(header: &str, size: u64) -> Result<Vec<ByteRange>, RangeError> {
+    let Range(specs) = header.parse().map_err(|_| RangeError::Invalid)?;
+
+    let mut ranges = Vec::with_capacity(specs.len());
+    for spec in specs {
+        let range = match spec {
+            RangeSpec::Last(suffix) => {
+                // suffix range, last N bytes
+                if suffix == 0 || size == 0 {
+                    continue;
+                }
+                let length = cmp::min(suffix, size);
+                ByteRange {
+                    start: size - length,
+                    length,
+                }
+            }
+            RangeSpec::From(start) | RangeSpec::FromTo(start, _) if start >= size => {
+                continue
+            }
+            RangeSpec::From(start) => ByteRange {
+                start,
+                length: size - start,
+            },
+            RangeSpec::FromTo(start, end) => ByteRange {
+                start,
+                length: cmp::min(end, size - 1) - start + 1,
+            },
+        };
+        ranges.push(range);
+    }
+
+    if ranges.is_empty() {
+        return Err(RangeError::Unsatisfiable);
+    }
+
+    // coalesce overlapping ranges
+    ranges.sort_by_key(|r| r.start);
+    let mut result: Vec<ByteRange> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        if let Some(prev) = result.last_mut() {
+            if range.start <= prev.start + prev.length {
+                let end = cmp::max(prev.start + prev.length, range.start + range.length);
+                prev.length = end - prev.start;
+                continue;
+            }
+        }
+        result.push(range);
+    }
+    Ok(result)
+}
+
+/// Check `Range` and `If-Range` headers of the request.
+///
+/// Returns `Ok(None)` if full representation must be sent.
+pub fn requested_ranges(
+    req: &RequestHead,
+    size: u64,
+    etag: Option<&str>,
+    last_modified: Option<&str>,
+) -> Result<Option<Vec<ByteRange>>, RangeError> {
+    if req.method != Method::GET {
+        return Ok(None);
+    }
+    let range = match req.headers.get(header::RANGE).map(|v| v.to_str()) {
+        Some(Ok(range)) => range,
+        _ => return Ok(None),
+    };
+
+    if let Some(if_range) = req.headers.get(header::IF_RANGE) {
+        let if_range = if_range.to_str().unwrap_or_default().trim();
+        let matched = if if_range.starts_with('"') {
+            // strong comparison of entity tags
+            etag.map(|etag| etag == if_range).unwrap_or(false)
+        } else if if_range.starts_with("W/") {
+            false
+        } else {
+            last_modified.map(|lm| lm == if_range).unwrap_or(false)
+        };
+        if !matched {
+            return Ok(None);
+        }
+    }
+
+    match parse_range(range, size) {
+        Ok(ranges) => Ok(Some(ranges)),
+        // malformed header must be ignored
+        Err(RangeError::Invalid) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Convert full `200 OK` response to partial content response.
+///
+/// Response body must have known size. Single range is sent as is,
+/// multiple ranges are sent as `multipart/byteranges` body.
+/// Unsatisfiable range produces `416 Range Not Satisfiable` response.
+pub fn ranged_response(req: &RequestHead, mut resp: Response) -> Response {
+    if resp.status() != StatusCode::OK {
+        return resp;
+    }
+    let size = match resp.body().size() {
+        BodySize::Sized(size) => size,
+        _ => return resp,
+    };
+
+    let result = {
+        let headers = resp.headers();
+        let etag = headers.get(header::ETAG).and_then(|v| v.to_str().ok());
+        let lm = headers
+            .get(header::LAST_MODIFIED)
+            .and_then(|v| v.to_str().ok());
+        requested_ranges(req, size, etag, lm)
+    };
+    resp.headers_mut()
+        .insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+
+    match result {
+        Ok(None) => resp,
+        Ok(Some(ranges)) => {
+            let body = Body::from(resp.take_body());
+            partial_content(resp, body, size, ranges)
+        }
+        Err(_) => unsatisfiable(resp, size),
+    }
+}
+
+/// Build `206 Partial Content` response, `body` is full representation
+pub(crate) fn partial_content(
+    mut resp: Response,
+    body: Body,
+    size: u64,
+    ranges: Vec<ByteRange>,
+) -> Response {
+    *resp.status_mut() = StatusCode::PARTIAL_CONTENT;
+
+    if ranges.len() == 1 {
+        let range = ranges[0];
+        if let Ok(value) = HeaderValue::try_from(range.content_range(size)) {
+            resp.headers_mut().insert(header::CONTENT_RANGE, value);
+        }
+        let body = match body {
+            Body::Bytes(b) => {
+                let start = range.start as usize;
+                Body::Bytes(b.slice(start..start + range.length as usize))
+            }
+            body => Body::from_message(RangeBody::new(body, &ranges, size, None)),
+        };
+        return resp.set_body(body);
+    }
+
+    multipart_content(resp, body, size, &ranges, false)
+}
+
+/// Build `206 Partial Content` response with `multipart/byteranges` body,
+/// `body` contains only selected ranges in order
+pub(crate) fn multipart_ranges(
+    resp: Response,
+    body: Body,
+    size: u64,
+    ranges: &[ByteRange],
+) -> Response {
+    multipart_content(resp, body, size, ranges, true)
+}
+
+fn multipart_content(
+    mut resp: Response,
+    body: Body,
+    size: u64,
+    ranges: &[ByteRange],
+    packed: bool,
+) -> Response {
+    *resp.status_mut() = StatusCode::PARTIAL_CONTENT;
+
+    let boundary = boundary();
+    let content_type = resp
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string());
+    let body = RangeBody::create(
+        body,
+        ranges,
+        size,
+        Some((boundary.as_str(), content_type.as_deref())),
+        packed,
+    );
+    if let Ok(value) =
+        HeaderValue::try_from(format!("multipart/byteranges; boundary={}", boundary))
+    {
+        resp.headers_mut().insert(header::CONTENT_TYPE, value);
+    }
+    resp.set_body(Body::from_message(body))
+}
+
+/// Build `416 Range Not Satisfiable` response
+pub(crate) fn unsatisfiable(mut resp: Response, size: u64) -> Response {
+    *resp.status_mut() = StatusCode::RANGE_NOT_SATISFIABLE;
+    if let Ok(value) = HeaderValue::try_from(format!("bytes */{}", size)) {
+        resp.headers_mut().insert(header::CONTENT_RANGE, value);
+    }
+    resp.headers_mut().remove(header::CONTENT_TYPE);
+    resp.set_body(Body::Empty)
+}
+
+fn boundary() -> String {
+    let mut rng = WyRand::new();
+    let mut boundary = String::with_capacity(32);
+    for _ in 0..4 {
+        let _ = write!(boundary, "{:016x}", rng.generate::<u64>());
+    }
+    boundary
+}
+
+struct Part {
+    start: u64,
+    end: u64,
+    header: Option<Bytes>,
+}
+
+/// Message body that contains selected ranges of the inner body.
+///
+/// Inner body is read once, ranges must be sorted and must not overlap.
+/// For multiple ranges, `multipart/byteranges` body is generated.
+pub struct RangeBody {
+    body: Body,
+    pos: u64,
+    size: u64,
+    buf: Option<Bytes>,
+    parts: VecDeque<Part>,
+    trailer: Option<Bytes>,
+}
+
+impl RangeBody {
+    /// Create range body.
+    ///
+    /// `multipart` contains boundary and content type of parts.
+    pub fn new(
+        body: Body,
+        ranges: &[ByteRange],
+        total: u64,
+        multipart: Option<(&str, Option<&str>)>,
+    ) -> Self {
+        RangeBody::create(body, ranges, total, multipart, false)
+    }
+
+    /// Create range body, if `packed` is set inner body contains
+    /// only selected ranges
+    fn create(
+        body: Body,
+        ranges: &[ByteRange],
+        total: u64,
+        multipart: Option<(&str, Option<&str>)>,
+        packed: bool,
+    ) -> Self {
+        let mut size = 0;
+        let mut offset = 0;
+        let mut parts = VecDeque::with_capacity(ranges.len());
+        for (idx, range) in ranges.iter().enumerate() {
+            let header = multipart.map(|(boundary, ctype)| {
+                let mut h = String::new();
+                if idx != 0 {
+                    h.push_str("\r\n");
+                }
+                let _ = write!(h, "--{}\r\n", boundary);
+                if let Some(ctype) = ctype {
+                    let _ = write!(h, "Content-Type: {}\r\n", ctype);
+                }
+                let _ = write!(h, "Content-Range: {}\r\n\r\n", range.content_range(total));
+                Bytes::from(h)
+            });
+            size += range.length + header.as_ref().map(|h| h.len() as u64).unwrap_or(0);
+
+            // position of the range in inner body
+            let start = if packed { offset } else { range.start };
+            offset += range.length;
+            parts.push_back(Part {
+                start,
+                end: start + range.length,
+                header,
+            });
+        }
+        let trailer =
+            multipart.map(|(boundary, _)| Bytes::from(format!("\r\n--{}--\r\n", boundary)));
+        size += trailer.as_ref().map(|t| t.len() as u64).unwrap_or(0);
+
+        RangeBody {
+            body,
+            size,
+            parts,
+            trailer,
+            pos: 0,
+            buf: None,
+        }
+    }
+}
+
+impl fmt::Debug for RangeBody {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RangeBody")
+            .field("size", &self.size)
+            .field("pos", &self.pos)
+            .field("parts", &self.parts.len())
+            .finish()
+    }
+}
+
+impl MessageBody for RangeBody {
+    fn size(&self) -> BodySize {
+        BodySize::Sized(self.size)
+    }
+
+    fn poll_next_chunk(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Box<dyn Error>>>> {
+        loop {
+            let part = if let Some(part) = self.parts.front_mut() {
+                part
+            } else {
+                return Poll::Ready(self.trailer.take().map(Ok));
+            };
+            if let Some(header) = part.header.take() {
+                return Poll::Ready(Some(Ok(header)));
+            }
+
+            let chunk = if let Some(chunk) = self.buf.take() {
+                chunk
+            } else {
+                match self.body.poll_next_chunk(cx) {
+                    Poll::Ready(Some(Ok(chunk))) => chunk,
+                    Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(e))),
+                    Poll::Ready(None) => {
+                        return Poll::Ready(Some(Err(Box::new(io::Error::new(
+                            io::ErrorKind::UnexpectedEof,
+                            "Body is shorter than expected",
+                        )))))
+                    }
+                    Poll::Pending => return Poll::Pending,
+                }
+            };
+
+            let chunk_start = self.pos;
+            let chunk_end = chunk_start + chunk.len() as u64;
+            if chunk_end <= part.start {
+                // chunk is before current range
+                self.pos = chunk_end;
+                continue;
+            }
+
+            let from = (part.start - chunk_start) as usize;
+            let to = (cmp::min(chunk_end, part.end) - chunk_start) as usize;
+            if to < chunk.len() {
+                self.buf = Some(chunk.slice(to..));
+            }
+            self.pos = chunk_start + to as u64;
+            part.start = self.pos;
+            if part.start >= part.end {
+                self.parts.pop_front();
+            }
+            return Poll::Ready(Some(Ok(chunk.slice(from..to))));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::poll_fn;
+
+    use super::*;
+    use crate::http::test::TestRequest;
+    use crate::util::BytesMut;
+
+    fn range(start: u64, length: u64) -> ByteRange {
+        ByteRange { start, length }
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("bytes=0-499", 1000), Ok(vec![range(0, 500)]));
+        assert_eq!(parse_range("bytes=500-", 1000), Ok(vec![range(500, 500)]));
+        assert_eq!(parse_range("bytes=-200", 1000), Ok(vec![range(800, 200)]));
+        assert_eq!(parse_range("bytes=-2000", 1000), Ok(vec![range(0, 1000)]));
+        assert_eq!(
+            parse_range("bytes=900-2000", 1000),
+            Ok(vec![range(900, 100)])
+        );
+        assert_eq!(
+            parse_range("bytes=0-1, 5-9", 1000),
+            Ok(vec![range(0, 2), range(5, 5)])
+        );
+        assert_eq!(parse_range("bytes=5-9,0-6", 1000), Ok(vec![range(0, 10)]));
+        assert_eq!(
+            parse_range("bytes=1000-", 1000),
+            Err(RangeError::Unsatisfiable)
+        );
+        assert_eq!(
+            parse_range("bytes=-0", 1000),
+            Err(RangeError::Unsatisfiable)
+        );
+        assert_eq!(parse_range("bytes=0-1", 0), Err(RangeError::Unsatisfiable));
+        assert_eq!(parse_range("bytes=5-1", 1000), Err(RangeError::Invalid));
+        assert_eq!(parse_range("items=0-1", 1000), Err(RangeError::Invalid));
+        assert_eq!(parse_range("bytes=a-b", 1000), Err(RangeError::Invalid));
+        assert_eq!(parse_range("bytes=", 1000), Err(RangeError::Invalid));
+    }
+
+    async fn read_body(mut resp: Response) -> Bytes {
+        let mut body = resp.take_body();
+        let mut buf = BytesMut::new();
+        while let Some(chunk) = poll_fn(|cx| body.poll_next_chunk(cx)).await {
+            buf.extend_from_slice(&chunk.unwrap());
+        }
+        buf.freeze()
+    }
+
+    #[crate::rt_test]
+    async fn test_ranged_response() {
+        let req = TestRequest::default()
+            .header(header::RANGE, "bytes=2-5")
+            .finish();
+        let resp = ranged_response(req.head(), Response::Ok().body("0123456789"));
+        assert_eq!(resp.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_RANGE).unwrap(),
+            "bytes 2-5/10"
+        );
+        assert_eq!(read_body(resp).await, Bytes::from_static(b"2345"));
+
+        let req = TestRequest::default()
+            .header(header::RANGE, "bytes=20-")
+            .finish();
+        let resp = ranged_response(req.head(), Response::Ok().body("0123456789"));
+        assert_eq!(resp.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_RANGE).unwrap(),
+            "bytes */10"
+        );
+
+        let req = TestRequest::default()
+            .header(header::RANGE, "bytes=0-1")
+            .header(header::IF_RANGE, "\"other\"")
+            .finish();
+        let resp = ranged_response(
+            req.head(),
+            Response::Ok()
+                .header(header::ETAG, "\"tag\"")
+                .body("0123456789"),
+        );
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers().get(header::ACCEPT_RANGES).unwrap(), "bytes");
+    }
+
+    #[crate::rt_test]
+    async fn test_multipart_ranges() {
+        let req = TestRequest::default()
+            .header(header::RANGE, "bytes=0-1,-2")
+            .finish();
+        let resp = ranged_response(
+            req.head(),
+            Response::Ok().content_type("text/plain").body("0123456789"),
+        );
+        assert_eq!(resp.status(), StatusCode::PARTIAL_CONTENT);
+        let ct = resp.headers().get(header::CONTENT_TYPE).unwrap();
+        let ct = ct.to_str().unwrap().to_string();
+        let boundary = ct
+            .strip_prefix("multipart/byteranges; boundary=")
+            .unwrap()
+            .to_string();
+        let size = match resp.body().size() {
+            BodySize::Sized(size) => size,
+            _ => panic!(),
+        };
+
+        let body = read_body(resp).await;
+        assert_eq!(body.len() as u64, size);
+        assert_eq!(
+            body,
+            Bytes::from(format!(
+                "--{b}\r\nContent-Type: text/plain\r\nContent-Range: bytes 0-1/10\r\n\r\n01\
+                 \r\n--{b}\r\nContent-Type: text/plain\r\nContent-Range: bytes 8-9/10\r\n\r\n89\
+                 \r\n--{b}--\r\n",
+                b = boundary
+            ))
+        );
+    }
+
+    #[crate::rt_test]
+    async fn test_range_body_stream() {
+        let chunks = vec![
+            Bytes::from_static(b"012"),
+            Bytes::from_static(b"345"),
+            Bytes::from_static(b"6789"),
+        ];
+        let stream =
+            futures_util::stream::iter(chunks.into_iter().map(Ok::<_, Box<dyn Error>>));
+        let body = Body::from_message(crate::http::body::SizedStream::new(10, stream));
+        let mut body = RangeBody::new(body, &[range(2, 5)], 10, None);
+        assert_eq!(body.size(), BodySize::Sized(5));
+
+        let mut buf = BytesMut::new();
+        while let Some(chunk) = poll_fn(|cx| body.poll_next_chunk(cx)).await {
+            buf.extend_from_slice(&chunk.unwrap());
+        }
+        assert_eq!(buf, b"23456"[..]);
+    }
+}
@@ -8,7 +8,7 @@ use coo_kie::{Cookie, CookieJar};
 
 use crate::http::body::{Body, BodyStream, MessageBody, ResponseBody};
 use crate::http::error::{HttpError, ResponseError};
-use crate::http::header::{self, HeaderMap, HeaderName, HeaderValue};
+use crate::http::header::{self, HeaderMap, HeaderName, HeaderValue, TypedHeader};
 use crate::http::message::{ConnectionType, Message, ResponseHead};
 use crate::http::StatusCode;
 use crate::util::{Bytes, BytesMut, Extensions, Stream};
@@ -378,6 +378,27 @@ impl ResponseBuilder {
         self
     }
 
+    /// Set a typed header, previous values of the header are replaced.
+    ///
+    /// ```rust
+    /// use ntex::http::{header, Response};
+    ///
+    /// fn index() -> Response {
+    ///     Response::Ok()
+    ///         .typed_header(header::CacheControl(vec![header::CacheDirective::NoStore]))
+    ///         .finish()
+    /// }
+    /// ```
+    pub fn typed_header<H: TypedHeader>(&mut self, header: H) -> &mut Self {
+        if let Some(parts) = parts(&mut self.head, &self.err) {
+            match header.encode() {
+                Ok(value) => parts.headers.insert(H::name(), value),
+                Err(e) => self.err = Some(log_error(e)),
+            }
+        }
+        self
+    }
+
     /// Set the custom reason for the response.
     #[inline]
     pub fn reason(&mut self, reason: &'static str) -> &mut Self {
@@ -1,8 +1,9 @@
-use std::{cell::Cell, cell::RefCell, error, fmt, marker, rc::Rc, task::Context};
+use std::{cell::Cell, cell::RefCell, error, fmt, io, marker, rc::Rc, task::Context};
 
-use crate::io::{types, Filter, Io, IoRef};
+use crate::io::proxy::{ProxyAcceptor, ProxyFilter};
+use crate::io::{types, Filter, Io, IoRef, Layer};
 use crate::service::{IntoServiceFactory, Service, ServiceCtx, ServiceFactory};
-use crate::{channel::oneshot, util::join, util::HashSet};
+use crate::{channel::oneshot, util::join, util::Either, util::HashSet};
 
 use super::body::MessageBody;
 use super::builder::HttpServiceBuilder;
@@ -132,13 +133,49 @@ where
     }
 }
 
+impl<F, S, B, C1, C2> HttpService<Layer<ProxyFilter, F>, S, B, C1, C2>
+where
+    F: Filter,
+    S: ServiceFactory<Request> + 'static,
+    S::Error: ResponseError,
+    S::InitError: fmt::Debug,
+    S::Response: Into<Response<B>>,
+    B: MessageBody,
+    C1: ServiceFactory<
+            h1::Control<Layer<ProxyFilter, F>, S::Error>,
+            Response = h1::ControlAck,
+        > + 'static,
+    C1::Error: error::Error,
+    C1::InitError: fmt::Debug,
+    C2: ServiceFactory<h2::Control<H2Error>, Response = h2::ControlAck> + 'static,
+    C2::Error: error::Error,
+    C2::InitError: fmt::Debug,
+{
+    /// Create service that requires PROXY protocol header
+    ///
+    /// Connection's peer address is replaced with source address
+    /// from the PROXY header. PROXY header errors are reported as `Either::Left`.
+    pub fn proxy_protocol(
+        self,
+    ) -> impl ServiceFactory<
+        Io<F>,
+        Response = (),
+        Error = Either<io::Error, DispatchError>,
+        InitError = (),
+    > {
+        ProxyAcceptor::new()
+            .map_err(Either::Left)
+            .and_then(self.map_err(Either::Right))
+    }
+}
+
 #[cfg(feature = "openssl")]
 mod openssl {
     use ntex_tls::openssl::{SslAcceptor, SslFilter};
     use tls_openssl::ssl;
 
     use super::*;
-    use crate::{io::Layer, server::SslError};
+    use crate::server::SslError;
 
     impl<F, S, B, C1, C2> HttpService<Layer<SslFilter, F>, S, B, C1, C2>
     where
@@ -183,7 +220,7 @@ mod rustls {
     use tls_rustls::ServerConfig;
 
     use super::*;
-    use crate::{io::Layer, server::SslError};
+    use crate::server::SslError;
 
     impl<F, S, B, C1, C2> HttpService<Layer<TlsServerFilter, F>, S, B, C1, C2>
     where
@@ -5,7 +5,13 @@
 //! * `openssl` - enables ssl support via `openssl` crate
 //! * `rustls` - enables ssl support via `rustls` crate
 //! * `compress` - enables compression support in http and web modules
+//! * `brotli` - enables `br` content encoding, implies `compress`
+//! * `zstd` - enables `zstd` content encoding, implies `compress`
 //! * `cookie` - enables cookie support in http and web modules
+//! * `session` - enables session support in web module, implies `cookie`
+//! * `files` - enables static files support in web module
+//! * `grpc` - enables gRPC server and client
+//! * `prost` - enables `prost` codec for gRPC, implies `grpc`
 #![warn(
     rust_2018_idioms,
     unreachable_pub,
@@ -36,6 +42,9 @@ pub mod web;
 #[cfg(feature = "ws")]
 pub mod ws;
 
+#[cfg(feature = "grpc")]
+pub mod grpc;
+
 pub use self::service::{
     chain, chain_factory, fn_service, IntoService, IntoServiceFactory, Middleware,
     Pipeline, Service, ServiceCtx, ServiceFactory,
@@ -35,6 +35,7 @@ pub struct App<M, F, Err: ErrorRenderer = DefaultError> {
     state_factories: Vec<FnStateFactory>,
     error_renderer: Err,
     case_insensitive: bool,
+    auto_options: bool,
 }
 
 impl App<Identity, Filter<DefaultError>, DefaultError> {
@@ -50,6 +51,7 @@ impl App<Identity, Filter<DefaultError>, DefaultError> {
             extensions: Extensions::new(),
             error_renderer: DefaultError,
             case_insensitive: false,
+            auto_options: false,
         }
     }
 }
@@ -67,6 +69,7 @@ impl<Err: ErrorRenderer> App<Identity, Filter<Err>, Err> {
             extensions: Extensions::new(),
             error_renderer: err,
             case_insensitive: false,
+            auto_options: false,
         }
     }
 }
@@ -368,6 +371,7 @@ where
             extensions: self.extensions,
             error_renderer: self.error_renderer,
             case_insensitive: self.case_insensitive,
+            auto_options: self.auto_options,
         }
     }
 
@@ -408,6 +412,7 @@ where
             extensions: self.extensions,
             error_renderer: self.error_renderer,
             case_insensitive: self.case_insensitive,
+            auto_options: self.auto_options,
         }
     }
 
@@ -418,6 +423,18 @@ where
         self.case_insensitive = true;
         self
     }
+
+    /// Answer `OPTIONS` requests automatically.
+    ///
+    /// If no route of the matched resource handles `OPTIONS` method,
+    /// resource responds with *204 No Content* and `Allow` header
+    /// that lists methods of the resource's routes.
+    ///
+    /// By default automatic `OPTIONS` responses are disabled.
+    pub fn auto_options(mut self, enabled: bool) -> Self {
+        self.auto_options = enabled;
+        self
+    }
 }
 
 impl<M, F, Err> App<M, F, Err>
@@ -500,6 +517,7 @@ where
             default: self.default,
             extensions: RefCell::new(Some(self.extensions)),
             case_insensitive: self.case_insensitive,
+            auto_options: self.auto_options,
         };
         map_config(app, move |_| cfg.clone())
     }
@@ -528,6 +546,7 @@ where
             default: self.default,
             extensions: RefCell::new(Some(self.extensions)),
             case_insensitive: self.case_insensitive,
+            auto_options: self.auto_options,
         }
     }
 }
@@ -554,6 +573,7 @@ where
             default: self.default,
             extensions: RefCell::new(Some(self.extensions)),
             case_insensitive: self.case_insensitive,
+            auto_options: self.auto_options,
         }
     }
 }
@@ -43,6 +43,7 @@ where
     pub(super) default: Option<Rc<HttpNewService<Err>>>,
     pub(super) external: RefCell<Vec<ResourceDef>>,
     pub(super) case_insensitive: bool,
+    pub(super) auto_options: bool,
 }
 
 impl<T, F, Err> ServiceFactory<Request> for AppFactory<T, F, Err>
@@ -118,15 +119,22 @@ where
 
         // App config
         let mut config = WebServiceConfig::new(state.clone(), default.clone());
+        config.set_auto_options(self.auto_options);
 
         // register services
         services
             .into_iter()
             .for_each(|mut srv| srv.register(&mut config));
+        #[cfg(feature = "openapi")]
+        let operations = config.take_operations();
+        #[cfg(feature = "openapi")]
+        let documents = config.take_documents();
         let services = config.into_services();
 
         // resource map
         let mut rmap = ResourceMap::new(ResourceDef::new(""));
+        #[cfg(feature = "openapi")]
+        rmap.add_operations(operations);
         for mut rdef in external {
             rmap.add(&mut rdef, None);
         }
@@ -144,6 +152,10 @@ where
         let rmap = Rc::new(rmap);
         rmap.finish(rmap.clone());
 
+        // generate api documents for complete resource map
+        #[cfg(feature = "openapi")]
+        documents.iter().for_each(|api| api.build(&rmap));
+
         // create http services
         for (path, factory, guards) in &mut services.iter() {
             let service = factory
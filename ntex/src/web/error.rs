@@ -171,6 +171,178 @@ pub enum PayloadError {
     Decoding,
 }
 
+/// A set of errors that can occur during parsing multipart payloads
+#[derive(Error, Debug)]
+pub enum MultipartError {
+    /// Content type is not `multipart/form-data`
+    #[error("Content type error")]
+    ContentType,
+    /// Boundary parameter is missing or malformed
+    #[error("Multipart boundary is not found")]
+    Boundary,
+    /// Payload ended before closing boundary
+    #[error("Multipart stream is incomplete")]
+    Incomplete,
+    /// Field headers are malformed
+    #[error("Can not parse multipart field headers")]
+    Header,
+    /// Field size is bigger than allowed
+    #[error("Multipart field size is bigger than allowed ({limit} bytes)")]
+    FieldOverflow { limit: usize },
+    /// Payload size is bigger than allowed
+    #[error("Multipart payload size is bigger than allowed ({limit} bytes)")]
+    Overflow { limit: usize },
+    /// Text field is not valid utf-8 string
+    #[error("Multipart field is not valid utf-8 string")]
+    Utf8,
+    /// Deserialize error
+    #[error("Multipart form deserialize error: {0}")]
+    Deserialize(#[from] serde::de::value::Error),
+    /// Payload error
+    #[error("Error that occur during reading payload: {0}")]
+    Payload(#[from] error::PayloadError),
+}
+
+/// Field-level validation error
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize)]
+pub struct FieldError {
+    /// Path to the field, i.e. `address.city` or `items[0].name`
+    pub path: String,
+    /// Error code
+    pub code: String,
+    /// Human-readable message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl FieldError {
+    /// Create field error
+    pub fn new<P: Into<String>, C: Into<String>>(path: P, code: C) -> Self {
+        FieldError {
+            path: path.into(),
+            code: code.into(),
+            message: None,
+        }
+    }
+
+    /// Set human-readable message
+    pub fn message<M: Into<String>>(mut self, message: M) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+}
+
+/// A set of errors that can occur during request data validation
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize)]
+pub struct ValidationErrors {
+    errors: Vec<FieldError>,
+}
+
+impl ValidationErrors {
+    /// Create empty set of errors
+    pub fn new() -> Self {
+        ValidationErrors::default()
+    }
+
+    /// Add field error
+    pub fn add(&mut self, error: FieldError) -> &mut Self {
+        self.errors.push(error);
+        self
+    }
+
+    /// Add errors of nested value, paths are prefixed with `prefix`
+    pub fn nest(&mut self, prefix: &str, errors: ValidationErrors) -> &mut Self {
+        for mut err in errors.errors {
+            err.path = if err.path.is_empty() {
+                prefix.to_string()
+            } else if err.path.starts_with('[') {
+                format!("{}{}", prefix, err.path)
+            } else {
+                format!("{}.{}", prefix, err.path)
+            };
+            self.errors.push(err);
+        }
+        self
+    }
+
+    /// Check if there are no errors
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Field errors
+    pub fn errors(&self) -> &[FieldError] {
+        &self.errors
+    }
+
+    /// Convert to `Result`, `Ok(())` if there are no errors
+    pub fn into_result(self) -> Result<(), ValidationErrors> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Validation failed")?;
+        for (idx, err) in self.errors.iter().enumerate() {
+            let sep = if idx == 0 { ": " } else { ", " };
+            let msg = err.message.as_ref().unwrap_or(&err.code);
+            write!(f, "{}{}: {}", sep, err.path, msg)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+#[cfg(feature = "validator")]
+impl From<validator::ValidationErrors> for ValidationErrors {
+    fn from(errors: validator::ValidationErrors) -> Self {
+        fn flatten(
+            prefix: &str,
+            errors: &validator::ValidationErrors,
+            result: &mut ValidationErrors,
+        ) {
+            use validator::ValidationErrorsKind;
+
+            for (field, kind) in errors.errors() {
+                let path = if prefix.is_empty() {
+                    field.to_string()
+                } else {
+                    format!("{}.{}", prefix, field)
+                };
+                match kind {
+                    ValidationErrorsKind::Field(errs) => {
+                        for err in errs {
+                            result.errors.push(FieldError {
+                                path: path.clone(),
+                                code: err.code.to_string(),
+                                message: err.message.as_ref().map(|m| m.to_string()),
+                            });
+                        }
+                    }
+                    ValidationErrorsKind::Struct(errs) => flatten(&path, errs, result),
+                    ValidationErrorsKind::List(items) => {
+                        for (idx, errs) in items {
+                            flatten(&format!("{}[{}]", path, idx), errs, result);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut result = ValidationErrors::new();
+        flatten("", &errors, &mut result);
+        // validator stores errors in hash map
+        result.errors.sort_by(|a, b| a.path.cmp(&b.path));
+        result
+    }
+}
+
 /// Helper type that can wrap any error and generate custom response.
 ///
 /// In following example any `io::Error` will be converted into "BAD REQUEST"
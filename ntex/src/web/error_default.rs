@@ -177,6 +177,17 @@ impl WebResponseError<DefaultError> for error::JsonPayloadError {
     }
 }
 
+/// Response renderer for `MultipartError`
+impl WebResponseError<DefaultError> for error::MultipartError {
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            error::MultipartError::FieldOverflow { .. }
+            | error::MultipartError::Overflow { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
 /// Error renderer for `PathError`
 impl WebResponseError<DefaultError> for error::PathError {
     fn status_code(&self) -> StatusCode {
@@ -184,6 +195,30 @@ impl WebResponseError<DefaultError> for error::PathError {
     }
 }
 
+/// Return `UNPROCESSABLE_ENTITY` for `ValidationErrors`, body contains
+/// json list of field errors
+impl WebResponseError<DefaultError> for error::ValidationErrors {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::UNPROCESSABLE_ENTITY
+    }
+
+    fn error_response(&self, _: &HttpRequest) -> HttpResponse {
+        match serde_json::to_vec(self) {
+            Ok(body) => HttpResponse::build(StatusCode::UNPROCESSABLE_ENTITY)
+                .content_type("application/json")
+                .body(body),
+            Err(_) => HttpResponse::new(StatusCode::UNPROCESSABLE_ENTITY),
+        }
+    }
+}
+
+/// Error renderer for `TypedHeaderError`
+impl WebResponseError<DefaultError> for header::TypedHeaderError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+}
+
 /// Error renderer `QueryPayloadError`
 impl WebResponseError<DefaultError> for error::QueryPayloadError {
     fn status_code(&self) -> StatusCode {
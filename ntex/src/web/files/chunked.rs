@@ -0,0 +1,118 @@
+use std::io::{Read, Seek, SeekFrom};
+use std::{cmp, collections::VecDeque, error::Error, fmt, fs::File, future::Future, io};
+use std::{pin::Pin, task::Context, task::Poll};
+
+use crate::http::range::ByteRange;
+use crate::rt::{spawn_blocking, JoinHandle};
+use crate::util::{Bytes, Stream};
+
+const CHUNK_SIZE: u64 = 65_536;
+
+/// Stream of file's content.
+///
+/// File is read in chunks on a blocking thread pool,
+/// so worker thread never blocks on file system operations.
+pub struct ChunkedReadFile {
+    size: u64,
+    offset: u64,
+    ranges: VecDeque<ByteRange>,
+    file: Option<File>,
+    fut: Option<JoinHandle<io::Result<(File, Bytes)>>>,
+}
+
+impl ChunkedReadFile {
+    /// Create stream that reads `size` bytes of `file` starting from `offset`.
+    pub fn new(file: File, offset: u64, size: u64) -> Self {
+        ChunkedReadFile {
+            size,
+            offset,
+            ranges: VecDeque::new(),
+            file: Some(file),
+            fut: None,
+        }
+    }
+
+    /// Create stream that reads selected `ranges` of `file` one after another.
+    pub fn with_ranges(file: File, ranges: &[ByteRange]) -> Self {
+        ChunkedReadFile {
+            size: 0,
+            offset: 0,
+            ranges: ranges.iter().copied().collect(),
+            file: Some(file),
+            fut: None,
+        }
+    }
+
+    /// Number of bytes left to read
+    pub fn remaining(&self) -> u64 {
+        self.size + self.ranges.iter().map(|r| r.length).sum::<u64>()
+    }
+}
+
+impl fmt::Debug for ChunkedReadFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChunkedReadFile")
+            .field("size", &self.size)
+            .field("offset", &self.offset)
+            .finish()
+    }
+}
+
+impl Stream for ChunkedReadFile {
+    type Item = Result<Bytes, Box<dyn Error>>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        if let Some(ref mut fut) = self.fut {
+            let (file, chunk) = match Pin::new(fut).poll(cx) {
+                Poll::Ready(Ok(Ok(item))) => item,
+                Poll::Ready(Ok(Err(e))) => {
+                    self.fut.take();
+                    return Poll::Ready(Some(Err(Box::new(e))));
+                }
+                Poll::Ready(Err(_)) => {
+                    self.fut.take();
+                    return Poll::Ready(Some(Err(Box::new(io::Error::new(
+                        io::ErrorKind::Interrupted,
+                        "Canceled",
+                    )))));
+                }
+                Poll::Pending => return Poll::Pending,
+            };
+            self.fut.take();
+            self.file = Some(file);
+            self.offset += chunk.len() as u64;
+            self.size -= chunk.len() as u64;
+            return Poll::Ready(Some(Ok(chunk)));
+        }
+
+        while self.size == 0 {
+            if let Some(range) = self.ranges.pop_front() {
+                self.offset = range.start;
+                self.size = range.length;
+            } else {
+                return Poll::Ready(None);
+            }
+        }
+
+        let offset = self.offset;
+        let max_bytes = cmp::min(self.size, CHUNK_SIZE);
+        let Some(mut file) = self.file.take() else {
+            return Poll::Ready(None);
+        };
+
+        self.fut = Some(spawn_blocking(move || {
+            let mut buf = Vec::with_capacity(max_bytes as usize);
+            file.seek(SeekFrom::Start(offset))?;
+            let n = file.by_ref().take(max_bytes).read_to_end(&mut buf)?;
+            if n == 0 {
+                Err(io::ErrorKind::UnexpectedEof.into())
+            } else {
+                Ok((file, Bytes::from(buf)))
+            }
+        }));
+        self.poll_next(cx)
+    }
+}
@@ -0,0 +1,146 @@
+use std::{fmt::Write, fs, io, path::PathBuf};
+
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+
+use crate::http::StatusCode;
+use crate::web::{HttpRequest, HttpResponse};
+
+/// Characters that must be escaped in the href of directory listing entries
+const HREF: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+/// Directory listing renderer
+pub type DirectoryRenderer = dyn Fn(&Directory, &HttpRequest) -> HttpResponse;
+
+/// A directory to be listed
+#[derive(Debug, Clone)]
+pub struct Directory {
+    /// Base directory of `Files` service
+    pub base: PathBuf,
+    /// Path of the listed directory
+    pub path: PathBuf,
+    /// Directory entries, sorted by name
+    pub entries: Vec<DirectoryEntry>,
+}
+
+/// Entry of the listed directory
+#[derive(Debug, Clone)]
+pub struct DirectoryEntry {
+    /// File name
+    pub name: String,
+    /// Entry is a directory
+    pub is_dir: bool,
+    /// File size in bytes
+    pub size: u64,
+}
+
+impl Directory {
+    /// Read directory entries.
+    ///
+    /// Hidden entries (names starting with `.`) are skipped unless
+    /// `hidden` is set. This method performs blocking file system operations.
+    pub fn read(base: PathBuf, path: PathBuf, hidden: bool) -> io::Result<Directory> {
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(&path)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if !hidden && name.starts_with('.') {
+                continue;
+            }
+            // follow symlinks
+            let md = match fs::metadata(entry.path()) {
+                Ok(md) => md,
+                Err(_) => continue,
+            };
+            entries.push(DirectoryEntry {
+                name,
+                is_dir: md.is_dir(),
+                size: md.len(),
+            });
+        }
+        entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+
+        Ok(Directory {
+            base,
+            path,
+            entries,
+        })
+    }
+}
+
+/// Default directory listing renderer, generates simple html page
+pub(super) fn directory_listing(dir: &Directory, req: &HttpRequest) -> HttpResponse {
+    let path = req.path();
+    let title = format!("Index of {}", escape_html(path));
+    let base = if path.ends_with('/') {
+        escape_html(path)
+    } else {
+        format!("{}/", escape_html(path))
+    };
+
+    let mut body = String::new();
+    let _ = write!(
+        body,
+        "<html><head><meta charset=\"utf-8\"><title>{}</title></head>\
+         <body><h1>{}</h1><ul>",
+        title, title
+    );
+    if base != "/" {
+        body.push_str("<li><a href=\"../\">../</a></li>");
+    }
+    for entry in &dir.entries {
+        let href = utf8_percent_encode(&entry.name, HREF);
+        let name = escape_html(&entry.name);
+        if entry.is_dir {
+            let _ = write!(body, "<li><a href=\"{}{}/\">{}/</a></li>", base, href, name);
+        } else {
+            let _ = write!(
+                body,
+                "<li><a href=\"{}{}\">{}</a> ({} bytes)</li>",
+                base, href, name, entry.size
+            );
+        }
+    }
+    body.push_str("</ul></body></html>");
+
+    HttpResponse::build(StatusCode::OK)
+        .content_type("text/html; charset=utf-8")
+        .body(body)
+}
+
+fn escape_html(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&#x27;"),
+            _ => result.push(ch),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(
+            escape_html("<a href='x'>&</a>"),
+            "&lt;a href=&#x27;x&#x27;&gt;&amp;&lt;/a&gt;"
+        );
+    }
+}
@@ -0,0 +1,36 @@
+use std::io;
+
+use crate::http::StatusCode;
+use crate::web::error::{ErrorRenderer, WebResponseError};
+
+/// Errors which can occur when serving static files
+#[derive(thiserror::Error, Debug)]
+pub enum FilesError {
+    /// Requested path contains segment that is not allowed
+    #[error("Path segment is not allowed: {0}")]
+    ForbiddenSegment(String),
+    /// Requested path is not valid utf-8 string
+    #[error("Path is not valid utf-8 string")]
+    InvalidPath,
+    /// File system error
+    #[error("File system error: {0}")]
+    Io(#[from] io::Error),
+    /// Blocking operation has been canceled
+    #[error("Blocking operation has been canceled")]
+    Canceled,
+}
+
+impl<Err: ErrorRenderer> WebResponseError<Err> for FilesError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            FilesError::ForbiddenSegment(_) => StatusCode::NOT_FOUND,
+            FilesError::InvalidPath => StatusCode::BAD_REQUEST,
+            FilesError::Io(ref err) => match err.kind() {
+                io::ErrorKind::NotFound => StatusCode::NOT_FOUND,
+                io::ErrorKind::PermissionDenied => StatusCode::FORBIDDEN,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            },
+            FilesError::Canceled => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
@@ -0,0 +1,22 @@
+//! Static files support
+//!
+//! ```rust
+//! use ntex::web::{self, files, App};
+//!
+//! let app = App::new().service(
+//!     files::Files::new("/static", ".")
+//!         .index_file("index.html")
+//!         .show_files_listing(),
+//! );
+//! ```
+mod chunked;
+mod directory;
+mod error;
+mod named;
+mod service;
+
+pub use self::chunked::ChunkedReadFile;
+pub use self::directory::{Directory, DirectoryEntry, DirectoryRenderer};
+pub use self::error::FilesError;
+pub use self::named::NamedFile;
+pub use self::service::{Files, FilesService};
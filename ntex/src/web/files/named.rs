@@ -0,0 +1,541 @@
+use std::fs::{File, Metadata};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::{fmt::Write, io};
+
+use mime::Mime;
+
+use crate::http::body::{Body, SizedStream};
+use crate::http::header::{self, HeaderValue};
+use crate::http::{range, Method, Response, StatusCode};
+use crate::web::{ErrorRenderer, HttpRequest, HttpResponse, Responder};
+
+use super::ChunkedReadFile;
+
+/// A file with an associated name.
+///
+/// `NamedFile` could be used as a handler's response, it
+/// handles `ETag` and `Last-Modified` based conditional requests
+/// and `Range` requests.
+///
+/// ```rust,no_run
+/// use ntex::web::{self, files::NamedFile};
+///
+/// async fn index() -> std::io::Result<NamedFile> {
+///     NamedFile::open("static/index.html")
+/// }
+/// ```
+#[derive(Debug)]
+pub struct NamedFile {
+    path: PathBuf,
+    file: File,
+    md: Metadata,
+    modified: Option<SystemTime>,
+    content_type: Mime,
+    content_disposition: HeaderValue,
+    use_etag: bool,
+    use_last_modified: bool,
+}
+
+impl NamedFile {
+    /// Attempts to open a file in read-only mode.
+    ///
+    /// This method performs blocking file system operations,
+    /// consider using it from a blocking thread pool.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<NamedFile> {
+        let path = path.as_ref().to_path_buf();
+        let file = File::open(&path)?;
+        NamedFile::from_file(file, path)
+    }
+
+    /// Create `NamedFile` from already opened file.
+    ///
+    /// `path` is used for content type and content disposition detection.
+    pub fn from_file<P: AsRef<Path>>(file: File, path: P) -> io::Result<NamedFile> {
+        let path = path.as_ref().to_path_buf();
+        let md = file.metadata()?;
+        if md.is_dir() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Path is a directory",
+            ));
+        }
+        let modified = md.modified().ok();
+
+        let filename = match path.file_name() {
+            Some(name) => name.to_string_lossy(),
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Provided path has no filename",
+                ))
+            }
+        };
+        let content_type = mime_guess::from_path(&path).first_or_octet_stream();
+        let content_disposition = content_disposition(&content_type, &filename);
+
+        Ok(NamedFile {
+            path,
+            file,
+            md,
+            modified,
+            content_type,
+            content_disposition,
+            use_etag: true,
+            use_last_modified: true,
+        })
+    }
+
+    /// Returns reference to the underlying `File` object.
+    pub fn file(&self) -> &File {
+        &self.file
+    }
+
+    /// Retrieve the path of this file.
+    pub fn path(&self) -> &Path {
+        self.path.as_path()
+    }
+
+    /// Returns file's metadata
+    pub fn metadata(&self) -> &Metadata {
+        &self.md
+    }
+
+    /// Returns content type of the file
+    pub fn content_type(&self) -> &Mime {
+        &self.content_type
+    }
+
+    /// Set the MIME Content-Type for serving this file.
+    ///
+    /// By default content type is guessed from the file extension.
+    pub fn set_content_type(mut self, mime_type: Mime) -> Self {
+        self.content_type = mime_type;
+        self
+    }
+
+    /// Set the Content-Disposition for serving this file.
+    ///
+    /// By default `inline` disposition is used for text, image, audio
+    /// and video files, and `attachment` for all other files.
+    pub fn set_content_disposition(mut self, value: HeaderValue) -> Self {
+        self.content_disposition = value;
+        self
+    }
+
+    /// Specifies whether to use ETag or not.
+    ///
+    /// Default is true.
+    pub fn use_etag(mut self, value: bool) -> Self {
+        self.use_etag = value;
+        self
+    }
+
+    /// Specifies whether to use Last-Modified or not.
+    ///
+    /// Default is true.
+    pub fn use_last_modified(mut self, value: bool) -> Self {
+        self.use_last_modified = value;
+        self
+    }
+
+    /// Strong entity tag of the file.
+    pub fn etag(&self) -> Option<String> {
+        self.modified.as_ref().map(|mtime| {
+            let dur = mtime
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or(Duration::from_secs(0));
+
+            #[cfg(unix)]
+            let ino = std::os::unix::fs::MetadataExt::ino(&self.md);
+            #[cfg(not(unix))]
+            let ino = 0;
+
+            format!(
+                "\"{:x}-{:x}-{:x}-{:x}\"",
+                ino,
+                self.md.len(),
+                dur.as_secs(),
+                dur.subsec_nanos()
+            )
+        })
+    }
+
+    /// Last modification time of the file.
+    pub fn last_modified(&self) -> Option<SystemTime> {
+        // http dates have second precision
+        self.modified.and_then(|mtime| {
+            mtime
+                .duration_since(UNIX_EPOCH)
+                .ok()
+                .map(|dur| UNIX_EPOCH + Duration::from_secs(dur.as_secs()))
+        })
+    }
+
+    /// Check request preconditions and build response.
+    pub fn into_response(self, req: &HttpRequest) -> HttpResponse {
+        let etag = if self.use_etag { self.etag() } else { None };
+        let last_modified = if self.use_last_modified {
+            self.last_modified()
+        } else {
+            None
+        };
+
+        let last_modified_str = last_modified.map(httpdate::fmt_http_date);
+
+        let mut resp = HttpResponse::build(StatusCode::OK);
+        resp.set_header(header::CONTENT_TYPE, self.content_type.to_string())
+            .set_header(
+                header::CONTENT_DISPOSITION,
+                self.content_disposition.clone(),
+            )
+            .set_header(header::ACCEPT_RANGES, "bytes");
+        if let Some(ref etag) = etag {
+            resp.set_header(header::ETAG, etag.as_str());
+        }
+        if let Some(ref lm) = last_modified_str {
+            resp.set_header(header::LAST_MODIFIED, lm.as_str());
+        }
+
+        match check_preconditions(req, etag.as_deref(), last_modified) {
+            Precondition::Failed => {
+                return resp.status(StatusCode::PRECONDITION_FAILED).finish()
+            }
+            Precondition::NotModified => {
+                return resp.status(StatusCode::NOT_MODIFIED).finish()
+            }
+            Precondition::Passed => (),
+        }
+
+        let size = self.md.len();
+        let ranges = range::requested_ranges(
+            req.head(),
+            size,
+            etag.as_deref(),
+            last_modified_str.as_deref(),
+        );
+        match ranges {
+            Ok(Some(ranges)) if ranges.len() == 1 => {
+                let range = ranges[0];
+                let reader = ChunkedReadFile::new(self.file, range.start, range.length);
+                resp.status(StatusCode::PARTIAL_CONTENT)
+                    .set_header(header::CONTENT_RANGE, range.content_range(size))
+                    .body(SizedStream::new(range.length, reader))
+            }
+            Ok(Some(ranges)) => {
+                // every range is read with its own seek
+                let reader = ChunkedReadFile::with_ranges(self.file, &ranges);
+                let length = reader.remaining();
+                let body = Body::from_message(SizedStream::new(length, reader));
+                range::multipart_ranges(resp.finish(), body, size, &ranges)
+            }
+            Ok(None) => {
+                let reader = ChunkedReadFile::new(self.file, 0, size);
+                resp.body(SizedStream::new(size, reader))
+            }
+            Err(_) => range::unsatisfiable(resp.finish(), size),
+        }
+    }
+}
+
+impl<Err: ErrorRenderer> Responder<Err> for NamedFile {
+    async fn respond_to(self, req: &HttpRequest) -> Response {
+        self.into_response(req)
+    }
+}
+
+/// Result of conditional request checks
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(super) enum Precondition {
+    Passed,
+    NotModified,
+    Failed,
+}
+
+/// Evaluate conditional request headers, RFC 9110 section 13.2.2
+pub(super) fn check_preconditions(
+    req: &HttpRequest,
+    etag: Option<&str>,
+    last_modified: Option<SystemTime>,
+) -> Precondition {
+    let headers = req.headers();
+
+    if let Some(val) = headers.get(header::IF_MATCH) {
+        let matched = match (val.to_str(), etag) {
+            (Ok(val), Some(etag)) => etag_matches(val, etag, false),
+            (Ok(val), None) => val.trim() == "*",
+            _ => false,
+        };
+        if !matched {
+            return Precondition::Failed;
+        }
+    } else if let Some(val) = headers.get(header::IF_UNMODIFIED_SINCE) {
+        if let (Some(since), Some(lm)) = (parse_date(val), last_modified) {
+            if lm > since {
+                return Precondition::Failed;
+            }
+        }
+    }
+
+    let safe = matches!(*req.method(), Method::GET | Method::HEAD);
+    if let Some(val) = headers.get(header::IF_NONE_MATCH) {
+        let matched = match (val.to_str(), etag) {
+            (Ok(val), Some(etag)) => etag_matches(val, etag, true),
+            (Ok(val), None) => val.trim() == "*",
+            _ => false,
+        };
+        if matched {
+            return if safe {
+                Precondition::NotModified
+            } else {
+                Precondition::Failed
+            };
+        }
+    } else if safe {
+        if let Some(val) = headers.get(header::IF_MODIFIED_SINCE) {
+            if let (Some(since), Some(lm)) = (parse_date(val), last_modified) {
+                if lm <= since {
+                    return Precondition::NotModified;
+                }
+            }
+        }
+    }
+    Precondition::Passed
+}
+
+/// Check if list of entity tags contains `etag`
+pub(super) fn etag_matches(list: &str, etag: &str, weak: bool) -> bool {
+    let (etag_weak, etag) = split_etag(etag);
+    list.split(',').map(str::trim).any(|item| {
+        if item == "*" {
+            return true;
+        }
+        let (item_weak, item) = split_etag(item);
+        if weak {
+            item == etag
+        } else {
+            !item_weak && !etag_weak && item == etag
+        }
+    })
+}
+
+fn split_etag(tag: &str) -> (bool, &str) {
+    if let Some(tag) = tag.strip_prefix("W/") {
+        (true, tag)
+    } else {
+        (false, tag)
+    }
+}
+
+fn parse_date(val: &HeaderValue) -> Option<SystemTime> {
+    val.to_str()
+        .ok()
+        .and_then(|s| httpdate::parse_http_date(s.trim()).ok())
+}
+
+fn content_disposition(ct: &Mime, filename: &str) -> HeaderValue {
+    let kind = match ct.type_() {
+        mime::IMAGE | mime::TEXT | mime::VIDEO | mime::AUDIO => "inline",
+        _ => "attachment",
+    };
+
+    let mut value = format!("{}; filename=\"", kind);
+    for ch in filename.chars() {
+        match ch {
+            '"' | '\\' => {
+                value.push('\\');
+                value.push(ch);
+            }
+            ch if ch.is_ascii() && !ch.is_ascii_control() => value.push(ch),
+            _ => value.push('_'),
+        }
+    }
+    value.push('"');
+
+    // RFC 5987 encoded filename for non-ascii names
+    if !filename.is_ascii() {
+        value.push_str("; filename*=UTF-8''");
+        for b in filename.bytes() {
+            if b.is_ascii_alphanumeric() || b"!#$&+-.^_`|~".contains(&b) {
+                value.push(b as char);
+            } else {
+                let _ = write!(value, "%{:02X}", b);
+            }
+        }
+    }
+
+    HeaderValue::try_from(value).unwrap_or_else(|_| HeaderValue::from_static("attachment"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::web::{test::TestRequest, WebResponse};
+
+    #[test]
+    fn test_etag_matches() {
+        assert!(etag_matches("\"a\"", "\"a\"", false));
+        assert!(etag_matches("\"b\", \"a\"", "\"a\"", false));
+        assert!(etag_matches("*", "\"a\"", false));
+        assert!(!etag_matches("W/\"a\"", "\"a\"", false));
+        assert!(etag_matches("W/\"a\"", "\"a\"", true));
+        assert!(!etag_matches("\"b\"", "\"a\"", true));
+    }
+
+    #[test]
+    fn test_content_disposition() {
+        let cd = content_disposition(&mime::TEXT_PLAIN, "test.txt");
+        assert_eq!(cd, "inline; filename=\"test.txt\"");
+        let cd = content_disposition(&mime::APPLICATION_OCTET_STREAM, "te\"st.bin");
+        assert_eq!(cd, "attachment; filename=\"te\\\"st.bin\"");
+        let cd = content_disposition(&mime::TEXT_PLAIN, "тест.txt");
+        assert_eq!(
+            cd,
+            "inline; filename=\"____.txt\"; filename*=UTF-8''%D1%82%D0%B5%D1%81%D1%82.txt"
+        );
+    }
+
+    #[test]
+    fn test_preconditions() {
+        let lm = UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let req = TestRequest::default()
+            .header(header::IF_NONE_MATCH, "\"tag\"")
+            .to_http_request();
+        assert_eq!(
+            check_preconditions(&req, Some("\"tag\""), Some(lm)),
+            Precondition::NotModified
+        );
+
+        let req = TestRequest::default()
+            .header(header::IF_MATCH, "\"other\"")
+            .to_http_request();
+        assert_eq!(
+            check_preconditions(&req, Some("\"tag\""), Some(lm)),
+            Precondition::Failed
+        );
+
+        let req = TestRequest::default()
+            .header(header::IF_MODIFIED_SINCE, httpdate::fmt_http_date(lm))
+            .to_http_request();
+        assert_eq!(
+            check_preconditions(&req, Some("\"tag\""), Some(lm)),
+            Precondition::NotModified
+        );
+
+        let req = TestRequest::default()
+            .header(
+                header::IF_MODIFIED_SINCE,
+                httpdate::fmt_http_date(lm - Duration::from_secs(10)),
+            )
+            .to_http_request();
+        assert_eq!(
+            check_preconditions(&req, Some("\"tag\""), Some(lm)),
+            Precondition::Passed
+        );
+    }
+
+    #[crate::rt_test]
+    async fn test_named_file() {
+        let req = TestRequest::default().to_http_request();
+        let file = NamedFile::open("tests/test.png").unwrap();
+        assert_eq!(file.content_type(), &mime::IMAGE_PNG);
+        let etag = file.etag().unwrap();
+        let resp = file.into_response(&req);
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers().get(header::ETAG).unwrap(), etag.as_str());
+        assert!(resp.headers().contains_key(header::LAST_MODIFIED));
+
+        let req = TestRequest::default()
+            .header(header::IF_NONE_MATCH, etag.as_str())
+            .to_http_request();
+        let resp = NamedFile::open("tests/test.png")
+            .unwrap()
+            .into_response(&req);
+        assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+
+        let req = TestRequest::default().to_http_request();
+        let resp = NamedFile::open("tests/test.png")
+            .unwrap()
+            .use_etag(false)
+            .use_last_modified(false)
+            .into_response(&req);
+        assert!(!resp.headers().contains_key(header::ETAG));
+        assert!(!resp.headers().contains_key(header::LAST_MODIFIED));
+    }
+
+    #[crate::rt_test]
+    async fn test_named_file_ranges() {
+        let size = std::fs::metadata("tests/test.png").unwrap().len();
+        let data = std::fs::read("tests/test.png").unwrap();
+
+        let req = TestRequest::default()
+            .header(header::RANGE, "bytes=10-19")
+            .to_http_request();
+        let resp = NamedFile::open("tests/test.png")
+            .unwrap()
+            .into_response(&req);
+        assert_eq!(resp.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_RANGE).unwrap(),
+            format!("bytes 10-19/{}", size).as_str()
+        );
+        let body = crate::web::test::read_body(WebResponse::new(resp, req)).await;
+        assert_eq!(&body[..], &data[10..20]);
+
+        let req = TestRequest::default()
+            .header(header::RANGE, "bytes=2-4,-3")
+            .to_http_request();
+        let resp = NamedFile::open("tests/test.png")
+            .unwrap()
+            .into_response(&req);
+        assert_eq!(resp.status(), StatusCode::PARTIAL_CONTENT);
+        let ct = resp.headers().get(header::CONTENT_TYPE).unwrap();
+        let boundary = ct
+            .to_str()
+            .unwrap()
+            .strip_prefix("multipart/byteranges; boundary=")
+            .unwrap()
+            .to_string();
+        let body = crate::web::test::read_body(WebResponse::new(resp, req)).await;
+        let mut expected = format!(
+            "--{b}\r\nContent-Type: image/png\r\nContent-Range: bytes 2-4/{s}\r\n\r\n",
+            b = boundary,
+            s = size
+        )
+        .into_bytes();
+        expected.extend_from_slice(&data[2..5]);
+        expected.extend_from_slice(
+            format!(
+                "\r\n--{b}\r\nContent-Type: image/png\r\nContent-Range: bytes {f}-{l}/{s}\
+                 \r\n\r\n",
+                b = boundary,
+                f = size - 3,
+                l = size - 1,
+                s = size
+            )
+            .as_bytes(),
+        );
+        expected.extend_from_slice(&data[data.len() - 3..]);
+        expected.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+        assert_eq!(&body[..], &expected[..]);
+
+        let req = TestRequest::default()
+            .header(header::RANGE, format!("bytes={}-", size))
+            .to_http_request();
+        let resp = NamedFile::open("tests/test.png")
+            .unwrap()
+            .into_response(&req);
+        assert_eq!(resp.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+
+        // stale validator, full content
+        let req = TestRequest::default()
+            .header(header::RANGE, "bytes=10-19")
+            .header(header::IF_RANGE, "\"stale\"")
+            .to_http_request();
+        let resp = NamedFile::open("tests/test.png")
+            .unwrap()
+            .into_response(&req);
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers().get(header::ACCEPT_RANGES).unwrap(), "bytes");
+    }
+}
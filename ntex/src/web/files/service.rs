@@ -0,0 +1,534 @@
+use std::{fmt, fs, io, path::Path, path::PathBuf, rc::Rc};
+
+use percent_encoding::percent_decode_str;
+
+use crate::http::{header, Method};
+use crate::router::ResourceDef;
+use crate::rt::spawn_blocking;
+use crate::service::boxed::{BoxService, BoxServiceFactory};
+use crate::service::{Service, ServiceCtx, ServiceFactory};
+use crate::web::dev::{WebServiceConfig, WebServiceFactory};
+use crate::web::guard::Guard;
+use crate::web::{ErrorRenderer, HttpRequest, HttpResponse, WebRequest, WebResponse};
+
+use super::directory::{directory_listing, Directory, DirectoryRenderer};
+use super::{FilesError, NamedFile};
+
+type HttpService<Err: ErrorRenderer> =
+    BoxService<WebRequest<Err>, WebResponse, Err::Container>;
+type HttpNewService<Err: ErrorRenderer> =
+    BoxServiceFactory<(), WebRequest<Err>, WebResponse, Err::Container, ()>;
+
+/// Static files handling service.
+///
+/// `Files` service must be registered with `App::service()` method.
+/// Requests that do not match any file are forwarded to the default service.
+///
+/// ```rust
+/// use ntex::web::{self, files, App};
+///
+/// let app = App::new()
+///     .service(files::Files::new("/static", ".").index_file("index.html"));
+/// ```
+pub struct Files {
+    path: String,
+    directory: PathBuf,
+    index: Option<String>,
+    show_index: bool,
+    hidden_files: bool,
+    redirect_to_slash: bool,
+    follow_symlinks: bool,
+    use_etag: bool,
+    use_last_modified: bool,
+    renderer: Rc<DirectoryRenderer>,
+    guards: Vec<Box<dyn Guard>>,
+}
+
+impl fmt::Debug for Files {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Files")
+            .field("path", &self.path)
+            .field("directory", &self.directory)
+            .field("index", &self.index)
+            .field("show_index", &self.show_index)
+            .field("hidden_files", &self.hidden_files)
+            .field("redirect_to_slash", &self.redirect_to_slash)
+            .field("follow_symlinks", &self.follow_symlinks)
+            .finish()
+    }
+}
+
+impl Files {
+    /// Create new `Files` instance for specified base directory.
+    ///
+    /// `mount_path` is a path prefix of the service, `serve_from`
+    /// is the directory files are served from.
+    pub fn new<T: Into<PathBuf>>(mount_path: &str, serve_from: T) -> Files {
+        let dir = serve_from.into();
+        let directory = dir.canonicalize().unwrap_or_else(|e| {
+            log::error!("Specified path is not a directory: {:?}: {}", dir, e);
+            dir
+        });
+
+        Files {
+            directory,
+            path: mount_path.trim_end_matches('/').to_string(),
+            index: None,
+            show_index: false,
+            hidden_files: false,
+            redirect_to_slash: false,
+            follow_symlinks: false,
+            use_etag: true,
+            use_last_modified: true,
+            renderer: Rc::new(directory_listing),
+            guards: Vec::new(),
+        }
+    }
+
+    /// Set index file.
+    ///
+    /// Shows specific index file for directory "/" instead of
+    /// showing files listing.
+    pub fn index_file<T: Into<String>>(mut self, index: T) -> Self {
+        self.index = Some(index.into());
+        self
+    }
+
+    /// Show files listing for directories.
+    ///
+    /// By default show files listing is disabled.
+    pub fn show_files_listing(mut self) -> Self {
+        self.show_index = true;
+        self
+    }
+
+    /// Set custom directory renderer.
+    pub fn files_listing_renderer<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&Directory, &HttpRequest) -> HttpResponse + 'static,
+    {
+        self.renderer = Rc::new(f);
+        self
+    }
+
+    /// Enables serving hidden files and directories (names starting with `.`).
+    ///
+    /// By default hidden files are not served.
+    pub fn use_hidden_files(mut self) -> Self {
+        self.hidden_files = true;
+        self
+    }
+
+    /// Redirects to a slash-ended path when browsing a directory.
+    ///
+    /// By default never redirect.
+    pub fn redirect_to_slash_directory(mut self) -> Self {
+        self.redirect_to_slash = true;
+        self
+    }
+
+    /// Follow symlinks that point outside of the served directory.
+    ///
+    /// By default files resolved outside of the served directory
+    /// are not accessible.
+    pub fn follow_symlinks(mut self) -> Self {
+        self.follow_symlinks = true;
+        self
+    }
+
+    /// Specifies whether to use ETag or not.
+    ///
+    /// Default is true.
+    pub fn use_etag(mut self, value: bool) -> Self {
+        self.use_etag = value;
+        self
+    }
+
+    /// Specifies whether to use Last-Modified or not.
+    ///
+    /// Default is true.
+    pub fn use_last_modified(mut self, value: bool) -> Self {
+        self.use_last_modified = value;
+        self
+    }
+
+    /// Add match guard to a files service.
+    pub fn guard<G: Guard + 'static>(mut self, guard: G) -> Self {
+        self.guards.push(Box::new(guard));
+        self
+    }
+}
+
+impl<Err: ErrorRenderer> WebServiceFactory<Err> for Files {
+    fn register(self, config: &mut WebServiceConfig<Err>) {
+        let Files {
+            path,
+            directory,
+            index,
+            show_index,
+            hidden_files,
+            redirect_to_slash,
+            follow_symlinks,
+            use_etag,
+            use_last_modified,
+            renderer,
+            guards,
+        } = self;
+
+        let guards = if guards.is_empty() {
+            None
+        } else {
+            Some(guards)
+        };
+        let rdef = if config.is_root() || !path.is_empty() {
+            ResourceDef::root_prefix(path.as_str())
+        } else {
+            ResourceDef::prefix(path.as_str())
+        };
+
+        let factory = FilesServiceFactory {
+            inner: Rc::new(FilesConfig {
+                directory,
+                index,
+                show_index,
+                hidden_files,
+                redirect_to_slash,
+                use_etag,
+                use_last_modified,
+                renderer,
+            }),
+            default: config.default_service(),
+        };
+        config.register_service(rdef, guards, factory, None)
+    }
+}
+
+struct FilesConfig {
+    directory: PathBuf,
+    index: Option<String>,
+    show_index: bool,
+    hidden_files: bool,
+    redirect_to_slash: bool,
+    follow_symlinks: bool,
+    use_etag: bool,
+    use_last_modified: bool,
+    renderer: Rc<DirectoryRenderer>,
+}
+
+struct FilesServiceFactory<Err: ErrorRenderer> {
+    inner: Rc<FilesConfig>,
+    default: Rc<HttpNewService<Err>>,
+}
+
+impl<Err: ErrorRenderer> ServiceFactory<WebRequest<Err>> for FilesServiceFactory<Err> {
+    type Response = WebResponse;
+    type Error = Err::Container;
+    type InitError = ();
+    type Service = FilesService<Err>;
+
+    async fn create(&self, _: ()) -> Result<Self::Service, Self::InitError> {
+        Ok(FilesService {
+            inner: self.inner.clone(),
+            default: self.default.create(()).await?,
+        })
+    }
+}
+
+/// Static files service
+pub struct FilesService<Err: ErrorRenderer> {
+    inner: Rc<FilesConfig>,
+    default: HttpService<Err>,
+}
+
+impl<Err: ErrorRenderer> Service<WebRequest<Err>> for FilesService<Err> {
+    type Response = WebResponse;
+    type Error = Err::Container;
+
+    async fn call(
+        &self,
+        req: WebRequest<Err>,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        if !matches!(*req.method(), Method::GET | Method::HEAD) {
+            return Ok(req.into_response(
+                HttpResponse::MethodNotAllowed()
+                    .header(header::ALLOW, "GET, HEAD")
+                    .finish(),
+            ));
+        }
+
+        let rel_path =
+            match sanitize_path(req.match_info().unprocessed(), self.inner.hidden_files) {
+                Ok(path) => path,
+                Err(FilesError::ForbiddenSegment(_)) => {
+                    return ctx.call(&self.default, req).await
+                }
+                Err(e) => return Ok(req.render_error(e)),
+            };
+
+        let resolver = Resolver {
+            base: self.inner.directory.clone(),
+            index: self.inner.index.clone(),
+            show_index: self.inner.show_index,
+            hidden_files: self.inner.hidden_files,
+            follow_symlinks: self.inner.follow_symlinks,
+            redirect: self.inner.redirect_to_slash && !req.path().ends_with('/'),
+        };
+        let path = self.inner.directory.join(rel_path);
+
+        let resolved = match spawn_blocking(move || resolver.resolve(path)).await {
+            Ok(Ok(resolved)) => resolved,
+            Ok(Err(e)) if e.kind() == io::ErrorKind::NotFound => Resolved::NotFound,
+            Ok(Err(e)) => return Ok(req.render_error(FilesError::Io(e))),
+            Err(_) => return Ok(req.render_error(FilesError::Canceled)),
+        };
+
+        match resolved {
+            Resolved::NotFound => ctx.call(&self.default, req).await,
+            Resolved::Redirect => {
+                let mut location = format!("{}/", req.path());
+                if let Some(query) = req.uri().query() {
+                    location.push('?');
+                    location.push_str(query);
+                }
+                Ok(req.into_response(
+                    HttpResponse::Found()
+                        .header(header::LOCATION, location)
+                        .finish(),
+                ))
+            }
+            Resolved::File(file) => {
+                let (req, _) = req.into_parts();
+                let res = file
+                    .use_etag(self.inner.use_etag)
+                    .use_last_modified(self.inner.use_last_modified)
+                    .into_response(&req);
+                Ok(WebResponse::new(res, req))
+            }
+            Resolved::Listing(dir) => {
+                let (req, _) = req.into_parts();
+                let res = (self.inner.renderer)(&dir, &req);
+                Ok(WebResponse::new(res, req))
+            }
+        }
+    }
+}
+
+enum Resolved {
+    File(NamedFile),
+    Listing(Directory),
+    Redirect,
+    NotFound,
+}
+
+/// File system lookup, executed on blocking thread pool
+struct Resolver {
+    base: PathBuf,
+    index: Option<String>,
+    show_index: bool,
+    hidden_files: bool,
+    follow_symlinks: bool,
+    redirect: bool,
+}
+
+impl Resolver {
+    fn resolve(self, path: PathBuf) -> io::Result<Resolved> {
+        if !self.is_accessible(&path)? {
+            return Ok(Resolved::NotFound);
+        }
+
+        let md = fs::metadata(&path)?;
+        if !md.is_dir() {
+            return NamedFile::open(path).map(Resolved::File);
+        }
+        if self.index.is_none() && !self.show_index {
+            return Ok(Resolved::NotFound);
+        }
+        if self.redirect {
+            return Ok(Resolved::Redirect);
+        }
+
+        if let Some(ref index) = self.index {
+            let index = path.join(index);
+            let file = match self.is_accessible(&index) {
+                Ok(true) => NamedFile::open(index),
+                Ok(false) => Err(io::ErrorKind::NotFound.into()),
+                Err(e) => Err(e),
+            };
+            match file {
+                Ok(file) => return Ok(Resolved::File(file)),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => (),
+                Err(e) => return Err(e),
+            }
+        }
+
+        if self.show_index {
+            Directory::read(self.base, path, self.hidden_files).map(Resolved::Listing)
+        } else {
+            Ok(Resolved::NotFound)
+        }
+    }
+
+    /// Check that resolved path does not escape base directory via symlinks
+    fn is_accessible(&self, path: &Path) -> io::Result<bool> {
+        if self.follow_symlinks {
+            Ok(true)
+        } else {
+            Ok(path.canonicalize()?.starts_with(&self.base))
+        }
+    }
+}
+
+/// Convert request path to relative file system path
+fn sanitize_path(path: &str, hidden_files: bool) -> Result<PathBuf, FilesError> {
+    let path = percent_decode_str(path)
+        .decode_utf8()
+        .map_err(|_| FilesError::InvalidPath)?;
+
+    let mut buf = PathBuf::new();
+    for segment in path.split('/') {
+        if segment.is_empty() || segment == "." {
+            continue;
+        } else if segment == ".."
+            || (!hidden_files && segment.starts_with('.'))
+            || segment.contains(['\\', '\0'])
+            || (cfg!(windows) && segment.contains(':'))
+        {
+            return Err(FilesError::ForbiddenSegment(segment.to_string()));
+        }
+        buf.push(segment)
+    }
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::StatusCode;
+    use crate::web::test::{call_service, init_service, read_body, TestRequest};
+    use crate::web::{self, App};
+
+    #[test]
+    fn test_sanitize_path() {
+        assert_eq!(
+            sanitize_path("/test/./file.txt", false).unwrap(),
+            PathBuf::from("test/file.txt")
+        );
+        assert_eq!(
+            sanitize_path("/test%20dir/file.txt", false).unwrap(),
+            PathBuf::from("test dir/file.txt")
+        );
+        assert!(sanitize_path("/../file.txt", false).is_err());
+        assert!(sanitize_path("/%2e%2e/file.txt", false).is_err());
+        assert!(sanitize_path("/.hidden", false).is_err());
+        assert!(sanitize_path("/.hidden", true).is_ok());
+        assert!(sanitize_path("/test\\..\\file", false).is_err());
+        assert!(matches!(
+            sanitize_path("/%ff", false),
+            Err(FilesError::InvalidPath)
+        ));
+    }
+
+    #[crate::rt_test]
+    async fn test_files() {
+        let srv = init_service(
+            App::new().service(
+                Files::new("/static", ".")
+                    .show_files_listing()
+                    .redirect_to_slash_directory(),
+            ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/static/Cargo.toml").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(resp.headers().contains_key(header::ETAG));
+        let body = read_body(resp).await;
+        assert_eq!(body, std::fs::read("Cargo.toml").unwrap());
+
+        let req = TestRequest::with_uri("/static/not-exists.txt").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        let req = TestRequest::with_uri("/static/../Cargo.toml").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        let req = TestRequest::with_uri("/static/Cargo.toml")
+            .method(Method::POST)
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+
+        let req = TestRequest::with_uri("/static/tests").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::FOUND);
+        assert_eq!(
+            resp.headers().get(header::LOCATION).unwrap(),
+            "/static/tests/"
+        );
+
+        let req = TestRequest::with_uri("/static/tests/").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = read_body(resp).await;
+        let body = String::from_utf8_lossy(&body);
+        assert!(body.contains("<a href=\"/static/tests/test.png\">test.png</a>"));
+    }
+
+    #[cfg(unix)]
+    #[crate::rt_test]
+    async fn test_files_symlinks() {
+        let dir = std::env::temp_dir().join(format!("ntex-files-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let cargo = Path::new("Cargo.toml").canonicalize().unwrap();
+        std::os::unix::fs::symlink(&cargo, dir.join("outside.txt")).unwrap();
+        std::os::unix::fs::symlink(&cargo, dir.join("index.html")).unwrap();
+
+        let srv = init_service(
+            App::new()
+                .service(Files::new("/static", &dir).index_file("index.html"))
+                .service(Files::new("/follow", &dir).follow_symlinks())
+                .default_service(web::to(|| async { HttpResponse::NoContent() })),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/static/outside.txt").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+        let req = TestRequest::with_uri("/static/").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+        let req = TestRequest::with_uri("/follow/outside.txt").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[crate::rt_test]
+    async fn test_files_index() {
+        let srv = init_service(
+            App::new()
+                .service(Files::new("/", "./tests").index_file("test.png"))
+                .default_service(web::to(|| async { HttpResponse::NoContent() })),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            "image/png"
+        );
+
+        let req = TestRequest::with_uri("/missing").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    }
+}
@@ -251,7 +251,7 @@ impl fmt::Debug for NotGuard {
 /// Http method guard
 #[doc(hidden)]
 #[derive(Debug)]
-pub struct MethodGuard(Method);
+pub struct MethodGuard(pub(super) Method);
 
 impl Guard for MethodGuard {
     fn check(&self, request: &RequestHead) -> bool {
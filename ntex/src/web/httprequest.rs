@@ -133,6 +133,27 @@ impl HttpRequest {
         &mut Rc::get_mut(&mut self.0).unwrap().path
     }
 
+    /// Create copy of the request without payload, extensions and match info.
+    ///
+    /// Copy does not share state with the request, so it could be used for
+    /// rendering responses after request is passed to the service.
+    pub(crate) fn detached(&self) -> HttpRequest {
+        let mut head = Message::<RequestHead>::new();
+        head.uri = self.0.head.uri.clone();
+        head.method = self.0.head.method.clone();
+        head.version = self.0.head.version;
+        head.headers = self.0.head.headers.clone();
+
+        HttpRequest::new(
+            Path::new(head.uri.clone()),
+            head,
+            Payload::None,
+            self.0.rmap.clone(),
+            self.0.app_state.clone(),
+            self.0.pool,
+        )
+    }
+
     /// Request extensions
     #[inline]
     pub fn extensions(&self) -> Ref<'_, Extensions> {
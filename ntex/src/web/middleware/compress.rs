@@ -1,7 +1,7 @@
 //! `Middleware` for compressing response body.
 use std::{cmp, str::FromStr};
 
-use crate::http::encoding::Encoder;
+use crate::http::encoding::{is_supported, Encoder};
 use crate::http::header::{ContentEncoding, ACCEPT_ENCODING};
 use crate::service::{Middleware, Service, ServiceCtx};
 use crate::web::{BodyEncoding, ErrorRenderer, WebRequest, WebResponse};
@@ -131,34 +131,89 @@ impl PartialEq for AcceptEncoding {
     }
 }
 
+/// Encodings in order of server preference, used for `Auto` and `*` negotiation
+const PREFERRED: [ContentEncoding; 4] = [
+    ContentEncoding::Br,
+    ContentEncoding::Zstd,
+    ContentEncoding::Gzip,
+    ContentEncoding::Deflate,
+];
+
 impl AcceptEncoding {
     fn new(tag: &str) -> Option<AcceptEncoding> {
-        let parts: Vec<&str> = tag.split(';').collect();
-        let encoding = match parts.len() {
-            0 => return None,
-            _ => ContentEncoding::from(parts[0]),
+        let mut parts = tag.split(';');
+        let name = parts.next()?.trim();
+        let encoding = if name == "*" {
+            ContentEncoding::Auto
+        } else {
+            match ContentEncoding::from(name) {
+                // unknown encoding
+                ContentEncoding::Identity if !name.eq_ignore_ascii_case("identity") => {
+                    return None
+                }
+                enc => enc,
+            }
         };
-        let quality = match parts.len() {
-            1 => encoding.quality(),
-            _ => f64::from_str(parts[1]).unwrap_or(0.0),
+        let quality = match parts.next() {
+            None => encoding.quality(),
+            Some(q) => {
+                let q = q.trim();
+                let q = q
+                    .strip_prefix("q=")
+                    .or_else(|| q.strip_prefix("Q="))
+                    .unwrap_or(q);
+                f64::from_str(q.trim()).unwrap_or(0.0)
+            }
         };
         Some(AcceptEncoding { encoding, quality })
     }
 
-    /// Parse a raw Accept-Encoding header value into an ordered list.
+    /// Parse a raw Accept-Encoding header value and select
+    /// most preferable encoding supported by server.
     fn parse(raw: &str, encoding: ContentEncoding) -> ContentEncoding {
-        let mut encodings: Vec<_> = raw
-            .replace(' ', "")
-            .split(',')
-            .map(AcceptEncoding::new)
-            .collect();
-        encodings.sort();
-
-        for enc in encodings.into_iter().flatten() {
-            if encoding == ContentEncoding::Auto {
-                return enc.encoding;
-            } else if encoding == enc.encoding {
-                return encoding;
+        let mut encodings: Vec<_> =
+            raw.split(',').filter_map(AcceptEncoding::new).collect();
+
+        // sort by client's q-value, on tie prefer server's default quality
+        encodings.sort_by(|a, b| {
+            a.cmp(b).then_with(|| {
+                b.encoding
+                    .quality()
+                    .partial_cmp(&a.encoding.quality())
+                    .unwrap_or(cmp::Ordering::Equal)
+            })
+        });
+
+        // "q=0" means "not acceptable"
+        let rejected = |enc: ContentEncoding| {
+            encodings
+                .iter()
+                .any(|item| item.encoding == enc && item.quality <= 0.0)
+        };
+        let acceptable = |enc: ContentEncoding| is_supported(enc) && !rejected(enc);
+
+        for enc in encodings.iter().filter(|item| item.quality > 0.0) {
+            let selected = if enc.encoding == ContentEncoding::Auto {
+                // wildcard, any encoding is acceptable
+                if encoding == ContentEncoding::Auto {
+                    PREFERRED.iter().copied().find(|enc| acceptable(*enc))
+                } else if acceptable(encoding) {
+                    Some(encoding)
+                } else {
+                    None
+                }
+            } else if enc.encoding == ContentEncoding::Identity {
+                return ContentEncoding::Identity;
+            } else if (encoding == ContentEncoding::Auto || encoding == enc.encoding)
+                && is_supported(enc.encoding)
+            {
+                Some(enc.encoding)
+            } else {
+                None
+            };
+
+            if let Some(enc) = selected {
+                return enc;
             }
         }
         ContentEncoding::Identity
@@ -234,4 +289,60 @@ mod tests {
         let accepting_encoding = AcceptEncoding::new("gzip;q=abc").unwrap();
         assert_eq!(accepting_encoding.quality, 0.0);
     }
+
+    #[test]
+    fn test_accepting_encoding_from_tag_with_q_value() {
+        let accepting_encoding = AcceptEncoding::new("gzip; q=0.3").unwrap();
+        assert_eq!(accepting_encoding.encoding, ContentEncoding::Gzip);
+        assert_eq!(accepting_encoding.quality, 0.3);
+
+        let accepting_encoding = AcceptEncoding::new("*;q=0.1").unwrap();
+        assert_eq!(accepting_encoding.encoding, ContentEncoding::Auto);
+        assert!(AcceptEncoding::new("compress").is_none());
+    }
+
+    #[test]
+    fn test_parse_accept_encoding() {
+        let auto = ContentEncoding::Auto;
+        assert_eq!(
+            AcceptEncoding::parse("gzip, deflate", auto),
+            ContentEncoding::Gzip
+        );
+        assert_eq!(
+            AcceptEncoding::parse("gzip;q=0.5, deflate;q=0.8", auto),
+            ContentEncoding::Deflate
+        );
+        assert_eq!(
+            AcceptEncoding::parse("gzip;q=0, deflate;q=0", auto),
+            ContentEncoding::Identity
+        );
+        assert_eq!(
+            AcceptEncoding::parse("identity;q=1, gzip;q=0.5", auto),
+            ContentEncoding::Identity
+        );
+        assert_eq!(
+            AcceptEncoding::parse("*, gzip;q=0", ContentEncoding::Deflate),
+            ContentEncoding::Deflate
+        );
+        assert_eq!(
+            AcceptEncoding::parse("gzip", ContentEncoding::Deflate),
+            ContentEncoding::Identity
+        );
+
+        #[cfg(feature = "brotli")]
+        assert_eq!(
+            AcceptEncoding::parse("gzip, deflate, br", auto),
+            ContentEncoding::Br
+        );
+        #[cfg(not(feature = "brotli"))]
+        assert_eq!(
+            AcceptEncoding::parse("gzip, deflate, br", auto),
+            ContentEncoding::Gzip
+        );
+        #[cfg(feature = "zstd")]
+        assert_eq!(
+            AcceptEncoding::parse("gzip, zstd;q=1.0, br;q=0.5", auto),
+            ContentEncoding::Zstd
+        );
+    }
 }
@@ -0,0 +1,678 @@
+//! Cross-origin resource sharing (CORS) middleware
+use std::{fmt, rc::Rc};
+
+use crate::http::header::{self, HeaderMap, HeaderName, HeaderValue};
+use crate::http::{Method, RequestHead, StatusCode};
+use crate::service::{Middleware, Service, ServiceCtx};
+use crate::util::HashSet;
+use crate::web::{ErrorRenderer, HttpResponse, WebRequest, WebResponse};
+
+/// `Middleware` for Cross-origin resource sharing support.
+///
+/// Middleware answers preflight `OPTIONS` requests and adds
+/// `Access-Control-*` headers to responses for allowed origins.
+/// Requests with not allowed origin are passed to the inner service
+/// without cors headers, so browser rejects response. Preflight requests
+/// with not allowed origin, method or headers are rejected with
+/// `403 Forbidden` response.
+///
+/// By default no origins are allowed. Middleware could be registered
+/// with `App`, `Scope` or `Resource` via `wrap()` method.
+///
+/// ```rust
+/// use ntex::http::{header, Method};
+/// use ntex::web::{self, middleware::Cors, App, HttpResponse};
+///
+/// fn main() {
+///     let app = App::new().service(
+///         web::resource("/index.html")
+///             .wrap(
+///                 Cors::new()
+///                     .allowed_origin("https://www.rust-lang.org")
+///                     .allowed_methods([Method::GET, Method::POST])
+///                     .allowed_header(header::CONTENT_TYPE)
+///                     .max_age(3600),
+///             )
+///             .route(web::get().to(|| async { HttpResponse::Ok() })),
+///     );
+/// }
+/// ```
+#[derive(Clone)]
+pub struct Cors {
+    inner: Inner,
+}
+
+#[derive(Clone)]
+struct Inner {
+    any_origin: bool,
+    origins: HashSet<String>,
+    origin_fns: Vec<Rc<dyn Fn(&HeaderValue, &RequestHead) -> bool>>,
+    methods: HashSet<Method>,
+    methods_value: HeaderValue,
+    any_header: bool,
+    headers: HashSet<HeaderName>,
+    expose_headers: Option<HeaderValue>,
+    max_age: Option<HeaderValue>,
+    credentials: bool,
+    preflight: bool,
+}
+
+impl Default for Cors {
+    fn default() -> Self {
+        let methods = [
+            Method::GET,
+            Method::HEAD,
+            Method::POST,
+            Method::PUT,
+            Method::PATCH,
+            Method::DELETE,
+            Method::OPTIONS,
+        ];
+        Cors {
+            inner: Inner {
+                any_origin: false,
+                origins: HashSet::default(),
+                origin_fns: Vec::new(),
+                methods_value: join(methods.iter().map(|m| m.as_str())),
+                methods: methods.into_iter().collect(),
+                any_header: false,
+                headers: HashSet::default(),
+                expose_headers: None,
+                max_age: None,
+                credentials: false,
+                preflight: true,
+            },
+        }
+    }
+}
+
+impl Cors {
+    /// Construct `Cors` middleware, no origins are allowed.
+    pub fn new() -> Cors {
+        Cors::default()
+    }
+
+    /// Allow requests from any origin.
+    ///
+    /// If credentials are not supported, `Access-Control-Allow-Origin: *`
+    /// header is sent, otherwise request's origin is echoed back.
+    pub fn allow_any_origin(mut self) -> Self {
+        self.inner.any_origin = true;
+        self
+    }
+
+    /// Add an origin that is allowed to make requests.
+    ///
+    /// Origin must be in `scheme://host[:port]` form, for example
+    /// `https://www.rust-lang.org`.
+    ///
+    /// Panics if origin is not a valid header value.
+    pub fn allowed_origin(mut self, origin: &str) -> Self {
+        if HeaderValue::from_str(origin).is_err() || origin == "*" {
+            panic!("Cannot use origin: {:?}", origin);
+        }
+        self.inner.origins.insert(origin.to_ascii_lowercase());
+        self
+    }
+
+    /// Add a predicate that decides if origin is allowed to make requests.
+    pub fn allowed_origin_fn<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&HeaderValue, &RequestHead) -> bool + 'static,
+    {
+        self.inner.origin_fns.push(Rc::new(f));
+        self
+    }
+
+    /// Set a list of methods which allowed origins can perform.
+    ///
+    /// By default `GET`, `HEAD`, `POST`, `PUT`, `PATCH`, `DELETE` and
+    /// `OPTIONS` methods are allowed.
+    ///
+    /// Panics if method is not valid.
+    pub fn allowed_methods<U, M>(mut self, methods: U) -> Self
+    where
+        U: IntoIterator<Item = M>,
+        Method: TryFrom<M>,
+    {
+        let methods: Vec<Method> = methods
+            .into_iter()
+            .map(|m| Method::try_from(m).unwrap_or_else(|_| panic!("Cannot create method")))
+            .collect();
+        self.inner.methods_value = join(methods.iter().map(|m| m.as_str()));
+        self.inner.methods = methods.into_iter().collect();
+        self
+    }
+
+    /// Add a request header which allowed origins can send.
+    ///
+    /// Panics if header name is not valid.
+    pub fn allowed_header<H>(mut self, header: H) -> Self
+    where
+        HeaderName: TryFrom<H>,
+    {
+        match HeaderName::try_from(header) {
+            Ok(name) => {
+                self.inner.headers.insert(name);
+            }
+            Err(_) => panic!("Cannot create header name"),
+        }
+        self
+    }
+
+    /// Add a list of request headers which allowed origins can send.
+    ///
+    /// Panics if header name is not valid.
+    pub fn allowed_headers<U, H>(self, headers: U) -> Self
+    where
+        U: IntoIterator<Item = H>,
+        HeaderName: TryFrom<H>,
+    {
+        headers
+            .into_iter()
+            .fold(self, |cors, header| cors.allowed_header(header))
+    }
+
+    /// Allow any request header.
+    pub fn allow_any_header(mut self) -> Self {
+        self.inner.any_header = true;
+        self
+    }
+
+    /// Set a list of response headers which browser can expose to the client.
+    ///
+    /// Panics if header name is not valid.
+    pub fn expose_headers<U, H>(mut self, headers: U) -> Self
+    where
+        U: IntoIterator<Item = H>,
+        HeaderName: TryFrom<H>,
+    {
+        let headers: Vec<HeaderName> = headers
+            .into_iter()
+            .map(|h| {
+                HeaderName::try_from(h)
+                    .unwrap_or_else(|_| panic!("Cannot create header name"))
+            })
+            .collect();
+        self.inner.expose_headers = Some(join(headers.iter().map(|h| h.as_str())));
+        self
+    }
+
+    /// Set how long, in seconds, preflight response could be cached.
+    pub fn max_age(mut self, max_age: usize) -> Self {
+        self.inner.max_age = Some(HeaderValue::from(max_age));
+        self
+    }
+
+    /// Allow requests with credentials, such as cookies or authorization headers.
+    pub fn supports_credentials(mut self) -> Self {
+        self.inner.credentials = true;
+        self
+    }
+
+    /// Do not handle preflight requests, pass them to the inner service.
+    pub fn disable_preflight(mut self) -> Self {
+        self.inner.preflight = false;
+        self
+    }
+}
+
+impl fmt::Debug for Cors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cors")
+            .field("any_origin", &self.inner.any_origin)
+            .field("origins", &self.inner.origins)
+            .field("methods", &self.inner.methods_value)
+            .field("any_header", &self.inner.any_header)
+            .field("headers", &self.inner.headers)
+            .field("credentials", &self.inner.credentials)
+            .finish()
+    }
+}
+
+impl<S> Middleware<S> for Cors {
+    type Service = CorsMiddleware<S>;
+
+    fn create(&self, service: S) -> Self::Service {
+        CorsMiddleware {
+            service,
+            inner: Rc::new(self.inner.clone()),
+        }
+    }
+}
+
+impl Inner {
+    fn validate_origin(&self, origin: &HeaderValue, head: &RequestHead) -> bool {
+        if self.any_origin {
+            return true;
+        }
+        if let Ok(s) = origin.to_str() {
+            if self.origins.contains(&s.to_ascii_lowercase()) {
+                return true;
+            }
+        }
+        self.origin_fns.iter().any(|f| f(origin, head))
+    }
+
+    fn allow_origin(&self, origin: &HeaderValue) -> HeaderValue {
+        if self.wildcard() {
+            HeaderValue::from_static("*")
+        } else {
+            origin.clone()
+        }
+    }
+
+    /// Response does not depend on request's origin only
+    /// if `Access-Control-Allow-Origin: *` is sent
+    fn wildcard(&self) -> bool {
+        self.any_origin && !self.credentials
+    }
+
+    fn vary(&self, headers: &mut HeaderMap) {
+        if !self.wildcard() {
+            headers.append(header::VARY, HeaderValue::from_static("Origin"));
+        }
+    }
+
+    /// Add cors headers to the response for allowed origin
+    fn add_headers(&self, origin: &HeaderValue, headers: &mut HeaderMap) {
+        headers.insert(
+            header::ACCESS_CONTROL_ALLOW_ORIGIN,
+            self.allow_origin(origin),
+        );
+        if self.credentials {
+            headers.insert(
+                header::ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                HeaderValue::from_static("true"),
+            );
+        }
+        if let Some(ref expose) = self.expose_headers {
+            headers.insert(header::ACCESS_CONTROL_EXPOSE_HEADERS, expose.clone());
+        }
+    }
+
+    fn forbidden(&self) -> HttpResponse {
+        let mut res = HttpResponse::Forbidden().finish();
+        self.vary(res.headers_mut());
+        res
+    }
+
+    fn preflight(&self, head: &RequestHead, origin: &HeaderValue) -> HttpResponse {
+        let method = head
+            .headers
+            .get(header::ACCESS_CONTROL_REQUEST_METHOD)
+            .and_then(|v| Method::from_bytes(v.as_bytes()).ok());
+        match method {
+            Some(ref method) if self.methods.contains(method) => (),
+            _ => return self.forbidden(),
+        }
+
+        let req_headers = head.headers.get(header::ACCESS_CONTROL_REQUEST_HEADERS);
+        if let Some(req_headers) = req_headers {
+            if !self.any_header {
+                let valid = req_headers.to_str().is_ok_and(|hdrs| {
+                    hdrs.split(',')
+                        .map(str::trim)
+                        .filter(|h| !h.is_empty())
+                        .all(|h| {
+                            HeaderName::try_from(h).is_ok_and(|h| self.headers.contains(&h))
+                        })
+                });
+                if !valid {
+                    return self.forbidden();
+                }
+            }
+        }
+
+        let mut res = HttpResponse::build(StatusCode::OK);
+        res.header(
+            header::ACCESS_CONTROL_ALLOW_ORIGIN,
+            self.allow_origin(origin),
+        )
+        .header(
+            header::ACCESS_CONTROL_ALLOW_METHODS,
+            self.methods_value.clone(),
+        );
+        if let Some(req_headers) = req_headers {
+            // request headers are validated, echo them back
+            res.header(header::ACCESS_CONTROL_ALLOW_HEADERS, req_headers.clone());
+        }
+        if self.credentials {
+            res.header(
+                header::ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                HeaderValue::from_static("true"),
+            );
+        }
+        if let Some(ref max_age) = self.max_age {
+            res.header(header::ACCESS_CONTROL_MAX_AGE, max_age.clone());
+        }
+        res.header(
+            header::VARY,
+            HeaderValue::from_static(
+                "Origin, Access-Control-Request-Method, Access-Control-Request-Headers",
+            ),
+        );
+        res.finish()
+    }
+}
+
+#[doc(hidden)]
+pub struct CorsMiddleware<S> {
+    service: S,
+    inner: Rc<Inner>,
+}
+
+impl<S> fmt::Debug for CorsMiddleware<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CorsMiddleware")
+            .field("any_origin", &self.inner.any_origin)
+            .field("origins", &self.inner.origins)
+            .finish()
+    }
+}
+
+impl<S, E> Service<WebRequest<E>> for CorsMiddleware<S>
+where
+    S: Service<WebRequest<E>, Response = WebResponse>,
+    S::Error: Into<E::Container>,
+    E: ErrorRenderer,
+{
+    type Response = WebResponse;
+    type Error = S::Error;
+
+    crate::forward_poll!(service);
+    crate::forward_ready!(service);
+    crate::forward_shutdown!(service);
+
+    async fn call(
+        &self,
+        req: WebRequest<E>,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        let is_preflight = self.inner.preflight
+            && req.method() == Method::OPTIONS
+            && req
+                .headers()
+                .contains_key(header::ACCESS_CONTROL_REQUEST_METHOD);
+
+        let origin = match req.headers().get(header::ORIGIN) {
+            Some(origin) if self.inner.validate_origin(origin, req.head()) => {
+                Some(origin.clone())
+            }
+            Some(_) if is_preflight => {
+                let res = self.inner.forbidden();
+                return Ok(req.into_response(res));
+            }
+            _ => None,
+        };
+
+        if let Some(ref origin) = origin {
+            if is_preflight {
+                let res = self.inner.preflight(req.head(), origin);
+                return Ok(req.into_response(res));
+            }
+        }
+
+        let mut res = if let Some(ref origin) = origin {
+            // errors of inner service are rendered here, so browser
+            // could read error responses
+            let http_req = req.detached_request();
+            let mut res = match ctx.call(&self.service, req).await {
+                Ok(res) => res,
+                Err(err) => WebResponse::from_err::<E, _>(err, http_req),
+            };
+            self.inner.add_headers(origin, res.headers_mut());
+            res
+        } else {
+            ctx.call(&self.service, req).await?
+        };
+
+        // responses for allowed and not allowed origins differ,
+        // caches must not mix them up
+        self.inner.vary(res.headers_mut());
+        Ok(res)
+    }
+}
+
+fn join<'a, I: Iterator<Item = &'a str>>(items: I) -> HeaderValue {
+    let value = items.collect::<Vec<_>>().join(", ");
+    HeaderValue::try_from(value).expect("Cannot create header value")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::{IntoService, Pipeline};
+    use crate::util::lazy;
+    use crate::web::test::{self, ok_service, TestRequest};
+    use crate::web::{self, App, DefaultError, Error};
+
+    #[crate::rt_test]
+    async fn test_not_allowed_origin() {
+        let mw = Pipeline::new(
+            Cors::new()
+                .allowed_origin("https://www.example.com")
+                .create(ok_service()),
+        )
+        .bind();
+        assert!(lazy(|cx| mw.poll_ready(cx).is_ready()).await);
+
+        let req = TestRequest::default()
+            .header(header::ORIGIN, "https://www.unknown.com")
+            .to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(!resp
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+        assert_eq!(resp.headers().get(header::VARY).unwrap(), "Origin");
+
+        let req = TestRequest::default().to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers().get(header::VARY).unwrap(), "Origin");
+
+        let req = TestRequest::default()
+            .method(Method::OPTIONS)
+            .header(header::ORIGIN, "https://www.unknown.com")
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "GET")
+            .to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+        assert_eq!(resp.headers().get(header::VARY).unwrap(), "Origin");
+
+        let mw = Pipeline::new(Cors::new().allow_any_origin().create(ok_service()));
+        let req = TestRequest::default().to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+        assert!(!resp.headers().contains_key(header::VARY));
+    }
+
+    #[crate::rt_test]
+    async fn test_preflight() {
+        let mw = Pipeline::new(
+            Cors::new()
+                .allowed_origin("https://www.example.com")
+                .allowed_methods(["GET", "POST"])
+                .allowed_headers([header::CONTENT_TYPE, header::AUTHORIZATION])
+                .supports_credentials()
+                .max_age(3600)
+                .create(ok_service()),
+        );
+
+        let req = TestRequest::default()
+            .method(Method::OPTIONS)
+            .header(header::ORIGIN, "https://www.example.com")
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+            .header(
+                header::ACCESS_CONTROL_REQUEST_HEADERS,
+                "content-type, authorization",
+            )
+            .to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let headers = resp.headers();
+        assert_eq!(
+            headers.get(header::ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(),
+            "https://www.example.com"
+        );
+        assert_eq!(
+            headers.get(header::ACCESS_CONTROL_ALLOW_METHODS).unwrap(),
+            "GET, POST"
+        );
+        assert_eq!(
+            headers.get(header::ACCESS_CONTROL_ALLOW_HEADERS).unwrap(),
+            "content-type, authorization"
+        );
+        assert_eq!(
+            headers
+                .get(header::ACCESS_CONTROL_ALLOW_CREDENTIALS)
+                .unwrap(),
+            "true"
+        );
+        assert_eq!(headers.get(header::ACCESS_CONTROL_MAX_AGE).unwrap(), "3600");
+
+        let req = TestRequest::default()
+            .method(Method::OPTIONS)
+            .header(header::ORIGIN, "https://www.example.com")
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "DELETE")
+            .to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+        assert_eq!(resp.headers().get(header::VARY).unwrap(), "Origin");
+
+        let req = TestRequest::default()
+            .method(Method::OPTIONS)
+            .header(header::ORIGIN, "https://www.example.com")
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "GET")
+            .header(header::ACCESS_CONTROL_REQUEST_HEADERS, "x-custom")
+            .to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[crate::rt_test]
+    async fn test_actual_request() {
+        let srv =
+            |req: WebRequest<DefaultError>| async move {
+                Ok::<_, Error>(req.into_response(
+                    HttpResponse::Ok().header(header::VARY, "Accept").finish(),
+                ))
+            };
+        let mw = Pipeline::new(
+            Cors::new()
+                .allowed_origin_fn(|origin, _| origin.as_bytes().ends_with(b".example.com"))
+                .expose_headers(["x-version"])
+                .create(srv.into_service()),
+        );
+
+        let req = TestRequest::default()
+            .header(header::ORIGIN, "https://api.example.com")
+            .to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+        let headers = resp.headers();
+        assert_eq!(
+            headers.get(header::ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(),
+            "https://api.example.com"
+        );
+        assert_eq!(
+            headers.get(header::ACCESS_CONTROL_EXPOSE_HEADERS).unwrap(),
+            "x-version"
+        );
+        assert_eq!(headers.get_all(header::VARY).count(), 2);
+
+        let mw = Pipeline::new(Cors::new().allow_any_origin().create(ok_service()));
+        let req = TestRequest::default()
+            .header(header::ORIGIN, "https://www.example.com")
+            .to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+        assert_eq!(
+            resp.headers()
+                .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+                .unwrap(),
+            "*"
+        );
+    }
+
+    #[crate::rt_test]
+    async fn test_error_response() {
+        let srv = |_: WebRequest<DefaultError>| async move {
+            Err::<WebResponse, _>(Error::from(
+                web::error::ErrorBadRequest::<_, DefaultError>("bad request"),
+            ))
+        };
+        let mw = Pipeline::new(
+            Cors::new()
+                .allowed_origin("https://www.example.com")
+                .create(srv.into_service()),
+        );
+
+        let req = TestRequest::default()
+            .header(header::ORIGIN, "https://www.example.com")
+            .to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            resp.headers()
+                .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+                .unwrap(),
+            "https://www.example.com"
+        );
+        assert_eq!(resp.headers().get(header::VARY).unwrap(), "Origin");
+
+        let req = TestRequest::default().to_srv_request();
+        assert!(mw.call(req).await.is_err());
+    }
+
+    #[crate::rt_test]
+    async fn test_clone() {
+        let cors = Cors::new().allowed_origin("https://www.example.com");
+        let _copy = cors.clone();
+        let mw = Pipeline::new(
+            cors.allowed_origin("https://api.example.com")
+                .create(ok_service()),
+        );
+
+        let req = TestRequest::default()
+            .header(header::ORIGIN, "https://api.example.com")
+            .to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+        assert!(resp
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+    }
+
+    #[crate::rt_test]
+    async fn test_scope() {
+        let srv = test::init_service(
+            App::new().service(
+                web::scope("/api")
+                    .wrap(Cors::new().allowed_origin("https://www.example.com"))
+                    .route(
+                        "/index.html",
+                        web::get().to(|| async { HttpResponse::Ok() }),
+                    ),
+            ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/api/index.html")
+            .method(Method::OPTIONS)
+            .header(header::ORIGIN, "https://www.example.com")
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "GET")
+            .to_request();
+        let resp = test::call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers()
+                .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+                .unwrap(),
+            "https://www.example.com"
+        );
+    }
+
+    #[crate::rt_test]
+    #[should_panic(expected = "Cannot use origin")]
+    async fn test_invalid_origin() {
+        let _ = Cors::new().allowed_origin("*");
+    }
+}
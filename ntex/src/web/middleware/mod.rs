@@ -10,3 +10,6 @@ pub use self::logger::Logger;
 
 mod defaultheaders;
 pub use self::defaultheaders::DefaultHeaders;
+
+mod cors;
+pub use self::cors::Cors;
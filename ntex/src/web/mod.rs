@@ -49,7 +49,7 @@
 //! * Streaming and pipelining
 //! * Keep-alive and slow requests handling
 //! * *WebSockets* server/client
-//! * Transparent content compression/decompression (br, gzip, deflate)
+//! * Transparent content compression/decompression (br, zstd, gzip, deflate)
 //! * Configurable request routing
 //! * SSL support with OpenSSL or `rustls`
 //! * Middlewares
@@ -59,6 +59,10 @@
 //!
 //! * `cookie` - enables http cookie support
 //! * `compress` - enables content encoding compression support
+//! * `files` - enables static files support
+//! * `session` - enables session support
+//! * `brotli` - enables brotli content encoding support
+//! * `zstd` - enables zstd content encoding support
 //! * `openssl` - enables ssl support via `openssl` crate
 //! * `rustls` - enables ssl support via `rustls` crate
 
@@ -68,11 +72,17 @@ mod config;
 pub mod error;
 mod error_default;
 mod extract;
+#[cfg(feature = "files")]
+pub mod files;
 pub mod guard;
 mod handler;
 mod httprequest;
 mod info;
 pub mod middleware;
+#[cfg(feature = "negotiated")]
+mod negotiated;
+#[cfg(feature = "openapi")]
+pub mod openapi;
 mod request;
 mod resource;
 mod responder;
@@ -82,6 +92,9 @@ mod route;
 mod scope;
 mod server;
 mod service;
+#[cfg(feature = "session")]
+pub mod session;
+pub mod sse;
 pub mod stack;
 pub mod test;
 pub mod types;
@@ -112,15 +125,19 @@ pub use self::error::{
 pub use self::extract::FromRequest;
 pub use self::handler::Handler;
 pub use self::httprequest::HttpRequest;
+#[cfg(feature = "negotiated")]
+pub use self::negotiated::{Negotiated, NegotiatedConfig};
 pub use self::request::WebRequest;
 pub use self::resource::Resource;
-pub use self::responder::Responder;
+pub use self::responder::{Ranged, Responder};
 pub use self::response::WebResponse;
 pub use self::route::Route;
 pub use self::scope::Scope;
 pub use self::server::HttpServer;
 pub use self::service::WebServiceFactory;
 pub use self::util::*;
+#[cfg(feature = "negotiated")]
+pub use erased_serde;
 
 pub mod dev {
     //! The `ntex::web` prelude for library developers
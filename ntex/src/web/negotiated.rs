@@ -0,0 +1,223 @@
+//! Content negotiation responder
+use std::{error::Error, fmt, sync::Arc};
+
+use erased_serde::Serialize as ErasedSerialize;
+use serde::Serialize;
+
+use crate::http::header::{self, Accept, HeaderValue};
+use crate::http::Response;
+use crate::util::Bytes;
+
+use super::error::ErrorRenderer;
+use super::httprequest::HttpRequest;
+use super::responder::Responder;
+
+type SerializeFn =
+    dyn Fn(&dyn ErasedSerialize) -> Result<Bytes, Box<dyn Error>> + Send + Sync;
+type Format = (String, HeaderValue, Arc<SerializeFn>);
+
+/// Serializers registry for [`Negotiated`] responder.
+///
+/// Registry is stored in application state with `App::state()` method.
+/// If registry is not configured, only `application/json` representation
+/// is available. Serializers get type-erased value, [`&dyn erased_serde::Serialize`]
+/// implements `serde::Serialize` and could be passed to any serde serializer.
+///
+/// [`&dyn erased_serde::Serialize`]: super::erased_serde::Serialize
+///
+/// ```rust
+/// use ntex::web::{self, App, Negotiated, NegotiatedConfig};
+///
+/// async fn index() -> Negotiated<Vec<u32>> {
+///     Negotiated(vec![1, 2, 3])
+/// }
+///
+/// let app = App::new()
+///     .state(
+///         NegotiatedConfig::default()
+///             .format("text/plain", |value| serde_json::to_string_pretty(value)),
+///     )
+///     .route("/", web::get().to(index));
+/// ```
+#[derive(Clone)]
+pub struct NegotiatedConfig {
+    formats: Vec<Format>,
+}
+
+impl NegotiatedConfig {
+    /// Create registry without representations
+    pub fn empty() -> Self {
+        NegotiatedConfig {
+            formats: Vec::new(),
+        }
+    }
+
+    /// Register serializer for media type.
+    ///
+    /// If several media types are equally acceptable, representation
+    /// registered first is selected. Panics if media type is not
+    /// a valid header value.
+    pub fn format<F, B, E>(mut self, media_type: &str, f: F) -> Self
+    where
+        F: Fn(&dyn ErasedSerialize) -> Result<B, E> + Send + Sync + 'static,
+        B: Into<Bytes>,
+        E: Error + 'static,
+    {
+        let value = HeaderValue::from_str(media_type).expect("Invalid media type");
+        self.formats.push((
+            media_type.to_string(),
+            value,
+            Arc::new(move |val: &dyn ErasedSerialize| {
+                f(val)
+                    .map(Into::into)
+                    .map_err(|e| Box::new(e) as Box<dyn Error>)
+            }),
+        ));
+        self
+    }
+
+    /// Select representation with highest quality
+    fn select(&self, accept: Option<&Accept>) -> Option<&Format> {
+        if let Some(accept) = accept {
+            let mut selected = None;
+            for format in &self.formats {
+                let quality = accept.quality(&format.0);
+                if quality > 0 && selected.map(|(q, _)| quality > q).unwrap_or(true) {
+                    selected = Some((quality, format));
+                }
+            }
+            selected.map(|(_, format)| format)
+        } else {
+            self.formats.first()
+        }
+    }
+}
+
+impl Default for NegotiatedConfig {
+    /// Registry with `application/json` representation
+    fn default() -> Self {
+        NegotiatedConfig::empty().format("application/json", |v| serde_json::to_vec(v))
+    }
+}
+
+impl fmt::Debug for NegotiatedConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NegotiatedConfig")
+            .field(
+                "formats",
+                &self.formats.iter().map(|f| &f.0).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+/// Serializes value to a representation selected by `Accept` request header.
+///
+/// Representations are configured with [`NegotiatedConfig`].
+/// If none of representations is acceptable, `406 Not Acceptable`
+/// response is returned. Missing or malformed `Accept` header selects
+/// first registered representation. Response always contains
+/// `Vary: Accept` header.
+///
+/// ```rust
+/// use ntex::web::Negotiated;
+///
+/// #[derive(serde::Serialize)]
+/// struct MyObj {
+///     name: String,
+/// }
+///
+/// async fn index() -> Negotiated<MyObj> {
+///     Negotiated(MyObj { name: "name".to_string() })
+/// }
+/// # fn main() {}
+/// ```
+#[derive(Debug)]
+pub struct Negotiated<T>(pub T);
+
+impl<T: Serialize, Err: ErrorRenderer> Responder<Err> for Negotiated<T> {
+    async fn respond_to(self, req: &HttpRequest) -> Response {
+        let default;
+        let config = if let Some(config) = req.app_state::<NegotiatedConfig>() {
+            config
+        } else {
+            default = NegotiatedConfig::default();
+            &default
+        };
+        let accept = req.headers().typed_get::<Accept>().ok();
+
+        let mut res = if let Some((_, ctype, serializer)) = config.select(accept.as_ref()) {
+            match serializer(&self.0) {
+                Ok(body) => {
+                    let mut res = Response::Ok().body(body);
+                    res.headers_mut()
+                        .insert(header::CONTENT_TYPE, ctype.clone());
+                    res
+                }
+                Err(e) => {
+                    log::error!("Cannot serialize negotiated response: {}", e);
+                    Response::InternalServerError().finish()
+                }
+            }
+        } else {
+            Response::NotAcceptable().finish()
+        };
+        res.headers_mut()
+            .insert(header::VARY, HeaderValue::from_static("Accept"));
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::header::CONTENT_TYPE;
+    use crate::http::StatusCode;
+    use crate::web::test::TestRequest;
+    use crate::web::DefaultError;
+
+    #[crate::rt_test]
+    async fn test_negotiated_responder() {
+        let req = TestRequest::default().to_http_request();
+        let res = Responder::<DefaultError>::respond_to(Negotiated(vec![1, 2]), &req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.get_body_ref(), b"[1,2]");
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), "application/json");
+        assert_eq!(res.headers().get(header::VARY).unwrap(), "Accept");
+
+        let config = NegotiatedConfig::default().format("text/plain", |v| {
+            serde_json::to_string(v).map(|s| format!("plain {}", s))
+        });
+        assert!(format!("{:?}", config).contains("text/plain"));
+
+        let req = TestRequest::default()
+            .header(header::ACCEPT, "application/json;q=0.5, text/*")
+            .state(config.clone())
+            .to_http_request();
+        let res = Responder::<DefaultError>::respond_to(Negotiated(vec![1, 2]), &req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.get_body_ref(), b"plain [1,2]");
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), "text/plain");
+
+        let req = TestRequest::default()
+            .header(header::ACCEPT, "*/*")
+            .state(config.clone())
+            .to_http_request();
+        let res = Responder::<DefaultError>::respond_to(Negotiated(vec![1, 2]), &req).await;
+        assert_eq!(res.get_body_ref(), b"[1,2]");
+
+        let req = TestRequest::default()
+            .header(header::ACCEPT, "text/html, application/json;q=0")
+            .state(config)
+            .to_http_request();
+        let res = Responder::<DefaultError>::respond_to(Negotiated(vec![1, 2]), &req).await;
+        assert_eq!(res.status(), StatusCode::NOT_ACCEPTABLE);
+        assert_eq!(res.headers().get(header::VARY).unwrap(), "Accept");
+
+        // value is serialized directly, without intermediate json value
+        let req = TestRequest::default().to_http_request();
+        let res = Responder::<DefaultError>::respond_to(Negotiated(u128::MAX), &req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.get_body_ref(), u128::MAX.to_string().as_bytes());
+    }
+}
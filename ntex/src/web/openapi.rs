@@ -0,0 +1,880 @@
+//! OpenAPI document generation
+//!
+//! Routes are annotated with [`Operation`] descriptions via
+//! [`Route::to_documented()`](super::Route::to_documented), operation parameters
+//! and request body are derived from handler's extractors. Annotated routes are
+//! recorded in the application's resource map and [`OpenApi`] service renders
+//! them as an OpenAPI 3.1 document during application startup.
+//!
+//! ```rust
+//! use ntex::web::{self, openapi, types::Json, types::Path, App, HttpResponse};
+//!
+//! #[derive(serde::Serialize, serde::Deserialize)]
+//! struct User {
+//!     name: String,
+//! }
+//!
+//! impl openapi::Schema for User {
+//!     fn schema() -> serde_json::Value {
+//!         openapi::Object::new().property::<String>("name").into()
+//!     }
+//! }
+//!
+//! async fn get_user(id: Path<u32>) -> Json<User> {
+//!     Json(User { name: id.to_string() })
+//! }
+//!
+//! let app = App::new()
+//!     .service(openapi::OpenApi::new("/openapi.json").title("Users").version("1.0"))
+//!     .service(
+//!         web::resource("/users/{id}").route(
+//!             web::get().to_documented(
+//!                 get_user,
+//!                 openapi::Operation::new()
+//!                     .summary("Get user")
+//!                     .response::<Json<User>>(ntex::http::StatusCode::OK, "User"),
+//!             ),
+//!         ),
+//!     );
+//! ```
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::{cell::RefCell, rc::Rc};
+
+use serde_json::{json, Map, Value};
+
+use crate::http::{header::TypedHeader, Method, StatusCode};
+use crate::util::Bytes;
+
+use super::dev::{ResourceMap, WebServiceConfig, WebServiceFactory};
+use super::error::ErrorRenderer;
+use super::types::{Form, Header, Json, Multipart, MultipartForm, Path, Payload};
+use super::types::{Query, State, Valid};
+use super::{HttpRequest, HttpResponse, Resource, Route};
+
+/// Operations registered for a resource
+pub(crate) type Operations = Vec<(Vec<Method>, Rc<Operation>)>;
+
+/// Type that could be described with JSON Schema
+pub trait Schema {
+    /// JSON Schema of the type
+    fn schema() -> Value;
+}
+
+macro_rules! schema_impl {
+    ($($t:ty => $schema:tt),+ $(,)?) => {$(
+        impl Schema for $t {
+            fn schema() -> Value {
+                json!($schema)
+            }
+        }
+    )+};
+}
+
+schema_impl!(
+    bool => {"type": "boolean"},
+    i8 => {"type": "integer", "format": "int32"},
+    i16 => {"type": "integer", "format": "int32"},
+    i32 => {"type": "integer", "format": "int32"},
+    i64 => {"type": "integer", "format": "int64"},
+    isize => {"type": "integer", "format": "int64"},
+    u8 => {"type": "integer", "format": "int32", "minimum": 0},
+    u16 => {"type": "integer", "format": "int32", "minimum": 0},
+    u32 => {"type": "integer", "format": "int64", "minimum": 0},
+    u64 => {"type": "integer", "format": "int64", "minimum": 0},
+    usize => {"type": "integer", "format": "int64", "minimum": 0},
+    f32 => {"type": "number", "format": "float"},
+    f64 => {"type": "number", "format": "double"},
+    char => {"type": "string", "minLength": 1, "maxLength": 1},
+    str => {"type": "string"},
+    String => {"type": "string"},
+    () => {"type": "null"},
+    Value => {},
+);
+
+impl<T: Schema + ?Sized> Schema for &T {
+    fn schema() -> Value {
+        T::schema()
+    }
+}
+
+impl<T: Schema + ?Sized> Schema for Box<T> {
+    fn schema() -> Value {
+        T::schema()
+    }
+}
+
+impl<T: Schema + ?Sized> Schema for Rc<T> {
+    fn schema() -> Value {
+        T::schema()
+    }
+}
+
+impl<T: Schema> Schema for Option<T> {
+    fn schema() -> Value {
+        json!({"anyOf": [T::schema(), {"type": "null"}]})
+    }
+}
+
+impl<T: Schema> Schema for [T] {
+    fn schema() -> Value {
+        json!({"type": "array", "items": T::schema()})
+    }
+}
+
+impl<T: Schema> Schema for Vec<T> {
+    fn schema() -> Value {
+        <[T]>::schema()
+    }
+}
+
+impl<T: Schema> Schema for VecDeque<T> {
+    fn schema() -> Value {
+        <[T]>::schema()
+    }
+}
+
+impl<T: Schema, S> Schema for HashMap<String, T, S> {
+    fn schema() -> Value {
+        json!({"type": "object", "additionalProperties": T::schema()})
+    }
+}
+
+impl<T: Schema> Schema for BTreeMap<String, T> {
+    fn schema() -> Value {
+        json!({"type": "object", "additionalProperties": T::schema()})
+    }
+}
+
+macro_rules! schema_tuple ({$n:tt, $($T:ident),+} => {
+    impl<$($T: Schema),+> Schema for ($($T,)+) {
+        fn schema() -> Value {
+            json!({
+                "type": "array",
+                "prefixItems": [$($T::schema()),+],
+                "minItems": $n,
+                "maxItems": $n,
+            })
+        }
+    }
+});
+
+schema_tuple!(1, A);
+schema_tuple!(2, A, B);
+schema_tuple!(3, A, B, C);
+schema_tuple!(4, A, B, C, D);
+schema_tuple!(5, A, B, C, D, E);
+schema_tuple!(6, A, B, C, D, E, F);
+
+/// Object schema builder
+///
+/// Helper for implementing [`Schema`] for structs.
+#[derive(Clone, Debug, Default)]
+pub struct Object {
+    properties: Map<String, Value>,
+    required: Vec<Value>,
+    description: Option<String>,
+}
+
+impl Object {
+    /// Create empty object schema
+    pub fn new() -> Self {
+        Object::default()
+    }
+
+    /// Add required property
+    pub fn property<T: Schema + ?Sized>(mut self, name: &str) -> Self {
+        self.properties.insert(name.to_string(), T::schema());
+        self.required.push(Value::String(name.to_string()));
+        self
+    }
+
+    /// Add optional property
+    pub fn optional<T: Schema + ?Sized>(mut self, name: &str) -> Self {
+        self.properties.insert(name.to_string(), T::schema());
+        self
+    }
+
+    /// Set object description
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+}
+
+impl From<Object> for Value {
+    fn from(obj: Object) -> Value {
+        let mut schema = json!({"type": "object", "properties": obj.properties});
+        if !obj.required.is_empty() {
+            schema["required"] = Value::Array(obj.required);
+        }
+        if let Some(description) = obj.description {
+            schema["description"] = Value::String(description);
+        }
+        schema
+    }
+}
+
+/// Request extractor that contributes parameters or request body to an operation
+///
+/// Implemented for tuples of extractors, so operation input could be
+/// derived from handler's arguments.
+pub trait OperationInput {
+    /// Add extractor description to the operation
+    fn describe(op: &mut Operation);
+}
+
+/// Responder that could be described as a response content
+pub trait OperationOutput {
+    /// Content type and schema of the response body
+    fn content() -> Option<(&'static str, Value)>;
+}
+
+impl<T: Schema> OperationInput for Json<T> {
+    fn describe(op: &mut Operation) {
+        op.set_request_body("application/json", T::schema());
+    }
+}
+
+impl<T: Schema> OperationInput for Form<T> {
+    fn describe(op: &mut Operation) {
+        op.set_request_body("application/x-www-form-urlencoded", T::schema());
+    }
+}
+
+impl<T: Schema> OperationInput for MultipartForm<T> {
+    fn describe(op: &mut Operation) {
+        op.set_request_body("multipart/form-data", T::schema());
+    }
+}
+
+impl OperationInput for Multipart {
+    fn describe(op: &mut Operation) {
+        op.set_request_body("multipart/form-data", json!({}));
+    }
+}
+
+impl<T: Schema> OperationInput for Query<T> {
+    fn describe(op: &mut Operation) {
+        let schema = T::schema();
+        for (name, schema, required) in properties(&schema) {
+            op.add_parameter(&name, "query", required, schema);
+        }
+    }
+}
+
+impl<T: TypedHeader> OperationInput for Header<T> {
+    fn describe(op: &mut Operation) {
+        op.add_parameter(T::name().as_str(), "header", true, String::schema());
+    }
+}
+
+impl<T: OperationInput> OperationInput for Valid<T> {
+    fn describe(op: &mut Operation) {
+        T::describe(op)
+    }
+}
+
+impl<T: Schema> OperationInput for Path<T> {
+    fn describe(op: &mut Operation) {
+        let schema = T::schema();
+        if schema.get("properties").is_some() {
+            for (name, schema, _) in properties(&schema) {
+                op.add_parameter(&name, "path", true, schema);
+            }
+        } else if let Some(Value::Array(items)) = schema.get("prefixItems") {
+            op.positional.extend(items.iter().cloned());
+        } else {
+            op.positional.push(schema);
+        }
+    }
+}
+
+impl OperationInput for String {
+    fn describe(op: &mut Operation) {
+        op.set_request_body("text/plain", String::schema());
+    }
+}
+
+impl OperationInput for Bytes {
+    fn describe(op: &mut Operation) {
+        op.set_request_body("application/octet-stream", json!({}));
+    }
+}
+
+impl OperationInput for Payload {
+    fn describe(op: &mut Operation) {
+        op.set_request_body("application/octet-stream", json!({}));
+    }
+}
+
+impl OperationInput for HttpRequest {
+    fn describe(_: &mut Operation) {}
+}
+
+impl<T> OperationInput for State<T> {
+    fn describe(_: &mut Operation) {}
+}
+
+#[cfg(feature = "session")]
+impl OperationInput for super::session::Session {
+    fn describe(_: &mut Operation) {}
+}
+
+impl OperationInput for () {
+    fn describe(_: &mut Operation) {}
+}
+
+macro_rules! input_tuple ({$($T:ident),+} => {
+    impl<$($T: OperationInput),+> OperationInput for ($($T,)+) {
+        fn describe(op: &mut Operation) {
+            $($T::describe(op);)+
+        }
+    }
+});
+
+input_tuple!(A);
+input_tuple!(A, B);
+input_tuple!(A, B, C);
+input_tuple!(A, B, C, D);
+input_tuple!(A, B, C, D, E);
+input_tuple!(A, B, C, D, E, F);
+input_tuple!(A, B, C, D, E, F, G);
+input_tuple!(A, B, C, D, E, F, G, H);
+input_tuple!(A, B, C, D, E, F, G, H, I);
+input_tuple!(A, B, C, D, E, F, G, H, I, J);
+
+impl<T: Schema> OperationOutput for Json<T> {
+    fn content() -> Option<(&'static str, Value)> {
+        Some(("application/json", T::schema()))
+    }
+}
+
+impl OperationOutput for String {
+    fn content() -> Option<(&'static str, Value)> {
+        Some(("text/plain", String::schema()))
+    }
+}
+
+impl OperationOutput for &'static str {
+    fn content() -> Option<(&'static str, Value)> {
+        Some(("text/plain", String::schema()))
+    }
+}
+
+impl OperationOutput for Bytes {
+    fn content() -> Option<(&'static str, Value)> {
+        Some(("application/octet-stream", json!({})))
+    }
+}
+
+impl OperationOutput for HttpResponse {
+    fn content() -> Option<(&'static str, Value)> {
+        None
+    }
+}
+
+impl OperationOutput for () {
+    fn content() -> Option<(&'static str, Value)> {
+        None
+    }
+}
+
+impl<T: OperationOutput, E> OperationOutput for Result<T, E> {
+    fn content() -> Option<(&'static str, Value)> {
+        T::content()
+    }
+}
+
+/// Properties of an object schema, `(name, schema, required)`
+fn properties(schema: &Value) -> Vec<(String, Value, bool)> {
+    let required = |name: &str| {
+        schema
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|req| req.iter().any(|v| v.as_str() == Some(name)))
+            .unwrap_or(false)
+    };
+
+    schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .map(|props| {
+            props
+                .iter()
+                .map(|(name, schema)| (name.clone(), schema.clone(), required(name)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// OpenAPI operation description
+///
+/// Operation uses builder-like pattern for configuration.
+#[derive(Clone, Debug, Default)]
+pub struct Operation {
+    id: Option<String>,
+    summary: Option<String>,
+    description: Option<String>,
+    tags: Vec<String>,
+    deprecated: bool,
+    parameters: Vec<Value>,
+    positional: Vec<Value>,
+    body: Option<(&'static str, Value)>,
+    responses: Map<String, Value>,
+}
+
+impl Operation {
+    /// Create new operation description
+    pub fn new() -> Self {
+        Operation::default()
+    }
+
+    /// Set unique operation id
+    pub fn id(mut self, id: &str) -> Self {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    /// Set short summary of the operation
+    pub fn summary(mut self, summary: &str) -> Self {
+        self.summary = Some(summary.to_string());
+        self
+    }
+
+    /// Set verbose description of the operation
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Add tag for logical grouping of operations
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.tags.push(tag.to_string());
+        self
+    }
+
+    /// Mark operation as deprecated
+    pub fn deprecated(mut self) -> Self {
+        self.deprecated = true;
+        self
+    }
+
+    /// Add operation parameter
+    ///
+    /// `location` is one of `path`, `query`, `header` or `cookie`.
+    pub fn add_parameter(
+        &mut self,
+        name: &str,
+        location: &str,
+        required: bool,
+        schema: Value,
+    ) {
+        self.parameters.push(json!({
+            "name": name,
+            "in": location,
+            "required": required,
+            "schema": schema,
+        }));
+    }
+
+    /// Set content type and schema of the request body
+    pub fn set_request_body(&mut self, content_type: &'static str, schema: Value) {
+        self.body = Some((content_type, schema));
+    }
+
+    /// Add response description for specific status code
+    pub fn response<T: OperationOutput>(
+        mut self,
+        status: StatusCode,
+        description: &str,
+    ) -> Self {
+        let mut response = json!({ "description": description });
+        if let Some((ctype, schema)) = T::content() {
+            response["content"] = json!({ ctype: { "schema": schema } });
+        }
+        self.responses.insert(status.as_u16().to_string(), response);
+        self
+    }
+
+    /// Operation object for path with specified parameters
+    fn to_value(&self, path_params: &[String]) -> Value {
+        let mut op = Map::new();
+        if let Some(ref id) = self.id {
+            op.insert("operationId".into(), id.as_str().into());
+        }
+        if let Some(ref summary) = self.summary {
+            op.insert("summary".into(), summary.as_str().into());
+        }
+        if let Some(ref description) = self.description {
+            op.insert("description".into(), description.as_str().into());
+        }
+        if !self.tags.is_empty() {
+            op.insert("tags".into(), self.tags.clone().into());
+        }
+        if self.deprecated {
+            op.insert("deprecated".into(), true.into());
+        }
+
+        // path parameters without explicit description get positional
+        // schemas in order of appearance, otherwise string schema
+        let mut parameters = self.parameters.clone();
+        let mut positional = self.positional.iter();
+        for name in path_params {
+            let exists = self.parameters.iter().any(|p| {
+                p["in"].as_str() == Some("path") && p["name"].as_str() == Some(name)
+            });
+            if !exists {
+                let schema = positional
+                    .next()
+                    .cloned()
+                    .unwrap_or_else(|| json!({"type": "string"}));
+                parameters.push(json!({
+                    "name": name,
+                    "in": "path",
+                    "required": true,
+                    "schema": schema,
+                }));
+            }
+        }
+        if !parameters.is_empty() {
+            op.insert("parameters".into(), parameters.into());
+        }
+
+        if let Some((ctype, ref schema)) = self.body {
+            op.insert(
+                "requestBody".into(),
+                json!({"required": true, "content": { ctype: { "schema": schema }}}),
+            );
+        }
+
+        let responses = if self.responses.is_empty() {
+            json!({"default": {"description": "Default response"}})
+        } else {
+            Value::Object(self.responses.clone())
+        };
+        op.insert("responses".into(), responses);
+
+        Value::Object(op)
+    }
+}
+
+/// Convert router pattern to OpenAPI path template
+///
+/// Returns path template and names of path parameters.
+fn path_template(pattern: &str) -> (String, Vec<String>) {
+    let mut path = String::new();
+    let mut params = Vec::new();
+    let mut rem = pattern;
+
+    while let Some(start) = rem.find('{') {
+        path.push_str(&rem[..start]);
+        rem = &rem[start..];
+
+        // find closing }, regex could contain nested brackets
+        let mut nesting = 0usize;
+        let end = rem
+            .find(|c| match c {
+                '{' => {
+                    nesting += 1;
+                    false
+                }
+                '}' => {
+                    nesting -= 1;
+                    nesting == 0
+                }
+                _ => false,
+            })
+            .unwrap_or(rem.len() - 1);
+
+        let param = &rem[1..end];
+        let name = param.split(':').next().unwrap_or(param);
+        path.push('{');
+        path.push_str(name);
+        path.push('}');
+        params.push(name.to_string());
+
+        rem = &rem[end + 1..];
+        if let Some(r) = rem.strip_prefix('*') {
+            rem = r;
+        }
+    }
+    path.push_str(rem);
+
+    if !path.starts_with('/') {
+        path.insert(0, '/');
+    }
+    (path, params)
+}
+
+/// Built-in OpenAPI document resource
+///
+/// Renders OpenAPI 3.1 document for all routes of the application
+/// annotated with [`Route::to_documented()`](super::Route::to_documented).
+/// Document is generated once, after application is configured.
+/// Only routes with method guards are included into document, if multiple
+/// routes of a resource use the same method, only first one is documented.
+///
+/// ```rust
+/// use ntex::web::{self, openapi, App};
+///
+/// let app = App::new().service(
+///     openapi::OpenApi::new("/openapi.json")
+///         .title("My service")
+///         .version("1.0.0")
+/// );
+/// ```
+#[derive(Debug)]
+pub struct OpenApi {
+    path: String,
+    title: String,
+    version: String,
+    description: Option<String>,
+    servers: Vec<String>,
+    body: RefCell<Bytes>,
+}
+
+impl OpenApi {
+    /// Create new document resource for specified path
+    pub fn new(path: &str) -> Self {
+        OpenApi {
+            path: path.to_string(),
+            title: "API".to_string(),
+            version: "0.0.0".to_string(),
+            description: None,
+            servers: Vec::new(),
+            body: RefCell::new(Bytes::new()),
+        }
+    }
+
+    /// Set title of the API
+    ///
+    /// By default title is set to `API`.
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = title.to_string();
+        self
+    }
+
+    /// Set version of the API document
+    ///
+    /// By default version is set to `0.0.0`.
+    pub fn version(mut self, version: &str) -> Self {
+        self.version = version.to_string();
+        self
+    }
+
+    /// Set description of the API
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Add server url
+    pub fn server(mut self, url: &str) -> Self {
+        self.servers.push(url.to_string());
+        self
+    }
+
+    /// Generate OpenAPI document for resource map
+    pub fn document(&self, rmap: &ResourceMap) -> Value {
+        let mut paths = Map::new();
+        rmap.operations("", &mut |pattern, methods, op| {
+            let (path, params) = path_template(pattern);
+            let item = paths
+                .entry(path)
+                .or_insert_with(|| Value::Object(Map::new()));
+
+            for method in methods {
+                let method = match *method {
+                    Method::GET => "get",
+                    Method::PUT => "put",
+                    Method::POST => "post",
+                    Method::DELETE => "delete",
+                    Method::OPTIONS => "options",
+                    Method::HEAD => "head",
+                    Method::PATCH => "patch",
+                    Method::TRACE => "trace",
+                    _ => continue,
+                };
+                if item.get(method).is_some() {
+                    log::warn!(
+                        "Operation {} {} is already documented, skipping",
+                        method,
+                        pattern
+                    );
+                } else {
+                    item[method] = op.to_value(&params);
+                }
+            }
+        });
+
+        let mut info = json!({"title": self.title, "version": self.version});
+        if let Some(ref description) = self.description {
+            info["description"] = description.as_str().into();
+        }
+        let mut doc = json!({"openapi": "3.1.0", "info": info, "paths": paths});
+        if !self.servers.is_empty() {
+            doc["servers"] = self.servers.iter().map(|url| json!({"url": url})).collect();
+        }
+        doc
+    }
+
+    /// Generate document for complete application's resource map
+    pub(crate) fn build(&self, rmap: &ResourceMap) {
+        *self.body.borrow_mut() = Bytes::from(self.document(rmap).to_string());
+    }
+
+    fn render(&self) -> HttpResponse {
+        HttpResponse::Ok()
+            .content_type("application/json")
+            .body(self.body.borrow().clone())
+    }
+}
+
+impl<Err: ErrorRenderer> WebServiceFactory<Err> for OpenApi {
+    fn register(self, config: &mut WebServiceConfig<Err>) {
+        let path = self.path.clone();
+        let api = Rc::new(self);
+        config.register_document(api.clone());
+
+        Resource::new(path)
+            .route(Route::new().method(Method::GET).to(move || {
+                let api = api.clone();
+                async move { api.render() }
+            }))
+            .register(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::web::test::{call_service, init_service, read_body, TestRequest};
+    use crate::web::{self, App};
+
+    #[derive(serde::Deserialize)]
+    struct User;
+
+    impl Schema for User {
+        fn schema() -> Value {
+            Object::new()
+                .property::<String>("name")
+                .optional::<u32>("age")
+                .into()
+        }
+    }
+
+    #[test]
+    fn test_path_template() {
+        assert_eq!(path_template(""), ("/".to_string(), vec![]));
+        assert_eq!(
+            path_template("/users/{id}/{tail}*"),
+            (
+                "/users/{id}/{tail}".to_string(),
+                vec!["id".to_string(), "tail".to_string()]
+            )
+        );
+        assert_eq!(
+            path_template("/users/{id:\\d{2}}.json"),
+            ("/users/{id}.json".to_string(), vec!["id".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_operation() {
+        let mut op = Operation::new()
+            .id("getUser")
+            .tag("users")
+            .response::<Json<User>>(StatusCode::OK, "User")
+            .response::<HttpResponse>(StatusCode::NOT_FOUND, "Not found");
+        <(Path<(u32, String)>, Query<User>, Json<User>)>::describe(&mut op);
+        let op = op.to_value(&["id".to_string(), "name".to_string()]);
+
+        assert_eq!(op["operationId"], "getUser");
+        assert_eq!(op["tags"], json!(["users"]));
+        let params = op["parameters"].as_array().unwrap();
+        let param = |name: &str, location: &str| {
+            params
+                .iter()
+                .find(|p| p["name"] == name && p["in"] == location)
+                .unwrap()
+        };
+        assert_eq!(params.len(), 4);
+        assert_eq!(param("age", "query")["required"], false);
+        assert_eq!(param("name", "query")["required"], true);
+        assert_eq!(param("id", "path")["schema"], u32::schema());
+        assert_eq!(param("name", "path")["schema"], String::schema());
+        assert_eq!(
+            op["requestBody"]["content"]["application/json"]["schema"],
+            User::schema()
+        );
+        assert_eq!(
+            op["responses"]["200"]["content"]["application/json"]["schema"],
+            User::schema()
+        );
+        assert_eq!(op["responses"]["404"], json!({"description": "Not found"}));
+    }
+
+    #[crate::rt_test]
+    async fn test_document() {
+        let srv = init_service(
+            App::new()
+                .service(
+                    OpenApi::new("/openapi.json")
+                        .title("Test")
+                        .version("1.0")
+                        .server("http://localhost"),
+                )
+                .service(
+                    web::scope("/api").service(
+                        web::resource("/users/{id}")
+                            .route(web::get().to_documented(
+                                |_: Path<u32>| async { HttpResponse::Ok() },
+                                Operation::new().summary("Get user"),
+                            ))
+                            .route(web::get().to_documented(
+                                || async { HttpResponse::Ok() },
+                                Operation::new().summary("Duplicate"),
+                            ))
+                            .route(web::post().to_documented(
+                                |_: Json<User>| async { HttpResponse::Ok() },
+                                Operation::new(),
+                            ))
+                            .route(web::delete().to(|| async { HttpResponse::Ok() })),
+                    ),
+                )
+                .route(
+                    "/status",
+                    web::get().to_documented(
+                        || async { "ok" },
+                        Operation::new().response::<String>(StatusCode::OK, "Ok"),
+                    ),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/openapi.json").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let doc: Value = serde_json::from_slice(&read_body(resp).await).unwrap();
+
+        assert_eq!(doc["openapi"], "3.1.0");
+        assert_eq!(doc["info"], json!({"title": "Test", "version": "1.0"}));
+        assert_eq!(doc["servers"], json!([{"url": "http://localhost"}]));
+
+        let paths = doc["paths"].as_object().unwrap();
+        assert_eq!(paths.len(), 2);
+        let user = &paths["/api/users/{id}"];
+        assert_eq!(user["get"]["summary"], "Get user");
+        assert_eq!(user["get"]["parameters"][0]["name"], "id");
+        assert_eq!(user["get"]["parameters"][0]["schema"], u32::schema());
+        assert_eq!(
+            user["post"]["requestBody"]["content"]["application/json"]["schema"],
+            User::schema()
+        );
+        assert!(user.get("delete").is_none());
+        assert_eq!(
+            paths["/status"]["get"]["responses"]["200"]["content"]["text/plain"]["schema"],
+            json!({"type": "string"})
+        );
+    }
+}
@@ -219,6 +219,11 @@ impl<Err> WebRequest<Err> {
         Rc::get_mut(&mut (self.req).0).unwrap().payload = payload;
     }
 
+    /// Create copy of the http request, see `HttpRequest::detached()`
+    pub(crate) fn detached_request(&self) -> HttpRequest {
+        self.req.detached()
+    }
+
     #[doc(hidden)]
     /// Set new app state container
     pub(super) fn set_state_container(&mut self, state: AppState) {
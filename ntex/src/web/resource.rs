@@ -1,6 +1,7 @@
 use std::{cell::RefCell, fmt, rc::Rc};
 
-use crate::http::Response;
+use crate::http::header::{self, HeaderValue};
+use crate::http::{Method, Response};
 use crate::router::{IntoPattern, ResourceDef};
 use crate::service::boxed::{self, BoxService, BoxServiceFactory};
 use crate::service::dev::{AndThen, ServiceChain, ServiceChainFactory};
@@ -43,7 +44,8 @@ type ResourcePipeline<F, Err> =
 /// }
 /// ```
 ///
-/// If no matching route could be found, *405* response code get returned.
+/// If no matching route could be found, *405* response code get returned,
+/// `Allow` header lists methods of resource's routes.
 /// Default behavior could be overriden with `default_resource()` method.
 pub struct Resource<Err: ErrorRenderer, M = Identity, T = Filter<Err>> {
     middleware: M,
@@ -342,10 +344,17 @@ where
             )
         });
 
+        #[cfg(feature = "openapi")]
+        config.register_operations(
+            &rdef,
+            self.routes.iter().filter_map(|r| r.operation()).collect(),
+        );
+
         let router_factory = ResourceRouterFactory {
             state,
             routes: self.routes,
             default: self.default.borrow_mut().take(),
+            auto_options: config.auto_options(),
         };
 
         config.register_service(
@@ -384,6 +393,7 @@ where
             state: None,
             routes: self.routes,
             default: self.default.borrow_mut().take(),
+            auto_options: false,
         };
 
         ResourceServiceFactory {
@@ -429,6 +439,25 @@ struct ResourceRouterFactory<Err: ErrorRenderer> {
     routes: Vec<Route<Err>>,
     default: Option<Rc<HttpNewService<Err>>>,
     state: Option<AppState>,
+    auto_options: bool,
+}
+
+impl<Err: ErrorRenderer> ResourceRouterFactory<Err> {
+    /// `Allow` header value, `None` if any route accepts all methods
+    fn allow(&self) -> Option<HeaderValue> {
+        let mut methods: Vec<&str> = Vec::new();
+        for route in &self.routes {
+            for method in route.allowed_methods()? {
+                if !methods.contains(&method.as_str()) {
+                    methods.push(method.as_str());
+                }
+            }
+        }
+        if self.auto_options && !methods.contains(&Method::OPTIONS.as_str()) {
+            methods.push(Method::OPTIONS.as_str());
+        }
+        HeaderValue::from_str(&methods.join(", ")).ok()
+    }
 }
 
 impl<Err: ErrorRenderer> ServiceFactory<WebRequest<Err>> for ResourceRouterFactory<Err> {
@@ -445,6 +474,8 @@ impl<Err: ErrorRenderer> ServiceFactory<WebRequest<Err>> for ResourceRouterFacto
         };
         Ok(ResourceRouter {
             default,
+            allow: self.allow(),
+            auto_options: self.auto_options,
             state: self.state.clone(),
             routes: self.routes.iter().map(|route| route.service()).collect(),
         })
@@ -455,6 +486,8 @@ pub struct ResourceRouter<Err: ErrorRenderer> {
     state: Option<AppState>,
     routes: Vec<RouteService<Err>>,
     default: Option<HttpService<Err>>,
+    allow: Option<HeaderValue>,
+    auto_options: bool,
 }
 
 impl<Err: ErrorRenderer> Service<WebRequest<Err>> for ResourceRouter<Err> {
@@ -474,13 +507,24 @@ impl<Err: ErrorRenderer> Service<WebRequest<Err>> for ResourceRouter<Err> {
                 return ctx.call(route, req).await;
             }
         }
+
+        // answer OPTIONS request with allowed methods
+        if self.auto_options && req.head().method == Method::OPTIONS {
+            if let Some(ref allow) = self.allow {
+                let mut res = Response::NoContent().finish();
+                res.headers_mut().insert(header::ALLOW, allow.clone());
+                return Ok(WebResponse::new(res, req.into_parts().0));
+            }
+        }
+
         if let Some(ref default) = self.default {
             ctx.call(default, req).await
         } else {
-            Ok(WebResponse::new(
-                Response::MethodNotAllowed().finish(),
-                req.into_parts().0,
-            ))
+            let mut res = Response::MethodNotAllowed().finish();
+            if let Some(ref allow) = self.allow {
+                res.headers_mut().insert(header::ALLOW, allow.clone());
+            }
+            Ok(WebResponse::new(res, req.into_parts().0))
         }
     }
 }
@@ -610,6 +654,73 @@ mod tests {
         assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
     }
 
+    #[crate::rt_test]
+    async fn test_method_not_allowed() {
+        let srv = init_service(
+            App::new()
+                .service(
+                    web::resource("/test")
+                        .route(web::get().to(|| async { HttpResponse::Ok() }))
+                        .route(
+                            web::route()
+                                .guard(guard::Post())
+                                .to(|| async { HttpResponse::Ok() }),
+                        ),
+                )
+                .service(
+                    web::scope("/scope")
+                        .auto_options(true)
+                        .service(
+                            web::resource("/test")
+                                .route(web::put().to(|| async { HttpResponse::Ok() })),
+                        )
+                        .service(web::resource("/any").to(|| async { HttpResponse::Ok() })),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/test")
+            .method(Method::DELETE)
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(
+            resp.headers().get(header::ALLOW).unwrap(),
+            HeaderValue::from_static("GET, POST")
+        );
+
+        // auto options is disabled by default
+        let req = TestRequest::with_uri("/test")
+            .method(Method::OPTIONS)
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+
+        let req = TestRequest::with_uri("/scope/test")
+            .method(Method::OPTIONS)
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+        assert_eq!(
+            resp.headers().get(header::ALLOW).unwrap(),
+            HeaderValue::from_static("PUT, OPTIONS")
+        );
+
+        let req = TestRequest::with_uri("/scope/test").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(
+            resp.headers().get(header::ALLOW).unwrap(),
+            HeaderValue::from_static("PUT, OPTIONS")
+        );
+
+        let req = TestRequest::with_uri("/scope/any")
+            .method(Method::OPTIONS)
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
     #[crate::rt_test]
     async fn test_resource_guards() {
         let srv = init_service(
@@ -293,6 +293,35 @@ where
     }
 }
 
+/// Adds http range requests support to a responder.
+///
+/// Response with known body size is converted to `206 Partial Content`
+/// response if request contains satisfiable `Range` header. Multiple
+/// ranges are sent as `multipart/byteranges` body, unsatisfiable
+/// range produces `416 Range Not Satisfiable` response.
+///
+/// ```rust
+/// use ntex::web::Ranged;
+///
+/// async fn index() -> Ranged<&'static str> {
+///     Ranged("0123456789")
+/// }
+/// # fn main() {}
+/// ```
+#[derive(Debug)]
+pub struct Ranged<T>(pub T);
+
+impl<T, Err> Responder<Err> for Ranged<T>
+where
+    T: Responder<Err>,
+    Err: ErrorRenderer,
+{
+    async fn respond_to(self, req: &HttpRequest) -> Response {
+        let res = self.0.respond_to(req).await;
+        crate::http::range::ranged_response(req.head(), res)
+    }
+}
+
 impl<T, Err> Responder<Err> for InternalError<T, Err>
 where
     T: std::fmt::Debug + std::fmt::Display + 'static,
@@ -502,4 +531,19 @@ pub(crate) mod tests {
             HeaderValue::from_static("json")
         );
     }
+
+    #[crate::rt_test]
+    async fn test_ranged_responder() {
+        let req = TestRequest::default()
+            .header(crate::http::header::RANGE, "bytes=-3")
+            .to_http_request();
+        let res = Responder::<DefaultError>::respond_to(Ranged("0123456789"), &req).await;
+        assert_eq!(res.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(res.get_body_ref(), b"789");
+
+        let req = TestRequest::default().to_http_request();
+        let res = Responder::<DefaultError>::respond_to(Ranged("0123456789"), &req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.get_body_ref(), b"0123456789");
+    }
 }
@@ -3,10 +3,14 @@ use std::{cell::RefCell, rc::Rc};
 #[cfg(feature = "url")]
 use url_pkg::Url;
 
+#[cfg(feature = "openapi")]
+use crate::http::Method;
 use crate::router::ResourceDef;
 use crate::util::HashMap;
 #[cfg(feature = "url")]
 use crate::web::httprequest::HttpRequest;
+#[cfg(feature = "openapi")]
+use crate::web::openapi::{Operation, Operations};
 
 #[derive(Clone, Debug)]
 pub struct ResourceMap {
@@ -15,6 +19,8 @@ pub struct ResourceMap {
     parent: RefCell<Option<Rc<ResourceMap>>>,
     named: HashMap<String, ResourceDef>,
     patterns: Vec<(ResourceDef, Option<Rc<ResourceMap>>)>,
+    #[cfg(feature = "openapi")]
+    operations: Vec<(ResourceDef, Operations)>,
 }
 
 impl ResourceMap {
@@ -24,6 +30,8 @@ impl ResourceMap {
             parent: RefCell::new(None),
             named: HashMap::default(),
             patterns: Vec::new(),
+            #[cfg(feature = "openapi")]
+            operations: Vec::new(),
         }
     }
 
@@ -46,6 +54,32 @@ impl ResourceMap {
     }
 }
 
+#[cfg(feature = "openapi")]
+impl ResourceMap {
+    pub(crate) fn add_operations(&mut self, operations: Vec<(ResourceDef, Operations)>) {
+        self.operations.extend(operations);
+    }
+
+    /// Walk registered operations, including nested resource maps
+    pub(crate) fn operations<F>(&self, prefix: &str, f: &mut F)
+    where
+        F: FnMut(&str, &[Method], &Operation),
+    {
+        let prefix = format!("{}{}", prefix, self.root.pattern());
+        for (rdef, operations) in &self.operations {
+            let path = format!("{}{}", prefix, rdef.pattern());
+            for (methods, op) in operations {
+                f(&path, methods, op);
+            }
+        }
+        for (_, nested) in &self.patterns {
+            if let Some(ref nested) = nested {
+                nested.operations(&prefix, f);
+            }
+        }
+    }
+}
+
 #[cfg(feature = "url")]
 impl ResourceMap {
     /// Generate url for named resource
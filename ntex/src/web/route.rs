@@ -1,4 +1,4 @@
-use std::{fmt, mem, rc::Rc};
+use std::{any::Any, fmt, mem, rc::Rc};
 
 use crate::{http::Method, service::Service, service::ServiceCtx, service::ServiceFactory};
 
@@ -7,6 +7,8 @@ use super::error_default::DefaultError;
 use super::extract::FromRequest;
 use super::guard::{self, AllGuard, Guard};
 use super::handler::{Handler, HandlerFn, HandlerWrapper};
+#[cfg(feature = "openapi")]
+use super::openapi::{Operation, OperationInput};
 use super::request::WebRequest;
 use super::response::WebResponse;
 use super::HttpResponse;
@@ -18,7 +20,10 @@ use super::HttpResponse;
 pub struct Route<Err: ErrorRenderer = DefaultError> {
     handler: Rc<dyn HandlerFn<Err>>,
     methods: Vec<Method>,
+    guard_methods: Vec<Method>,
     guards: Rc<AllGuard>,
+    #[cfg(feature = "openapi")]
+    operation: Option<Rc<Operation>>,
 }
 
 impl<Err: ErrorRenderer> Route<Err> {
@@ -27,7 +32,32 @@ impl<Err: ErrorRenderer> Route<Err> {
         Route {
             handler: Rc::new(HandlerWrapper::new(|| async { HttpResponse::NotFound() })),
             methods: Vec::new(),
+            guard_methods: Vec::new(),
             guards: Default::default(),
+            #[cfg(feature = "openapi")]
+            operation: None,
+        }
+    }
+
+    /// Methods accepted by the route, `None` if route accepts any method
+    pub(super) fn allowed_methods(&self) -> Option<&[Method]> {
+        if !self.methods.is_empty() {
+            Some(&self.methods)
+        } else if !self.guard_methods.is_empty() {
+            Some(&self.guard_methods)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "openapi")]
+    pub(super) fn operation(&self) -> Option<(Vec<Method>, Rc<Operation>)> {
+        if self.methods.is_empty() {
+            None
+        } else {
+            self.operation
+                .as_ref()
+                .map(|op| (self.methods.clone(), op.clone()))
         }
     }
 
@@ -143,6 +173,9 @@ impl<Err: ErrorRenderer> Route<Err> {
     /// # }
     /// ```
     pub fn guard<F: Guard + 'static>(mut self, f: F) -> Self {
+        if let Some(g) = (&f as &dyn Any).downcast_ref::<guard::MethodGuard>() {
+            self.guard_methods.push(g.0.clone());
+        }
         Rc::get_mut(&mut self.guards).unwrap().add(f);
         self
     }
@@ -202,6 +235,43 @@ impl<Err: ErrorRenderer> Route<Err> {
         self.handler = Rc::new(HandlerWrapper::new(handler));
         self
     }
+
+    #[cfg(feature = "openapi")]
+    /// Set handler function and OpenAPI operation description for the route.
+    ///
+    /// Parameters and request body of the operation are derived from
+    /// handler's extractors. Operation is included into document generated
+    /// by [`OpenApi`](super::openapi::OpenApi) service. Route must
+    /// have at least one method guard.
+    ///
+    /// ```rust
+    /// use ntex::web::{self, openapi, types::Json, App};
+    ///
+    /// async fn index(body: Json<String>) -> String {
+    ///     body.into_inner()
+    /// }
+    ///
+    /// let app = App::new().service(
+    ///     web::resource("/echo").route(
+    ///         web::post().to_documented(
+    ///             index,
+    ///             openapi::Operation::new()
+    ///                 .summary("Echo request")
+    ///                 .response::<String>(ntex::http::StatusCode::OK, "Request body"),
+    ///         ),
+    ///     ),
+    /// );
+    /// ```
+    pub fn to_documented<F, Args>(mut self, handler: F, mut operation: Operation) -> Self
+    where
+        F: Handler<Args, Err> + 'static,
+        Args: FromRequest<Err> + OperationInput + 'static,
+        Args::Error: Into<Err::Container>,
+    {
+        Args::describe(&mut operation);
+        self.operation = Some(Rc::new(operation));
+        self.to(handler)
+    }
 }
 
 /// Convert object to a vec of routes
@@ -65,6 +65,7 @@ pub struct Scope<Err: ErrorRenderer, M = Identity, T = Filter<Err>> {
     default: Rc<RefCell<Option<Rc<HttpNewService<Err>>>>>,
     external: Vec<ResourceDef>,
     case_insensitive: bool,
+    auto_options: Option<bool>,
 }
 
 impl<Err: ErrorRenderer> Scope<Err> {
@@ -80,6 +81,7 @@ impl<Err: ErrorRenderer> Scope<Err> {
             default: Rc::new(RefCell::new(None)),
             external: Vec::new(),
             case_insensitive: false,
+            auto_options: None,
         }
     }
 }
@@ -161,6 +163,15 @@ where
         self
     }
 
+    /// Answer `OPTIONS` requests automatically.
+    ///
+    /// Overrides application setting for resources of the scope,
+    /// see [`App::auto_options()`](super::App::auto_options).
+    pub fn auto_options(mut self, enabled: bool) -> Self {
+        self.auto_options = Some(enabled);
+        self
+    }
+
     /// Run external configuration as part of the scope building
     /// process
     ///
@@ -330,6 +341,7 @@ where
             default: self.default,
             external: self.external,
             case_insensitive: self.case_insensitive,
+            auto_options: self.auto_options,
         }
     }
 
@@ -354,6 +366,7 @@ where
             default: self.default,
             external: self.external,
             case_insensitive: self.case_insensitive,
+            auto_options: self.auto_options,
         }
     }
 }
@@ -386,6 +399,9 @@ where
 
         // register nested services
         let mut cfg = config.clone_config(state.clone());
+        if let Some(enabled) = self.auto_options {
+            cfg.set_auto_options(enabled);
+        }
         self.services
             .into_iter()
             .for_each(|mut srv| srv.register(&mut cfg));
@@ -398,6 +414,10 @@ where
             rmap.add(&mut rdef, None);
         }
 
+        // documented operations
+        #[cfg(feature = "openapi")]
+        rmap.add_operations(cfg.take_operations());
+
         // complete scope pipeline creation
         let router_factory = ScopeRouterFactory {
             state,
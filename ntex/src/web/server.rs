@@ -8,8 +8,10 @@ use tls_rustls::ServerConfig as RustlsServerConfig;
 use crate::http::{
     self, body::MessageBody, HttpService, KeepAlive, Request, Response, ResponseError,
 };
-use crate::server::{Server, ServerBuilder};
+use crate::server::{Config as ServerConfig, Server, ServerBuilder};
 use crate::service::{map_config, IntoServiceFactory, ServiceFactory};
+#[cfg(any(feature = "openssl", feature = "rustls"))]
+use crate::{io::proxy::ProxyAcceptor, util::Either};
 use crate::{time::Seconds, util::PoolId};
 
 use super::config::AppConfig;
@@ -23,6 +25,7 @@ struct Config {
     payload_read_rate: Option<ReadRate>,
     tag: &'static str,
     pool: PoolId,
+    proxy_protocol: bool,
 }
 
 #[derive(Default, Copy, Clone)]
@@ -47,6 +50,22 @@ impl Config {
         }
         svc_cfg
     }
+
+    fn app_config(
+        &self,
+        r: &ServerConfig,
+        secure: bool,
+        addr: net::SocketAddr,
+    ) -> AppConfig {
+        r.tag(self.tag);
+        r.memory_pool(self.pool);
+
+        AppConfig::new(
+            secure,
+            addr,
+            self.host.clone().unwrap_or_else(|| format!("{}", addr)),
+        )
+    }
 }
 
 /// An HTTP Server.
@@ -110,6 +129,7 @@ where
                 payload_read_rate: None,
                 tag: "WEB",
                 pool: PoolId::P0,
+                proxy_protocol: false,
             })),
             backlog: 1024,
             builder: ServerBuilder::default(),
@@ -332,6 +352,21 @@ where
         self
     }
 
+    /// Require PROXY protocol header for incoming connections.
+    ///
+    /// Load balancer must send PROXY protocol v1 or v2 header for each
+    /// connection, connections without valid header get dropped. Peer address
+    /// of the request is set to the source address from the header.
+    ///
+    /// Setting applies to listeners that are added after this call,
+    /// so this method should be called before `bind()` method call.
+    ///
+    /// By default PROXY protocol is disabled.
+    pub fn proxy_protocol(self, enabled: bool) -> Self {
+        self.config.lock().unwrap().proxy_protocol = enabled;
+        self
+    }
+
     /// Use listener for accepting incoming connection requests
     ///
     /// HttpServer does not change any configuration for TcpListener,
@@ -340,22 +375,26 @@ where
         let cfg = self.config.clone();
         let factory = self.factory.clone();
         let addr = lst.local_addr().unwrap();
+        let name = format!("ntex-web-service-{}", addr);
 
-        self.builder =
-            self.builder
-                .listen(format!("ntex-web-service-{}", addr), lst, move |r| {
-                    let c = cfg.lock().unwrap();
-                    let cfg = AppConfig::new(
-                        false,
-                        addr,
-                        c.host.clone().unwrap_or_else(|| format!("{}", addr)),
-                    );
-                    r.tag(c.tag);
-                    r.memory_pool(c.pool);
+        self.builder = if self.config.lock().unwrap().proxy_protocol {
+            self.builder.listen(name, lst, move |r| {
+                let c = cfg.lock().unwrap();
+                let cfg = c.app_config(&r, false, addr);
 
-                    HttpService::build_with_config(c.into_cfg())
-                        .finish(map_config(factory(), move |_| cfg.clone()))
-                })?;
+                HttpService::build_with_config(c.into_cfg())
+                    .finish(map_config(factory(), move |_| cfg.clone()))
+                    .proxy_protocol()
+            })?
+        } else {
+            self.builder.listen(name, lst, move |r| {
+                let c = cfg.lock().unwrap();
+                let cfg = c.app_config(&r, false, addr);
+
+                HttpService::build_with_config(c.into_cfg())
+                    .finish(map_config(factory(), move |_| cfg.clone()))
+            })?
+        };
         Ok(self)
     }
 
@@ -380,23 +419,30 @@ where
         let factory = self.factory.clone();
         let cfg = self.config.clone();
         let addr = lst.local_addr().unwrap();
+        let name = format!("ntex-web-service-{}", addr);
 
-        self.builder =
-            self.builder
-                .listen(format!("ntex-web-service-{}", addr), lst, move |r| {
-                    let c = cfg.lock().unwrap();
-                    let cfg = AppConfig::new(
-                        true,
-                        addr,
-                        c.host.clone().unwrap_or_else(|| format!("{}", addr)),
-                    );
-                    r.tag(c.tag);
-                    r.memory_pool(c.pool);
+        self.builder = if self.config.lock().unwrap().proxy_protocol {
+            self.builder.listen(name, lst, move |r| {
+                let c = cfg.lock().unwrap();
+                let cfg = c.app_config(&r, true, addr);
 
+                ProxyAcceptor::new().map_err(Either::Left).and_then(
                     HttpService::build_with_config(c.into_cfg())
                         .finish(map_config(factory(), move |_| cfg.clone()))
                         .openssl(acceptor.clone())
-                })?;
+                        .map_err(Either::Right),
+                )
+            })?
+        } else {
+            self.builder.listen(name, lst, move |r| {
+                let c = cfg.lock().unwrap();
+                let cfg = c.app_config(&r, true, addr);
+
+                HttpService::build_with_config(c.into_cfg())
+                    .finish(map_config(factory(), move |_| cfg.clone()))
+                    .openssl(acceptor.clone())
+            })?
+        };
         Ok(self)
     }
 
@@ -421,25 +467,30 @@ where
         let factory = self.factory.clone();
         let cfg = self.config.clone();
         let addr = lst.local_addr().unwrap();
+        let name = format!("ntex-web-rustls-service-{}", addr);
 
-        self.builder = self.builder.listen(
-            format!("ntex-web-rustls-service-{}", addr),
-            lst,
-            move |r| {
+        self.builder = if self.config.lock().unwrap().proxy_protocol {
+            self.builder.listen(name, lst, move |r| {
                 let c = cfg.lock().unwrap();
-                let cfg = AppConfig::new(
-                    true,
-                    addr,
-                    c.host.clone().unwrap_or_else(|| format!("{}", addr)),
-                );
-                r.tag(c.tag);
-                r.memory_pool(c.pool);
+                let cfg = c.app_config(&r, true, addr);
+
+                ProxyAcceptor::new().map_err(Either::Left).and_then(
+                    HttpService::build_with_config(c.into_cfg())
+                        .finish(map_config(factory(), move |_| cfg.clone()))
+                        .rustls(config.clone())
+                        .map_err(Either::Right),
+                )
+            })?
+        } else {
+            self.builder.listen(name, lst, move |r| {
+                let c = cfg.lock().unwrap();
+                let cfg = c.app_config(&r, true, addr);
 
                 HttpService::build_with_config(c.into_cfg())
                     .finish(map_config(factory(), move |_| cfg.clone()))
                     .rustls(config.clone())
-            },
-        )?;
+            })?
+        };
         Ok(self)
     }
 
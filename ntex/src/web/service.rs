@@ -1,3 +1,5 @@
+#[cfg(feature = "openapi")]
+use std::cell::RefCell;
 use std::rc::Rc;
 
 use crate::router::{IntoPattern, ResourceDef};
@@ -8,6 +10,8 @@ use super::config::AppConfig;
 use super::dev::insert_slash;
 use super::error::ErrorRenderer;
 use super::guard::{AllGuard, Guard};
+#[cfg(feature = "openapi")]
+use super::openapi::{OpenApi, Operations};
 use super::{request::WebRequest, response::WebResponse, rmap::ResourceMap};
 
 pub trait WebServiceFactory<Err: ErrorRenderer> {
@@ -99,6 +103,7 @@ impl AppState {
 pub struct WebServiceConfig<Err: ErrorRenderer> {
     state: AppState,
     root: bool,
+    auto_options: bool,
     default: Rc<HttpServiceFactory<Err>>,
     services: Vec<(
         ResourceDef,
@@ -106,6 +111,10 @@ pub struct WebServiceConfig<Err: ErrorRenderer> {
         Option<Guards>,
         Option<Rc<ResourceMap>>,
     )>,
+    #[cfg(feature = "openapi")]
+    operations: Vec<(ResourceDef, Operations)>,
+    #[cfg(feature = "openapi")]
+    documents: Rc<RefCell<Vec<Rc<OpenApi>>>>,
 }
 
 impl<Err: ErrorRenderer> WebServiceConfig<Err> {
@@ -115,7 +124,12 @@ impl<Err: ErrorRenderer> WebServiceConfig<Err> {
             state,
             default,
             root: true,
+            auto_options: false,
             services: Vec::new(),
+            #[cfg(feature = "openapi")]
+            operations: Vec::new(),
+            #[cfg(feature = "openapi")]
+            documents: Default::default(),
         }
     }
 
@@ -124,6 +138,15 @@ impl<Err: ErrorRenderer> WebServiceConfig<Err> {
         self.root
     }
 
+    /// Check if `OPTIONS` requests are answered automatically
+    pub fn auto_options(&self) -> bool {
+        self.auto_options
+    }
+
+    pub(crate) fn set_auto_options(&mut self, enabled: bool) {
+        self.auto_options = enabled;
+    }
+
     pub(super) fn state(&self) -> &AppState {
         &self.state
     }
@@ -145,6 +168,11 @@ impl<Err: ErrorRenderer> WebServiceConfig<Err> {
             default: self.default.clone(),
             services: Vec::new(),
             root: false,
+            auto_options: self.auto_options,
+            #[cfg(feature = "openapi")]
+            operations: Vec::new(),
+            #[cfg(feature = "openapi")]
+            documents: self.documents.clone(),
         }
     }
 
@@ -177,6 +205,29 @@ impl<Err: ErrorRenderer> WebServiceConfig<Err> {
         self.services
             .push((rdef, boxed::factory(factory.into_factory()), guards, nested));
     }
+
+    #[cfg(feature = "openapi")]
+    pub(crate) fn register_operations(&mut self, rdef: &ResourceDef, ops: Operations) {
+        if !ops.is_empty() {
+            self.operations.push((rdef.clone(), ops));
+        }
+    }
+
+    #[cfg(feature = "openapi")]
+    pub(crate) fn take_operations(&mut self) -> Vec<(ResourceDef, Operations)> {
+        std::mem::take(&mut self.operations)
+    }
+
+    #[cfg(feature = "openapi")]
+    /// Register document that is generated after application is configured
+    pub(crate) fn register_document(&mut self, api: Rc<OpenApi>) {
+        self.documents.borrow_mut().push(api);
+    }
+
+    #[cfg(feature = "openapi")]
+    pub(crate) fn take_documents(&mut self) -> Vec<Rc<OpenApi>> {
+        self.documents.take()
+    }
 }
 
 /// Create service adapter for a specific path.
@@ -0,0 +1,127 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::{Deserialize, Serialize};
+
+use super::{SessionError, SessionState, SessionStore};
+
+/// Max size of the cookie value
+const MAX_SIZE: usize = 4064;
+
+/// Cookie session store
+///
+/// Whole session state is stored on the client side, in the session cookie,
+/// as base64 encoded json.
+/// Cookie is signed or encrypted by `SessionMiddleware`, so client can not
+/// forge session state. Cookie size is limited, so only small amount of data
+/// could be stored. Session can not be invalidated on the server side,
+/// session expiration time is stored in the cookie together with session state,
+/// so stale cookies are rejected even if client ignores cookie's `Max-Age`.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct CookieSessionStore;
+
+/// Cookie content
+#[derive(Serialize, Deserialize)]
+struct Payload {
+    /// Expiration time, seconds since unix epoch
+    expires: u64,
+    state: SessionState,
+}
+
+impl Payload {
+    fn decode(key: &str) -> Option<Payload> {
+        let data = URL_SAFE_NO_PAD.decode(key).ok()?;
+        serde_json::from_slice::<Payload>(&data)
+            .ok()
+            .filter(|payload| payload.expires > now())
+    }
+
+    fn encode(state: SessionState, ttl: Duration) -> Result<String, SessionError> {
+        let payload = Payload {
+            expires: now().saturating_add(ttl.as_secs()),
+            state,
+        };
+        let value = URL_SAFE_NO_PAD.encode(serde_json::to_vec(&payload)?);
+        if value.len() > MAX_SIZE {
+            Err(SessionError::Overflow(value.len()))
+        } else {
+            Ok(value)
+        }
+    }
+}
+
+/// Seconds since unix epoch
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+impl SessionStore for CookieSessionStore {
+    async fn load(&self, key: &str) -> Result<Option<SessionState>, SessionError> {
+        Ok(Payload::decode(key).map(|payload| payload.state))
+    }
+
+    async fn save(
+        &self,
+        state: SessionState,
+        ttl: Duration,
+    ) -> Result<String, SessionError> {
+        Payload::encode(state, ttl)
+    }
+
+    async fn update(
+        &self,
+        _: &str,
+        state: SessionState,
+        ttl: Duration,
+    ) -> Result<String, SessionError> {
+        self.save(state, ttl).await
+    }
+
+    async fn update_ttl(&self, key: &str, ttl: Duration) -> Result<String, SessionError> {
+        // cookie carries expiration time, so it has to be re-issued
+        match Payload::decode(key) {
+            Some(payload) => Payload::encode(payload.state, ttl),
+            None => Ok(key.to_string()),
+        }
+    }
+
+    async fn delete(&self, _: &str) -> Result<(), SessionError> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[crate::rt_test]
+    async fn test_cookie_store() {
+        let store = CookieSessionStore;
+        let ttl = Duration::from_secs(60);
+
+        let mut state = SessionState::new();
+        state.insert("key".to_string(), "\"value\"".to_string());
+        let key = store.save(state.clone(), ttl).await.unwrap();
+        assert_eq!(store.load(&key).await.unwrap(), Some(state.clone()));
+        assert_eq!(store.load("garbage").await.unwrap(), None);
+
+        let key = store.update_ttl(&key, ttl).await.unwrap();
+        assert_eq!(store.load(&key).await.unwrap(), Some(state.clone()));
+
+        // stale cookie
+        let key = store.save(state.clone(), Duration::ZERO).await.unwrap();
+        assert_eq!(store.load(&key).await.unwrap(), None);
+        let key = URL_SAFE_NO_PAD.encode(serde_json::to_vec(&state).unwrap());
+        assert_eq!(store.load(&key).await.unwrap(), None);
+
+        let mut state = SessionState::new();
+        state.insert("key".to_string(), "x".repeat(MAX_SIZE));
+        assert!(matches!(
+            store.save(state, ttl).await,
+            Err(SessionError::Overflow(_))
+        ));
+    }
+}
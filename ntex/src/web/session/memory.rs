@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+
+use super::{SessionError, SessionState, SessionStore};
+
+/// Number of random bytes in session key, 384 bits
+const KEY_BYTES: usize = 48;
+/// Interval between expired sessions sweeps
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// In-memory session store
+///
+/// Session state is kept in the process memory, all state is lost on restart.
+/// Store must be created outside of the `App` factory and cloned into it,
+/// otherwise each worker gets separate store. Expired sessions are removed
+/// periodically during store operations.
+#[derive(Clone, Debug, Default)]
+pub struct MemorySessionStore {
+    inner: Arc<Mutex<Sessions>>,
+}
+
+#[derive(Debug)]
+struct Sessions {
+    entries: HashMap<String, Entry>,
+    next_sweep: Instant,
+}
+
+#[derive(Debug)]
+struct Entry {
+    state: SessionState,
+    expires: Instant,
+}
+
+impl Default for Sessions {
+    fn default() -> Self {
+        Sessions {
+            entries: HashMap::new(),
+            next_sweep: Instant::now() + SWEEP_INTERVAL,
+        }
+    }
+}
+
+impl MemorySessionStore {
+    /// Create new in-memory store
+    pub fn new() -> Self {
+        MemorySessionStore::default()
+    }
+
+    /// Number of stored sessions, including expired ones
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().entries.len()
+    }
+
+    /// Check if store is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn insert(&self, state: SessionState, ttl: Duration) -> Result<String, SessionError> {
+        let mut sessions = self.inner.lock().unwrap();
+
+        let now = Instant::now();
+        if now >= sessions.next_sweep {
+            sessions.entries.retain(|_, entry| entry.expires > now);
+            sessions.next_sweep = now + SWEEP_INTERVAL;
+        }
+
+        loop {
+            let key = generate_key()?;
+            if !sessions.entries.contains_key(&key) {
+                sessions.entries.insert(
+                    key.clone(),
+                    Entry {
+                        state,
+                        expires: now + ttl,
+                    },
+                );
+                return Ok(key);
+            }
+        }
+    }
+}
+
+/// Generate session key with os random number generator
+fn generate_key() -> Result<String, SessionError> {
+    let mut buf = [0u8; KEY_BYTES];
+    getrandom::fill(&mut buf).map_err(|e| SessionError::Store(e.to_string()))?;
+    Ok(URL_SAFE_NO_PAD.encode(buf))
+}
+
+impl SessionStore for MemorySessionStore {
+    async fn load(&self, key: &str) -> Result<Option<SessionState>, SessionError> {
+        let sessions = self.inner.lock().unwrap();
+        Ok(sessions
+            .entries
+            .get(key)
+            .filter(|entry| entry.expires > Instant::now())
+            .map(|entry| entry.state.clone()))
+    }
+
+    async fn save(
+        &self,
+        state: SessionState,
+        ttl: Duration,
+    ) -> Result<String, SessionError> {
+        self.insert(state, ttl)
+    }
+
+    async fn update(
+        &self,
+        key: &str,
+        state: SessionState,
+        ttl: Duration,
+    ) -> Result<String, SessionError> {
+        {
+            let mut sessions = self.inner.lock().unwrap();
+            if let Some(entry) = sessions.entries.get_mut(key) {
+                if entry.expires > Instant::now() {
+                    entry.state = state;
+                    entry.expires = Instant::now() + ttl;
+                    return Ok(key.to_string());
+                }
+            }
+        }
+        self.insert(state, ttl)
+    }
+
+    async fn update_ttl(&self, key: &str, ttl: Duration) -> Result<String, SessionError> {
+        if let Some(entry) = self.inner.lock().unwrap().entries.get_mut(key) {
+            entry.expires = Instant::now() + ttl;
+        }
+        Ok(key.to_string())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), SessionError> {
+        self.inner.lock().unwrap().entries.remove(key);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[crate::rt_test]
+    async fn test_memory_store() {
+        let store = MemorySessionStore::new();
+        let ttl = Duration::from_secs(60);
+
+        let mut state = SessionState::new();
+        state.insert("key".to_string(), "1".to_string());
+        let key = store.save(state.clone(), ttl).await.unwrap();
+        assert_eq!(key.len(), 64);
+        let other = store.save(state.clone(), ttl).await.unwrap();
+        assert_ne!(key, other);
+        store.delete(&other).await.unwrap();
+        assert_eq!(store.load(&key).await.unwrap(), Some(state.clone()));
+
+        state.insert("key".to_string(), "2".to_string());
+        let key2 = store.update(&key, state.clone(), ttl).await.unwrap();
+        assert_eq!(key, key2);
+        assert_eq!(store.load(&key).await.unwrap(), Some(state.clone()));
+
+        store.delete(&key).await.unwrap();
+        assert_eq!(store.load(&key).await.unwrap(), None);
+        assert!(store.is_empty());
+
+        // expired session
+        let key = store.save(state.clone(), Duration::ZERO).await.unwrap();
+        assert_eq!(store.load(&key).await.unwrap(), None);
+        assert_eq!(store.len(), 1);
+
+        // expired sessions are swept periodically
+        store.inner.lock().unwrap().next_sweep = Instant::now();
+        store.save(state, ttl).await.unwrap();
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.load(&key).await.unwrap(), None);
+    }
+}
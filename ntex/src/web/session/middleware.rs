@@ -0,0 +1,453 @@
+use std::{fmt, rc::Rc, time::Duration};
+
+use coo_kie::{Cookie, CookieJar, Key, SameSite};
+
+use crate::http::HttpMessage;
+use crate::service::{Middleware, Service, ServiceCtx};
+use crate::web::{ErrorRenderer, WebRequest, WebResponse, WebResponseError};
+
+use super::{Session, SessionError, SessionStatus, SessionStore};
+
+/// Session cookie protection
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CookieContentSecurity {
+    /// Cookie value is encrypted, client can not read or modify it
+    Private,
+    /// Cookie value is signed, client can read but can not modify it
+    Signed,
+}
+
+/// Session time to live extension policy
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TtlExtensionPolicy {
+    /// Session ttl is extended on every request
+    OnEveryRequest,
+    /// Session ttl is extended only if session state has been changed
+    OnStateChanges,
+}
+
+/// `Middleware` for session management.
+///
+/// Middleware loads session state from the store and makes it available
+/// via [`Session`] extractor. Changed session state is persisted after
+/// request handling and session cookie is updated.
+///
+/// ```rust
+/// use ntex::web::{self, App, HttpResponse};
+/// use ntex::web::session::{Key, MemorySessionStore, SameSite, SessionMiddleware};
+///
+/// let key = Key::generate();
+/// let store = MemorySessionStore::new();
+///
+/// let app = App::new()
+///     .wrap(
+///         SessionMiddleware::new(store.clone(), key.clone())
+///             .cookie_name("sid")
+///             .cookie_same_site(SameSite::Strict)
+///             .session_ttl(std::time::Duration::from_secs(3600)),
+///     )
+///     .service(web::resource("/").to(|| async { HttpResponse::Ok() }));
+/// ```
+pub struct SessionMiddleware<St> {
+    inner: Rc<Inner<St>>,
+}
+
+struct Inner<St> {
+    store: St,
+    key: Key,
+    name: String,
+    path: String,
+    domain: Option<String>,
+    secure: bool,
+    http_only: bool,
+    same_site: Option<SameSite>,
+    security: CookieContentSecurity,
+    ttl: Duration,
+    ttl_policy: TtlExtensionPolicy,
+}
+
+impl<St: SessionStore> SessionMiddleware<St> {
+    /// Construct `SessionMiddleware` middleware.
+    ///
+    /// `key` is used for cookie signing and encryption, it must be
+    /// the same for all workers.
+    pub fn new(store: St, key: Key) -> Self {
+        SessionMiddleware {
+            inner: Rc::new(Inner {
+                store,
+                key,
+                name: "id".to_string(),
+                path: "/".to_string(),
+                domain: None,
+                secure: true,
+                http_only: true,
+                same_site: Some(SameSite::Lax),
+                security: CookieContentSecurity::Private,
+                ttl: Duration::from_secs(24 * 60 * 60),
+                ttl_policy: TtlExtensionPolicy::OnStateChanges,
+            }),
+        }
+    }
+
+    fn inner(&mut self) -> &mut Inner<St> {
+        Rc::get_mut(&mut self.inner).expect("Multiple copies exist")
+    }
+
+    /// Set session cookie name. By default name is `id`
+    pub fn cookie_name(mut self, name: impl Into<String>) -> Self {
+        self.inner().name = name.into();
+        self
+    }
+
+    /// Set session cookie path. By default path is `/`
+    pub fn cookie_path(mut self, path: impl Into<String>) -> Self {
+        self.inner().path = path.into();
+        self
+    }
+
+    /// Set session cookie domain
+    pub fn cookie_domain(mut self, domain: impl Into<String>) -> Self {
+        self.inner().domain = Some(domain.into());
+        self
+    }
+
+    /// Set `Secure` attribute of session cookie. By default it is set
+    pub fn cookie_secure(mut self, secure: bool) -> Self {
+        self.inner().secure = secure;
+        self
+    }
+
+    /// Set `HttpOnly` attribute of session cookie. By default it is set
+    pub fn cookie_http_only(mut self, http_only: bool) -> Self {
+        self.inner().http_only = http_only;
+        self
+    }
+
+    /// Set `SameSite` attribute of session cookie. By default it is `Lax`
+    pub fn cookie_same_site(mut self, same_site: SameSite) -> Self {
+        self.inner().same_site = Some(same_site);
+        self
+    }
+
+    /// Set session cookie protection. By default cookie is encrypted
+    pub fn cookie_content_security(mut self, security: CookieContentSecurity) -> Self {
+        self.inner().security = security;
+        self
+    }
+
+    /// Set session time to live. By default ttl is one day
+    ///
+    /// Ttl is used for store expiration and for cookie's `Max-Age` attribute.
+    pub fn session_ttl(mut self, ttl: Duration) -> Self {
+        self.inner().ttl = ttl;
+        self
+    }
+
+    /// Set session ttl extension policy. By default ttl is extended
+    /// only on state changes
+    pub fn ttl_extension_policy(mut self, policy: TtlExtensionPolicy) -> Self {
+        self.inner().ttl_policy = policy;
+        self
+    }
+}
+
+impl<St> fmt::Debug for SessionMiddleware<St> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SessionMiddleware")
+            .field("name", &self.inner.name)
+            .field("security", &self.inner.security)
+            .field("ttl", &self.inner.ttl)
+            .finish()
+    }
+}
+
+impl<S, St: SessionStore> Middleware<S> for SessionMiddleware<St> {
+    type Service = SessionService<S, St>;
+
+    fn create(&self, service: S) -> Self::Service {
+        SessionService {
+            service,
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<St: SessionStore> Inner<St> {
+    /// Extract session key from the request cookie
+    fn session_key<Err>(&self, req: &WebRequest<Err>) -> Option<String> {
+        let cookie = req.cookie(&self.name)?;
+        let mut jar = CookieJar::new();
+        jar.add_original(cookie);
+
+        let cookie = match self.security {
+            CookieContentSecurity::Private => jar.private(&self.key).get(&self.name),
+            CookieContentSecurity::Signed => jar.signed(&self.key).get(&self.name),
+        };
+        cookie.map(|c| c.value().to_string())
+    }
+
+    /// Build protected session cookie
+    fn cookie(&self, value: Option<String>) -> Cookie<'static> {
+        let mut cookie = Cookie::new(self.name.clone(), value.clone().unwrap_or_default());
+        cookie.set_path(self.path.clone());
+        cookie.set_secure(self.secure);
+        cookie.set_http_only(self.http_only);
+        if let Some(ref domain) = self.domain {
+            cookie.set_domain(domain.clone());
+        }
+        if let Some(same_site) = self.same_site {
+            cookie.set_same_site(same_site);
+        }
+
+        if value.is_none() {
+            cookie.make_removal();
+            return cookie;
+        }
+        cookie.set_max_age(coo_kie::time::Duration::seconds(
+            self.ttl.as_secs().try_into().unwrap_or(i64::MAX),
+        ));
+
+        let mut jar = CookieJar::new();
+        match self.security {
+            CookieContentSecurity::Private => jar.private_mut(&self.key).add(cookie),
+            CookieContentSecurity::Signed => jar.signed_mut(&self.key).add(cookie),
+        }
+        jar.get(&self.name).cloned().expect("Cookie is added")
+    }
+
+    /// Persist session state, returns new cookie value
+    async fn persist(
+        &self,
+        key: Option<String>,
+        session: &Session,
+    ) -> Result<Option<Cookie<'static>>, SessionError> {
+        let (status, state) = session.take();
+
+        let value = match (status, key) {
+            (SessionStatus::Purged, Some(key)) => {
+                self.store.delete(&key).await?;
+                return Ok(Some(self.cookie(None)));
+            }
+            (SessionStatus::Purged, None) => return Ok(None),
+            (SessionStatus::Renewed, key) => {
+                if let Some(key) = key {
+                    self.store.delete(&key).await?;
+                }
+                self.store.save(state, self.ttl).await?
+            }
+            (SessionStatus::Changed, Some(key)) => {
+                self.store.update(&key, state, self.ttl).await?
+            }
+            (SessionStatus::Changed, None) => {
+                if state.is_empty() {
+                    return Ok(None);
+                }
+                self.store.save(state, self.ttl).await?
+            }
+            (SessionStatus::Unchanged, Some(key)) => {
+                if self.ttl_policy != TtlExtensionPolicy::OnEveryRequest {
+                    return Ok(None);
+                }
+                self.store.update_ttl(&key, self.ttl).await?
+            }
+            (SessionStatus::Unchanged, None) => return Ok(None),
+        };
+        Ok(Some(self.cookie(Some(value))))
+    }
+}
+
+#[doc(hidden)]
+pub struct SessionService<S, St> {
+    service: S,
+    inner: Rc<Inner<St>>,
+}
+
+impl<S, St> fmt::Debug for SessionService<S, St> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SessionService")
+            .field("name", &self.inner.name)
+            .finish()
+    }
+}
+
+impl<S, St, Err> Service<WebRequest<Err>> for SessionService<S, St>
+where
+    S: Service<WebRequest<Err>, Response = WebResponse>,
+    St: SessionStore,
+    Err: ErrorRenderer,
+{
+    type Response = WebResponse;
+    type Error = S::Error;
+
+    crate::forward_poll!(service);
+    crate::forward_ready!(service);
+    crate::forward_shutdown!(service);
+
+    async fn call(
+        &self,
+        req: WebRequest<Err>,
+        ctx: ServiceCtx<'_, Self>,
+    ) -> Result<Self::Response, Self::Error> {
+        let key = self.inner.session_key(&req);
+
+        let state = if let Some(ref key) = key {
+            match self.inner.store.load(key).await {
+                Ok(state) => state,
+                Err(e) => return Ok(req.render_error(e)),
+            }
+        } else {
+            None
+        };
+        // session key is not valid anymore
+        let key = if state.is_some() { key } else { None };
+
+        let session = Session::new(state.unwrap_or_default());
+        req.extensions_mut().insert(session.clone());
+
+        let mut res = ctx.call(&self.service, req).await?;
+
+        match self.inner.persist(key, &session).await {
+            Ok(Some(cookie)) => {
+                if let Err(e) = res.response_mut().add_cookie(cookie) {
+                    log::error!("Cannot set session cookie: {:?}", e);
+                }
+                Ok(res)
+            }
+            Ok(None) => Ok(res),
+            Err(e) => {
+                log::error!("Cannot persist session: {}", e);
+                let req = res.request().clone();
+                let resp = WebResponseError::<Err>::error_response(&e, &req);
+                Ok(WebResponse::new(resp, req))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{header, StatusCode};
+    use crate::util::Bytes;
+    use crate::web::session::{CookieSessionStore, MemorySessionStore};
+    use crate::web::test::{self, TestRequest};
+    use crate::web::{self, App, Error, HttpResponse};
+
+    async fn counter(session: Session) -> Result<HttpResponse, Error> {
+        let counter = session.get::<u32>("counter")?.unwrap_or(0) + 1;
+        session.insert("counter", counter)?;
+        Ok(HttpResponse::Ok().body(counter.to_string()))
+    }
+
+    fn session_cookie(res: &WebResponse) -> Option<Cookie<'static>> {
+        res.response()
+            .cookies()
+            .find(|c| c.name() == "id")
+            .map(|c| c.into_owned())
+    }
+
+    #[crate::rt_test]
+    async fn test_cookie_session() {
+        let key = Key::generate();
+        let srv = test::init_service(
+            App::new()
+                .wrap(
+                    SessionMiddleware::new(CookieSessionStore, key.clone())
+                        .cookie_content_security(CookieContentSecurity::Signed)
+                        .cookie_same_site(SameSite::Strict),
+                )
+                .service(web::resource("/").to(counter))
+                .service(web::resource("/purge").to(|session: Session| async move {
+                    session.purge();
+                    HttpResponse::Ok()
+                })),
+        )
+        .await;
+
+        let res = test::call_service(&srv, TestRequest::default().to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let cookie = session_cookie(&res).unwrap();
+        assert!(cookie.http_only().unwrap());
+        assert!(cookie.secure().unwrap());
+        assert_eq!(cookie.same_site(), Some(SameSite::Strict));
+        assert_eq!(cookie.path(), Some("/"));
+
+        let req = TestRequest::default()
+            .header(header::COOKIE, cookie.stripped().to_string())
+            .to_request();
+        let res = test::call_service(&srv, req).await;
+        assert_eq!(test::read_body(res).await, Bytes::from_static(b"2"));
+
+        // forged cookie is ignored
+        let req = TestRequest::default()
+            .header(header::COOKIE, "id={\"counter\":\"10\"}")
+            .to_request();
+        let res = test::call_service(&srv, req).await;
+        assert_eq!(test::read_body(res).await, Bytes::from_static(b"1"));
+
+        let req = TestRequest::with_uri("/purge")
+            .header(header::COOKIE, cookie.stripped().to_string())
+            .to_request();
+        let res = test::call_service(&srv, req).await;
+        let cookie = session_cookie(&res).unwrap();
+        assert_eq!(cookie.value(), "");
+        assert_eq!(cookie.max_age(), Some(coo_kie::time::Duration::ZERO));
+    }
+
+    #[crate::rt_test]
+    async fn test_memory_session() {
+        let store = MemorySessionStore::new();
+        let srv = test::init_service(
+            App::new()
+                .wrap(
+                    SessionMiddleware::new(store.clone(), Key::generate())
+                        .ttl_extension_policy(TtlExtensionPolicy::OnEveryRequest),
+                )
+                .service(web::resource("/").to(counter))
+                .service(web::resource("/renew").to(|session: Session| async move {
+                    session.renew();
+                    HttpResponse::Ok()
+                }))
+                .service(web::resource("/noop").to(|| async { HttpResponse::Ok() })),
+        )
+        .await;
+
+        let res = test::call_service(&srv, TestRequest::default().to_request()).await;
+        let cookie = session_cookie(&res).unwrap();
+        assert_eq!(store.len(), 1);
+
+        let req = TestRequest::default()
+            .header(header::COOKIE, cookie.stripped().to_string())
+            .to_request();
+        let res = test::call_service(&srv, req).await;
+        assert_eq!(test::read_body(res).await, Bytes::from_static(b"2"));
+
+        // ttl is extended, cookie is set
+        let req = TestRequest::with_uri("/noop")
+            .header(header::COOKIE, cookie.stripped().to_string())
+            .to_request();
+        let res = test::call_service(&srv, req).await;
+        assert!(session_cookie(&res).is_some());
+
+        let req = TestRequest::with_uri("/renew")
+            .header(header::COOKIE, cookie.stripped().to_string())
+            .to_request();
+        let res = test::call_service(&srv, req).await;
+        let new_cookie = session_cookie(&res).unwrap();
+        assert_ne!(new_cookie.value(), cookie.value());
+        assert_eq!(store.len(), 1);
+
+        // old session key is not valid
+        let req = TestRequest::default()
+            .header(header::COOKIE, cookie.stripped().to_string())
+            .to_request();
+        let res = test::call_service(&srv, req).await;
+        assert_eq!(test::read_body(res).await, Bytes::from_static(b"1"));
+
+        let req = TestRequest::default()
+            .header(header::COOKIE, new_cookie.stripped().to_string())
+            .to_request();
+        let res = test::call_service(&srv, req).await;
+        assert_eq!(test::read_body(res).await, Bytes::from_static(b"3"));
+    }
+}
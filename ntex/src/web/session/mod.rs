@@ -0,0 +1,261 @@
+//! Session support
+//!
+//! Session state is a map of string keys to json serialized values.
+//! [`SessionMiddleware`] loads state from a [`SessionStore`] before
+//! request handling and persists modified state afterwards. Session
+//! key is transferred in a signed or encrypted cookie.
+//!
+//! ```rust,no_run
+//! use ntex::web::{self, App, HttpResponse, Error};
+//! use ntex::web::session::{CookieSessionStore, Key, Session, SessionMiddleware};
+//!
+//! async fn index(session: Session) -> Result<HttpResponse, Error> {
+//!     let counter = session.get::<i32>("counter")?.unwrap_or(0) + 1;
+//!     session.insert("counter", counter)?;
+//!     Ok(HttpResponse::Ok().body(format!("Counter: {}", counter)))
+//! }
+//!
+//! #[ntex::main]
+//! async fn main() -> std::io::Result<()> {
+//!     // key must be shared between workers
+//!     let key = Key::generate();
+//!
+//!     web::server(move || {
+//!         App::new()
+//!             .wrap(SessionMiddleware::new(CookieSessionStore::default(), key.clone()))
+//!             .service(web::resource("/").to(index))
+//!     })
+//!     .bind("127.0.0.1:8080")?
+//!     .run()
+//!     .await
+//! }
+//! ```
+use std::{cell::RefCell, collections::HashMap, fmt, mem, rc::Rc};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::http::{Payload, StatusCode};
+use crate::web::error::{ErrorRenderer, WebResponseError};
+use crate::web::{FromRequest, HttpRequest};
+
+mod cookie;
+mod memory;
+mod middleware;
+mod store;
+
+pub use self::cookie::CookieSessionStore;
+pub use self::memory::MemorySessionStore;
+pub use self::middleware::{
+    CookieContentSecurity, SessionMiddleware, SessionService, TtlExtensionPolicy,
+};
+pub use self::store::{SessionState, SessionStore};
+pub use coo_kie::{Key, SameSite};
+
+/// Errors which can occur during session processing
+#[derive(thiserror::Error, Debug)]
+pub enum SessionError {
+    /// Session value serialization error
+    #[error("Session value serialization error: {0}")]
+    Serialize(#[from] serde_json::Error),
+    /// Session state does not fit into cookie
+    #[error("Session state is too large ({0} bytes)")]
+    Overflow(usize),
+    /// Session store error
+    #[error("Session store error: {0}")]
+    Store(String),
+}
+
+impl<Err: ErrorRenderer> WebResponseError<Err> for SessionError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
+/// Status of the session after request handling
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SessionStatus {
+    /// Session state has been changed
+    Changed,
+    /// Session must be removed
+    Purged,
+    /// Session key must be regenerated
+    Renewed,
+    /// Session state has not been changed
+    Unchanged,
+}
+
+/// Session extractor
+///
+/// Session is loaded by [`SessionMiddleware`], if middleware is not
+/// registered all session changes are lost.
+#[derive(Clone)]
+pub struct Session(Rc<RefCell<SessionInner>>);
+
+struct SessionInner {
+    state: SessionState,
+    status: SessionStatus,
+}
+
+impl Session {
+    fn new(state: SessionState) -> Self {
+        Session(Rc::new(RefCell::new(SessionInner {
+            state,
+            status: SessionStatus::Unchanged,
+        })))
+    }
+
+    /// Get a value from the session
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, SessionError> {
+        if let Some(value) = self.0.borrow().state.get(key) {
+            Ok(Some(serde_json::from_str(value)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Check if session contains a value
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.0.borrow().state.contains_key(key)
+    }
+
+    /// Set a value to the session
+    pub fn insert<T: Serialize>(
+        &self,
+        key: impl Into<String>,
+        value: T,
+    ) -> Result<(), SessionError> {
+        let value = serde_json::to_string(&value)?;
+        let mut inner = self.0.borrow_mut();
+        if inner.status != SessionStatus::Purged {
+            inner.state.insert(key.into(), value);
+            inner.changed();
+        }
+        Ok(())
+    }
+
+    /// Remove a value from the session
+    pub fn remove(&self, key: &str) -> Option<String> {
+        let mut inner = self.0.borrow_mut();
+        if inner.status != SessionStatus::Purged {
+            let value = inner.state.remove(key);
+            if value.is_some() {
+                inner.changed();
+            }
+            value
+        } else {
+            None
+        }
+    }
+
+    /// Remove all values from the session
+    pub fn clear(&self) {
+        let mut inner = self.0.borrow_mut();
+        if inner.status != SessionStatus::Purged && !inner.state.is_empty() {
+            inner.state.clear();
+            inner.changed();
+        }
+    }
+
+    /// Remove session, both client and server side
+    pub fn purge(&self) {
+        let mut inner = self.0.borrow_mut();
+        inner.status = SessionStatus::Purged;
+        inner.state.clear();
+    }
+
+    /// Regenerate session key, session state is preserved.
+    ///
+    /// Session key must be renewed on privilege level change,
+    /// for example after login.
+    pub fn renew(&self) {
+        let mut inner = self.0.borrow_mut();
+        if inner.status != SessionStatus::Purged {
+            inner.status = SessionStatus::Renewed;
+        }
+    }
+
+    /// Session status
+    pub fn status(&self) -> SessionStatus {
+        self.0.borrow().status
+    }
+
+    /// Copy of the session state
+    pub fn entries(&self) -> SessionState {
+        self.0.borrow().state.clone()
+    }
+
+    fn take(&self) -> (SessionStatus, SessionState) {
+        let mut inner = self.0.borrow_mut();
+        (inner.status, mem::take(&mut inner.state))
+    }
+}
+
+impl SessionInner {
+    fn changed(&mut self) {
+        if self.status == SessionStatus::Unchanged {
+            self.status = SessionStatus::Changed;
+        }
+    }
+}
+
+impl fmt::Debug for Session {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inner = self.0.borrow();
+        f.debug_struct("Session")
+            .field("state", &inner.state)
+            .field("status", &inner.status)
+            .finish()
+    }
+}
+
+impl<Err: ErrorRenderer> FromRequest<Err> for Session {
+    type Error = Err::Container;
+
+    #[inline]
+    async fn from_request(
+        req: &HttpRequest,
+        _: &mut Payload,
+    ) -> Result<Session, Self::Error> {
+        if let Some(session) = req.extensions().get::<Session>() {
+            return Ok(session.clone());
+        }
+        let session = Session::new(HashMap::new());
+        req.extensions_mut().insert(session.clone());
+        Ok(session)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::web::test::{from_request, TestRequest};
+
+    #[crate::rt_test]
+    async fn test_session() {
+        let (req, mut pl) = TestRequest::default().to_http_parts();
+        let session = from_request::<Session>(&req, &mut pl).await.unwrap();
+        assert_eq!(session.status(), SessionStatus::Unchanged);
+        assert_eq!(session.get::<u32>("counter").unwrap(), None);
+
+        session.insert("counter", 10).unwrap();
+        assert_eq!(session.status(), SessionStatus::Changed);
+        assert_eq!(session.get::<u32>("counter").unwrap(), Some(10));
+        assert!(session.get::<String>("counter").is_err());
+
+        // same session instance for request
+        let s = from_request::<Session>(&req, &mut pl).await.unwrap();
+        assert!(s.contains_key("counter"));
+
+        session.renew();
+        assert_eq!(session.status(), SessionStatus::Renewed);
+        assert_eq!(session.remove("counter"), Some("10".to_string()));
+        assert_eq!(session.status(), SessionStatus::Renewed);
+
+        session.insert("key", "value").unwrap();
+        session.purge();
+        assert_eq!(session.status(), SessionStatus::Purged);
+        assert!(session.entries().is_empty());
+        session.insert("key", "value").unwrap();
+        assert!(session.entries().is_empty());
+    }
+}
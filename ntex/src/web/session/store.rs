@@ -0,0 +1,49 @@
+use std::{collections::HashMap, future::Future, time::Duration};
+
+use super::SessionError;
+
+/// Session state, map of keys to json serialized values
+pub type SessionState = HashMap<String, String>;
+
+/// Session storage backend
+///
+/// Store is responsible for persisting session state and for session key
+/// generation. Session key is sent to the client in a cookie.
+pub trait SessionStore: 'static {
+    /// Load session state for the session key.
+    ///
+    /// Returns `None` if session does not exist or is expired.
+    fn load(
+        &self,
+        key: &str,
+    ) -> impl Future<Output = Result<Option<SessionState>, SessionError>>;
+
+    /// Persist new session state, returns new session key.
+    fn save(
+        &self,
+        state: SessionState,
+        ttl: Duration,
+    ) -> impl Future<Output = Result<String, SessionError>>;
+
+    /// Update existing session state, returns session key.
+    ///
+    /// Returned key could be different from provided key.
+    fn update(
+        &self,
+        key: &str,
+        state: SessionState,
+        ttl: Duration,
+    ) -> impl Future<Output = Result<String, SessionError>>;
+
+    /// Extend session time to live, returns session key.
+    ///
+    /// Returned key could be different from provided key.
+    fn update_ttl(
+        &self,
+        key: &str,
+        ttl: Duration,
+    ) -> impl Future<Output = Result<String, SessionError>>;
+
+    /// Remove session.
+    fn delete(&self, key: &str) -> impl Future<Output = Result<(), SessionError>>;
+}
@@ -0,0 +1,323 @@
+//! Server-Sent Events support
+//!
+//! ```rust
+//! use ntex::web::{self, sse};
+//!
+//! async fn events() -> sse::Sse {
+//!     let (tx, sse) = sse::channel();
+//!
+//!     ntex::rt::spawn(async move {
+//!         for idx in 0..10 {
+//!             let event = sse::Event::new().event("counter").data(idx.to_string());
+//!             if tx.send(event).is_err() {
+//!                 // client is disconnected
+//!                 break;
+//!             }
+//!             ntex::time::sleep(ntex::time::Seconds(1)).await;
+//!         }
+//!     });
+//!     sse
+//! }
+//! # fn main() {}
+//! ```
+use std::{error::Error, fmt, task::Context, task::Poll, time::Duration};
+
+use serde::Serialize;
+
+use crate::channel::mpsc;
+use crate::http::body::{Body, BodySize, MessageBody};
+use crate::http::header::{self, HeaderValue};
+use crate::http::{Response, StatusCode};
+use crate::io::OnDisconnect;
+use crate::time::{interval, Interval, Millis};
+use crate::util::{BufMut, Bytes, BytesMut};
+use crate::web::{ErrorRenderer, HttpRequest, Responder};
+
+/// Default keep-alive ping interval
+const DEFAULT_KEEP_ALIVE: Millis = Millis(15_000);
+
+/// Create server-sent events channel.
+///
+/// Events sent to the sender are streamed to the client. Stream is
+/// finished when all senders are dropped. If client disconnects,
+/// `Sender::send()` returns error and `Sender::is_closed()` returns `true`.
+pub fn channel() -> (mpsc::Sender<Event>, Sse) {
+    let (tx, rx) = mpsc::channel();
+    (tx, Sse::new(rx))
+}
+
+/// Server-sent event
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Event {
+    id: Option<String>,
+    event: Option<String>,
+    data: Option<String>,
+    retry: Option<Duration>,
+    comment: Option<String>,
+}
+
+impl Event {
+    /// Create empty event
+    pub fn new() -> Self {
+        Event::default()
+    }
+
+    /// Set event id
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Set event type
+    pub fn event(mut self, event: impl Into<String>) -> Self {
+        self.event = Some(event.into());
+        self
+    }
+
+    /// Set event data, multi-line data is split to several `data` fields
+    pub fn data(mut self, data: impl Into<String>) -> Self {
+        self.data = Some(data.into());
+        self
+    }
+
+    /// Set json serialized event data
+    pub fn json<T: Serialize>(mut self, data: &T) -> Result<Self, serde_json::Error> {
+        self.data = Some(serde_json::to_string(data)?);
+        Ok(self)
+    }
+
+    /// Set client reconnection time
+    pub fn retry(mut self, retry: Duration) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    /// Set comment
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    fn encode(&self, buf: &mut BytesMut) {
+        fn field(buf: &mut BytesMut, name: &str, value: &str) {
+            for line in value.split('\n') {
+                let line = line.strip_suffix('\r').unwrap_or(line);
+                // comment line starts with colon
+                buf.put_slice(if name.is_empty() {
+                    &b":"[..]
+                } else {
+                    name.as_bytes()
+                });
+                if !line.is_empty() {
+                    buf.put_slice(if name.is_empty() {
+                        &b" "[..]
+                    } else {
+                        &b": "[..]
+                    });
+                    buf.put_slice(line.replace('\r', "").as_bytes());
+                }
+                buf.put_u8(b'\n');
+            }
+        }
+
+        let len = buf.len();
+        if let Some(ref comment) = self.comment {
+            field(buf, "", comment);
+        }
+        if let Some(ref event) = self.event {
+            field(buf, "event", &event.replace(['\r', '\n'], ""));
+        }
+        if let Some(ref id) = self.id {
+            field(buf, "id", &id.replace(['\r', '\n', '\0'], ""));
+        }
+        if let Some(retry) = self.retry {
+            field(buf, "retry", &retry.as_millis().to_string());
+        }
+        if let Some(ref data) = self.data {
+            field(buf, "data", data);
+        }
+        if buf.len() != len {
+            buf.put_u8(b'\n');
+        }
+    }
+}
+
+/// Server-sent events responder
+///
+/// Responds with `text/event-stream` body. Keep-alive comments are sent
+/// periodically to keep connection open, by default every 15 seconds.
+pub struct Sse {
+    rx: mpsc::Receiver<Event>,
+    keep_alive: Option<Millis>,
+}
+
+impl Sse {
+    /// Create responder for events receiver
+    pub fn new(rx: mpsc::Receiver<Event>) -> Self {
+        Sse {
+            rx,
+            keep_alive: Some(DEFAULT_KEEP_ALIVE),
+        }
+    }
+
+    /// Set keep-alive ping interval
+    pub fn keep_alive<T: Into<Millis>>(mut self, period: T) -> Self {
+        self.keep_alive = Some(period.into());
+        self
+    }
+
+    /// Disable keep-alive pings
+    pub fn disable_keep_alive(mut self) -> Self {
+        self.keep_alive = None;
+        self
+    }
+}
+
+impl fmt::Debug for Sse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sse")
+            .field("keep_alive", &self.keep_alive)
+            .finish()
+    }
+}
+
+impl<Err: ErrorRenderer> Responder<Err> for Sse {
+    async fn respond_to(self, req: &HttpRequest) -> Response {
+        let body = SseBody {
+            rx: self.rx,
+            keep_alive: self.keep_alive.map(interval),
+            disconnect: req.io().map(|io| io.on_disconnect()),
+        };
+
+        Response::build(StatusCode::OK)
+            .header(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("text/event-stream"),
+            )
+            .header(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"))
+            .body(Body::from_message(body))
+    }
+}
+
+struct SseBody {
+    rx: mpsc::Receiver<Event>,
+    keep_alive: Option<Interval>,
+    disconnect: Option<OnDisconnect>,
+}
+
+impl MessageBody for SseBody {
+    fn size(&self) -> BodySize {
+        BodySize::Stream
+    }
+
+    fn poll_next_chunk(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Box<dyn Error>>>> {
+        if let Some(ref disconnect) = self.disconnect {
+            if disconnect.poll_ready(cx).is_ready() {
+                log::trace!("Client is disconnected, close sse stream");
+                self.rx.close();
+                return Poll::Ready(None);
+            }
+        }
+
+        let mut buf = BytesMut::new();
+        loop {
+            match self.rx.poll_recv(cx) {
+                Poll::Ready(Some(event)) => event.encode(&mut buf),
+                Poll::Ready(None) => {
+                    if buf.is_empty() {
+                        return Poll::Ready(None);
+                    }
+                    break;
+                }
+                Poll::Pending => break,
+            }
+        }
+        if !buf.is_empty() {
+            return Poll::Ready(Some(Ok(buf.freeze())));
+        }
+
+        if let Some(ref keep_alive) = self.keep_alive {
+            if keep_alive.poll_tick(cx).is_ready() {
+                return Poll::Ready(Some(Ok(Bytes::from_static(b": ping\n\n"))));
+            }
+        }
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::web::test::{read_body, respond_to, TestRequest};
+    use crate::web::WebResponse;
+
+    fn encode(event: Event) -> Bytes {
+        let mut buf = BytesMut::new();
+        event.encode(&mut buf);
+        buf.freeze()
+    }
+
+    #[test]
+    fn test_event() {
+        assert_eq!(encode(Event::new()), Bytes::new());
+        assert_eq!(
+            encode(Event::new().data("test")),
+            Bytes::from_static(b"data: test\n\n")
+        );
+        assert_eq!(
+            encode(
+                Event::new()
+                    .id("1")
+                    .event("update")
+                    .retry(Duration::from_secs(1))
+                    .data("line1\r\nline2\n")
+            ),
+            Bytes::from_static(
+                b"event: update\nid: 1\nretry: 1000\ndata: line1\ndata: line2\ndata\n\n"
+            )
+        );
+        assert_eq!(
+            encode(Event::new().comment("ping").event("a\nb")),
+            Bytes::from_static(b": ping\nevent: ab\n\n")
+        );
+        assert_eq!(
+            encode(Event::new().json(&vec![1, 2]).unwrap()),
+            Bytes::from_static(b"data: [1,2]\n\n")
+        );
+    }
+
+    #[crate::rt_test]
+    async fn test_sse() {
+        let (tx, sse) = channel();
+        tx.send(Event::new().data("1")).unwrap();
+        tx.send(Event::new().data("2")).unwrap();
+        drop(tx);
+
+        let req = TestRequest::default().to_http_request();
+        let resp = respond_to(sse.disable_keep_alive(), &req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            "text/event-stream"
+        );
+        let body = read_body(WebResponse::new(resp, req)).await;
+        assert_eq!(body, Bytes::from_static(b"data: 1\n\ndata: 2\n\n"));
+    }
+
+    #[crate::rt_test]
+    async fn test_keep_alive() {
+        let (tx, sse) = channel();
+        let req = TestRequest::default().to_http_request();
+        let resp = respond_to(sse.keep_alive(Millis(50)), &req).await;
+
+        crate::rt::spawn(async move {
+            crate::time::sleep(Millis(120)).await;
+            drop(tx);
+        });
+        let body = read_body(WebResponse::new(resp, req)).await;
+        assert!(body.starts_with(b": ping\n\n"));
+    }
+}
@@ -0,0 +1,132 @@
+//! Typed header extractor
+use std::{fmt, ops};
+
+use crate::http::header::{TypedHeader, TypedHeaderError};
+use crate::http::Payload;
+use crate::web::error::ErrorRenderer;
+use crate::web::{FromRequest, HttpRequest};
+
+/// Extract typed header from the request.
+///
+/// Missing or malformed header results in *400 Bad Request* response,
+/// `Option<Header<T>>` could be used for optional headers.
+///
+/// ## Example
+///
+/// ```rust
+/// use ntex::http::header::{Accept, Authorization};
+/// use ntex::web::{self, types::Header};
+///
+/// async fn index(
+///     auth: Header<Authorization>,
+///     _accept: Option<Header<Accept>>,
+/// ) -> String {
+///     match auth.into_inner() {
+///         Authorization::Bearer(token) => format!("Token {}", token),
+///         _ => "Unsupported scheme".to_string(),
+///     }
+/// }
+///
+/// fn main() {
+///     let app = web::App::new().service(
+///         web::resource("/index.html").route(web::get().to(index)));
+/// }
+/// ```
+pub struct Header<T>(pub T);
+
+impl<T> Header<T> {
+    /// Deconstruct to an inner value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> ops::Deref for Header<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> ops::DerefMut for Header<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Header<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Header").field(&self.0).finish()
+    }
+}
+
+impl<T, Err> FromRequest<Err> for Header<T>
+where
+    T: TypedHeader,
+    Err: ErrorRenderer,
+{
+    type Error = TypedHeaderError;
+
+    #[inline]
+    async fn from_request(req: &HttpRequest, _: &mut Payload) -> Result<Self, Self::Error> {
+        req.headers().typed_get::<T>().map(Header).map_err(|e| {
+            log::debug!("Failed during Header extractor: {}", e);
+            e
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::header::{self, Authorization, CacheControl, CacheDirective};
+    use crate::http::StatusCode;
+    use crate::web::test::{call_service, from_request, init_service, TestRequest};
+    use crate::web::{self, App, HttpResponse};
+
+    #[crate::rt_test]
+    async fn test_extract() {
+        let (req, mut pl) = TestRequest::default()
+            .header(header::AUTHORIZATION, "Bearer abc")
+            .to_http_parts();
+        let auth = from_request::<Header<Authorization>>(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(*auth, Authorization::bearer("abc"));
+        assert!(format!("{:?}", auth).contains("Bearer"));
+
+        let (req, mut pl) = TestRequest::default().to_http_parts();
+        let res = from_request::<Header<Authorization>>(&req, &mut pl).await;
+        assert_eq!(
+            res.err(),
+            Some(TypedHeaderError::Missing(header::AUTHORIZATION))
+        );
+    }
+
+    #[crate::rt_test]
+    async fn test_bad_request() {
+        let srv = init_service(App::new().service(web::resource("/").to(
+            |cc: Header<CacheControl>| async move {
+                HttpResponse::Ok().typed_header(cc.into_inner()).finish()
+            },
+        )))
+        .await;
+
+        let req = TestRequest::default()
+            .header(header::CACHE_CONTROL, "no-cache, max-age=10")
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().typed_get::<CacheControl>().unwrap().0,
+            vec![CacheDirective::NoCache, CacheDirective::MaxAge(10)]
+        );
+
+        let req = TestRequest::default()
+            .header(header::CACHE_CONTROL, "max-age=abc")
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,820 @@
+//! Multipart payload extractor
+use std::{fmt, ops};
+
+use mime::Mime;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, NON_ALPHANUMERIC};
+use serde::de::DeserializeOwned;
+
+#[cfg(feature = "compress")]
+use crate::http::encoding::Decoder;
+use crate::http::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_DISPOSITION};
+use crate::http::{header, HttpMessage, Payload};
+use crate::util::{stream_recv, Buf, Bytes, BytesMut};
+use crate::web::error::{ErrorRenderer, MultipartError};
+use crate::web::{FromRequest, HttpRequest};
+
+/// Max size of field headers
+const MAX_HEADERS_SIZE: usize = 8192;
+/// Max number of field headers
+const MAX_HEADERS: usize = 32;
+
+/// Multipart payload extractor (`multipart/form-data`)
+///
+/// Streaming access to fields of the request's body. Fields must be
+/// processed in order, unread field data is skipped when the next field
+/// is requested.
+///
+/// [**MultipartConfig**](struct.MultipartConfig.html) allows to configure
+/// extraction process.
+///
+/// ## Example
+///
+/// ```rust
+/// use ntex::web::{self, error::MultipartError};
+///
+/// async fn upload(mut mp: web::types::Multipart) -> Result<String, MultipartError> {
+///     let mut total = 0;
+///     while let Some(field) = mp.next_field().await {
+///         let mut field = field?;
+///         while let Some(chunk) = field.chunk().await {
+///             total += chunk?.len();
+///         }
+///     }
+///     Ok(format!("Received {} bytes", total))
+/// }
+/// # fn main() {}
+/// ```
+pub struct Multipart {
+    #[cfg(feature = "compress")]
+    stream: Decoder<Payload>,
+    #[cfg(not(feature = "compress"))]
+    stream: Payload,
+    buf: BytesMut,
+    // "\r\n--" + boundary
+    delimiter: Bytes,
+    state: State,
+    eof: bool,
+    size: usize,
+    field_size: usize,
+    field_limit: usize,
+    total_limit: usize,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum State {
+    Preamble,
+    Boundary,
+    Headers,
+    Field,
+    Eof,
+}
+
+impl Multipart {
+    fn new(
+        req: &HttpRequest,
+        payload: &mut Payload,
+        cfg: &MultipartConfig,
+    ) -> Result<Multipart, MultipartError> {
+        let mime = match req.mime_type() {
+            Ok(Some(mime)) => mime,
+            _ => return Err(MultipartError::ContentType),
+        };
+        if mime.type_() != mime::MULTIPART || mime.subtype() != mime::FORM_DATA {
+            return Err(MultipartError::ContentType);
+        }
+        let boundary = match mime.get_param(mime::BOUNDARY) {
+            Some(b) if !b.as_str().is_empty() && b.as_str().len() <= 70 => b.as_str(),
+            _ => return Err(MultipartError::Boundary),
+        };
+
+        let mut delimiter = BytesMut::with_capacity(boundary.len() + 4);
+        delimiter.extend_from_slice(b"\r\n--");
+        delimiter.extend_from_slice(boundary.as_bytes());
+
+        #[cfg(feature = "compress")]
+        let stream = Decoder::from_headers(payload.take(), req.headers());
+        #[cfg(not(feature = "compress"))]
+        let stream = payload.take();
+
+        Ok(Multipart {
+            stream,
+            delimiter: delimiter.freeze(),
+            buf: BytesMut::new(),
+            state: State::Preamble,
+            eof: false,
+            size: 0,
+            field_size: 0,
+            field_limit: cfg.field_limit,
+            total_limit: cfg.total_limit,
+        })
+    }
+
+    /// Get next field of the multipart payload.
+    ///
+    /// Returns `None` if there are no more fields.
+    pub async fn next_field(&mut self) -> Option<Result<Field<'_>, MultipartError>> {
+        match self.poll_field().await {
+            Ok(Some(headers)) => Some(Ok(Field::new(self, headers))),
+            Ok(None) => None,
+            Err(e) => {
+                self.state = State::Eof;
+                Some(Err(e))
+            }
+        }
+    }
+
+    async fn poll_field(&mut self) -> Result<Option<HeaderMap>, MultipartError> {
+        loop {
+            match self.state {
+                State::Eof => return Ok(None),
+                State::Field => {
+                    // skip rest of the current field
+                    while let Some(chunk) = self.field_chunk().await {
+                        chunk?;
+                    }
+                }
+                State::Preamble => {
+                    // first boundary could be the first line of payload
+                    let delimiter = &self.delimiter[2..];
+                    if let Some(idx) = find(&self.buf, delimiter) {
+                        self.buf.advance(idx + delimiter.len());
+                        self.state = State::Boundary;
+                    } else {
+                        let keep = tail_len(self.buf.len(), delimiter.len());
+                        self.buf.advance(self.buf.len() - keep);
+                        self.read().await?;
+                    }
+                }
+                State::Boundary => {
+                    if self.buf.len() < 2 {
+                        self.read().await?;
+                    } else if &self.buf[..2] == b"--" {
+                        self.state = State::Eof;
+                        return Ok(None);
+                    } else {
+                        // transport padding is allowed after boundary
+                        match find(&self.buf, b"\r\n") {
+                            Some(idx) => {
+                                if self.buf[..idx].iter().any(|c| *c != b' ' && *c != b'\t')
+                                {
+                                    return Err(MultipartError::Boundary);
+                                }
+                                self.buf.advance(idx + 2);
+                                self.state = State::Headers;
+                            }
+                            None if self.buf.len() > MAX_HEADERS_SIZE => {
+                                return Err(MultipartError::Boundary)
+                            }
+                            None => self.read().await?,
+                        }
+                    }
+                }
+                State::Headers => {
+                    if self.buf.starts_with(b"\r\n") {
+                        self.buf.advance(2);
+                        self.state = State::Field;
+                        self.field_size = 0;
+                        return Ok(Some(HeaderMap::new()));
+                    }
+                    match find(&self.buf, b"\r\n\r\n") {
+                        Some(idx) => {
+                            let data = self.buf.split_to(idx + 4);
+                            let headers = parse_headers(&data)?;
+                            self.state = State::Field;
+                            self.field_size = 0;
+                            return Ok(Some(headers));
+                        }
+                        None if self.buf.len() > MAX_HEADERS_SIZE => {
+                            return Err(MultipartError::Header)
+                        }
+                        None => self.read().await?,
+                    }
+                }
+            }
+        }
+    }
+
+    /// Read next chunk of the current field
+    async fn field_chunk(&mut self) -> Option<Result<Bytes, MultipartError>> {
+        loop {
+            if self.state != State::Field {
+                return None;
+            }
+
+            let chunk = if let Some(idx) = find(&self.buf, &self.delimiter) {
+                let chunk = self.buf.split_to(idx).freeze();
+                self.buf.advance(self.delimiter.len());
+                self.state = State::Boundary;
+                chunk
+            } else {
+                // tail of the buffer could contain part of delimiter
+                let keep = tail_len(self.buf.len(), self.delimiter.len());
+                self.buf.split_to(self.buf.len() - keep).freeze()
+            };
+
+            if !chunk.is_empty() {
+                self.field_size += chunk.len();
+                if self.field_size > self.field_limit {
+                    self.state = State::Eof;
+                    return Some(Err(MultipartError::FieldOverflow {
+                        limit: self.field_limit,
+                    }));
+                }
+                return Some(Ok(chunk));
+            }
+            if self.state == State::Field {
+                if let Err(e) = self.read().await {
+                    self.state = State::Eof;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+
+    /// Read more data from the payload stream
+    async fn read(&mut self) -> Result<(), MultipartError> {
+        if self.eof {
+            return Err(MultipartError::Incomplete);
+        }
+        match stream_recv(&mut self.stream).await {
+            Some(Ok(chunk)) => {
+                self.size += chunk.len();
+                if self.size > self.total_limit {
+                    Err(MultipartError::Overflow {
+                        limit: self.total_limit,
+                    })
+                } else {
+                    self.buf.extend_from_slice(&chunk);
+                    Ok(())
+                }
+            }
+            Some(Err(e)) => Err(e.into()),
+            None => {
+                self.eof = true;
+                Err(MultipartError::Incomplete)
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Multipart {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Multipart")
+            .field("state", &self.state)
+            .field("size", &self.size)
+            .field("field_limit", &self.field_limit)
+            .field("total_limit", &self.total_limit)
+            .finish()
+    }
+}
+
+impl<Err: ErrorRenderer> FromRequest<Err> for Multipart {
+    type Error = MultipartError;
+
+    async fn from_request(
+        req: &HttpRequest,
+        payload: &mut Payload,
+    ) -> Result<Multipart, Self::Error> {
+        let tmp;
+        let cfg = if let Some(cfg) = req.app_state::<MultipartConfig>() {
+            cfg
+        } else {
+            tmp = MultipartConfig::default();
+            &tmp
+        };
+        Multipart::new(req, payload, cfg)
+    }
+}
+
+/// A single field of multipart payload
+pub struct Field<'a> {
+    mp: &'a mut Multipart,
+    headers: HeaderMap,
+    name: Option<String>,
+    filename: Option<String>,
+    content_type: Option<Mime>,
+}
+
+impl<'a> Field<'a> {
+    fn new(mp: &'a mut Multipart, headers: HeaderMap) -> Self {
+        let (name, filename) = headers
+            .get(CONTENT_DISPOSITION)
+            .and_then(|v| v.to_str().ok())
+            .map(parse_content_disposition)
+            .unwrap_or_default();
+        let content_type = headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok());
+
+        Field {
+            mp,
+            headers,
+            name,
+            filename,
+            content_type,
+        }
+    }
+
+    /// Field headers
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Raw value of `Content-Disposition` header
+    pub fn content_disposition(&self) -> Option<&str> {
+        self.headers
+            .get(CONTENT_DISPOSITION)
+            .and_then(|v| v.to_str().ok())
+    }
+
+    /// Field name from `Content-Disposition` header
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// File name from `Content-Disposition` header
+    pub fn filename(&self) -> Option<&str> {
+        self.filename.as_deref()
+    }
+
+    /// Field content type
+    pub fn content_type(&self) -> Option<&Mime> {
+        self.content_type.as_ref()
+    }
+
+    /// Get next chunk of field data.
+    ///
+    /// Returns `None` if field data is exhausted.
+    pub async fn chunk(&mut self) -> Option<Result<Bytes, MultipartError>> {
+        self.mp.field_chunk().await
+    }
+
+    /// Load all remaining field data
+    pub async fn bytes(&mut self) -> Result<Bytes, MultipartError> {
+        let mut buf = BytesMut::new();
+        while let Some(chunk) = self.chunk().await {
+            buf.extend_from_slice(&chunk?);
+        }
+        Ok(buf.freeze())
+    }
+
+    /// Load all remaining field data as utf-8 string
+    pub async fn text(&mut self) -> Result<String, MultipartError> {
+        let data = self.bytes().await?;
+        String::from_utf8(data.to_vec()).map_err(|_| MultipartError::Utf8)
+    }
+}
+
+impl fmt::Debug for Field<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Field")
+            .field("name", &self.name)
+            .field("filename", &self.filename)
+            .field("content_type", &self.content_type)
+            .field("headers", &self.headers)
+            .finish()
+    }
+}
+
+/// Multipart extractor configuration
+///
+/// ```rust
+/// use ntex::web::{self, App};
+///
+/// async fn index(mut mp: web::types::Multipart) -> &'static str {
+///     "Uploaded"
+/// }
+///
+/// fn main() {
+///     let app = App::new().service(
+///         web::resource("/upload")
+///             // change `Multipart` extractor configuration
+///             .state(
+///                 web::types::MultipartConfig::default()
+///                     .field_limit(1024 * 1024)
+///                     .total_limit(4 * 1024 * 1024)
+///             )
+///             .route(web::post().to(index))
+///     );
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct MultipartConfig {
+    field_limit: usize,
+    total_limit: usize,
+}
+
+impl MultipartConfig {
+    /// Change max size of a single field. By default max size is 2Mb
+    pub fn field_limit(mut self, limit: usize) -> Self {
+        self.field_limit = limit;
+        self
+    }
+
+    /// Change max size of the whole payload. By default max size is 8Mb
+    pub fn total_limit(mut self, limit: usize) -> Self {
+        self.total_limit = limit;
+        self
+    }
+}
+
+impl Default for MultipartConfig {
+    fn default() -> Self {
+        MultipartConfig {
+            field_limit: 2 * 1024 * 1024,
+            total_limit: 8 * 1024 * 1024,
+        }
+    }
+}
+
+/// Typed multipart form collector
+///
+/// Loads all fields of `multipart/form-data` payload into memory. Text
+/// fields are deserialized to type `T`, the same way as `Form<T>` does,
+/// so `T` only needs to implement `Deserialize` trait from *serde*.
+/// Fields with file name are collected as [`MultipartFile`] values.
+///
+/// ## Example
+///
+/// ```rust
+/// use ntex::web;
+///
+/// #[derive(serde::Deserialize)]
+/// struct Upload {
+///     title: String,
+/// }
+///
+/// async fn index(form: web::types::MultipartForm<Upload>) -> String {
+///     let size = form.file("file").map(|f| f.data.len()).unwrap_or(0);
+///     format!("{}: {} bytes", form.title, size)
+/// }
+/// # fn main() {}
+/// ```
+pub struct MultipartForm<T> {
+    form: T,
+    files: Vec<MultipartFile>,
+}
+
+/// Uploaded file of the `MultipartForm`
+#[derive(Clone, Debug)]
+pub struct MultipartFile {
+    /// Field name
+    pub name: String,
+    /// File name
+    pub filename: String,
+    /// File content type
+    pub content_type: Option<Mime>,
+    /// File content
+    pub data: Bytes,
+}
+
+impl<T> MultipartForm<T> {
+    /// Deconstruct to an inner value
+    pub fn into_inner(self) -> T {
+        self.form
+    }
+
+    /// Deconstruct to an inner value and uploaded files
+    pub fn into_parts(self) -> (T, Vec<MultipartFile>) {
+        (self.form, self.files)
+    }
+
+    /// Uploaded files
+    pub fn files(&self) -> &[MultipartFile] {
+        &self.files
+    }
+
+    /// First uploaded file with specified field name
+    pub fn file(&self, name: &str) -> Option<&MultipartFile> {
+        self.files.iter().find(|f| f.name == name)
+    }
+}
+
+impl<T> ops::Deref for MultipartForm<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.form
+    }
+}
+
+impl<T> ops::DerefMut for MultipartForm<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.form
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for MultipartForm<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MultipartForm")
+            .field("form", &self.form)
+            .field("files", &self.files)
+            .finish()
+    }
+}
+
+impl<T, Err> FromRequest<Err> for MultipartForm<T>
+where
+    T: DeserializeOwned + 'static,
+    Err: ErrorRenderer,
+{
+    type Error = MultipartError;
+
+    async fn from_request(
+        req: &HttpRequest,
+        payload: &mut Payload,
+    ) -> Result<Self, Self::Error> {
+        let mut mp = <Multipart as FromRequest<Err>>::from_request(req, payload).await?;
+
+        let mut encoded = String::new();
+        let mut files = Vec::new();
+        while let Some(field) = mp.next_field().await {
+            let mut field = field?;
+            let name = if let Some(name) = field.name() {
+                name.to_string()
+            } else {
+                continue;
+            };
+
+            if let Some(filename) = field.filename().map(|s| s.to_string()) {
+                let content_type = field.content_type().cloned();
+                files.push(MultipartFile {
+                    name,
+                    filename,
+                    content_type,
+                    data: field.bytes().await?,
+                });
+            } else {
+                let value = field.text().await?;
+                if !encoded.is_empty() {
+                    encoded.push('&');
+                }
+                encoded.extend(utf8_percent_encode(&name, NON_ALPHANUMERIC));
+                encoded.push('=');
+                encoded.extend(utf8_percent_encode(&value, NON_ALPHANUMERIC));
+            }
+        }
+
+        Ok(MultipartForm {
+            form: serde_urlencoded::from_str(&encoded)?,
+            files,
+        })
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if haystack.len() < needle.len() {
+        return None;
+    }
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// Number of trailing bytes that could contain part of delimiter
+fn tail_len(len: usize, delimiter: usize) -> usize {
+    std::cmp::min(len, delimiter - 1)
+}
+
+fn parse_headers(data: &[u8]) -> Result<HeaderMap, MultipartError> {
+    let mut parsed = [httparse::EMPTY_HEADER; MAX_HEADERS];
+    match httparse::parse_headers(data, &mut parsed) {
+        Ok(httparse::Status::Complete((_, parsed))) => {
+            let mut headers = HeaderMap::with_capacity(parsed.len());
+            for h in parsed {
+                let name = HeaderName::from_bytes(h.name.as_bytes())
+                    .map_err(|_| MultipartError::Header)?;
+                let value =
+                    HeaderValue::from_bytes(h.value).map_err(|_| MultipartError::Header)?;
+                headers.append(name, value);
+            }
+            Ok(headers)
+        }
+        _ => Err(MultipartError::Header),
+    }
+}
+
+/// Parse `name` and `filename` parameters of `Content-Disposition` header
+fn parse_content_disposition(value: &str) -> (Option<String>, Option<String>) {
+    let mut name = None;
+    let mut filename = None;
+    let mut filename_ext = None;
+
+    let mut rest = match value.find(';') {
+        Some(idx) => &value[idx + 1..],
+        None => return (None, None),
+    };
+    while !rest.is_empty() {
+        let (key, val, tail) = if let Some(idx) = rest.find('=') {
+            let key = rest[..idx].trim();
+            let (val, tail) = parse_param_value(rest[idx + 1..].trim_start());
+            (key, val, tail)
+        } else {
+            break;
+        };
+        rest = tail;
+
+        if key.eq_ignore_ascii_case("name") {
+            name = Some(val);
+        } else if key.eq_ignore_ascii_case("filename") {
+            filename = Some(val);
+        } else if key.eq_ignore_ascii_case("filename*") {
+            // RFC 5987 extended value, charset'language'value
+            let mut parts = val.splitn(3, '\'');
+            if let (Some(charset), Some(_), Some(v)) =
+                (parts.next(), parts.next(), parts.next())
+            {
+                if charset.eq_ignore_ascii_case("utf-8") {
+                    filename_ext = percent_decode_str(v)
+                        .decode_utf8()
+                        .ok()
+                        .map(|s| s.into_owned());
+                }
+            }
+        }
+    }
+    (name, filename_ext.or(filename))
+}
+
+/// Parse token or quoted string, returns value and rest of the input
+fn parse_param_value(s: &str) -> (String, &str) {
+    if let Some(s) = s.strip_prefix('"') {
+        let mut value = String::new();
+        let mut escaped = false;
+        for (idx, ch) in s.char_indices() {
+            if escaped {
+                value.push(ch);
+                escaped = false;
+            } else if ch == '\\' {
+                escaped = true;
+            } else if ch == '"' {
+                let tail = &s[idx + 1..];
+                let tail = tail.find(';').map(|i| &tail[i + 1..]).unwrap_or("");
+                return (value, tail);
+            } else {
+                value.push(ch);
+            }
+        }
+        (value, "")
+    } else {
+        match s.find(';') {
+            Some(idx) => (s[..idx].trim().to_string(), &s[idx + 1..]),
+            None => (s.trim().to_string(), ""),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::http::header::CONTENT_TYPE;
+    use crate::web::test::{from_request, TestRequest};
+
+    const BODY: &[u8] = b"preamble\r\n\
+        --abbc761f78ff4d7cb7573b5a23f96ef0\r\n\
+        Content-Disposition: form-data; name=\"title\"\r\n\
+        \r\n\
+        test\r\n\
+        --abbc761f78ff4d7cb7573b5a23f96ef0\r\n\
+        Content-Disposition: form-data; name=\"counter\"\r\n\
+        \r\n\
+        10\r\n\
+        --abbc761f78ff4d7cb7573b5a23f96ef0\r\n\
+        Content-Disposition: form-data; name=\"file\"; filename=\"fn.txt\"\r\n\
+        Content-Type: text/plain\r\n\
+        \r\n\
+        data\r\n--data\r\n\
+        --abbc761f78ff4d7cb7573b5a23f96ef0--\r\n";
+
+    fn request(body: &'static [u8]) -> TestRequest {
+        TestRequest::with_header(
+            CONTENT_TYPE,
+            "multipart/form-data; boundary=\"abbc761f78ff4d7cb7573b5a23f96ef0\"",
+        )
+        .set_payload(Bytes::from_static(body))
+    }
+
+    #[test]
+    fn test_content_disposition() {
+        assert_eq!(
+            parse_content_disposition("form-data; name=\"a\"; filename=\"b.txt\""),
+            (Some("a".to_string()), Some("b.txt".to_string()))
+        );
+        assert_eq!(
+            parse_content_disposition("form-data; name=a"),
+            (Some("a".to_string()), None)
+        );
+        assert_eq!(
+            parse_content_disposition(
+                "form-data; name=\"a;b\"; filename=\"x\\\"y\"; \
+                 filename*=UTF-8''%D1%82%D0%B5%D1%81%D1%82.txt"
+            ),
+            (Some("a;b".to_string()), Some("тест.txt".to_string()))
+        );
+        assert_eq!(parse_content_disposition("form-data"), (None, None));
+    }
+
+    #[crate::rt_test]
+    async fn test_multipart() {
+        let (req, mut pl) = request(BODY).to_http_parts();
+        let mut mp = from_request::<Multipart>(&req, &mut pl).await.unwrap();
+
+        let mut field = mp.next_field().await.unwrap().unwrap();
+        assert_eq!(field.name(), Some("title"));
+        assert_eq!(field.filename(), None);
+        assert_eq!(field.text().await.unwrap(), "test");
+
+        // unread field is skipped
+        let field = mp.next_field().await.unwrap().unwrap();
+        assert_eq!(field.name(), Some("counter"));
+
+        let mut field = mp.next_field().await.unwrap().unwrap();
+        assert_eq!(field.name(), Some("file"));
+        assert_eq!(field.filename(), Some("fn.txt"));
+        assert_eq!(field.content_type(), Some(&mime::TEXT_PLAIN));
+        assert_eq!(
+            field.content_disposition(),
+            Some("form-data; name=\"file\"; filename=\"fn.txt\"")
+        );
+        assert_eq!(
+            field.bytes().await.unwrap(),
+            Bytes::from_static(b"data\r\n--data")
+        );
+        assert!(field.chunk().await.is_none());
+
+        assert!(mp.next_field().await.is_none());
+    }
+
+    #[crate::rt_test]
+    async fn test_multipart_errors() {
+        let (req, mut pl) =
+            TestRequest::with_header(CONTENT_TYPE, "text/plain").to_http_parts();
+        let res = from_request::<Multipart>(&req, &mut pl).await;
+        assert!(matches!(res, Err(MultipartError::ContentType)));
+
+        let (req, mut pl) =
+            TestRequest::with_header(CONTENT_TYPE, "multipart/form-data").to_http_parts();
+        let res = from_request::<Multipart>(&req, &mut pl).await;
+        assert!(matches!(res, Err(MultipartError::Boundary)));
+
+        let (req, mut pl) = request(&BODY[..100]).to_http_parts();
+        let mut mp = from_request::<Multipart>(&req, &mut pl).await.unwrap();
+        let mut field = mp.next_field().await.unwrap().unwrap();
+        assert!(matches!(
+            field.bytes().await,
+            Err(MultipartError::Incomplete)
+        ));
+
+        let (req, mut pl) = request(BODY)
+            .state(MultipartConfig::default().field_limit(3))
+            .to_http_parts();
+        let mut mp = from_request::<Multipart>(&req, &mut pl).await.unwrap();
+        let mut field = mp.next_field().await.unwrap().unwrap();
+        assert!(matches!(
+            field.bytes().await,
+            Err(MultipartError::FieldOverflow { limit: 3 })
+        ));
+
+        let (req, mut pl) = request(BODY)
+            .state(MultipartConfig::default().total_limit(32))
+            .to_http_parts();
+        let mut mp = from_request::<Multipart>(&req, &mut pl).await.unwrap();
+        assert!(matches!(
+            mp.next_field().await,
+            Some(Err(MultipartError::Overflow { limit: 32 }))
+        ));
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Upload {
+        title: String,
+        counter: u32,
+    }
+
+    #[crate::rt_test]
+    async fn test_multipart_form() {
+        let (req, mut pl) = request(BODY).to_http_parts();
+        let form = from_request::<MultipartForm<Upload>>(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(form.title, "test");
+        assert_eq!(form.counter, 10);
+        assert_eq!(form.files().len(), 1);
+
+        let file = form.file("file").unwrap();
+        assert_eq!(file.filename, "fn.txt");
+        assert_eq!(file.content_type, Some(mime::TEXT_PLAIN));
+        assert_eq!(file.data, Bytes::from_static(b"data\r\n--data"));
+
+        let (upload, files) = form.into_parts();
+        assert_eq!(
+            upload,
+            Upload {
+                title: "test".to_string(),
+                counter: 10
+            }
+        );
+        assert_eq!(files.len(), 1);
+    }
+}
@@ -0,0 +1,254 @@
+//! Request data validation
+use std::{fmt, ops};
+
+use crate::http::Payload;
+use crate::util::Either;
+use crate::web::error::{ErrorRenderer, ValidationErrors};
+use crate::web::{FromRequest, HttpRequest};
+
+/// Validation hook for extracted request data.
+///
+/// With `validator` feature enabled, trait is implemented for all types
+/// that implement `validator::Validate`.
+pub trait Validate {
+    /// Validate value
+    fn validate(&self) -> Result<(), ValidationErrors>;
+}
+
+#[cfg(feature = "validator")]
+impl<T: validator::Validate> Validate for T {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        validator::Validate::validate(self).map_err(ValidationErrors::from)
+    }
+}
+
+/// Validate data extracted by inner extractor.
+///
+/// Could be used with any extractor that dereferences to a type that
+/// implements [`Validate`], i.e. `Json<T>`, `Form<T>` or `Query<T>`.
+/// Validation failure results in *422 Unprocessable Entity* response,
+/// default error renderer returns json list of field errors.
+///
+/// ## Example
+///
+/// ```rust
+/// use ntex::web::{self, error::{FieldError, ValidationErrors}};
+/// use ntex::web::types::{Json, Valid, Validate};
+///
+/// #[derive(serde::Deserialize)]
+/// struct Info {
+///     username: String,
+/// }
+///
+/// impl Validate for Info {
+///     fn validate(&self) -> Result<(), ValidationErrors> {
+///         let mut errors = ValidationErrors::new();
+///         if self.username.is_empty() {
+///             errors.add(FieldError::new("username", "required"));
+///         }
+///         errors.into_result()
+///     }
+/// }
+///
+/// async fn index(info: Valid<Json<Info>>) -> String {
+///     format!("Welcome {}!", info.username)
+/// }
+///
+/// fn main() {
+///     let app = web::App::new().service(
+///        web::resource("/index.html").route(web::post().to(index)));
+/// }
+/// ```
+pub struct Valid<T>(pub T);
+
+impl<T> Valid<T> {
+    /// Deconstruct to an inner extractor
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> ops::Deref for Valid<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> ops::DerefMut for Valid<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Valid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Valid").field(&self.0).finish()
+    }
+}
+
+impl<T, Err> FromRequest<Err> for Valid<T>
+where
+    T: FromRequest<Err> + ops::Deref,
+    T::Target: Validate,
+    Err: ErrorRenderer,
+{
+    type Error = Either<T::Error, ValidationErrors>;
+
+    async fn from_request(
+        req: &HttpRequest,
+        payload: &mut Payload,
+    ) -> Result<Self, Self::Error> {
+        let item = T::from_request(req, payload).await.map_err(Either::Left)?;
+        if let Err(e) = item.validate() {
+            log::debug!(
+                "Failed to validate extracted data: {}. Request path: {}",
+                e,
+                req.path()
+            );
+            Err(Either::Right(e))
+        } else {
+            Ok(Valid(item))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{header, StatusCode};
+    use crate::util::Bytes;
+    use crate::web::error::FieldError;
+    use crate::web::test::{call_service, init_service, read_body, TestRequest};
+    use crate::web::types::{Json, Query};
+    use crate::web::{self, App};
+
+    #[derive(Debug, serde::Deserialize)]
+    struct Item {
+        name: String,
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    struct Order {
+        count: u32,
+        items: Vec<Item>,
+    }
+
+    impl Validate for Item {
+        fn validate(&self) -> Result<(), ValidationErrors> {
+            let mut errors = ValidationErrors::new();
+            if self.name.is_empty() {
+                errors.add(FieldError::new("name", "required").message("Name is required"));
+            }
+            errors.into_result()
+        }
+    }
+
+    impl Validate for Order {
+        fn validate(&self) -> Result<(), ValidationErrors> {
+            let mut errors = ValidationErrors::new();
+            if self.count == 0 {
+                errors.add(FieldError::new("count", "range"));
+            }
+            for (idx, item) in self.items.iter().enumerate() {
+                if let Err(e) = item.validate() {
+                    errors.nest(&format!("items[{}]", idx), e);
+                }
+            }
+            errors.into_result()
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut errors = ValidationErrors::new();
+        assert!(errors.is_empty());
+        errors.add(FieldError::new("count", "range"));
+        errors.add(FieldError::new("name", "required").message("Name is required"));
+        assert_eq!(errors.errors().len(), 2);
+        assert_eq!(
+            errors.to_string(),
+            "Validation failed: count: range, name: Name is required"
+        );
+
+        let mut nested = ValidationErrors::new();
+        nested.nest("order", errors.clone());
+        nested.nest("list", {
+            let mut e = ValidationErrors::new();
+            e.add(FieldError::new("[1]", "required"));
+            e
+        });
+        let paths: Vec<_> = nested.errors().iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, vec!["order.count", "order.name", "list[1]"]);
+        assert!(ValidationErrors::new().into_result().is_ok());
+    }
+
+    #[crate::rt_test]
+    async fn test_valid_json() {
+        let srv =
+            init_service(App::new().service(web::resource("/").route(web::post().to(
+                |order: Valid<Json<Order>>| async move { format!("{}", order.items.len()) },
+            ))))
+            .await;
+
+        let req = TestRequest::post()
+            .header(header::CONTENT_TYPE, "application/json")
+            .set_payload(Bytes::from_static(
+                b"{\"count\": 1, \"items\": [{\"name\": \"a\"}]}",
+            ))
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(read_body(resp).await, Bytes::from_static(b"1"));
+
+        let req = TestRequest::post()
+            .header(header::CONTENT_TYPE, "application/json")
+            .set_payload(Bytes::from_static(
+                b"{\"count\": 0, \"items\": [{\"name\": \"a\"}, {\"name\": \"\"}]}",
+            ))
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/json"
+        );
+        let body: serde_json::Value =
+            serde_json::from_slice(&read_body(resp).await).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({"errors": [
+                {"path": "count", "code": "range"},
+                {
+                    "path": "items[1].name",
+                    "code": "required",
+                    "message": "Name is required"
+                },
+            ]})
+        );
+
+        let req = TestRequest::post()
+            .header(header::CONTENT_TYPE, "application/json")
+            .set_payload(Bytes::from_static(b"{\"count\": 1"))
+            .to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[crate::rt_test]
+    async fn test_valid_query() {
+        let srv = init_service(App::new().service(web::resource("/").to(
+            |item: Valid<Query<Item>>| async move { item.into_inner().into_inner().name },
+        )))
+        .await;
+
+        let req = TestRequest::with_uri("/?name=test").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let req = TestRequest::with_uri("/?name=").to_request();
+        let resp = call_service(&srv, req).await;
+        assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+}
@@ -33,6 +33,8 @@ pub struct WsClient<F, T> {
     max_size: usize,
     server_mode: bool,
     timeout: Millis,
+    #[cfg(feature = "ws-deflate")]
+    deflate: Option<ws::DeflateConfig>,
     extra_headers: RefCell<Option<HeaderMap>>,
     config: DispatcherConfig,
     client_cfg: Rc<client::ClientConfig>,
@@ -56,6 +58,8 @@ struct Inner<F, T> {
     max_size: usize,
     server_mode: bool,
     timeout: Millis,
+    #[cfg(feature = "ws-deflate")]
+    deflate: Option<ws::DeflateConfig>,
     config: DispatcherConfig,
     _t: marker::PhantomData<F>,
 }
@@ -241,17 +245,39 @@ where
             log::trace!("{}: Missing SEC-WEBSOCKET-ACCEPT header", tag);
             return Err(WsClientError::MissingWebSocketAcceptHeader);
         };
+
+        // permessage-deflate extension
+        #[cfg(feature = "ws-deflate")]
+        let deflate = if let Some(ref cfg) = self.deflate {
+            cfg.accept(&response.headers).map_err(|hdr| {
+                log::trace!("{}: Invalid extension header: {:?}", tag, hdr);
+                let err = "Invalid websocket extension";
+                WsClientError::InvalidResponse(
+                    crate::http::error::DecodeError::InvalidInput(err),
+                )
+            })?
+        } else {
+            None
+        };
         log::trace!("{}: Ws handshake response verification is completed", tag);
 
+        let codec = if server_mode {
+            ws::Codec::new().max_size(max_size)
+        } else {
+            ws::Codec::new().max_size(max_size).client_mode()
+        };
+        #[cfg(feature = "ws-deflate")]
+        let codec = if let Some(cfg) = deflate {
+            codec.deflate(cfg)
+        } else {
+            codec
+        };
+
         // response and ws io
         Ok(WsConnection::new(
             io,
             ClientResponse::with_empty_payload(response, self.client_cfg.clone()),
-            if server_mode {
-                ws::Codec::new().max_size(max_size)
-            } else {
-                ws::Codec::new().max_size(max_size).client_mode()
-            },
+            codec,
             self.config.clone(),
         ))
     }
@@ -302,6 +328,8 @@ impl WsClientBuilder<Base, ()> {
                 max_size: 65_536,
                 server_mode: false,
                 timeout: Millis(5_000),
+                #[cfg(feature = "ws-deflate")]
+                deflate: None,
                 _t: marker::PhantomData,
             }),
             #[cfg(feature = "cookie")]
@@ -386,6 +414,18 @@ where
         self
     }
 
+    #[cfg(feature = "ws-deflate")]
+    /// Enable permessage-deflate extension.
+    ///
+    /// Extension is offered to the server, if server does not accept
+    /// offer messages are not compressed.
+    pub fn deflate(&mut self, cfg: ws::DeflateConfig) -> &mut Self {
+        if let Some(parts) = parts(&mut self.inner, &self.err) {
+            parts.deflate = Some(cfg);
+        }
+        self
+    }
+
     /// Append a header.
     ///
     /// Header gets appended to existing header.
@@ -518,6 +558,9 @@ where
                 max_size: inner.max_size,
                 server_mode: inner.server_mode,
                 timeout: inner.timeout,
+                #[cfg(feature = "ws-deflate")]
+                #[cfg(feature = "ws-deflate")]
+                deflate: inner.deflate,
                 config: inner.config,
                 _t: marker::PhantomData,
             }),
@@ -635,6 +678,14 @@ where
             );
         }
 
+        #[cfg(feature = "ws-deflate")]
+        if let Some(ref cfg) = inner.deflate {
+            inner
+                .head
+                .headers
+                .insert(header::SEC_WEBSOCKET_EXTENSIONS, cfg.offer());
+        }
+
         Ok(WsClient {
             connector: inner.connector.into(),
             head: Rc::new(inner.head),
@@ -642,6 +693,8 @@ where
             max_size: inner.max_size,
             server_mode: inner.server_mode,
             timeout: inner.timeout,
+            #[cfg(feature = "ws-deflate")]
+            deflate: inner.deflate,
             config: inner.config,
             extra_headers: RefCell::new(None),
             client_cfg: Default::default(),
@@ -844,6 +897,23 @@ mod tests {
         );
     }
 
+    #[crate::rt_test]
+    #[cfg(feature = "ws-deflate")]
+    async fn deflate_offer() {
+        let client = WsClient::build("http://localhost")
+            .deflate(ws::DeflateConfig::new().server_no_context_takeover(true))
+            .finish()
+            .unwrap();
+        assert_eq!(
+            client
+                .head
+                .headers
+                .get(header::SEC_WEBSOCKET_EXTENSIONS)
+                .unwrap(),
+            "permessage-deflate; server_no_context_takeover"
+        );
+    }
+
     #[test]
     fn basic_errs() {
         let err = WsClient::build("localhost").finish().err().unwrap();
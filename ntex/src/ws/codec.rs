@@ -1,8 +1,12 @@
 use std::cell::Cell;
+#[cfg(feature = "ws-deflate")]
+use std::rc::Rc;
 
 use crate::codec::{Decoder, Encoder};
 use crate::util::{ByteString, Bytes, BytesMut};
 
+#[cfg(feature = "ws-deflate")]
+use super::deflate::{Deflate, DeflateConfig};
 use super::error::ProtocolError;
 use super::frame::Parser;
 use super::proto::{CloseReason, OpCode};
@@ -55,6 +59,8 @@ pub enum Item {
 pub struct Codec {
     flags: Cell<Flags>,
     max_size: usize,
+    #[cfg(feature = "ws-deflate")]
+    deflate: Option<Rc<Deflate>>,
 }
 
 bitflags::bitflags! {
@@ -64,6 +70,8 @@ bitflags::bitflags! {
         const R_CONTINUATION = 0b0000_0010;
         const W_CONTINUATION = 0b0000_0100;
         const CLOSED         = 0b0000_1000;
+        const R_COMPRESSED   = 0b0001_0000;
+        const W_COMPRESSED   = 0b0010_0000;
     }
 }
 
@@ -73,6 +81,8 @@ impl Codec {
         Codec {
             max_size: 65_536,
             flags: Cell::new(Flags::SERVER),
+            #[cfg(feature = "ws-deflate")]
+            deflate: None,
         }
     }
 
@@ -92,11 +102,97 @@ impl Codec {
         self
     }
 
+    #[cfg(feature = "ws-deflate")]
+    /// Enable permessage-deflate extension with negotiated parameters.
+    ///
+    /// Data messages are compressed and compressed frames are inflated.
+    /// Max frame size limits size of inflated payload.
+    pub fn deflate(mut self, cfg: DeflateConfig) -> Self {
+        self.deflate = Some(Rc::new(Deflate::new(cfg)));
+        self
+    }
+
+    #[cfg(feature = "ws-deflate")]
+    /// Get negotiated permessage-deflate parameters
+    pub fn deflate_config(&self) -> Option<&DeflateConfig> {
+        self.deflate.as_ref().map(|d| d.config())
+    }
+
     /// Check if codec encoded `Close` message
     pub fn is_closed(&self) -> bool {
         self.flags.get().contains(Flags::CLOSED)
     }
 
+    fn write_data(
+        &self,
+        dst: &mut BytesMut,
+        data: &[u8],
+        op: OpCode,
+        fin: bool,
+    ) -> Result<(), ProtocolError> {
+        let flags = self.flags.get();
+        let server = flags.contains(Flags::SERVER);
+
+        #[cfg(feature = "ws-deflate")]
+        if let Some(ref deflate) = self.deflate {
+            // rsv1 bit is set only for first frame of the message
+            let first = op != OpCode::Continue;
+            if first || flags.contains(Flags::W_COMPRESSED) {
+                let data = deflate.compress(data, fin, server)?;
+                if fin {
+                    self.remove_flags(Flags::W_COMPRESSED);
+                } else {
+                    self.insert_flags(Flags::W_COMPRESSED);
+                }
+                Parser::write_message_ext(dst, data, op, fin, first, !server);
+                return Ok(());
+            }
+        }
+        Parser::write_message(dst, data, op, fin, !server);
+        Ok(())
+    }
+
+    fn read_data(
+        &self,
+        payload: Option<Bytes>,
+        rsv1: bool,
+        first: bool,
+        fin: bool,
+    ) -> Result<Bytes, ProtocolError> {
+        let payload = payload.unwrap_or_else(Bytes::new);
+        let compressed = if first {
+            rsv1
+        } else if rsv1 {
+            // rsv1 bit must be set only for first frame
+            return Err(ProtocolError::BadOpCode);
+        } else {
+            self.flags.get().contains(Flags::R_COMPRESSED)
+        };
+
+        if !compressed {
+            return Ok(payload);
+        }
+
+        if fin {
+            self.remove_flags(Flags::R_COMPRESSED);
+        } else {
+            self.insert_flags(Flags::R_COMPRESSED);
+        }
+
+        #[cfg(feature = "ws-deflate")]
+        if let Some(ref deflate) = self.deflate {
+            return deflate.decompress(
+                &payload,
+                fin,
+                self.flags.get().contains(Flags::SERVER),
+                self.max_size,
+            );
+        }
+
+        // rsv1 bit is set but extension is not negotiated
+        Err(ProtocolError::BadOpCode)
+    }
+
     fn insert_flags(&self, f: Flags) {
         let mut flags = self.flags.get();
         flags.insert(f);
@@ -122,20 +218,8 @@ impl Encoder for Codec {
 
     fn encode(&self, item: Message, dst: &mut BytesMut) -> Result<(), Self::Error> {
         match item {
-            Message::Text(txt) => Parser::write_message(
-                dst,
-                txt.as_ref(),
-                OpCode::Text,
-                true,
-                !self.flags.get().contains(Flags::SERVER),
-            ),
-            Message::Binary(bin) => Parser::write_message(
-                dst,
-                bin,
-                OpCode::Binary,
-                true,
-                !self.flags.get().contains(Flags::SERVER),
-            ),
+            Message::Text(txt) => self.write_data(dst, txt.as_ref(), OpCode::Text, true)?,
+            Message::Binary(bin) => self.write_data(dst, &bin, OpCode::Binary, true)?,
             Message::Ping(txt) => Parser::write_message(
                 dst,
                 txt,
@@ -160,13 +244,7 @@ impl Encoder for Codec {
                         return Err(ProtocolError::ContinuationStarted);
                     } else {
                         self.insert_flags(Flags::W_CONTINUATION);
-                        Parser::write_message(
-                            dst,
-                            &data[..],
-                            OpCode::Text,
-                            false,
-                            !self.flags.get().contains(Flags::SERVER),
-                        )
+                        self.write_data(dst, &data, OpCode::Text, false)?
                     }
                 }
                 Item::FirstBinary(data) => {
@@ -174,24 +252,12 @@ impl Encoder for Codec {
                         return Err(ProtocolError::ContinuationStarted);
                     } else {
                         self.insert_flags(Flags::W_CONTINUATION);
-                        Parser::write_message(
-                            dst,
-                            &data[..],
-                            OpCode::Binary,
-                            false,
-                            !self.flags.get().contains(Flags::SERVER),
-                        )
+                        self.write_data(dst, &data, OpCode::Binary, false)?
                     }
                 }
                 Item::Continue(data) => {
                     if self.flags.get().contains(Flags::W_CONTINUATION) {
-                        Parser::write_message(
-                            dst,
-                            &data[..],
-                            OpCode::Continue,
-                            false,
-                            !self.flags.get().contains(Flags::SERVER),
-                        )
+                        self.write_data(dst, &data, OpCode::Continue, false)?
                     } else {
                         return Err(ProtocolError::ContinuationNotStarted);
                     }
@@ -199,13 +265,7 @@ impl Encoder for Codec {
                 Item::Last(data) => {
                     if self.flags.get().contains(Flags::W_CONTINUATION) {
                         self.remove_flags(Flags::W_CONTINUATION);
-                        Parser::write_message(
-                            dst,
-                            &data[..],
-                            OpCode::Continue,
-                            true,
-                            !self.flags.get().contains(Flags::SERVER),
-                        )
+                        self.write_data(dst, &data, OpCode::Continue, true)?
                     } else {
                         return Err(ProtocolError::ContinuationNotStarted);
                     }
@@ -221,15 +281,19 @@ impl Decoder for Codec {
     type Error = ProtocolError;
 
     fn decode(&self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-        match Parser::parse(src, self.flags.get().contains(Flags::SERVER), self.max_size) {
-            Ok(Some((finished, opcode, payload))) => {
+        match Parser::parse_ext(
+            src,
+            self.flags.get().contains(Flags::SERVER),
+            self.max_size,
+        ) {
+            Ok(Some((finished, rsv1, opcode, payload))) => {
                 // handle continuation
                 if !finished {
                     match opcode {
                         OpCode::Continue => {
                             if self.flags.get().contains(Flags::R_CONTINUATION) {
                                 Ok(Some(Frame::Continuation(Item::Continue(
-                                    payload.unwrap_or_else(Bytes::new),
+                                    self.read_data(payload, rsv1, false, false)?,
                                 ))))
                             } else {
                                 Err(ProtocolError::ContinuationNotStarted)
@@ -239,7 +303,7 @@ impl Decoder for Codec {
                             if !self.flags.get().contains(Flags::R_CONTINUATION) {
                                 self.insert_flags(Flags::R_CONTINUATION);
                                 Ok(Some(Frame::Continuation(Item::FirstBinary(
-                                    payload.unwrap_or_else(Bytes::new),
+                                    self.read_data(payload, rsv1, true, false)?,
                                 ))))
                             } else {
                                 Err(ProtocolError::ContinuationStarted)
@@ -249,7 +313,7 @@ impl Decoder for Codec {
                             if !self.flags.get().contains(Flags::R_CONTINUATION) {
                                 self.insert_flags(Flags::R_CONTINUATION);
                                 Ok(Some(Frame::Continuation(Item::FirstText(
-                                    payload.unwrap_or_else(Bytes::new),
+                                    self.read_data(payload, rsv1, true, false)?,
                                 ))))
                             } else {
                                 Err(ProtocolError::ContinuationStarted)
@@ -273,7 +337,7 @@ impl Decoder for Codec {
                             if self.flags.get().contains(Flags::R_CONTINUATION) {
                                 self.remove_flags(Flags::R_CONTINUATION);
                                 Ok(Some(Frame::Continuation(Item::Last(
-                                    payload.unwrap_or_else(Bytes::new),
+                                    self.read_data(payload, rsv1, false, true)?,
                                 ))))
                             } else {
                                 Err(ProtocolError::ContinuationNotStarted)
@@ -294,12 +358,12 @@ impl Decoder for Codec {
                         OpCode::Pong => {
                             Ok(Some(Frame::Pong(payload.unwrap_or_else(Bytes::new))))
                         }
-                        OpCode::Binary => {
-                            Ok(Some(Frame::Binary(payload.unwrap_or_else(Bytes::new))))
-                        }
-                        OpCode::Text => {
-                            Ok(Some(Frame::Text(payload.unwrap_or_else(Bytes::new))))
-                        }
+                        OpCode::Binary => Ok(Some(Frame::Binary(
+                            self.read_data(payload, rsv1, true, true)?,
+                        ))),
+                        OpCode::Text => Ok(Some(Frame::Text(
+                            self.read_data(payload, rsv1, true, true)?,
+                        ))),
                     }
                 }
             }
@@ -308,3 +372,84 @@ impl Decoder for Codec {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "ws-deflate")]
+    fn test_deflate() {
+        let cfg = DeflateConfig::new();
+        let server = Codec::new().deflate(cfg);
+        let client = Codec::new().client_mode().deflate(cfg);
+        assert_eq!(server.deflate_config(), Some(&cfg));
+
+        let mut buf = BytesMut::new();
+        server
+            .encode(Message::Text("Hello, Hello".into()), &mut buf)
+            .unwrap();
+        // rsv1 bit is set
+        assert_eq!(buf[0], 0xc1);
+        assert_eq!(
+            client.decode(&mut buf).unwrap(),
+            Some(Frame::Text(Bytes::from_static(b"Hello, Hello")))
+        );
+
+        // control frames are not compressed
+        server
+            .encode(Message::Ping(Bytes::from_static(b"p")), &mut buf)
+            .unwrap();
+        assert_eq!(&buf[..], &[0x89, 0x01, b'p'][..]);
+        assert_eq!(
+            client.decode(&mut buf).unwrap(),
+            Some(Frame::Ping(Bytes::from_static(b"p")))
+        );
+
+        // fragmented message
+        client
+            .encode(
+                Message::Continuation(Item::FirstBinary(Bytes::from_static(b"Hello"))),
+                &mut buf,
+            )
+            .unwrap();
+        client
+            .encode(
+                Message::Continuation(Item::Last(Bytes::from_static(b", World"))),
+                &mut buf,
+            )
+            .unwrap();
+        assert_eq!(buf[0], 0x42);
+        assert_eq!(
+            server.decode(&mut buf).unwrap(),
+            Some(Frame::Continuation(Item::FirstBinary(Bytes::from_static(
+                b"Hello"
+            ))))
+        );
+        assert_eq!(buf[0], 0x80);
+        assert_eq!(
+            server.decode(&mut buf).unwrap(),
+            Some(Frame::Continuation(Item::Last(Bytes::from_static(
+                b", World"
+            ))))
+        );
+
+        // uncompressed message
+        let mut buf = BytesMut::new();
+        Parser::write_message(&mut buf, "Hello", OpCode::Text, true, false);
+        assert_eq!(
+            client.decode(&mut buf).unwrap(),
+            Some(Frame::Text(Bytes::from_static(b"Hello")))
+        );
+    }
+
+    #[test]
+    fn test_unexpected_rsv1() {
+        let mut buf = BytesMut::new();
+        Parser::write_message_ext(&mut buf, "Hello", OpCode::Text, true, true, false);
+        assert!(matches!(
+            Codec::new().client_mode().decode(&mut buf),
+            Err(ProtocolError::BadOpCode)
+        ));
+    }
+}
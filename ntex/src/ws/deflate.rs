@@ -0,0 +1,553 @@
+//! Permessage-deflate extension (RFC 7692)
+use std::{cell::RefCell, fmt};
+
+use flate2::{Compress, Compression, Decompress, FlushCompress, FlushDecompress};
+
+use crate::http::header::{self, HeaderMap, HeaderValue};
+use crate::util::{Bytes, BytesMut};
+
+use super::error::ProtocolError;
+
+const EXTENSION: &str = "permessage-deflate";
+const TAIL: [u8; 4] = [0x00, 0x00, 0xff, 0xff];
+
+/// Permessage-deflate extension configuration
+///
+/// On the server side configuration is used for negotiation with client
+/// offers, on the client side it is used for building extension offer.
+/// Local compressor always uses 15 bits LZ77 window, offers that
+/// require smaller window for the local compressor are declined.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DeflateConfig {
+    server_no_context_takeover: bool,
+    client_no_context_takeover: bool,
+    server_max_window_bits: Option<u8>,
+    client_max_window_bits: Option<u8>,
+    level: u32,
+}
+
+impl Default for DeflateConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DeflateConfig {
+    /// Create default configuration
+    pub fn new() -> Self {
+        DeflateConfig {
+            server_no_context_takeover: false,
+            client_no_context_takeover: false,
+            server_max_window_bits: None,
+            client_max_window_bits: None,
+            level: Compression::default().level(),
+        }
+    }
+
+    /// Server resets compression context after each message.
+    ///
+    /// By default context takeover is enabled.
+    pub fn server_no_context_takeover(mut self, val: bool) -> Self {
+        self.server_no_context_takeover = val;
+        self
+    }
+
+    /// Client resets compression context after each message.
+    ///
+    /// By default context takeover is enabled.
+    pub fn client_no_context_takeover(mut self, val: bool) -> Self {
+        self.client_no_context_takeover = val;
+        self
+    }
+
+    /// Request server to limit LZ77 window size, client side only.
+    ///
+    /// Panics if `bits` is not in `8..=15` range.
+    pub fn server_max_window_bits(mut self, bits: u8) -> Self {
+        assert!(
+            (8..=15).contains(&bits),
+            "Window bits must be in 8..=15 range"
+        );
+        self.server_max_window_bits = Some(bits);
+        self
+    }
+
+    /// Request client to limit LZ77 window size, server side only.
+    ///
+    /// Limit is applied only if client supports it.
+    /// Panics if `bits` is not in `8..=15` range.
+    pub fn client_max_window_bits(mut self, bits: u8) -> Self {
+        assert!(
+            (8..=15).contains(&bits),
+            "Window bits must be in 8..=15 range"
+        );
+        self.client_max_window_bits = Some(bits);
+        self
+    }
+
+    /// Set compression level, in `0..=9` range.
+    ///
+    /// By default level is set to 6.
+    pub fn compression_level(mut self, level: u32) -> Self {
+        self.level = level.min(9);
+        self
+    }
+
+    /// Negotiate extension parameters with client offers.
+    ///
+    /// Returns negotiated parameters and value for `Sec-WebSocket-Extensions`
+    /// response header, or `None` if client does not offer acceptable parameters.
+    pub(crate) fn negotiate(&self, headers: &HeaderMap) -> Option<(Self, HeaderValue)> {
+        for hdr in headers.get_all(header::SEC_WEBSOCKET_EXTENSIONS) {
+            let Ok(hdr) = hdr.to_str() else {
+                continue;
+            };
+            for (name, params) in parse_extensions(hdr) {
+                if name.eq_ignore_ascii_case(EXTENSION) {
+                    if let Some(cfg) = self.accept_offer(&params) {
+                        let value = HeaderValue::try_from(cfg.to_string()).unwrap();
+                        return Some((cfg, value));
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn accept_offer(&self, params: &[(&str, Option<&str>)]) -> Option<Self> {
+        let mut cfg = DeflateConfig {
+            server_max_window_bits: None,
+            client_max_window_bits: None,
+            ..*self
+        };
+        let mut seen = Vec::with_capacity(params.len());
+
+        for (name, value) in params {
+            if seen.contains(name) {
+                return None;
+            }
+            seen.push(*name);
+
+            match (*name, value) {
+                ("server_no_context_takeover", None) => {
+                    cfg.server_no_context_takeover = true;
+                }
+                ("client_no_context_takeover", None) => {
+                    cfg.client_no_context_takeover = true;
+                }
+                ("server_max_window_bits", Some(bits)) => {
+                    // local compressor supports only default window size
+                    if window_bits(bits)? != 15 {
+                        return None;
+                    }
+                }
+                ("client_max_window_bits", None) => {
+                    cfg.client_max_window_bits = self.client_max_window_bits;
+                }
+                ("client_max_window_bits", Some(bits)) => {
+                    let bits = window_bits(bits)?;
+                    cfg.client_max_window_bits =
+                        Some(self.client_max_window_bits.map_or(bits, |b| b.min(bits)));
+                }
+                _ => return None,
+            }
+        }
+        Some(cfg)
+    }
+
+    /// Value for `Sec-WebSocket-Extensions` request header
+    pub(crate) fn offer(&self) -> HeaderValue {
+        let mut offer = EXTENSION.to_string();
+        if self.server_no_context_takeover {
+            offer.push_str("; server_no_context_takeover");
+        }
+        if self.client_no_context_takeover {
+            offer.push_str("; client_no_context_takeover");
+        }
+        if let Some(bits) = self.server_max_window_bits {
+            offer.push_str(&format!("; server_max_window_bits={}", bits));
+        }
+        HeaderValue::try_from(offer).unwrap()
+    }
+
+    /// Validate server response for the offer.
+    ///
+    /// Returns negotiated parameters, or `None` if server declined extension.
+    pub(crate) fn accept(&self, headers: &HeaderMap) -> Result<Option<Self>, HeaderValue> {
+        let mut result = None;
+
+        for hdr in headers.get_all(header::SEC_WEBSOCKET_EXTENSIONS) {
+            let Ok(s) = hdr.to_str() else {
+                return Err(hdr.clone());
+            };
+            for (name, params) in parse_extensions(s) {
+                if !name.eq_ignore_ascii_case(EXTENSION) || result.is_some() {
+                    return Err(hdr.clone());
+                }
+                let mut cfg = DeflateConfig {
+                    server_no_context_takeover: false,
+                    client_no_context_takeover: self.client_no_context_takeover,
+                    server_max_window_bits: None,
+                    client_max_window_bits: None,
+                    level: self.level,
+                };
+                for (name, value) in params {
+                    match (name, value) {
+                        ("server_no_context_takeover", None) => {
+                            cfg.server_no_context_takeover = true;
+                        }
+                        ("client_no_context_takeover", None) => {
+                            cfg.client_no_context_takeover = true;
+                        }
+                        ("server_max_window_bits", Some(bits)) => {
+                            let bits = window_bits(bits).ok_or_else(|| hdr.clone())?;
+                            if bits > self.server_max_window_bits.unwrap_or(15) {
+                                return Err(hdr.clone());
+                            }
+                            cfg.server_max_window_bits = Some(bits);
+                        }
+                        // client_max_window_bits is not offered
+                        _ => return Err(hdr.clone()),
+                    }
+                }
+                if self.server_no_context_takeover && !cfg.server_no_context_takeover {
+                    return Err(hdr.clone());
+                }
+                result = Some(cfg);
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl fmt::Display for DeflateConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(EXTENSION)?;
+        if self.server_no_context_takeover {
+            f.write_str("; server_no_context_takeover")?;
+        }
+        if self.client_no_context_takeover {
+            f.write_str("; client_no_context_takeover")?;
+        }
+        if let Some(bits) = self.server_max_window_bits {
+            write!(f, "; server_max_window_bits={}", bits)?;
+        }
+        if let Some(bits) = self.client_max_window_bits {
+            write!(f, "; client_max_window_bits={}", bits)?;
+        }
+        Ok(())
+    }
+}
+
+fn window_bits(val: &str) -> Option<u8> {
+    let val = val.trim_matches('"');
+    if val.len() <= 2 && val.bytes().all(|b| b.is_ascii_digit()) {
+        val.parse().ok().filter(|bits| (8..=15).contains(bits))
+    } else {
+        None
+    }
+}
+
+/// Parse `Sec-WebSocket-Extensions` header value
+fn parse_extensions(hdr: &str) -> Vec<(&str, Vec<(&str, Option<&str>)>)> {
+    hdr.split(',')
+        .filter_map(|ext| {
+            let mut parts = ext.split(';').map(|s| s.trim());
+            let name = parts.next().filter(|s| !s.is_empty())?;
+            let params = parts
+                .filter(|s| !s.is_empty())
+                .map(|param| match param.split_once('=') {
+                    Some((name, value)) => (name.trim(), Some(value.trim())),
+                    None => (param, None),
+                })
+                .collect();
+            Some((name, params))
+        })
+        .collect()
+}
+
+/// Compression state of the connection
+pub(crate) struct Deflate {
+    cfg: DeflateConfig,
+    compress: RefCell<Compress>,
+    decompress: RefCell<Decompress>,
+}
+
+impl Deflate {
+    pub(crate) fn new(cfg: DeflateConfig) -> Self {
+        Deflate {
+            cfg,
+            compress: RefCell::new(Compress::new(Compression::new(cfg.level), false)),
+            decompress: RefCell::new(Decompress::new(false)),
+        }
+    }
+
+    pub(crate) fn config(&self) -> &DeflateConfig {
+        &self.cfg
+    }
+
+    /// Compress message payload, `fin` marks last fragment of the message
+    pub(crate) fn compress(
+        &self,
+        data: &[u8],
+        fin: bool,
+        server: bool,
+    ) -> Result<Bytes, ProtocolError> {
+        let mut c = self.compress.borrow_mut();
+        let mut out = Vec::with_capacity(data.len() + 64);
+        let mut input = data;
+
+        loop {
+            let total_in = c.total_in();
+            c.compress_vec(input, &mut out, FlushCompress::Sync)
+                .map_err(|e| {
+                    log::trace!("Permessage-deflate compression error: {}", e);
+                    ProtocolError::BadOpCode
+                })?;
+            input = &input[(c.total_in() - total_in) as usize..];
+
+            // flush is completed if output buffer is not full
+            if input.is_empty() && out.len() < out.capacity() {
+                break;
+            }
+            out.reserve(out.capacity().max(64));
+        }
+
+        if fin {
+            if out.ends_with(&TAIL) {
+                out.truncate(out.len() - TAIL.len());
+            }
+            let no_context_takeover = if server {
+                self.cfg.server_no_context_takeover
+            } else {
+                self.cfg.client_no_context_takeover
+            };
+            if no_context_takeover {
+                c.reset();
+            }
+        }
+        Ok(Bytes::from(out))
+    }
+
+    /// Decompress message payload, `fin` marks last fragment of the message
+    pub(crate) fn decompress(
+        &self,
+        data: &[u8],
+        fin: bool,
+        server: bool,
+        max_size: usize,
+    ) -> Result<Bytes, ProtocolError> {
+        let mut d = self.decompress.borrow_mut();
+        let mut out = BytesMut::with_capacity(data.len() * 2 + 64);
+
+        inflate(&mut d, data, &mut out, max_size)?;
+        if fin {
+            inflate(&mut d, &TAIL, &mut out, max_size)?;
+
+            let no_context_takeover = if server {
+                self.cfg.client_no_context_takeover
+            } else {
+                self.cfg.server_no_context_takeover
+            };
+            if no_context_takeover {
+                d.reset(false);
+            }
+        }
+        Ok(out.freeze())
+    }
+}
+
+fn inflate(
+    d: &mut Decompress,
+    mut input: &[u8],
+    out: &mut BytesMut,
+    max_size: usize,
+) -> Result<(), ProtocolError> {
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        buf.reserve(input.len().max(1024) * 2);
+
+        let total_in = d.total_in();
+        d.decompress_vec(input, &mut buf, FlushDecompress::Sync)
+            .map_err(|e| {
+                log::trace!("Permessage-deflate decompression error: {}", e);
+                ProtocolError::BadOpCode
+            })?;
+        let consumed = (d.total_in() - total_in) as usize;
+        input = &input[consumed..];
+
+        out.extend_from_slice(&buf);
+        if out.len() > max_size {
+            return Err(ProtocolError::Overflow);
+        }
+        if (input.is_empty() && buf.len() < buf.capacity())
+            || (consumed == 0 && buf.is_empty())
+        {
+            break;
+        }
+    }
+    Ok(())
+}
+
+impl fmt::Debug for Deflate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Deflate")
+            .field("config", &self.cfg)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::header::HeaderName;
+
+    fn headers(val: &'static str) -> HeaderMap {
+        let mut hdrs = HeaderMap::new();
+        hdrs.insert(
+            HeaderName::from_static("sec-websocket-extensions"),
+            HeaderValue::from_static(val),
+        );
+        hdrs
+    }
+
+    #[test]
+    fn test_negotiate() {
+        let cfg = DeflateConfig::new();
+        assert!(cfg.negotiate(&HeaderMap::new()).is_none());
+        assert!(cfg.negotiate(&headers("x-webkit-deflate-frame")).is_none());
+
+        let (params, hdr) = cfg
+            .negotiate(&headers("permessage-deflate; client_max_window_bits"))
+            .unwrap();
+        assert_eq!(params, cfg);
+        assert_eq!(hdr, "permessage-deflate");
+
+        // smaller server window is not supported
+        let (params, hdr) = cfg
+            .negotiate(&headers(
+                "permessage-deflate; server_max_window_bits=10, \
+                 permessage-deflate; server_no_context_takeover",
+            ))
+            .unwrap();
+        assert!(params.server_no_context_takeover);
+        assert_eq!(hdr, "permessage-deflate; server_no_context_takeover");
+
+        let cfg = DeflateConfig::new()
+            .client_max_window_bits(12)
+            .client_no_context_takeover(true);
+        let (_, hdr) = cfg
+            .negotiate(&headers(
+                "permessage-deflate; client_max_window_bits=\"10\"",
+            ))
+            .unwrap();
+        assert_eq!(
+            hdr,
+            "permessage-deflate; client_no_context_takeover; client_max_window_bits=10"
+        );
+        let (_, hdr) = cfg.negotiate(&headers("permessage-deflate")).unwrap();
+        assert_eq!(hdr, "permessage-deflate; client_no_context_takeover");
+
+        assert!(cfg
+            .negotiate(&headers("permessage-deflate; unknown"))
+            .is_none());
+        assert!(cfg
+            .negotiate(&headers(
+                "permessage-deflate; server_no_context_takeover; server_no_context_takeover"
+            ))
+            .is_none());
+        assert!(cfg
+            .negotiate(&headers("permessage-deflate; client_max_window_bits=16"))
+            .is_none());
+    }
+
+    #[test]
+    fn test_offer() {
+        let cfg = DeflateConfig::new();
+        assert_eq!(cfg.offer(), "permessage-deflate");
+        assert_eq!(cfg.accept(&HeaderMap::new()).unwrap(), None);
+
+        let params = cfg
+            .accept(&headers(
+                "permessage-deflate; server_no_context_takeover; server_max_window_bits=10",
+            ))
+            .unwrap()
+            .unwrap();
+        assert!(params.server_no_context_takeover);
+        assert!(cfg
+            .accept(&headers("permessage-deflate; client_max_window_bits=10"))
+            .is_err());
+        assert!(cfg.accept(&headers("x-webkit-deflate-frame")).is_err());
+
+        let cfg = DeflateConfig::new()
+            .server_no_context_takeover(true)
+            .server_max_window_bits(10);
+        assert_eq!(
+            cfg.offer(),
+            "permessage-deflate; server_no_context_takeover; server_max_window_bits=10"
+        );
+        assert!(cfg.accept(&headers("permessage-deflate")).is_err());
+        assert!(cfg
+            .accept(&headers(
+                "permessage-deflate; server_no_context_takeover; server_max_window_bits=12"
+            ))
+            .is_err());
+    }
+
+    #[test]
+    fn test_compress() {
+        let client = Deflate::new(DeflateConfig::new());
+
+        // rfc7692 7.2.3.1
+        let msg = client
+            .decompress(
+                &[0xf2, 0x48, 0xcd, 0xc9, 0xc9, 0x07, 0x00],
+                true,
+                false,
+                1024,
+            )
+            .unwrap();
+        assert_eq!(&msg[..], b"Hello");
+
+        let server = Deflate::new(DeflateConfig::new());
+        let client = Deflate::new(DeflateConfig::new());
+        let data = server.compress(b"Hello", true, true).unwrap();
+        assert!(!data.ends_with(&TAIL));
+        let msg = client.decompress(&data, true, false, 1024).unwrap();
+        assert_eq!(&msg[..], b"Hello");
+
+        // context takeover
+        let data = server.compress(b"Hello", true, true).unwrap();
+        let msg = client.decompress(&data, true, false, 1024).unwrap();
+        assert_eq!(&msg[..], b"Hello");
+
+        // fragmented message
+        let part1 = server.compress(b"Hello, ", false, true).unwrap();
+        let part2 = server.compress(b"World", true, true).unwrap();
+        let mut msg = BytesMut::new();
+        msg.extend_from_slice(&client.decompress(&part1, false, false, 1024).unwrap());
+        msg.extend_from_slice(&client.decompress(&part2, true, false, 1024).unwrap());
+        assert_eq!(&msg[..], b"Hello, World");
+
+        let data = server.compress(&[b'a'; 2048], true, true).unwrap();
+        assert!(matches!(
+            client.decompress(&data, true, false, 1024),
+            Err(ProtocolError::Overflow)
+        ));
+    }
+
+    #[test]
+    fn test_no_context_takeover() {
+        let cfg = DeflateConfig::new().server_no_context_takeover(true);
+        let server = Deflate::new(cfg);
+        let data = server.compress(b"Hello", true, true).unwrap();
+        let data2 = server.compress(b"Hello", true, true).unwrap();
+        assert_eq!(data, data2);
+
+        // fresh decompressor can inflate any message
+        let client = Deflate::new(cfg);
+        let msg = client.decompress(&data2, true, false, 1024).unwrap();
+        assert_eq!(&msg[..], b"Hello");
+    }
+}
@@ -13,7 +13,7 @@ impl Parser {
         src: &[u8],
         server: bool,
         max_size: usize,
-    ) -> Result<Option<(usize, bool, OpCode, usize, Option<u32>)>, ProtocolError> {
+    ) -> Result<Option<(usize, bool, u8, OpCode, usize, Option<u32>)>, ProtocolError> {
         let chunk_len = src.len();
 
         let mut idx = 2;
@@ -24,6 +24,7 @@ impl Parser {
         let first = src[0];
         let second = src[1];
         let finished = first & 0x80 != 0;
+        let rsv = first & 0x70;
 
         // check masking
         let masked = second & 0x80 != 0;
@@ -81,7 +82,7 @@ impl Parser {
             None
         };
 
-        Ok(Some((idx, finished, opcode, length, mask)))
+        Ok(Some((idx, finished, rsv, opcode, length, mask)))
     }
 
     /// Parse the input stream into a frame.
@@ -90,12 +91,47 @@ impl Parser {
         server: bool,
         max_size: usize,
     ) -> Result<Option<(bool, OpCode, Option<Bytes>)>, ProtocolError> {
+        Ok(Parser::parse_frame(src, server, max_size, false)?
+            .map(|(finished, _, opcode, payload)| (finished, opcode, payload)))
+    }
+
+    /// Parse the input stream into a frame, returns state of the `RSV1` bit.
+    ///
+    /// `RSV1` bit is used by permessage-deflate extension, frames with
+    /// `RSV2` or `RSV3` bits set are rejected.
+    pub fn parse_ext(
+        src: &mut BytesMut,
+        server: bool,
+        max_size: usize,
+    ) -> Result<Option<(bool, bool, OpCode, Option<Bytes>)>, ProtocolError> {
+        Parser::parse_frame(src, server, max_size, true)
+    }
+
+    fn parse_frame(
+        src: &mut BytesMut,
+        server: bool,
+        max_size: usize,
+        check_rsv: bool,
+    ) -> Result<Option<(bool, bool, OpCode, Option<Bytes>)>, ProtocolError> {
         // try to parse ws frame metadata
-        let (idx, finished, opcode, length, mask) =
+        let (idx, finished, rsv, opcode, length, mask) =
             match Parser::parse_metadata(src, server, max_size)? {
                 None => return Ok(None),
                 Some(res) => res,
             };
+        let rsv1 = rsv & 0x40 != 0;
+
+        if check_rsv {
+            if rsv & 0x30 != 0 {
+                log::trace!("Unexpected reserved bits: {:#05b}", rsv >> 4);
+                return Err(ProtocolError::BadOpCode);
+            }
+            // control frames cannot be compressed
+            if rsv1 && matches!(opcode, OpCode::Ping | OpCode::Pong | OpCode::Close) {
+                log::trace!("Compressed control frame: {:?}", opcode);
+                return Err(ProtocolError::BadOpCode);
+            }
+        }
 
         // not enough data
         if src.len() < idx + length {
@@ -107,7 +143,7 @@ impl Parser {
 
         // no need for body
         if length == 0 {
-            return Ok(Some((finished, opcode, None)));
+            return Ok(Some((finished, rsv1, opcode, None)));
         }
 
         // control frames must have length <= 125
@@ -117,7 +153,7 @@ impl Parser {
             }
             OpCode::Close if length > 125 => {
                 log::trace!("Received close frame with payload length exceeding 125. Morphing to protocol close frame.");
-                return Ok(Some((true, OpCode::Close, None)));
+                return Ok(Some((true, false, OpCode::Close, None)));
             }
             _ => (),
         }
@@ -129,6 +165,7 @@ impl Parser {
 
         Ok(Some((
             finished,
+            rsv1,
             opcode,
             Some(src.split_to(length).freeze()),
         )))
@@ -157,13 +194,28 @@ impl Parser {
         op: OpCode,
         fin: bool,
         mask: bool,
+    ) {
+        Parser::write_message_ext(dst, pl, op, fin, false, mask)
+    }
+
+    /// Generate binary representation, `rsv1` sets `RSV1` bit of the frame
+    pub fn write_message_ext<B: AsRef<[u8]>>(
+        dst: &mut BytesMut,
+        pl: B,
+        op: OpCode,
+        fin: bool,
+        rsv1: bool,
+        mask: bool,
     ) {
         let payload = pl.as_ref();
-        let one: u8 = if fin {
+        let mut one: u8 = if fin {
             0x80 | Into::<u8>::into(op)
         } else {
             op.into()
         };
+        if rsv1 {
+            one |= 0x40;
+        }
         let payload_len = payload.len();
         let (two, p_len) = if mask {
             (0x80, payload_len + 4)
@@ -331,6 +383,37 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_rsv() {
+        let mut buf = BytesMut::from(&[0b1100_0001u8, 0b0000_0001u8][..]);
+        buf.extend(b"1");
+        let frame = extract(Parser::parse(&mut buf.clone(), false, 1024));
+        assert!(frame.finished);
+        assert_eq!(frame.opcode, OpCode::Text);
+
+        let (finished, rsv1, opcode, payload) =
+            Parser::parse_ext(&mut buf, false, 1024).unwrap().unwrap();
+        assert!(finished);
+        assert!(rsv1);
+        assert_eq!(opcode, OpCode::Text);
+        assert_eq!(payload.unwrap().as_ref(), &b"1"[..]);
+
+        let mut buf = BytesMut::from(&[0b1010_0001u8, 0b0000_0000u8][..]);
+        assert!(matches!(
+            Parser::parse_ext(&mut buf, false, 1024),
+            Err(ProtocolError::BadOpCode)
+        ));
+
+        let mut buf = BytesMut::from(&[0b1100_1001u8, 0b0000_0000u8][..]);
+        assert!(Parser::parse_ext(&mut buf, false, 1024).is_err());
+
+        let mut buf = BytesMut::new();
+        Parser::write_message_ext(&mut buf, "data", OpCode::Binary, true, true, false);
+        let mut v = vec![0xc2u8, 4u8];
+        v.extend(b"data");
+        assert_eq!(&buf[..], &v[..]);
+    }
+
     #[test]
     fn test_ping_frame() {
         let mut buf = BytesMut::new();
@@ -2,6 +2,8 @@
 use crate::http::{header, Method, StatusCode};
 use crate::http::{RequestHead, Response, ResponseBuilder};
 
+#[cfg(feature = "ws-deflate")]
+use super::deflate::DeflateConfig;
 use super::error::HandshakeError;
 
 /// Verify `WebSocket` handshake request and create handshake reponse.
@@ -13,6 +15,20 @@ pub fn handshake(req: &RequestHead) -> Result<ResponseBuilder, HandshakeError> {
     Ok(handshake_response(req))
 }
 
+#[cfg(feature = "ws-deflate")]
+/// Verify `WebSocket` handshake request and create handshake reponse,
+/// negotiate permessage-deflate extension.
+///
+/// Returns negotiated extension parameters for `Codec::deflate()`,
+/// or `None` if client does not support extension.
+pub fn handshake_with_deflate(
+    req: &RequestHead,
+    config: &DeflateConfig,
+) -> Result<(ResponseBuilder, Option<DeflateConfig>), HandshakeError> {
+    verify_handshake(req)?;
+    Ok(handshake_response_with_deflate(req, config))
+}
+
 /// Verify `WebSocket` handshake request.
 // /// `protocols` is a sequence of known protocols. On successful handshake,
 // /// the returned response headers contain the first protocol in this list
@@ -80,6 +96,23 @@ pub fn handshake_response(req: &RequestHead) -> ResponseBuilder {
         .take()
 }
 
+#[cfg(feature = "ws-deflate")]
+/// Create websocket's handshake response, negotiate permessage-deflate extension
+///
+/// Negotiated parameters are added to `Sec-WebSocket-Extensions` response header.
+pub fn handshake_response_with_deflate(
+    req: &RequestHead,
+    config: &DeflateConfig,
+) -> (ResponseBuilder, Option<DeflateConfig>) {
+    let mut res = handshake_response(req);
+    if let Some((cfg, value)) = config.negotiate(req.headers()) {
+        res.header(header::SEC_WEBSOCKET_EXTENSIONS, value);
+        (res, Some(cfg))
+    } else {
+        (res, None)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -195,6 +228,46 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg(feature = "ws-deflate")]
+    fn test_handshake_deflate() {
+        let req = TestRequest::default()
+            .header(header::UPGRADE, "websocket")
+            .header(header::CONNECTION, "upgrade")
+            .header(header::SEC_WEBSOCKET_VERSION, "13")
+            .header(header::SEC_WEBSOCKET_KEY, "13")
+            .finish();
+        let (mut res, cfg) =
+            handshake_with_deflate(req.head(), &DeflateConfig::new()).unwrap();
+        assert!(cfg.is_none());
+        assert!(!res
+            .finish()
+            .headers()
+            .contains_key(header::SEC_WEBSOCKET_EXTENSIONS));
+
+        let req = TestRequest::default()
+            .header(header::UPGRADE, "websocket")
+            .header(header::CONNECTION, "upgrade")
+            .header(header::SEC_WEBSOCKET_VERSION, "13")
+            .header(header::SEC_WEBSOCKET_KEY, "13")
+            .header(
+                header::SEC_WEBSOCKET_EXTENSIONS,
+                "permessage-deflate; client_max_window_bits",
+            )
+            .finish();
+        let config = DeflateConfig::new()
+            .server_no_context_takeover(true)
+            .client_max_window_bits(10);
+        let (mut res, cfg) = handshake_with_deflate(req.head(), &config).unwrap();
+        assert_eq!(cfg, Some(config));
+        let res = res.finish();
+        assert_eq!(res.status(), StatusCode::SWITCHING_PROTOCOLS);
+        assert_eq!(
+            res.headers().get(header::SEC_WEBSOCKET_EXTENSIONS).unwrap(),
+            "permessage-deflate; server_no_context_takeover; client_max_window_bits=10"
+        );
+    }
+
     #[test]
     fn test_wserror_http_response() {
         let resp: Response = HandshakeError::GetMethodRequired.error_response();
@@ -5,6 +5,8 @@
 //! communicate with the peer.
 mod client;
 mod codec;
+#[cfg(feature = "ws-deflate")]
+mod deflate;
 mod frame;
 mod handshake;
 mod mask;
@@ -16,8 +18,12 @@ pub mod error;
 
 pub use self::client::{WsClient, WsClientBuilder, WsConnection};
 pub use self::codec::{Codec, Frame, Item, Message};
+#[cfg(feature = "ws-deflate")]
+pub use self::deflate::DeflateConfig;
 pub use self::frame::Parser;
 pub use self::handshake::{handshake, handshake_response, verify_handshake};
+#[cfg(feature = "ws-deflate")]
+pub use self::handshake::{handshake_response_with_deflate, handshake_with_deflate};
 pub use self::proto::{hash_key, CloseCode, CloseReason, OpCode};
 pub use self::sink::WsSink;
 pub use self::transport::{WsTransport, WsTransportService};
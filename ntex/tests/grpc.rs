@@ -0,0 +1,149 @@
+#![cfg(all(feature = "grpc", feature = "openssl"))]
+use futures_util::stream::{self, StreamExt};
+use tls_openssl::ssl::{
+    AlpnError, SslAcceptor, SslConnector, SslFiletype, SslMethod, SslVerifyMode,
+};
+
+use ntex::grpc::{self, Code, RawCodec, Request, Status, Streaming};
+use ntex::http::client::{Client, Connector};
+use ntex::http::{test::server as test_server, HttpService};
+use ntex::service::fn_service;
+use ntex::time::{sleep, Millis};
+use ntex::util::Bytes;
+
+fn ssl_acceptor() -> SslAcceptor {
+    let mut builder = SslAcceptor::mozilla_intermediate(SslMethod::tls()).unwrap();
+    builder
+        .set_private_key_file("./tests/key.pem", SslFiletype::PEM)
+        .unwrap();
+    builder
+        .set_certificate_chain_file("./tests/cert.pem")
+        .unwrap();
+    builder.set_alpn_select_callback(|_, protos| {
+        const H2: &[u8] = b"\x02h2";
+        if protos.windows(3).any(|window| window == H2) {
+            Ok(b"h2")
+        } else {
+            Err(AlpnError::NOACK)
+        }
+    });
+    builder.build()
+}
+
+fn client(url: String) -> grpc::Client {
+    let mut builder = SslConnector::builder(SslMethod::tls()).unwrap();
+    builder.set_verify(SslVerifyMode::NONE);
+    builder.set_alpn_protos(b"\x02h2").unwrap();
+
+    let client = Client::build()
+        .connector(Connector::default().openssl(builder.build()).finish())
+        .finish();
+    grpc::Client::new(client, &url)
+}
+
+fn server() -> grpc::Server {
+    grpc::Server::new()
+        .unary(
+            "/test.Test/Echo",
+            RawCodec,
+            fn_service(|req: Request<Bytes>| async move {
+                if req.message().is_empty() {
+                    Err(Status::invalid_argument("empty message"))
+                } else {
+                    Ok(req.into_inner())
+                }
+            }),
+        )
+        .unary(
+            "/test.Test/Slow",
+            RawCodec,
+            fn_service(|req: Request<Bytes>| async move {
+                sleep(Millis(1000)).await;
+                Ok::<_, Status>(req.into_inner())
+            }),
+        )
+        .streaming(
+            "/test.Test/Split",
+            RawCodec,
+            fn_service(|req: Request<Streaming<Bytes>>| async move {
+                let mut items = Vec::new();
+                let mut stream = req.into_inner();
+                while let Some(msg) = stream.recv().await {
+                    let msg = msg?;
+                    for i in 0..msg.len() {
+                        items.push(Ok(msg.slice(i..i + 1)));
+                    }
+                }
+                items.push(Err(Status::new(Code::Aborted, "done")));
+                Ok::<_, Status>(stream::iter(items))
+            }),
+        )
+}
+
+#[ntex::test]
+async fn test_grpc_unary() {
+    let srv = test_server(|| {
+        HttpService::build()
+            .h2(server())
+            .openssl(ssl_acceptor())
+            .map_err(|_| ())
+    });
+    let client = client(srv.surl(""));
+
+    let res = client
+        .unary(
+            "/test.Test/Echo",
+            RawCodec,
+            Request::new(Bytes::from_static(b"hello")),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.into_inner(), Bytes::from_static(b"hello"));
+
+    let err = client
+        .unary("/test.Test/Echo", RawCodec, Request::new(Bytes::new()))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+    assert_eq!(err.message(), "empty message");
+
+    let err = client
+        .unary("/test.Test/Unknown", RawCodec, Request::new(Bytes::new()))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::Unimplemented);
+
+    let mut req = Request::new(Bytes::from_static(b"slow"));
+    req.set_timeout(Millis(100));
+    let err = client
+        .unary("/test.Test/Slow", RawCodec, req)
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::DeadlineExceeded);
+}
+
+#[ntex::test]
+async fn test_grpc_streaming() {
+    let srv = test_server(|| {
+        HttpService::build()
+            .h2(server())
+            .openssl(ssl_acceptor())
+            .map_err(|_| ())
+    });
+    let client = client(srv.surl(""));
+
+    let req = Request::new(stream::iter(vec![
+        Bytes::from_static(b"ab"),
+        Bytes::from_static(b"c"),
+    ]));
+    let res = client
+        .streaming("/test.Test/Split", RawCodec, req)
+        .await
+        .unwrap();
+    let items: Vec<_> = res.into_inner().collect().await;
+    assert_eq!(items.len(), 4);
+    assert_eq!(items[0].as_ref().unwrap(), &Bytes::from_static(b"a"));
+    assert_eq!(items[1].as_ref().unwrap(), &Bytes::from_static(b"b"));
+    assert_eq!(items[2].as_ref().unwrap(), &Bytes::from_static(b"c"));
+    assert_eq!(items[3].as_ref().unwrap_err().code(), Code::Aborted);
+}
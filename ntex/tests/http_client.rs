@@ -1,7 +1,9 @@
 use std::io;
 
+use ntex::http::client::error::{ConnectError, SendRequestError};
+use ntex::http::client::{Client, Connector, Proxy};
 use ntex::http::test::server as test_server;
-use ntex::http::{client::error::SendRequestError, HttpService, Method, Request, Response};
+use ntex::http::{HttpService, Method, Request, Response, StatusCode};
 use ntex::service::ServiceFactory;
 use ntex::{time, util::Bytes, util::Ready};
 
@@ -109,3 +111,90 @@ async fn test_client_timeout() {
         .unwrap();
     assert!(matches!(err, SendRequestError::Timeout));
 }
+
+#[ntex::test]
+async fn test_proxy_forward() {
+    let srv = test_server(move || {
+        HttpService::build()
+            .finish(|req: Request| {
+                let valid = req.uri().to_string() == "http://example.com/test?q=1"
+                    && req
+                        .headers()
+                        .get("proxy-authorization")
+                        .map_or(false, |v| v == "Basic dXNlcjpwYXNz");
+                if valid {
+                    Ready::Ok::<_, io::Error>(Response::Ok().body(STR))
+                } else {
+                    Ready::Ok::<_, io::Error>(Response::BadRequest().finish())
+                }
+            })
+            .map(|_| ())
+    });
+
+    let proxy = Proxy::new(format!("http://user:pass@{}", srv.addr())).unwrap();
+    let client = Client::build()
+        .connector(Connector::default().proxy(proxy).finish())
+        .finish();
+
+    let mut response = client
+        .get("http://example.com/test?q=1")
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    let bytes = response.body().await.unwrap();
+    assert_eq!(bytes, Bytes::from_static(STR.as_ref()));
+
+    // hosts from no-proxy list are connected directly
+    let proxy = Proxy::new("http://127.0.0.1:1").unwrap();
+    let client = Client::build()
+        .connector(
+            Connector::default()
+                .proxy(proxy)
+                .no_proxy(["127.0.0.1"])
+                .finish(),
+        )
+        .finish();
+    let response = client.get(srv.url("/")).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[cfg(any(feature = "openssl", feature = "rustls"))]
+#[ntex::test]
+async fn test_proxy_tunnel_refused() {
+    use ntex::{codec::BytesCodec, io::Io, service::fn_service};
+
+    let srv = ntex::server::test_server(|| {
+        fn_service(|io: Io<_>| async move {
+            let req = io.recv(&BytesCodec).await.unwrap().unwrap();
+            assert!(req.starts_with(b"CONNECT example.com:443 HTTP/1.1\r\n"));
+            io.send(
+                Bytes::from_static(
+                    b"HTTP/1.1 407 Proxy Authentication Required\r\ncontent-length: 0\r\n\r\n",
+                ),
+                &BytesCodec,
+            )
+            .await
+            .unwrap();
+            Ok::<_, io::Error>(())
+        })
+    });
+
+    let proxy = Proxy::new(format!("http://{}", srv.addr())).unwrap();
+    let client = Client::build()
+        .connector(Connector::default().proxy(proxy).finish())
+        .finish();
+
+    let err = client
+        .get("https://example.com/")
+        .send()
+        .await
+        .err()
+        .unwrap();
+    match err {
+        SendRequestError::Connect(ConnectError::Proxy(status)) => {
+            assert_eq!(status, StatusCode::PROXY_AUTHENTICATION_REQUIRED)
+        }
+        _ => panic!("{:?}", err),
+    }
+}
@@ -7,7 +7,8 @@ use regex::Regex;
 
 use ntex::http::header::{self, HeaderName, HeaderValue};
 use ntex::http::{body, h1::Control, test::server as test_server};
-use ntex::http::{HttpService, KeepAlive, Method, Request, Response, StatusCode, Version};
+use ntex::http::{HeaderMap, HttpService, KeepAlive, Method, Request, Response};
+use ntex::http::{StatusCode, Version};
 use ntex::time::{sleep, timeout, Millis, Seconds};
 use ntex::{
     channel::oneshot, rt, service::fn_service, util::Bytes, util::Ready, web::error,
@@ -150,6 +151,72 @@ async fn test_chunked_payload() {
     assert_eq!(returned_size, total_size);
 }
 
+#[ntex::test]
+async fn test_h1_trailers() {
+    let srv = test_server(|| {
+        HttpService::build().h1(fn_service(|mut req: Request| async move {
+            let mut pl = req.take_payload();
+            let mut size = 0;
+            while let Some(chunk) = pl.recv().await {
+                size += chunk.unwrap().len();
+            }
+            let mut trailers = pl.take_trailers().unwrap();
+            trailers.insert(
+                HeaderName::from_static("x-size"),
+                HeaderValue::from(size as u64),
+            );
+            Ok::<_, io::Error>(
+                Response::Ok().body(body::WithTrailers::new("data", trailers)),
+            )
+        }))
+    });
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(
+        b"POST /test HTTP/1.1\r\nConnection: close\r\nTransfer-Encoding: chunked\r\n\r\n\
+          4\r\ntest\r\n0\r\nx-check: 1\r\n\r\n",
+    );
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(data.contains("transfer-encoding: chunked\r\n"));
+    assert!(
+        data.ends_with("4\r\ndata\r\n0\r\nx-check: 1\r\nx-size: 4\r\n\r\n")
+            || data.ends_with("4\r\ndata\r\n0\r\nx-size: 4\r\nx-check: 1\r\n\r\n")
+    );
+}
+
+#[ntex::test]
+async fn test_h1_client_trailers() {
+    let srv = test_server(|| {
+        HttpService::build().h1(fn_service(|_| async move {
+            let mut trailers = HeaderMap::new();
+            trailers.insert(
+                HeaderName::from_static("x-check"),
+                HeaderValue::from_static("1"),
+            );
+            trailers.insert(header::CONTENT_LENGTH, HeaderValue::from_static("4"));
+            Ok::<_, io::Error>(
+                Response::Ok().body(body::WithTrailers::new("data", trailers)),
+            )
+        }))
+    });
+
+    let mut response = srv.request(Method::GET, "/").send().await.unwrap();
+    assert!(response.status().is_success());
+
+    let mut pl = response.take_payload();
+    let mut data = Vec::new();
+    while let Some(chunk) = pl.recv().await {
+        data.extend_from_slice(&chunk.unwrap());
+    }
+    assert_eq!(data, b"data");
+
+    let trailers = pl.take_trailers().unwrap();
+    assert_eq!(trailers.get("x-check").unwrap(), "1");
+    assert!(!trailers.contains_key(header::CONTENT_LENGTH));
+}
+
 #[ntex::test]
 async fn test_slow_request() {
     const DATA: &[u8] = b"GET /test/tests/test HTTP/1.1\r\n";
@@ -211,6 +278,63 @@ async fn test_http1_malformed_request() {
     assert!(data.starts_with("HTTP/1.1 400 Bad Request"));
 }
 
+#[ntex::test]
+async fn test_http1_parser_limits() {
+    let srv = test_server(|| {
+        HttpService::build()
+            .max_headers(4)
+            .max_uri_length(32)
+            .h1(|_| Ready::Ok::<_, io::Error>(Response::Ok().finish()))
+    });
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ =
+        stream.write_all(format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(64)).as_bytes());
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.starts_with("HTTP/1.1 414 URI Too Long"));
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream
+        .write_all(b"GET /test HTTP/1.1\r\na: 1\r\nb: 2\r\nc: 3\r\nd: 4\r\ne: 5\r\n\r\n");
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.starts_with("HTTP/1.1 431 Request Header Fields Too Large"));
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(b"GET /test HTTP/1.1\r\na: 1\r\n\r\n");
+    let mut data = vec![0; 1024];
+    let _ = stream.read(&mut data);
+    assert_eq!(&data[..17], b"HTTP/1.1 200 OK\r\n");
+}
+
+#[ntex::test]
+async fn test_http1_proxy_protocol() {
+    let srv = test_server(|| {
+        HttpService::build()
+            .finish(|req: Request| {
+                let addr = req.peer_addr().unwrap();
+                Ready::Ok::<_, io::Error>(Response::Ok().body(addr.to_string()))
+            })
+            .proxy_protocol()
+    });
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(b"PROXY TCP4 10.0.0.1 10.0.0.2 5000 80\r\n");
+    let _ = stream.write_all(b"GET /test HTTP/1.1\r\nconnection: close\r\n\r\n");
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.starts_with("HTTP/1.1 200 OK"));
+    assert!(data.ends_with("10.0.0.1:5000"));
+
+    // connection without proxy header
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(b"GET /test HTTP/1.1\r\n\r\n");
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.is_empty());
+}
+
 #[ntex::test]
 async fn test_http1_keepalive() {
     let srv = test_server(|| {
@@ -91,6 +91,78 @@ async fn test_simple() {
     assert_eq!(item, ws::Frame::Close(Some(ws::CloseCode::Normal.into())));
 }
 
+#[cfg(feature = "ws-deflate")]
+#[ntex::test]
+async fn test_deflate() {
+    let srv = test_server(|| {
+        HttpService::build()
+            .h1_control(|req: h1::Control<_, _>| async move {
+                let ack = if let h1::Control::Upgrade(upg) = req {
+                    upg.handle(|req, io, codec| async move {
+                        let cfg = ws::DeflateConfig::new().server_no_context_takeover(true);
+                        let (mut res, deflate) =
+                            ws::handshake_response_with_deflate(req.head(), &cfg);
+                        let res = res.finish();
+
+                        // send handshake respone
+                        io.encode(
+                            h1::Message::Item((res.drop_body(), BodySize::None)),
+                            &codec,
+                        )
+                        .unwrap();
+
+                        // start websocket service
+                        Dispatcher::new(
+                            io.seal(),
+                            ws::Codec::default().deflate(deflate.unwrap()),
+                            ws_service,
+                            &Default::default(),
+                        )
+                        .await
+                    })
+                } else {
+                    req.ack()
+                };
+                Ok::<_, io::Error>(ack)
+            })
+            .finish(|_| Ready::Ok::<_, io::Error>(Response::NotFound()))
+    });
+
+    // client service
+    let con = ws::WsClient::build(srv.url("/"))
+        .address(srv.addr())
+        .deflate(ws::DeflateConfig::new())
+        .finish()
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    assert_eq!(
+        con.response()
+            .headers()
+            .get(ntex::http::header::SEC_WEBSOCKET_EXTENSIONS)
+            .unwrap(),
+        "permessage-deflate; server_no_context_takeover"
+    );
+    let (io, codec, _) = con.into_inner();
+    assert!(codec.deflate_config().is_some());
+
+    for _ in 0..2 {
+        io.send(ws::Message::Text(ByteString::from_static("text")), &codec)
+            .await
+            .unwrap();
+        let item = io.recv(&codec).await.unwrap().unwrap();
+        assert_eq!(item, ws::Frame::Text(Bytes::from_static(b"text")));
+    }
+
+    let data = Bytes::from(vec![b'a'; 16 * 1024]);
+    io.send(ws::Message::Binary(data.clone()), &codec)
+        .await
+        .unwrap();
+    let item = io.recv(&codec).await.unwrap().unwrap();
+    assert_eq!(item, ws::Frame::Binary(data));
+}
+
 #[ntex::test]
 async fn test_transport() {
     let mut srv = test_server(|| {
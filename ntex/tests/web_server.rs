@@ -8,8 +8,8 @@ use rand::{distributions::Alphanumeric, Rng};
 use thiserror::Error;
 
 use ntex::http::header::{
-    ContentEncoding, ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE,
-    TRANSFER_ENCODING,
+    ContentEncoding, HeaderValue, ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_LENGTH,
+    CONTENT_TYPE, TRANSFER_ENCODING,
 };
 use ntex::http::{body::Body, client, ConnectionType, Method, StatusCode};
 use ntex::time::{sleep, Millis, Seconds, Sleep};
@@ -154,6 +154,82 @@ async fn test_body_gzip2() {
     assert_eq!(Bytes::from(dec), Bytes::from_static(STR.as_ref()));
 }
 
+#[cfg(feature = "brotli")]
+#[ntex::test]
+async fn test_body_brotli() {
+    let srv = test::server_with(test::config().h1(), || {
+        App::new().wrap(Compress::default()).service(
+            web::resource("/").route(web::to(|| async { HttpResponse::Ok().body(STR) })),
+        )
+    });
+
+    let mut response = srv
+        .get("/")
+        .no_decompress()
+        .header(ACCEPT_ENCODING, "gzip;q=0.8, br")
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(
+        response.headers().get(CONTENT_ENCODING).unwrap(),
+        HeaderValue::from_static("br")
+    );
+
+    // read response
+    let bytes = response.body().await.unwrap();
+
+    // decode brotli
+    let mut dec = Vec::new();
+    brotli::BrotliDecompress(&mut &bytes[..], &mut dec).unwrap();
+    assert_eq!(Bytes::from(dec), Bytes::from_static(STR.as_ref()));
+
+    // client side decoding
+    let mut response = srv.get("/").send().await.unwrap();
+    assert!(response.status().is_success());
+    let bytes = response.body().await.unwrap();
+    assert_eq!(bytes, Bytes::from_static(STR.as_ref()));
+}
+
+#[cfg(feature = "zstd")]
+#[ntex::test]
+async fn test_body_zstd() {
+    let srv = test::server_with(test::config().h1(), || {
+        App::new()
+            .wrap(Compress::new(ContentEncoding::Zstd))
+            .service(
+                web::resource("/")
+                    .route(web::to(|| async { HttpResponse::Ok().body(STR) })),
+            )
+    });
+
+    let mut response = srv
+        .get("/")
+        .no_decompress()
+        .header(ACCEPT_ENCODING, "gzip, zstd")
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(
+        response.headers().get(CONTENT_ENCODING).unwrap(),
+        HeaderValue::from_static("zstd")
+    );
+
+    // read response
+    let bytes = response.body().await.unwrap();
+
+    // decode zstd
+    let dec = zstd::stream::decode_all(&bytes[..]).unwrap();
+    assert_eq!(Bytes::from(dec), Bytes::from_static(STR.as_ref()));
+
+    // client side decoding
+    let mut response = srv.get("/").send().await.unwrap();
+    assert!(response.status().is_success());
+    let bytes = response.body().await.unwrap();
+    assert_eq!(bytes, Bytes::from_static(STR.as_ref()));
+}
+
 #[ntex::test]
 async fn test_body_encoding_override() {
     let srv = test::server_with(test::config().h1(), || {